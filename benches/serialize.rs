@@ -0,0 +1,87 @@
+//! Compares `to_json` with `to_json_fast` and `to_writer` with
+//! `to_writer_fast`, run with `cargo bench --bench serialize`.
+
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+use std::hint::black_box;
+use std::io;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 30;
+
+/// A document of a few megabytes of json with the usual mix of nodes
+fn document() -> Pandoc {
+    let mut doc = Pandoc::from_json(r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[]}"#);
+    for i in 0..10_000 {
+        let id = format!("section-{}", i);
+        doc.blocks.push(Block::Header(
+            2,
+            (id, vec!["unnumbered".into()], vec![]),
+            vec![
+                Inline::str("Section"),
+                Inline::Space,
+                Inline::str(i.to_string()),
+            ],
+        ));
+        doc.blocks.push(Block::para(vec![
+            Inline::str("Some"),
+            Inline::Space,
+            Inline::Emph(vec![Inline::str("\"quoted\"")]),
+            Inline::Space,
+            Inline::str("text\twith"),
+            Inline::SoftBreak,
+            Inline::Link(
+                Default::default(),
+                vec![Inline::str("a"), Inline::Space, Inline::str("link")],
+                ("https://example.com/".into(), String::new()),
+            ),
+            Inline::str("."),
+        ]));
+        doc.blocks.push(Block::BulletList(vec![
+            vec![Block::plain(vec![Inline::Code(
+                Default::default(),
+                "let x = 1;".into(),
+            )])],
+            vec![Block::plain(vec![Inline::Math(
+                MathType::InlineMath,
+                "x^2".into(),
+            )])],
+        ]));
+    }
+    doc
+}
+
+/// The median time of `RUNS` calls of `f`
+fn median<T, F: FnMut() -> T>(mut f: F) -> Duration {
+    let mut times: Vec<_> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    times[RUNS / 2]
+}
+
+fn compare(name: &str, serde: Duration, fast: Duration) {
+    println!(
+        "{:<10} serde {:>8.2?}  fast {:>8.2?}  {:.2}x",
+        name,
+        serde,
+        fast,
+        serde.as_secs_f64() / fast.as_secs_f64()
+    );
+}
+
+fn main() {
+    let doc = document();
+    assert_eq!(doc.to_json_fast(), doc.to_json());
+    let serde = median(|| doc.to_json());
+    let fast = median(|| doc.to_json_fast());
+    compare("to_json", serde, fast);
+    let serde = median(|| doc.to_writer(io::sink()));
+    let fast = median(|| doc.to_writer_fast(io::sink()));
+    compare("to_writer", serde, fast);
+}
@@ -1,7 +1,6 @@
 extern crate pandoc_ast;
 
 use pandoc_ast::{Inline, MutVisitor};
-use std::io::{self, Read, Write};
 
 struct MyVisitor;
 
@@ -16,11 +15,8 @@ impl MutVisitor for MyVisitor {
 }
 
 fn main() {
-    let mut s = String::new();
-    io::stdin().read_to_string(&mut s).unwrap();
-    let s = pandoc_ast::filter(s, |mut pandoc| {
+    pandoc_ast::run_filter(|_format, mut pandoc| {
         MyVisitor.walk_pandoc(&mut pandoc);
         pandoc
     });
-    io::stdout().write(s.as_bytes()).unwrap();
 }
@@ -0,0 +1,189 @@
+//! Expansion of the first occurrence of abbreviations
+//!
+//! ```
+//! # use pandoc_ast::acronym::{Acronyms, Style};
+//! # use pandoc_ast::*;
+//! let mut definitions = Map::new();
+//! definitions.insert("HTML".to_owned(), "Hyper Text Markup Language".to_owned());
+//! let mut blocks = vec![para!["HTML is HTML"]];
+//! Acronyms::new(definitions).style(Style::Inline).expand(&mut blocks);
+//! assert_eq!(blocks, vec![para!["Hyper Text Markup Language (HTML) is HTML"]]);
+//! ```
+//!
+//! Abbreviations are matched as whole words in runs of adjacent `Str`,
+//! `Space` and `SoftBreak` inlines, like the patterns of the `text` module,
+//! so they may span several `Str`s and contain spaces. Headers and metadata
+//! are neither expanded nor count as occurrences.
+
+use super::*;
+use std::collections::HashSet;
+
+/// How the first occurrence of an abbreviation is expanded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// a `Span` with the class `abbr` and the expansion as its `title`
+    /// attribute, which the html writer turns into a tooltip
+    Span,
+    /// the expansion followed by the abbreviation in parentheses
+    Inline,
+}
+
+/// Where abbreviations count as first occurrences again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// only the first occurrence in the document is expanded
+    Document,
+    /// the first occurrence after every header of at most the given level
+    /// is expanded
+    Section(Int),
+}
+
+/// Abbreviations and their expansions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Acronyms {
+    definitions: Map<String, String>,
+    style: Style,
+    scope: Scope,
+}
+
+impl Acronyms {
+    /// Expands the keys of `definitions` to their values as `Style::Span`
+    /// once per document. Empty abbreviations are ignored.
+    pub fn new(mut definitions: Map<String, String>) -> Self {
+        definitions.remove("");
+        Acronyms {
+            definitions,
+            style: Style::Span,
+            scope: Scope::Document,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Expands the first occurrences of the abbreviations below `node`, in
+    /// document order
+    pub fn expand<T: MutVisitable>(&self, node: &mut T) {
+        node.accept_mut(&mut Expander {
+            acronyms: self,
+            seen: HashSet::new(),
+        })
+    }
+
+    fn expansion(&self, abbreviation: &str) -> Vec<Inline> {
+        let expansion = &self.definitions[abbreviation];
+        match self.style {
+            Style::Span => vec![Inline::Span(
+                (
+                    String::new(),
+                    vec!["abbr".to_owned()],
+                    vec![("title".to_owned(), expansion.clone())],
+                ),
+                inlines_from_str(abbreviation),
+            )],
+            Style::Inline => inlines_from_str(&format!("{} ({})", expansion, abbreviation)),
+        }
+    }
+}
+
+struct Expander<'a> {
+    acronyms: &'a Acronyms,
+    seen: HashSet<&'a str>,
+}
+
+fn is_text(inline: &Inline) -> bool {
+    matches!(*inline, Inline::Str(_) | Inline::Space | Inline::SoftBreak)
+}
+
+fn is_word_char(c: Option<char>) -> bool {
+    c.is_some_and(char::is_alphanumeric)
+}
+
+impl<'a> Expander<'a> {
+    /// The start of the first unseen abbreviation in `text` and the
+    /// abbreviation, the longest one if several start there
+    fn find(&self, text: &str) -> Option<(usize, &'a str)> {
+        let starts = text
+            .char_indices()
+            .filter(|&(i, _)| !is_word_char(text[..i].chars().next_back()));
+        for (start, _) in starts {
+            let rest = &text[start..];
+            let found = self
+                .acronyms
+                .definitions
+                .keys()
+                .filter(|abbreviation| !self.seen.contains(abbreviation.as_str()))
+                .filter(|abbreviation| {
+                    rest.starts_with(abbreviation.as_str())
+                        && !is_word_char(rest[abbreviation.len()..].chars().next())
+                })
+                .max_by_key(|abbreviation| abbreviation.len());
+            if let Some(abbreviation) = found {
+                return Some((start, abbreviation));
+            }
+        }
+        None
+    }
+
+    fn flush(&mut self, run: &mut Vec<Inline>, result: &mut Vec<Inline>) {
+        if run.is_empty() {
+            return;
+        }
+        let text = inlines_to_string(run);
+        let mut rest = text.as_str();
+        let mut parts = Vec::new();
+        while let Some((start, abbreviation)) = self.find(rest) {
+            self.seen.insert(abbreviation);
+            parts.extend(inlines_from_str(&rest[..start]));
+            parts.extend(self.acronyms.expansion(abbreviation));
+            rest = &rest[start + abbreviation.len()..];
+        }
+        if parts.is_empty() {
+            result.append(run);
+            return;
+        }
+        run.clear();
+        result.extend(parts);
+        result.extend(inlines_from_str(rest));
+    }
+}
+
+impl<'a> MutVisitor for Expander<'a> {
+    fn visit_meta(&mut self, _key: &str, _meta: &mut MetaValue) {}
+
+    fn visit_block(&mut self, block: &mut Block) {
+        match *block {
+            Block::Header(level, _, _) => {
+                if let Scope::Section(max) = self.acronyms.scope {
+                    if level <= max {
+                        self.seen.clear();
+                    }
+                }
+            }
+            _ => self.walk_block(block),
+        }
+    }
+
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        let mut result = Vec::with_capacity(vec_inline.len());
+        let mut run = Vec::new();
+        for mut inline in vec_inline.drain(..) {
+            if is_text(&inline) {
+                run.push(inline);
+            } else {
+                self.flush(&mut run, &mut result);
+                self.visit_inline(&mut inline);
+                result.push(inline);
+            }
+        }
+        self.flush(&mut run, &mut result);
+        *vec_inline = result;
+    }
+}
@@ -0,0 +1,405 @@
+//! `quickcheck::Arbitrary` for documents, blocks and inlines
+//!
+//! Generated nodes are what pandoc's readers produce: inlines are
+//! normalized like by `transform::normalize`, `Str`s are non-empty words,
+//! tables are rectangular, notes don't contain notes and there are no
+//! `Null`, `Unknown` or `Unparsed` nodes. The nesting depth is bounded and
+//! the length of lists grows with the size of the generator. Shrinking
+//! removes nodes or replaces them with their children and keeps these
+//! properties.
+
+use super::*;
+use quickcheck::{Arbitrary, Gen};
+use table::TableBuilder;
+
+/// deepest nesting of blocks and of inlines
+const MAX_DEPTH: usize = 3;
+
+const WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "a",
+    "Ünïcödé",
+    "x_1",
+    "*",
+    "<b>",
+    "&amp;",
+    "\"q\"",
+];
+const IDS: &[&str] = &["", "", "sec", "fig-1"];
+const CLASSES: &[&str] = &["note", "rust", "wide"];
+const URLS: &[&str] = &["https://example.com/", "#sec", "img.png"];
+const FORMATS: &[&str] = &["html", "latex", "tex"];
+
+#[derive(Clone, Copy)]
+struct Context {
+    depth: usize,
+    in_note: bool,
+}
+
+impl Context {
+    fn nested(self) -> Self {
+        Context {
+            depth: self.depth + 1,
+            ..self
+        }
+    }
+
+    fn is_deep(self) -> bool {
+        self.depth >= MAX_DEPTH
+    }
+}
+
+fn below(g: &mut Gen, n: usize) -> usize {
+    usize::arbitrary(g) % n.max(1)
+}
+
+fn pick(g: &mut Gen, items: &[&str]) -> String {
+    (*g.choose(items).expect("not empty")).to_owned()
+}
+
+/// a length for lists, at least `min`
+fn len(g: &mut Gen, min: usize) -> usize {
+    min + below(g, g.size().min(6) + 1)
+}
+
+fn attr(g: &mut Gen) -> Attr {
+    let classes = (0..below(g, 3)).map(|_| pick(g, CLASSES)).collect();
+    let attributes = if bool::arbitrary(g) {
+        vec![("width".to_owned(), "50%".to_owned())]
+    } else {
+        Vec::new()
+    };
+    (pick(g, IDS), classes, attributes)
+}
+
+fn words(g: &mut Gen) -> String {
+    let words: Vec<String> = (0..len(g, 1)).map(|_| pick(g, WORDS)).collect();
+    words.join(" ")
+}
+
+fn inlines(g: &mut Gen, cx: Context) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    for i in 0..len(g, 1) {
+        if i > 0 {
+            inlines.push(Inline::Space);
+        }
+        inlines.push(inline(g, cx));
+    }
+    transform::normalize(&mut inlines);
+    inlines
+}
+
+fn inline(g: &mut Gen, cx: Context) -> Inline {
+    let inner = cx.nested();
+    let choices = if cx.is_deep() { 8 } else { 22 };
+    match below(g, choices) {
+        0..=3 => Inline::Str(pick(g, WORDS)),
+        4 => Inline::Code(attr(g), words(g)),
+        5 => {
+            let math_type = *g
+                .choose(&[MathType::InlineMath, MathType::DisplayMath])
+                .expect("not empty");
+            Inline::Math(math_type, "x^2".to_owned())
+        }
+        6 => Inline::RawInline(Format(pick(g, FORMATS)), "<br>".to_owned()),
+        7 => Inline::LineBreak,
+        8 => Inline::Emph(inlines(g, inner)),
+        9 => Inline::Strong(inlines(g, inner)),
+        10 => Inline::Underline(inlines(g, inner)),
+        11 => Inline::Strikeout(inlines(g, inner)),
+        12 => Inline::Superscript(inlines(g, inner)),
+        13 => Inline::Subscript(inlines(g, inner)),
+        14 => Inline::SmallCaps(inlines(g, inner)),
+        15 => {
+            let quote_type = *g
+                .choose(&[QuoteType::SingleQuote, QuoteType::DoubleQuote])
+                .expect("not empty");
+            Inline::Quoted(quote_type, inlines(g, inner))
+        }
+        16 => Inline::Link(attr(g), inlines(g, inner), (pick(g, URLS), String::new())),
+        17 => Inline::Image(attr(g), inlines(g, inner), (pick(g, URLS), words(g))),
+        18 => Inline::Span(attr(g), inlines(g, inner)),
+        19 => {
+            let citation = Citation {
+                citationId: pick(g, &["doe99", "roe2020"]),
+                citationPrefix: Vec::new(),
+                citationSuffix: Vec::new(),
+                citationMode: *g
+                    .choose(&[CitationMode::NormalCitation, CitationMode::AuthorInText])
+                    .expect("not empty"),
+                citationNoteNum: 1,
+                citationHash: 0,
+            };
+            Inline::Cite(vec![citation], inlines(g, inner))
+        }
+        20 if !cx.in_note => {
+            let cx = Context {
+                in_note: true,
+                ..inner
+            };
+            Inline::Note(vec![Block::Para(inlines(g, cx))])
+        }
+        _ => Inline::Str(pick(g, WORDS)),
+    }
+}
+
+fn blocks(g: &mut Gen, cx: Context) -> Vec<Block> {
+    (0..len(g, 1)).map(|_| block(g, cx)).collect()
+}
+
+fn block(g: &mut Gen, cx: Context) -> Block {
+    let inner = cx.nested();
+    let choices = if cx.is_deep() { 6 } else { 15 };
+    match below(g, choices) {
+        0 | 1 => Block::Para(inlines(g, inner)),
+        2 => Block::Plain(inlines(g, inner)),
+        3 => Block::Header(1 + below(g, 6) as Int, attr(g), inlines(g, inner)),
+        4 => Block::CodeBlock(attr(g), words(g)),
+        5 => Block::HorizontalRule,
+        6 => Block::RawBlock(Format(pick(g, FORMATS)), "<hr>".to_owned()),
+        7 => Block::BlockQuote(blocks(g, inner)),
+        8 => Block::BulletList((0..len(g, 1)).map(|_| blocks(g, inner)).collect()),
+        9 => Block::OrderedList(
+            ListAttributes {
+                start: 1 + below(g, 3) as Int,
+                style: ListNumberStyle::Decimal,
+                delim: ListNumberDelim::Period,
+            },
+            (0..len(g, 1)).map(|_| blocks(g, inner)).collect(),
+        ),
+        10 => Block::DefinitionList(
+            (0..len(g, 1))
+                .map(|_| (inlines(g, inner), vec![blocks(g, inner)]))
+                .collect(),
+        ),
+        11 => Block::LineBlock((0..len(g, 1)).map(|_| inlines(g, inner)).collect()),
+        12 => {
+            let columns = len(g, 1);
+            let mut row =
+                || -> Vec<Vec<Inline>> { (0..columns).map(|_| inlines(g, inner)).collect() };
+            let header = row();
+            let rows: Vec<_> = (0..2).map(|_| row()).collect();
+            TableBuilder::new().header(header).rows(rows).build()
+        }
+        13 => Block::Figure(
+            attr(g),
+            (None, vec![Block::Plain(inlines(g, inner))]),
+            vec![Block::Plain(vec![Inline::Image(
+                Attr::default(),
+                Vec::new(),
+                ("img.png".to_owned(), String::new()),
+            )])],
+        ),
+        _ => Block::Div(attr(g), blocks(g, inner)),
+    }
+}
+
+const TOP: Context = Context {
+    depth: 0,
+    in_note: false,
+};
+
+impl Arbitrary for Inline {
+    fn arbitrary(g: &mut Gen) -> Self {
+        inline(g, TOP)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(shrink_inline(self).into_iter())
+    }
+}
+
+impl Arbitrary for Block {
+    fn arbitrary(g: &mut Gen) -> Self {
+        block(g, TOP)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(shrink_block(self).into_iter())
+    }
+}
+
+impl Arbitrary for Pandoc {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let mut doc = Pandoc::new();
+        if bool::arbitrary(g) {
+            doc.meta.insert(
+                "title".to_owned(),
+                MetaValue::MetaInlines(inlines(g, TOP.nested())),
+            );
+        }
+        if bool::arbitrary(g) {
+            doc.meta
+                .insert("draft".to_owned(), MetaValue::MetaBool(bool::arbitrary(g)));
+        }
+        doc.blocks = (0..below(g, g.size().min(8) + 1))
+            .map(|_| block(g, TOP))
+            .collect();
+        doc
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let mut candidates = Vec::new();
+        if !self.meta.is_empty() {
+            let mut doc = self.clone();
+            doc.meta.clear();
+            candidates.push(doc);
+        }
+        for blocks in shrink_list(&self.blocks, shrink_block) {
+            let mut doc = self.clone();
+            doc.blocks = blocks;
+            candidates.push(doc);
+        }
+        Box::new(candidates.into_iter())
+    }
+}
+
+/// Lists with one element removed or shrunk
+fn shrink_list<T: Clone, F: Fn(&T) -> Vec<T>>(list: &[T], shrink: F) -> Vec<Vec<T>> {
+    let mut candidates = Vec::new();
+    for i in 0..list.len() {
+        let mut shorter = list.to_vec();
+        shorter.remove(i);
+        candidates.push(shorter);
+    }
+    for (i, item) in list.iter().enumerate() {
+        for smaller in shrink(item) {
+            let mut list = list.to_vec();
+            list[i] = smaller;
+            candidates.push(list);
+        }
+    }
+    candidates
+}
+
+/// Shrinks a non-empty list of inlines, keeping it non-empty and normalized
+fn shrink_inlines(inlines: &[Inline]) -> Vec<Vec<Inline>> {
+    shrink_list(inlines, shrink_inline)
+        .into_iter()
+        .filter_map(|mut candidate| {
+            transform::normalize(&mut candidate);
+            let is_valid = !candidate.is_empty()
+                && !matches!(candidate[..], [Inline::Space, ..] | [.., Inline::Space]);
+            if is_valid && candidate[..] != *inlines {
+                Some(candidate)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn shrink_inline(inline: &Inline) -> Vec<Inline> {
+    let simplest = Inline::Str("a".to_owned());
+    let mut candidates = Vec::new();
+    match *inline {
+        Inline::Str(ref s) => {
+            if s != "a" {
+                candidates.push(simplest);
+            }
+            return candidates;
+        }
+        Inline::Space | Inline::SoftBreak | Inline::LineBreak => return candidates,
+        Inline::Emph(ref c)
+        | Inline::Underline(ref c)
+        | Inline::Strong(ref c)
+        | Inline::Strikeout(ref c)
+        | Inline::Superscript(ref c)
+        | Inline::Subscript(ref c)
+        | Inline::SmallCaps(ref c)
+        | Inline::Quoted(_, ref c)
+        | Inline::Cite(_, ref c)
+        | Inline::Link(_, ref c, _)
+        | Inline::Image(_, ref c, _)
+        | Inline::Span(_, ref c) => {
+            candidates.push(simplest);
+            candidates.extend(c.iter().filter(|i| **i != Inline::Space).cloned());
+            for smaller in shrink_inlines(c) {
+                let mut inline = inline.clone();
+                match inline {
+                    Inline::Emph(ref mut c)
+                    | Inline::Underline(ref mut c)
+                    | Inline::Strong(ref mut c)
+                    | Inline::Strikeout(ref mut c)
+                    | Inline::Superscript(ref mut c)
+                    | Inline::Subscript(ref mut c)
+                    | Inline::SmallCaps(ref mut c)
+                    | Inline::Quoted(_, ref mut c)
+                    | Inline::Cite(_, ref mut c)
+                    | Inline::Link(_, ref mut c, _)
+                    | Inline::Image(_, ref mut c, _)
+                    | Inline::Span(_, ref mut c) => *c = smaller,
+                    _ => unreachable!(),
+                }
+                candidates.push(inline);
+            }
+        }
+        _ => candidates.push(simplest),
+    }
+    candidates
+}
+
+fn shrink_block(block: &Block) -> Vec<Block> {
+    let mut candidates = Vec::new();
+    match *block {
+        Block::Para(ref c) | Block::Plain(ref c) | Block::Header(_, _, ref c) => {
+            for smaller in shrink_inlines(c) {
+                let mut block = block.clone();
+                match block {
+                    Block::Para(ref mut c)
+                    | Block::Plain(ref mut c)
+                    | Block::Header(_, _, ref mut c) => *c = smaller,
+                    _ => unreachable!(),
+                }
+                candidates.push(block);
+            }
+            if let Block::Header(..) = *block {
+                candidates.push(Block::Para(c.clone()));
+            }
+        }
+        Block::BlockQuote(ref content) | Block::Div(_, ref content) => {
+            candidates.extend(content.iter().cloned());
+        }
+        Block::BulletList(ref items) | Block::OrderedList(_, ref items) => {
+            candidates.extend(items.iter().flatten().cloned());
+            if items.len() > 1 {
+                for i in 0..items.len() {
+                    let mut block = block.clone();
+                    match block {
+                        Block::BulletList(ref mut items) | Block::OrderedList(_, ref mut items) => {
+                            items.remove(i);
+                        }
+                        _ => unreachable!(),
+                    }
+                    candidates.push(block);
+                }
+            }
+        }
+        Block::DefinitionList(ref items) => {
+            candidates.extend(items.iter().map(|item| Block::Para(item.0.clone())));
+        }
+        Block::LineBlock(ref lines) => {
+            candidates.extend(lines.iter().map(|line| Block::Para(line.clone())));
+        }
+        Block::Figure(_, ref caption, ref content) => {
+            candidates.extend(content.iter().chain(&caption.1).cloned());
+        }
+        Block::Table(..) => {
+            let cells = table::Table::from_block(block.clone())
+                .map(|table| {
+                    table
+                        .rows()
+                        .flat_map(|row| row.1.iter())
+                        .flat_map(|cell| cell.4.iter())
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            candidates.extend(cells);
+        }
+        Block::CodeBlock(..) | Block::RawBlock(..) => candidates.push(Block::HorizontalRule),
+        _ => {}
+    }
+    candidates
+}
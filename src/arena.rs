@@ -0,0 +1,576 @@
+//! A variant of the AST allocated from a `bumpalo` arena.
+//!
+//! Nodes only hold references into the arena, so they are never dropped
+//! one by one: the whole document is freed at once when the `Bump` is.
+//! This suits generators building millions of small nodes, where dropping
+//! the owned AST takes a noticeable share of the run time. The documents
+//! serialize to pandoc's json directly. The metadata is kept in the owned
+//! representation, as it is rarely large.
+//!
+//! Formats, classes and attribute keys can be shared between all nodes using
+//! them, see `Interner`.
+
+use super::{Alignment, CitationMode, ColSpan, ColSpec, Error, Int, ListAttributes};
+use super::{MathType, Meta, QuoteType, RowHeadColumns, RowSpan};
+use serde::ser::Error as _;
+use serde::{Serialize, Serializer};
+use serde_json::value::RawValue;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+pub use bumpalo::Bump;
+
+pub type Attr<'b> = (&'b str, &'b [&'b str], &'b [(&'b str, &'b str)]);
+pub type Target<'b> = (&'b str, &'b str);
+pub type Caption<'b> = (Option<&'b [Inline<'b>]>, &'b [Block<'b>]);
+pub type Cell<'b> = (Attr<'b>, Alignment, RowSpan, ColSpan, &'b [Block<'b>]);
+pub type Row<'b> = (Attr<'b>, &'b [Cell<'b>]);
+pub type TableHead<'b> = (Attr<'b>, &'b [Row<'b>]);
+pub type TableBody<'b> = (Attr<'b>, RowHeadColumns, &'b [Row<'b>], &'b [Row<'b>]);
+pub type TableFoot<'b> = (Attr<'b>, &'b [Row<'b>]);
+
+/// the root object of a pandoc document, see `super::Pandoc`
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Pandoc<'b> {
+    pub meta: Meta,
+    pub blocks: &'b [Block<'b>],
+    #[serde(rename = "pandoc-api-version")]
+    pub pandoc_api_version: Vec<u32>,
+}
+
+/// see `super::Block`
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "t", content = "c")]
+pub enum Block<'b> {
+    Plain(&'b [Inline<'b>]),
+    Para(&'b [Inline<'b>]),
+    LineBlock(&'b [&'b [Inline<'b>]]),
+    CodeBlock(Attr<'b>, &'b str),
+    RawBlock(Format<'b>, &'b str),
+    BlockQuote(&'b [Block<'b>]),
+    OrderedList(ListAttributes, &'b [&'b [Block<'b>]]),
+    BulletList(&'b [&'b [Block<'b>]]),
+    DefinitionList(&'b [(&'b [Inline<'b>], &'b [&'b [Block<'b>]])]),
+    Header(Int, Attr<'b>, &'b [Inline<'b>]),
+    HorizontalRule,
+    Table(
+        Attr<'b>,
+        Caption<'b>,
+        &'b [ColSpec],
+        TableHead<'b>,
+        &'b [TableBody<'b>],
+        TableFoot<'b>,
+    ),
+    Figure(Attr<'b>, Caption<'b>, &'b [Block<'b>]),
+    Div(Attr<'b>, &'b [Block<'b>]),
+    Null,
+    /// see `super::Block::Unknown`, holding the json of the whole block
+    #[serde(untagged)]
+    Unknown(RawJson<'b>),
+    /// see `super::Block::Unparsed`
+    #[serde(untagged)]
+    Unparsed(RawJson<'b>),
+}
+
+/// see `super::Inline`
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "t", content = "c")]
+pub enum Inline<'b> {
+    Str(&'b str),
+    Emph(&'b [Inline<'b>]),
+    Underline(&'b [Inline<'b>]),
+    Strong(&'b [Inline<'b>]),
+    Strikeout(&'b [Inline<'b>]),
+    Superscript(&'b [Inline<'b>]),
+    Subscript(&'b [Inline<'b>]),
+    SmallCaps(&'b [Inline<'b>]),
+    Quoted(QuoteType, &'b [Inline<'b>]),
+    Cite(&'b [Citation<'b>], &'b [Inline<'b>]),
+    Code(Attr<'b>, &'b str),
+    Space,
+    SoftBreak,
+    LineBreak,
+    Math(MathType, &'b str),
+    RawInline(Format<'b>, &'b str),
+    Link(Attr<'b>, &'b [Inline<'b>], Target<'b>),
+    Image(Attr<'b>, &'b [Inline<'b>], Target<'b>),
+    Note(&'b [Block<'b>]),
+    Span(Attr<'b>, &'b [Inline<'b>]),
+    /// see `super::Inline::Unknown`, holding the json of the whole inline
+    #[serde(untagged)]
+    Unknown(RawJson<'b>),
+}
+
+/// The json of a `Block::Unparsed` or an unknown node, written out as is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawJson<'b>(pub &'b str);
+
+impl<'b> Serialize for RawJson<'b> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let raw: &RawValue = serde_json::from_str(self.0).map_err(S::Error::custom)?;
+        raw.serialize(serializer)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Format<'b>(pub &'b str);
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(non_snake_case)]
+pub struct Citation<'b> {
+    pub citationId: &'b str,
+    pub citationPrefix: &'b [Inline<'b>],
+    pub citationSuffix: &'b [Inline<'b>],
+    pub citationMode: CitationMode,
+    pub citationNoteNum: Int,
+    pub citationHash: Int,
+}
+
+/// Strings allocated in an arena, each distinct string only once.
+///
+/// Documents with many code spans or styled spans repeat the same few
+/// formats, classes and attribute keys over and over. Copying them with
+/// `Pandoc::from_owned_interned` makes all their occurrences point to one
+/// allocation. Hand-built nodes can use `intern` directly:
+///
+/// ```
+/// # use pandoc_ast::arena::{Block, Bump, Interner};
+/// let bump = Bump::new();
+/// let interner = Interner::new(&bump);
+/// let classes = bump.alloc_slice_fill_iter((0..3).map(|_| interner.intern("rust")));
+/// let block = Block::CodeBlock(("", classes, &[]), "fn main() {}");
+/// assert_eq!(interner.len(), 1);
+/// ```
+pub struct Interner<'b> {
+    bump: &'b Bump,
+    strings: RefCell<HashSet<&'b str>>,
+}
+
+impl<'b> Interner<'b> {
+    pub fn new(bump: &'b Bump) -> Self {
+        Interner {
+            bump,
+            strings: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// The string equal to `s` in the arena, allocated on first use
+    pub fn intern(&self, s: &str) -> &'b str {
+        let mut strings = self.strings.borrow_mut();
+        if let Some(&interned) = strings.get(s) {
+            return interned;
+        }
+        let interned: &'b str = self.bump.alloc_str(s);
+        strings.insert(interned);
+        interned
+    }
+
+    /// The number of distinct strings interned so far
+    pub fn len(&self) -> usize {
+        self.strings.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Where copied nodes allocate their strings
+struct Alloc<'i, 'b: 'i> {
+    bump: &'b Bump,
+    interner: Option<&'i Interner<'b>>,
+}
+
+impl<'i, 'b> Alloc<'i, 'b> {
+    fn new(bump: &'b Bump) -> Self {
+        Alloc {
+            bump,
+            interner: None,
+        }
+    }
+
+    fn interned(interner: &'i Interner<'b>) -> Self {
+        Alloc {
+            bump: interner.bump,
+            interner: Some(interner),
+        }
+    }
+
+    fn str(&self, s: &str) -> &'b str {
+        self.bump.alloc_str(s)
+    }
+
+    /// Allocates a format, class or attribute key, interned if requested
+    fn name(&self, s: &str) -> &'b str {
+        match self.interner {
+            Some(interner) => interner.intern(s),
+            None => self.str(s),
+        }
+    }
+}
+
+fn slice<'b, T, U, F>(alloc: &Alloc<'_, 'b>, items: &[U], f: F) -> &'b [T]
+where
+    F: FnMut(&U) -> T,
+{
+    alloc.bump.alloc_slice_fill_iter(items.iter().map(f))
+}
+
+fn unknown<'b, T: Serialize>(alloc: &Alloc<'_, 'b>, node: &T) -> RawJson<'b> {
+    RawJson(alloc.str(&serde_json::to_string(node).expect("serialization failed")))
+}
+
+fn owned_unknown<T, F: FnOnce(String, serde_json::Value) -> T>(json: RawJson, f: F) -> T {
+    let mut node: serde_json::Value = serde_json::from_str(json.0).expect("invalid raw json");
+    let name = node["t"]
+        .as_str()
+        .expect("unknown node without name")
+        .to_owned();
+    f(name, node["c"].take())
+}
+
+fn attr<'b>(alloc: &Alloc<'_, 'b>, attr: &super::Attr) -> Attr<'b> {
+    (
+        alloc.str(&attr.0),
+        slice(alloc, &attr.1, |class| alloc.name(class)),
+        slice(alloc, &attr.2, |(key, value)| {
+            (alloc.name(key), alloc.str(value))
+        }),
+    )
+}
+
+fn inlines<'b>(alloc: &Alloc<'_, 'b>, inlines: &[super::Inline]) -> &'b [Inline<'b>] {
+    slice(alloc, inlines, |inline| Inline::copy(alloc, inline))
+}
+
+fn blocks<'b>(alloc: &Alloc<'_, 'b>, blocks: &[super::Block]) -> &'b [Block<'b>] {
+    slice(alloc, blocks, |block| Block::copy(alloc, block))
+}
+
+fn items<'b>(alloc: &Alloc<'_, 'b>, items: &[Vec<super::Block>]) -> &'b [&'b [Block<'b>]] {
+    slice(alloc, items, |item| blocks(alloc, item))
+}
+
+fn caption<'b>(alloc: &Alloc<'_, 'b>, caption: &super::Caption) -> Caption<'b> {
+    (
+        caption.0.as_ref().map(|short| inlines(alloc, short)),
+        blocks(alloc, &caption.1),
+    )
+}
+
+fn rows<'b>(alloc: &Alloc<'_, 'b>, rows: &[super::Row]) -> &'b [Row<'b>] {
+    slice(alloc, rows, |row| {
+        let cells = slice(alloc, &row.1, |cell| {
+            let content = blocks(alloc, &cell.4);
+            (attr(alloc, &cell.0), cell.1, cell.2, cell.3, content)
+        });
+        (attr(alloc, &row.0), cells)
+    })
+}
+
+fn owned_attr(attr: &Attr) -> super::Attr {
+    (
+        attr.0.to_owned(),
+        attr.1.iter().map(|&class| class.to_owned()).collect(),
+        attr.2
+            .iter()
+            .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+            .collect(),
+    )
+}
+
+fn owned_inlines(inlines: &[Inline]) -> Vec<super::Inline> {
+    inlines.iter().map(Inline::to_owned_ast).collect()
+}
+
+fn owned_blocks(blocks: &[Block]) -> Vec<super::Block> {
+    blocks.iter().map(Block::to_owned_ast).collect()
+}
+
+fn owned_items(items: &[&[Block]]) -> Vec<Vec<super::Block>> {
+    items.iter().map(|item| owned_blocks(item)).collect()
+}
+
+fn owned_caption(caption: &Caption) -> super::Caption {
+    (caption.0.map(owned_inlines), owned_blocks(caption.1))
+}
+
+fn owned_rows(rows: &[Row]) -> Vec<super::Row> {
+    rows.iter()
+        .map(|row| {
+            let cells = row
+                .1
+                .iter()
+                .map(|cell| {
+                    let content = owned_blocks(cell.4);
+                    (owned_attr(&cell.0), cell.1, cell.2, cell.3, content)
+                })
+                .collect();
+            (owned_attr(&row.0), cells)
+        })
+        .collect()
+}
+
+impl<'b> Pandoc<'b> {
+    /// Copies an owned document into the arena
+    pub fn from_owned(bump: &'b Bump, doc: &super::Pandoc) -> Self {
+        Self::copy(&Alloc::new(bump), doc)
+    }
+
+    /// Copies an owned document into the arena of `interner`, with the
+    /// formats, classes and attribute keys interned
+    pub fn from_owned_interned(interner: &Interner<'b>, doc: &super::Pandoc) -> Self {
+        Self::copy(&Alloc::interned(interner), doc)
+    }
+
+    fn copy(alloc: &Alloc<'_, 'b>, doc: &super::Pandoc) -> Self {
+        Pandoc {
+            meta: doc.meta.clone(),
+            blocks: blocks(alloc, &doc.blocks),
+            pandoc_api_version: doc.pandoc_api_version.clone(),
+        }
+    }
+
+    pub fn to_owned_ast(&self) -> super::Pandoc {
+        super::Pandoc {
+            meta: self.meta.clone(),
+            blocks: owned_blocks(self.blocks),
+            pandoc_api_version: self.pandoc_api_version.clone(),
+        }
+    }
+
+    /// Parses a document into the arena, going through the owned AST
+    pub fn from_json(bump: &'b Bump, json: &str) -> Result<Self, Error> {
+        Ok(Self::from_owned(bump, &super::Pandoc::try_from_json(json)?))
+    }
+
+    /// `from_json` with the strings interned like by `from_owned_interned`
+    pub fn from_json_interned(interner: &Interner<'b>, json: &str) -> Result<Self, Error> {
+        Ok(Self::from_owned_interned(
+            interner,
+            &super::Pandoc::try_from_json(json)?,
+        ))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("serialization failed")
+    }
+}
+
+impl<'b> Block<'b> {
+    /// Copies an owned block into the arena
+    pub fn from_owned(bump: &'b Bump, block: &super::Block) -> Self {
+        Self::copy(&Alloc::new(bump), block)
+    }
+
+    fn copy(alloc: &Alloc<'_, 'b>, block: &super::Block) -> Self {
+        use super::Block as B;
+        match *block {
+            B::Plain(ref content) => Block::Plain(inlines(alloc, content)),
+            B::Para(ref content) => Block::Para(inlines(alloc, content)),
+            B::LineBlock(ref lines) => {
+                Block::LineBlock(slice(alloc, lines, |line| inlines(alloc, line)))
+            }
+            B::CodeBlock(ref a, ref code) => Block::CodeBlock(attr(alloc, a), alloc.str(code)),
+            B::RawBlock(ref format, ref content) => {
+                Block::RawBlock(Format(alloc.name(&format.0)), alloc.str(content))
+            }
+            B::BlockQuote(ref content) => Block::BlockQuote(blocks(alloc, content)),
+            B::OrderedList(list_attributes, ref content) => {
+                Block::OrderedList(list_attributes, items(alloc, content))
+            }
+            B::BulletList(ref content) => Block::BulletList(items(alloc, content)),
+            B::DefinitionList(ref content) => {
+                Block::DefinitionList(slice(alloc, content, |(term, definitions)| {
+                    (inlines(alloc, term), items(alloc, definitions))
+                }))
+            }
+            B::Header(level, ref a, ref content) => {
+                Block::Header(level, attr(alloc, a), inlines(alloc, content))
+            }
+            B::HorizontalRule => Block::HorizontalRule,
+            B::Table(ref a, ref c, ref specs, ref head, ref bodies, ref foot) => Block::Table(
+                attr(alloc, a),
+                caption(alloc, c),
+                alloc.bump.alloc_slice_clone(specs),
+                (attr(alloc, &head.0), rows(alloc, &head.1)),
+                slice(alloc, bodies, |body| {
+                    let head_rows = rows(alloc, &body.2);
+                    (
+                        attr(alloc, &body.0),
+                        body.1,
+                        head_rows,
+                        rows(alloc, &body.3),
+                    )
+                }),
+                (attr(alloc, &foot.0), rows(alloc, &foot.1)),
+            ),
+            B::Figure(ref a, ref c, ref content) => {
+                Block::Figure(attr(alloc, a), caption(alloc, c), blocks(alloc, content))
+            }
+            B::Div(ref a, ref content) => Block::Div(attr(alloc, a), blocks(alloc, content)),
+            B::Null => Block::Null,
+            B::Unknown(..) => Block::Unknown(unknown(alloc, block)),
+            B::Unparsed(ref unparsed) => Block::Unparsed(RawJson(alloc.str(unparsed.json()))),
+        }
+    }
+
+    pub fn to_owned_ast(&self) -> super::Block {
+        use super::Block as B;
+        match *self {
+            Block::Plain(content) => B::Plain(owned_inlines(content)),
+            Block::Para(content) => B::Para(owned_inlines(content)),
+            Block::LineBlock(lines) => {
+                B::LineBlock(lines.iter().map(|line| owned_inlines(line)).collect())
+            }
+            Block::CodeBlock(ref a, code) => B::CodeBlock(owned_attr(a), code.to_owned()),
+            Block::RawBlock(format, content) => {
+                B::RawBlock(super::Format(format.0.to_owned()), content.to_owned())
+            }
+            Block::BlockQuote(content) => B::BlockQuote(owned_blocks(content)),
+            Block::OrderedList(list_attributes, content) => {
+                B::OrderedList(list_attributes, owned_items(content))
+            }
+            Block::BulletList(content) => B::BulletList(owned_items(content)),
+            Block::DefinitionList(content) => B::DefinitionList(
+                content
+                    .iter()
+                    .map(|&(term, definitions)| (owned_inlines(term), owned_items(definitions)))
+                    .collect(),
+            ),
+            Block::Header(level, ref a, content) => {
+                B::Header(level, owned_attr(a), owned_inlines(content))
+            }
+            Block::HorizontalRule => B::HorizontalRule,
+            Block::Table(ref a, ref c, specs, ref head, bodies, ref foot) => B::Table(
+                owned_attr(a),
+                owned_caption(c),
+                specs.to_vec(),
+                (owned_attr(&head.0), owned_rows(head.1)),
+                bodies
+                    .iter()
+                    .map(|body| {
+                        let head_rows = owned_rows(body.2);
+                        (owned_attr(&body.0), body.1, head_rows, owned_rows(body.3))
+                    })
+                    .collect(),
+                (owned_attr(&foot.0), owned_rows(foot.1)),
+            ),
+            Block::Figure(ref a, ref c, content) => {
+                B::Figure(owned_attr(a), owned_caption(c), owned_blocks(content))
+            }
+            Block::Div(ref a, content) => B::Div(owned_attr(a), owned_blocks(content)),
+            Block::Null => B::Null,
+            Block::Unknown(json) => owned_unknown(json, B::Unknown),
+            Block::Unparsed(json) => {
+                B::Unparsed(serde_json::from_str(json.0).expect("invalid raw json"))
+            }
+        }
+    }
+}
+
+impl<'b> Inline<'b> {
+    /// Copies an owned inline into the arena
+    pub fn from_owned(bump: &'b Bump, inline: &super::Inline) -> Self {
+        Self::copy(&Alloc::new(bump), inline)
+    }
+
+    fn copy(alloc: &Alloc<'_, 'b>, inline: &super::Inline) -> Self {
+        use super::Inline as I;
+        match *inline {
+            I::Str(ref s) => Inline::Str(alloc.str(s)),
+            I::Emph(ref content) => Inline::Emph(inlines(alloc, content)),
+            I::Underline(ref content) => Inline::Underline(inlines(alloc, content)),
+            I::Strong(ref content) => Inline::Strong(inlines(alloc, content)),
+            I::Strikeout(ref content) => Inline::Strikeout(inlines(alloc, content)),
+            I::Superscript(ref content) => Inline::Superscript(inlines(alloc, content)),
+            I::Subscript(ref content) => Inline::Subscript(inlines(alloc, content)),
+            I::SmallCaps(ref content) => Inline::SmallCaps(inlines(alloc, content)),
+            I::Quoted(quote_type, ref content) => {
+                Inline::Quoted(quote_type, inlines(alloc, content))
+            }
+            I::Cite(ref citations, ref content) => Inline::Cite(
+                slice(alloc, citations, |citation| Citation {
+                    citationId: alloc.str(&citation.citationId),
+                    citationPrefix: inlines(alloc, &citation.citationPrefix),
+                    citationSuffix: inlines(alloc, &citation.citationSuffix),
+                    citationMode: citation.citationMode,
+                    citationNoteNum: citation.citationNoteNum,
+                    citationHash: citation.citationHash,
+                }),
+                inlines(alloc, content),
+            ),
+            I::Code(ref a, ref code) => Inline::Code(attr(alloc, a), alloc.str(code)),
+            I::Space => Inline::Space,
+            I::SoftBreak => Inline::SoftBreak,
+            I::LineBreak => Inline::LineBreak,
+            I::Math(math_type, ref tex) => Inline::Math(math_type, alloc.str(tex)),
+            I::RawInline(ref format, ref content) => {
+                Inline::RawInline(Format(alloc.name(&format.0)), alloc.str(content))
+            }
+            I::Link(ref a, ref content, ref target) => Inline::Link(
+                attr(alloc, a),
+                inlines(alloc, content),
+                (alloc.str(&target.0), alloc.str(&target.1)),
+            ),
+            I::Image(ref a, ref content, ref target) => Inline::Image(
+                attr(alloc, a),
+                inlines(alloc, content),
+                (alloc.str(&target.0), alloc.str(&target.1)),
+            ),
+            I::Note(ref content) => Inline::Note(blocks(alloc, content)),
+            I::Span(ref a, ref content) => Inline::Span(attr(alloc, a), inlines(alloc, content)),
+            I::Unknown(..) => Inline::Unknown(unknown(alloc, inline)),
+        }
+    }
+
+    pub fn to_owned_ast(&self) -> super::Inline {
+        use super::Inline as I;
+        match *self {
+            Inline::Str(s) => I::Str(s.to_owned()),
+            Inline::Emph(content) => I::Emph(owned_inlines(content)),
+            Inline::Underline(content) => I::Underline(owned_inlines(content)),
+            Inline::Strong(content) => I::Strong(owned_inlines(content)),
+            Inline::Strikeout(content) => I::Strikeout(owned_inlines(content)),
+            Inline::Superscript(content) => I::Superscript(owned_inlines(content)),
+            Inline::Subscript(content) => I::Subscript(owned_inlines(content)),
+            Inline::SmallCaps(content) => I::SmallCaps(owned_inlines(content)),
+            Inline::Quoted(quote_type, content) => I::Quoted(quote_type, owned_inlines(content)),
+            Inline::Cite(citations, content) => I::Cite(
+                citations
+                    .iter()
+                    .map(|citation| super::Citation {
+                        citationId: citation.citationId.to_owned(),
+                        citationPrefix: owned_inlines(citation.citationPrefix),
+                        citationSuffix: owned_inlines(citation.citationSuffix),
+                        citationMode: citation.citationMode,
+                        citationNoteNum: citation.citationNoteNum,
+                        citationHash: citation.citationHash,
+                    })
+                    .collect(),
+                owned_inlines(content),
+            ),
+            Inline::Code(ref a, code) => I::Code(owned_attr(a), code.to_owned()),
+            Inline::Space => I::Space,
+            Inline::SoftBreak => I::SoftBreak,
+            Inline::LineBreak => I::LineBreak,
+            Inline::Math(math_type, tex) => I::Math(math_type, tex.to_owned()),
+            Inline::RawInline(format, content) => {
+                I::RawInline(super::Format(format.0.to_owned()), content.to_owned())
+            }
+            Inline::Link(ref a, content, target) => I::Link(
+                owned_attr(a),
+                owned_inlines(content),
+                (target.0.to_owned(), target.1.to_owned()),
+            ),
+            Inline::Image(ref a, content, target) => I::Image(
+                owned_attr(a),
+                owned_inlines(content),
+                (target.0.to_owned(), target.1.to_owned()),
+            ),
+            Inline::Note(content) => I::Note(owned_blocks(content)),
+            Inline::Span(ref a, content) => I::Span(owned_attr(a), owned_inlines(content)),
+            Inline::Unknown(json) => owned_unknown(json, I::Unknown),
+        }
+    }
+}
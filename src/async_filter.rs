@@ -0,0 +1,107 @@
+use super::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// bytes read from the input at a time
+const CHUNK: usize = 8 * 1024;
+
+/// Like `run_filter`, but reads the document from `reader`, transforms it
+/// with the future returned by `f` and writes the result to `writer`
+/// without blocking the thread, e.g. for http endpoints transforming
+/// pandoc's json. The document is read completely before it is parsed.
+/// Documents of older pandoc versions are converted like by `filter`.
+pub fn run_filter_async<R, W, F, Fut>(reader: R, writer: W, f: F) -> RunFilterAsync<R, W, F, Fut>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+    F: FnOnce(Pandoc) -> Fut,
+    Fut: Future<Output = Pandoc>,
+{
+    RunFilterAsync {
+        reader,
+        writer,
+        f: Some(Box::new(f)),
+        state: State::Reading(Vec::new()),
+    }
+}
+
+enum State<Fut> {
+    Reading(Vec<u8>),
+    Filtering(Pin<Box<Fut>>, Vec<u32>),
+    Writing(Vec<u8>, usize),
+    Flushing,
+    Done,
+}
+
+/// The future returned by `run_filter_async`
+pub struct RunFilterAsync<R, W, F, Fut> {
+    reader: R,
+    writer: W,
+    f: Option<Box<F>>,
+    state: State<Fut>,
+}
+
+impl<R, W, F, Fut> Future for RunFilterAsync<R, W, F, Fut>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+    F: FnOnce(Pandoc) -> Fut,
+    Fut: Future<Output = Pandoc>,
+{
+    type Output = Result<(), Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            match this.state {
+                State::Reading(ref mut input) => {
+                    let mut chunk = [0; CHUNK];
+                    let mut buf = ReadBuf::new(&mut chunk);
+                    match Pin::new(&mut this.reader).poll_read(cx, &mut buf) {
+                        Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                            let (doc, version) = from_any_version(input)?;
+                            let f = this.f.take().expect("the filter runs once");
+                            this.state = State::Filtering(Box::pin(f(doc)), version);
+                        }
+                        Poll::Ready(Ok(())) => input.extend_from_slice(buf.filled()),
+                        Poll::Ready(Err(err)) => return Poll::Ready(Err(err.into())),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                State::Filtering(ref mut future, ref version) => match future.as_mut().poll(cx) {
+                    Poll::Ready(doc) => {
+                        let json = to_version(doc, version)?;
+                        this.state = State::Writing(json.into_bytes(), 0);
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                State::Writing(ref output, ref mut written) => {
+                    if *written == output.len() {
+                        this.state = State::Flushing;
+                        continue;
+                    }
+                    match Pin::new(&mut this.writer).poll_write(cx, &output[*written..]) {
+                        Poll::Ready(Ok(0)) => {
+                            return Poll::Ready(Err(
+                                io::Error::from(io::ErrorKind::WriteZero).into()
+                            ))
+                        }
+                        Poll::Ready(Ok(n)) => *written += n,
+                        Poll::Ready(Err(err)) => return Poll::Ready(Err(err.into())),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                State::Flushing => match Pin::new(&mut this.writer).poll_flush(cx) {
+                    Poll::Ready(result) => {
+                        this.state = State::Done;
+                        return Poll::Ready(result.map_err(Error::from));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                State::Done => panic!("`RunFilterAsync` polled after completion"),
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Binary encodings of documents, for passing them between Rust processes.
+//! Pandoc itself only reads and writes json.
+//!
+//! `Block::Unparsed` can't be encoded this way, call `Pandoc::ensure_parsed` first.
+
+use super::*;
+
+impl Pandoc {
+    /// Encodes the document as MessagePack
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, Error> {
+        rmp_serde::to_vec_named(self).map_err(|err| Error::Binary(err.to_string()))
+    }
+
+    /// Decodes a document written by `to_msgpack`
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, Error> {
+        let doc: Pandoc =
+            rmp_serde::from_slice(bytes).map_err(|err| Error::Binary(err.to_string()))?;
+        check_api_version(&doc.pandoc_api_version)?;
+        Ok(doc)
+    }
+
+    /// Encodes the document as CBOR
+    #[cfg(feature = "cbor")]
+    pub fn to_cbor(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(self, &mut bytes)
+            .map_err(|err| Error::Binary(err.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// Decodes a document written by `to_cbor`
+    #[cfg(feature = "cbor")]
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, Error> {
+        let doc: Pandoc =
+            ciborium::de::from_reader(bytes).map_err(|err| Error::Binary(err.to_string()))?;
+        check_api_version(&doc.pandoc_api_version)?;
+        Ok(doc)
+    }
+}
@@ -0,0 +1,358 @@
+//! A variant of the AST borrowing its strings from the json input.
+//!
+//! Strings are `Text`s, which point into the input unless they contain
+//! escape sequences. For documents that a filter mostly passes through,
+//! this avoids allocating a `String` for every word. Use `into_owned` to
+//! convert to the regular AST.
+
+use super::{Alignment, CitationMode, ColSpan, Error, Int, Map, MathType, QuoteType};
+use super::{RowHeadColumns, RowSpan};
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+
+/// A string borrowed from the input if possible
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Text<'a>(#[serde(borrow)] pub Cow<'a, str>);
+
+impl<'a> Text<'a> {
+    pub fn into_owned(self) -> String {
+        self.0.into_owned()
+    }
+
+    /// Whether the text points into the input
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Cow::Borrowed(_))
+    }
+}
+
+impl<'a> Deref for Text<'a> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> fmt::Display for Text<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a> From<&'a str> for Text<'a> {
+    fn from(s: &'a str) -> Self {
+        Text(Cow::Borrowed(s))
+    }
+}
+
+impl<'a> From<String> for Text<'a> {
+    fn from(s: String) -> Self {
+        Text(Cow::Owned(s))
+    }
+}
+
+pub type Attr<'a> = (Text<'a>, Vec<Text<'a>>, Vec<(Text<'a>, Text<'a>)>);
+pub type Target<'a> = (Text<'a>, Text<'a>);
+pub type ListAttributes = super::ListAttributes;
+pub type Caption<'a> = (Option<Vec<Inline<'a>>>, Vec<Block<'a>>);
+pub type ColSpec = super::ColSpec;
+pub type Cell<'a> = (Attr<'a>, Alignment, RowSpan, ColSpan, Vec<Block<'a>>);
+pub type Row<'a> = (Attr<'a>, Vec<Cell<'a>>);
+pub type TableHead<'a> = (Attr<'a>, Vec<Row<'a>>);
+pub type TableBody<'a> = (Attr<'a>, RowHeadColumns, Vec<Row<'a>>, Vec<Row<'a>>);
+pub type TableFoot<'a> = (Attr<'a>, Vec<Row<'a>>);
+
+/// the root object of a pandoc document, see `super::Pandoc`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(bound(deserialize = "'de: 'a"))]
+pub struct Pandoc<'a> {
+    pub meta: Map<Text<'a>, MetaValue<'a>>,
+    pub blocks: Vec<Block<'a>>,
+    #[serde(rename = "pandoc-api-version")]
+    pub pandoc_api_version: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "t", content = "c", bound(deserialize = "'de: 'a"))]
+pub enum MetaValue<'a> {
+    MetaMap(Map<Text<'a>, Box<MetaValue<'a>>>),
+    MetaList(Vec<MetaValue<'a>>),
+    MetaBool(bool),
+    MetaString(Text<'a>),
+    MetaInlines(Vec<Inline<'a>>),
+    MetaBlocks(Vec<Block<'a>>),
+}
+
+/// see `super::Block`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "t", content = "c", bound(deserialize = "'de: 'a"))]
+#[allow(clippy::large_enum_variant)]
+pub enum Block<'a> {
+    Plain(Vec<Inline<'a>>),
+    Para(Vec<Inline<'a>>),
+    LineBlock(Vec<Vec<Inline<'a>>>),
+    CodeBlock(Attr<'a>, Text<'a>),
+    RawBlock(Format<'a>, Text<'a>),
+    BlockQuote(Vec<Block<'a>>),
+    OrderedList(ListAttributes, Vec<Vec<Block<'a>>>),
+    BulletList(Vec<Vec<Block<'a>>>),
+    DefinitionList(Vec<(Vec<Inline<'a>>, Vec<Vec<Block<'a>>>)>),
+    Header(Int, Attr<'a>, Vec<Inline<'a>>),
+    #[serde(deserialize_with = "::lenient::nullary")]
+    HorizontalRule,
+    Table(
+        Attr<'a>,
+        Caption<'a>,
+        Vec<ColSpec>,
+        TableHead<'a>,
+        Vec<TableBody<'a>>,
+        TableFoot<'a>,
+    ),
+    Figure(Attr<'a>, Caption<'a>, Vec<Block<'a>>),
+    Div(Attr<'a>, Vec<Block<'a>>),
+    #[serde(deserialize_with = "::lenient::nullary")]
+    Null,
+}
+
+/// see `super::Inline`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "t", content = "c", bound(deserialize = "'de: 'a"))]
+pub enum Inline<'a> {
+    Str(Text<'a>),
+    Emph(Vec<Inline<'a>>),
+    Underline(Vec<Inline<'a>>),
+    Strong(Vec<Inline<'a>>),
+    Strikeout(Vec<Inline<'a>>),
+    Superscript(Vec<Inline<'a>>),
+    Subscript(Vec<Inline<'a>>),
+    SmallCaps(Vec<Inline<'a>>),
+    Quoted(QuoteType, Vec<Inline<'a>>),
+    Cite(Vec<Citation<'a>>, Vec<Inline<'a>>),
+    Code(Attr<'a>, Text<'a>),
+    #[serde(deserialize_with = "::lenient::nullary")]
+    Space,
+    #[serde(deserialize_with = "::lenient::nullary")]
+    SoftBreak,
+    #[serde(deserialize_with = "::lenient::nullary")]
+    LineBreak,
+    Math(MathType, Text<'a>),
+    RawInline(Format<'a>, Text<'a>),
+    Link(Attr<'a>, Vec<Inline<'a>>, Target<'a>),
+    Image(Attr<'a>, Vec<Inline<'a>>, Target<'a>),
+    Note(Vec<Block<'a>>),
+    Span(Attr<'a>, Vec<Inline<'a>>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(bound(deserialize = "'de: 'a"))]
+pub struct Format<'a>(pub Text<'a>);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(bound(deserialize = "'de: 'a"))]
+#[allow(non_snake_case)]
+pub struct Citation<'a> {
+    pub citationId: Text<'a>,
+    pub citationPrefix: Vec<Inline<'a>>,
+    pub citationSuffix: Vec<Inline<'a>>,
+    pub citationMode: CitationMode,
+    pub citationNoteNum: Int,
+    pub citationHash: Int,
+}
+
+impl<'a> Pandoc<'a> {
+    /// Parses a document borrowing from `json`, checking the pandoc version
+    /// like `super::Pandoc::from_reader`
+    pub fn from_json(json: &'a str) -> Result<Self, Error> {
+        let doc: Pandoc = serde_json::from_str(json)?;
+        super::check_api_version(&doc.pandoc_api_version)?;
+        Ok(doc)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("serialization failed")
+    }
+
+    pub fn into_owned(self) -> super::Pandoc {
+        super::Pandoc {
+            meta: self
+                .meta
+                .into_iter()
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect(),
+            blocks: blocks(self.blocks),
+            pandoc_api_version: self.pandoc_api_version,
+        }
+    }
+}
+
+fn attr(attr: Attr) -> super::Attr {
+    (
+        attr.0.into_owned(),
+        attr.1.into_iter().map(Text::into_owned).collect(),
+        attr.2
+            .into_iter()
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect(),
+    )
+}
+
+fn target(target: Target) -> super::Target {
+    (target.0.into_owned(), target.1.into_owned())
+}
+
+fn inlines(inlines: Vec<Inline>) -> Vec<super::Inline> {
+    inlines.into_iter().map(Inline::into_owned).collect()
+}
+
+fn blocks(blocks: Vec<Block>) -> Vec<super::Block> {
+    blocks.into_iter().map(Block::into_owned).collect()
+}
+
+fn items(items: Vec<Vec<Block>>) -> Vec<Vec<super::Block>> {
+    items.into_iter().map(blocks).collect()
+}
+
+fn caption(caption: Caption) -> super::Caption {
+    (caption.0.map(inlines), blocks(caption.1))
+}
+
+fn rows(rows: Vec<Row>) -> Vec<super::Row> {
+    rows.into_iter()
+        .map(|(row_attr, cells)| {
+            let cells = cells
+                .into_iter()
+                .map(|(cell_attr, alignment, row_span, col_span, content)| {
+                    (
+                        attr(cell_attr),
+                        alignment,
+                        row_span,
+                        col_span,
+                        blocks(content),
+                    )
+                })
+                .collect();
+            (attr(row_attr), cells)
+        })
+        .collect()
+}
+
+impl<'a> MetaValue<'a> {
+    pub fn into_owned(self) -> super::MetaValue {
+        use super::MetaValue as M;
+        match self {
+            MetaValue::MetaMap(map) => M::MetaMap(
+                map.into_iter()
+                    .map(|(key, value)| (key.into_owned(), Box::new(value.into_owned())))
+                    .collect(),
+            ),
+            MetaValue::MetaList(list) => {
+                M::MetaList(list.into_iter().map(MetaValue::into_owned).collect())
+            }
+            MetaValue::MetaBool(b) => M::MetaBool(b),
+            MetaValue::MetaString(s) => M::MetaString(s.into_owned()),
+            MetaValue::MetaInlines(content) => M::MetaInlines(inlines(content)),
+            MetaValue::MetaBlocks(content) => M::MetaBlocks(blocks(content)),
+        }
+    }
+}
+
+impl<'a> Block<'a> {
+    pub fn into_owned(self) -> super::Block {
+        use super::Block as B;
+        match self {
+            Block::Plain(content) => B::Plain(inlines(content)),
+            Block::Para(content) => B::Para(inlines(content)),
+            Block::LineBlock(lines) => B::LineBlock(lines.into_iter().map(inlines).collect()),
+            Block::CodeBlock(a, code) => B::CodeBlock(attr(a), code.into_owned()),
+            Block::RawBlock(format, content) => {
+                B::RawBlock(format.into_owned(), content.into_owned())
+            }
+            Block::BlockQuote(content) => B::BlockQuote(blocks(content)),
+            Block::OrderedList(list_attributes, content) => {
+                B::OrderedList(list_attributes, items(content))
+            }
+            Block::BulletList(content) => B::BulletList(items(content)),
+            Block::DefinitionList(content) => B::DefinitionList(
+                content
+                    .into_iter()
+                    .map(|(term, definitions)| (inlines(term), items(definitions)))
+                    .collect(),
+            ),
+            Block::Header(level, a, content) => B::Header(level, attr(a), inlines(content)),
+            Block::HorizontalRule => B::HorizontalRule,
+            Block::Table(a, c, specs, head, bodies, foot) => B::Table(
+                attr(a),
+                caption(c),
+                specs,
+                (attr(head.0), rows(head.1)),
+                bodies
+                    .into_iter()
+                    .map(|(body_attr, head_columns, head_rows, body_rows)| {
+                        (
+                            attr(body_attr),
+                            head_columns,
+                            rows(head_rows),
+                            rows(body_rows),
+                        )
+                    })
+                    .collect(),
+                (attr(foot.0), rows(foot.1)),
+            ),
+            Block::Figure(a, c, content) => B::Figure(attr(a), caption(c), blocks(content)),
+            Block::Div(a, content) => B::Div(attr(a), blocks(content)),
+            Block::Null => B::Null,
+        }
+    }
+}
+
+impl<'a> Inline<'a> {
+    pub fn into_owned(self) -> super::Inline {
+        use super::Inline as I;
+        match self {
+            Inline::Str(s) => I::Str(s.into_owned()),
+            Inline::Emph(content) => I::Emph(inlines(content)),
+            Inline::Underline(content) => I::Underline(inlines(content)),
+            Inline::Strong(content) => I::Strong(inlines(content)),
+            Inline::Strikeout(content) => I::Strikeout(inlines(content)),
+            Inline::Superscript(content) => I::Superscript(inlines(content)),
+            Inline::Subscript(content) => I::Subscript(inlines(content)),
+            Inline::SmallCaps(content) => I::SmallCaps(inlines(content)),
+            Inline::Quoted(quote_type, content) => I::Quoted(quote_type, inlines(content)),
+            Inline::Cite(citations, content) => I::Cite(
+                citations.into_iter().map(Citation::into_owned).collect(),
+                inlines(content),
+            ),
+            Inline::Code(a, code) => I::Code(attr(a), code.into_owned()),
+            Inline::Space => I::Space,
+            Inline::SoftBreak => I::SoftBreak,
+            Inline::LineBreak => I::LineBreak,
+            Inline::Math(math_type, tex) => I::Math(math_type, tex.into_owned()),
+            Inline::RawInline(format, content) => {
+                I::RawInline(format.into_owned(), content.into_owned())
+            }
+            Inline::Link(a, content, t) => I::Link(attr(a), inlines(content), target(t)),
+            Inline::Image(a, content, t) => I::Image(attr(a), inlines(content), target(t)),
+            Inline::Note(content) => I::Note(blocks(content)),
+            Inline::Span(a, content) => I::Span(attr(a), inlines(content)),
+        }
+    }
+}
+
+impl<'a> Format<'a> {
+    pub fn into_owned(self) -> super::Format {
+        super::Format(self.0.into_owned())
+    }
+}
+
+impl<'a> Citation<'a> {
+    pub fn into_owned(self) -> super::Citation {
+        super::Citation {
+            citationId: self.citationId.into_owned(),
+            citationPrefix: inlines(self.citationPrefix),
+            citationSuffix: inlines(self.citationSuffix),
+            citationMode: self.citationMode,
+            citationNoteNum: self.citationNoteNum,
+            citationHash: self.citationHash,
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use super::*;
+
+/// Splits text into `Str` and `Space` inlines the way pandoc's markdown reader
+/// does. Runs of whitespace become a single `Space`, or a `SoftBreak` if they
+/// contain a newline. Leading and trailing whitespace is kept.
+pub fn inlines_from_str(text: &str) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    let mut word = String::new();
+    let mut space: Option<Inline> = None;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !word.is_empty() {
+                inlines.push(Inline::Str(word.split_off(0)));
+            }
+            if c == '\n' || c == '\r' {
+                space = Some(Inline::SoftBreak);
+            } else if space.is_none() {
+                space = Some(Inline::Space);
+            }
+        } else {
+            if let Some(space) = space.take() {
+                inlines.push(space);
+            }
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        inlines.push(Inline::Str(word));
+    }
+    if let Some(space) = space {
+        inlines.push(space);
+    }
+    inlines
+}
+
+/// Joins inlines back into text, the inverse of `inlines_from_str`.
+/// Inlines other than `Str`, `Space` and `SoftBreak` contribute their `stringify`d text.
+pub fn inlines_to_string(inlines: &[Inline]) -> String {
+    let mut text = String::new();
+    for inline in inlines {
+        match *inline {
+            Inline::Str(ref s) => text.push_str(s),
+            Inline::Space => text.push(' '),
+            Inline::SoftBreak => text.push('\n'),
+            ref other => text.push_str(&stringify(other)),
+        }
+    }
+    text
+}
+
+fn class_attr(class: &str) -> Attr {
+    if class.is_empty() {
+        Attr::default()
+    } else {
+        (String::new(), vec![class.to_owned()], Vec::new())
+    }
+}
+
+impl Inline {
+    /// A single `Str`, without splitting on whitespace
+    pub fn str<S: Into<String>>(text: S) -> Inline {
+        Inline::Str(text.into())
+    }
+
+    /// Inline code, optionally tagged with a language class (pass `""` for none)
+    pub fn code<S: Into<String>>(language: &str, code: S) -> Inline {
+        Inline::Code(class_attr(language), code.into())
+    }
+
+    pub fn emph(content: Vec<Inline>) -> Inline {
+        Inline::Emph(content)
+    }
+
+    pub fn strong(content: Vec<Inline>) -> Inline {
+        Inline::Strong(content)
+    }
+
+    /// Hyperlink with an empty title
+    pub fn link<S: Into<String>>(content: Vec<Inline>, url: S) -> Inline {
+        Inline::Link(Attr::default(), content, (url.into(), String::new()))
+    }
+
+    /// Image with an empty title
+    pub fn image<S: Into<String>>(alt: Vec<Inline>, url: S) -> Inline {
+        Inline::Image(Attr::default(), alt, (url.into(), String::new()))
+    }
+
+    /// Span carrying a single class (pass `""` for none)
+    pub fn span(class: &str, content: Vec<Inline>) -> Inline {
+        Inline::Span(class_attr(class), content)
+    }
+
+    /// `Cite` with the markdown pandoc would read the citations from as
+    /// content, like `[see @doe99, p. 33; -@smith04]` or `@doe99 [p. 33]`
+    pub fn cite(citations: Vec<Citation>) -> Inline {
+        let text = match citations[..] {
+            [ref citation] if citation.citationMode == CitationMode::AuthorInText => {
+                let suffix = inlines_to_string(&citation.citationSuffix);
+                let suffix = suffix.trim_start_matches([',', ' ']);
+                if suffix.is_empty() {
+                    format!("@{}", citation.citationId)
+                } else {
+                    format!("@{} [{}]", citation.citationId, suffix)
+                }
+            }
+            _ => {
+                let items: Vec<String> = citations.iter().map(citation_text).collect();
+                format!("[{}]", items.join("; "))
+            }
+        };
+        Inline::Cite(citations, inlines_from_str(&text))
+    }
+}
+
+/// a citation as written in a bracketed markdown citation
+fn citation_text(citation: &Citation) -> String {
+    let mut text = inlines_to_string(&citation.citationPrefix);
+    if !text.is_empty() && !text.ends_with(' ') {
+        text.push(' ');
+    }
+    if citation.citationMode == CitationMode::SuppressAuthor {
+        text.push('-');
+    }
+    text.push('@');
+    text.push_str(&citation.citationId);
+    text.push_str(&inlines_to_string(&citation.citationSuffix));
+    text
+}
+
+impl Block {
+    pub fn plain(content: Vec<Inline>) -> Block {
+        Block::Plain(content)
+    }
+
+    pub fn para(content: Vec<Inline>) -> Block {
+        Block::Para(content)
+    }
+
+    /// Header with empty attributes, the title is split into `Str` and `Space` inlines
+    pub fn header<S: AsRef<str>>(level: Int, title: S) -> Block {
+        Block::Header(level, Attr::default(), inlines_from_str(title.as_ref()))
+    }
+
+    pub fn code_block<S: Into<String>>(code: S) -> Block {
+        Block::CodeBlock(Attr::default(), code.into())
+    }
+
+    /// Code block tagged with a single class, usually the language
+    pub fn code_block_with_class<S: Into<String>>(class: &str, code: S) -> Block {
+        Block::CodeBlock(class_attr(class), code.into())
+    }
+
+    /// Div carrying a single class (pass `""` for none)
+    pub fn div(class: &str, content: Vec<Block>) -> Block {
+        Block::Div(class_attr(class), content)
+    }
+
+    pub fn bullet_list(items: Vec<Vec<Block>>) -> Block {
+        Block::BulletList(items)
+    }
+}
+
+impl Citation {
+    /// A `NormalCitation` of `id` without prefix or suffix. The note number
+    /// and hash are 0, as in the output of pandoc's readers.
+    pub fn new<S: Into<String>>(id: S) -> Citation {
+        Citation {
+            citationId: id.into(),
+            citationPrefix: Vec::new(),
+            citationSuffix: Vec::new(),
+            citationMode: CitationMode::NormalCitation,
+            citationNoteNum: 0,
+            citationHash: 0,
+        }
+    }
+
+    /// Text before the citation, like "see"
+    pub fn prefix<C: IntoInlines>(mut self, prefix: C) -> Self {
+        self.citationPrefix = to_inlines(prefix);
+        self
+    }
+
+    /// Text after the citation, usually starting with a comma, like ", p. 33"
+    pub fn suffix<C: IntoInlines>(mut self, suffix: C) -> Self {
+        self.citationSuffix = to_inlines(suffix);
+        self
+    }
+
+    pub fn mode(mut self, mode: CitationMode) -> Self {
+        self.citationMode = mode;
+        self
+    }
+
+    /// `AuthorInText`, as in "@doe99 says"
+    pub fn author_in_text(self) -> Self {
+        self.mode(CitationMode::AuthorInText)
+    }
+
+    /// `SuppressAuthor`, as in "Doe says [-@doe99]"
+    pub fn suppress_author(self) -> Self {
+        self.mode(CitationMode::SuppressAuthor)
+    }
+
+    /// The number of the footnote the citation is in, for note styles
+    pub fn note_num(mut self, note_num: Int) -> Self {
+        self.citationNoteNum = note_num;
+        self
+    }
+}
+
+/// Values that the `inlines!` macro accepts
+pub trait IntoInlines {
+    fn extend_inlines(self, inlines: &mut Vec<Inline>);
+}
+
+impl IntoInlines for &str {
+    fn extend_inlines(self, inlines: &mut Vec<Inline>) {
+        inlines.extend(inlines_from_str(self))
+    }
+}
+
+impl IntoInlines for String {
+    fn extend_inlines(self, inlines: &mut Vec<Inline>) {
+        inlines.extend(inlines_from_str(&self))
+    }
+}
+
+impl IntoInlines for Inline {
+    fn extend_inlines(self, inlines: &mut Vec<Inline>) {
+        inlines.push(self)
+    }
+}
+
+impl IntoInlines for Vec<Inline> {
+    fn extend_inlines(self, inlines: &mut Vec<Inline>) {
+        inlines.extend(self)
+    }
+}
+
+/// Values that the `blocks!` macro accepts
+pub trait IntoBlocks {
+    fn extend_blocks(self, blocks: &mut Vec<Block>);
+}
+
+impl IntoBlocks for Block {
+    fn extend_blocks(self, blocks: &mut Vec<Block>) {
+        blocks.push(self)
+    }
+}
+
+impl IntoBlocks for Vec<Block> {
+    fn extend_blocks(self, blocks: &mut Vec<Block>) {
+        blocks.extend(self)
+    }
+}
+
+fn to_inlines<I: IntoInlines>(content: I) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    content.extend_inlines(&mut inlines);
+    inlines
+}
+
+impl Pandoc {
+    /// An empty document of the api version this crate implements
+    pub fn new() -> Self {
+        Pandoc {
+            meta: Meta::new(),
+            blocks: Vec::new(),
+            pandoc_api_version: API_VERSION.to_vec(),
+        }
+    }
+
+    pub fn builder() -> PandocBuilder {
+        PandocBuilder::default()
+    }
+}
+
+impl Default for Pandoc {
+    fn default() -> Self {
+        Pandoc::new()
+    }
+}
+
+/// Builds a document from scratch, filling in the metadata keys pandoc's
+/// templates use. Text arguments accept anything `inlines!` does.
+///
+/// ```
+/// # use pandoc_ast::*;
+/// let doc = Pandoc::builder()
+///     .title("Quarterly report")
+///     .author("Ada")
+///     .author("Grace")
+///     .block(Block::header(1, "Summary"))
+///     .block(para!["All good."])
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PandocBuilder {
+    doc: Pandoc,
+}
+
+impl PandocBuilder {
+    pub fn title<I: IntoInlines>(self, title: I) -> Self {
+        self.meta("title", MetaValue::MetaInlines(to_inlines(title)))
+    }
+
+    pub fn subtitle<I: IntoInlines>(self, subtitle: I) -> Self {
+        self.meta("subtitle", MetaValue::MetaInlines(to_inlines(subtitle)))
+    }
+
+    /// Adds an author, `author` becomes a list if there are several
+    pub fn author<I: IntoInlines>(mut self, author: I) -> Self {
+        let author = MetaValue::MetaInlines(to_inlines(author));
+        let authors = match self.doc.meta.remove("author") {
+            None => author,
+            Some(MetaValue::MetaList(mut authors)) => {
+                authors.push(author);
+                MetaValue::MetaList(authors)
+            }
+            Some(first) => MetaValue::MetaList(vec![first, author]),
+        };
+        self.meta("author", authors)
+    }
+
+    pub fn date<I: IntoInlines>(self, date: I) -> Self {
+        self.meta("date", MetaValue::MetaInlines(to_inlines(date)))
+    }
+
+    /// Sets any metadata entry, replacing an earlier value
+    pub fn meta<K: Into<String>, V: Into<MetaValue>>(mut self, key: K, value: V) -> Self {
+        self.doc.meta.insert(key.into(), value.into());
+        self
+    }
+
+    /// Appends a block or a list of blocks
+    pub fn block<B: IntoBlocks>(mut self, block: B) -> Self {
+        block.extend_blocks(&mut self.doc.blocks);
+        self
+    }
+
+    pub fn build(self) -> Pandoc {
+        self.doc
+    }
+}
@@ -0,0 +1,249 @@
+//! Tracked changes as produced by pandoc's docx reader with
+//! `--track-changes=all`
+//!
+//! Insertions and deletions are `Span`s with the class `insertion` or
+//! `deletion` around the changed text, comments an empty `Span` with the
+//! class `comment-end` after the commented text and a `Span` with the class
+//! `comment-start` holding the comment before it, both with the same
+//! identifier. Inserted and deleted paragraph breaks are empty `Span`s with
+//! the class `paragraph-insertion` or `paragraph-deletion` at the end of the
+//! paragraph. All of them have `author` and `date` attributes.
+
+use super::*;
+use path::walk_with_paths;
+use transform::{Action, TraversalOrder};
+
+const INSERTION: &str = "insertion";
+const DELETION: &str = "deletion";
+const COMMENT_START: &str = "comment-start";
+const COMMENT_END: &str = "comment-end";
+const PARAGRAPH_INSERTION: &str = "paragraph-insertion";
+const PARAGRAPH_DELETION: &str = "paragraph-deletion";
+
+/// What a `Change` records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Insertion,
+    Deletion,
+    ParagraphInsertion,
+    ParagraphDeletion,
+    Comment,
+}
+
+impl ChangeKind {
+    fn class(self) -> &'static str {
+        match self {
+            ChangeKind::Insertion => INSERTION,
+            ChangeKind::Deletion => DELETION,
+            ChangeKind::ParagraphInsertion => PARAGRAPH_INSERTION,
+            ChangeKind::ParagraphDeletion => PARAGRAPH_DELETION,
+            ChangeKind::Comment => COMMENT_START,
+        }
+    }
+
+    fn of(attr: &Attr) -> Option<ChangeKind> {
+        [
+            ChangeKind::Insertion,
+            ChangeKind::Deletion,
+            ChangeKind::ParagraphInsertion,
+            ChangeKind::ParagraphDeletion,
+            ChangeKind::Comment,
+        ]
+        .iter()
+        .cloned()
+        .find(|kind| attr.1.iter().any(|class| class == kind.class()))
+    }
+}
+
+/// A change or comment of a document, see `report`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// the identifier of a comment, otherwise usually empty
+    pub id: String,
+    pub author: Option<String>,
+    pub date: Option<String>,
+    /// the inserted or deleted text, or the text of a comment
+    pub text: String,
+    pub path: AstPath,
+}
+
+fn attr(id: &str, kind: ChangeKind, author: &str, date: &str) -> Attr {
+    (
+        id.to_owned(),
+        vec![kind.class().to_owned()],
+        vec![
+            ("author".to_owned(), author.to_owned()),
+            ("date".to_owned(), date.to_owned()),
+        ],
+    )
+}
+
+/// `content` marked as inserted by `author` at `date`, e.g.
+/// `2024-01-31T12:00:00Z`
+pub fn insertion(author: &str, date: &str, content: Vec<Inline>) -> Inline {
+    Inline::Span(attr("", ChangeKind::Insertion, author, date), content)
+}
+
+/// `content` marked as deleted by `author` at `date`
+pub fn deletion(author: &str, date: &str, content: Vec<Inline>) -> Inline {
+    Inline::Span(attr("", ChangeKind::Deletion, author, date), content)
+}
+
+/// The `commented` inlines between the start and the end of the comment
+/// `text` with the identifier `id`
+pub fn comment(
+    id: &str,
+    author: &str,
+    date: &str,
+    text: Vec<Inline>,
+    commented: Vec<Inline>,
+) -> Vec<Inline> {
+    let mut inlines = Vec::with_capacity(commented.len() + 2);
+    inlines.push(Inline::Span(
+        attr(id, ChangeKind::Comment, author, date),
+        text,
+    ));
+    inlines.extend(commented);
+    inlines.push(Inline::Span(
+        (id.to_owned(), vec![COMMENT_END.to_owned()], Vec::new()),
+        Vec::new(),
+    ));
+    inlines
+}
+
+/// All changes and comments of `doc` in document order, with the paths of
+/// their spans
+pub fn report(doc: &Pandoc) -> Vec<Change> {
+    let mut changes = Vec::new();
+    walk_with_paths(doc, |path, node| {
+        let (attr, content) = match node {
+            Node::Inline(Inline::Span(attr, content)) => (attr, content),
+            _ => return,
+        };
+        let kind = match ChangeKind::of(attr) {
+            Some(kind) => kind,
+            None => return,
+        };
+        let value = |key: &str| {
+            attr.2
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.clone())
+        };
+        changes.push(Change {
+            kind,
+            id: attr.0.clone(),
+            author: value("author"),
+            date: value("date"),
+            text: stringify(content),
+            path: path.clone(),
+        });
+    });
+    changes
+}
+
+/// Accepts all insertions and deletions below `node`: inserted text is
+/// kept without its span, deleted text is removed. Paragraphs with a
+/// deleted break are joined with the following one. Comments are kept.
+pub fn accept_all<T: MutVisitable>(node: &mut T) {
+    resolve(node, ChangeKind::Insertion, ChangeKind::ParagraphDeletion);
+}
+
+/// Rejects all insertions and deletions below `node`: deleted text is
+/// kept without its span, inserted text is removed. Paragraphs with an
+/// inserted break are joined with the following one. Comments are kept.
+pub fn reject_all<T: MutVisitable>(node: &mut T) {
+    resolve(node, ChangeKind::Deletion, ChangeKind::ParagraphInsertion);
+}
+
+/// Removes the start and end spans of all comments below `node`, keeping
+/// the commented text
+pub fn strip_comments<T: MutVisitable>(node: &mut T) {
+    transform::inlines(node, TraversalOrder::BottomUp, |inline| match *inline {
+        Inline::Span(ref attr, _)
+            if attr
+                .1
+                .iter()
+                .any(|class| class == COMMENT_START || class == COMMENT_END) =>
+        {
+            Action::Delete
+        }
+        _ => Action::Keep,
+    });
+}
+
+fn resolve<T: MutVisitable>(node: &mut T, keep: ChangeKind, join: ChangeKind) {
+    transform::inlines(node, TraversalOrder::BottomUp, |inline| {
+        let kind = match *inline {
+            Inline::Span(ref attr, _) => ChangeKind::of(attr),
+            _ => None,
+        };
+        match (kind, inline) {
+            (Some(kind), &mut Inline::Span(_, ref mut content)) if kind == keep => {
+                Action::ReplaceMany(content.split_off(0))
+            }
+            (Some(ChangeKind::Insertion), _) | (Some(ChangeKind::Deletion), _) => Action::Delete,
+            _ => Action::Keep,
+        }
+    });
+    node.accept_mut(&mut ParagraphJoiner { join });
+}
+
+/// Removes the marks of paragraph breaks and joins the paragraphs with
+/// marks of the kind `join` with the following ones
+struct ParagraphJoiner {
+    join: ChangeKind,
+}
+
+fn paragraph_inlines(block: &mut Block) -> Option<&mut Vec<Inline>> {
+    match *block {
+        Block::Para(ref mut inlines) | Block::Plain(ref mut inlines) => Some(inlines),
+        _ => None,
+    }
+}
+
+impl ParagraphJoiner {
+    /// Removes the marks from `inlines` and returns whether one of them
+    /// joins the paragraph with the next
+    fn take_marks(&self, inlines: &mut Vec<Inline>) -> bool {
+        let mut join = false;
+        inlines.retain(|inline| {
+            let kind = match *inline {
+                Inline::Span(ref attr, _) => ChangeKind::of(attr),
+                _ => None,
+            };
+            match kind {
+                Some(ChangeKind::ParagraphInsertion) | Some(ChangeKind::ParagraphDeletion) => {
+                    join |= kind == Some(self.join);
+                    false
+                }
+                _ => true,
+            }
+        });
+        join
+    }
+}
+
+impl MutVisitor for ParagraphJoiner {
+    fn visit_vec_block(&mut self, vec_block: &mut Vec<Block>) {
+        self.walk_vec_block(vec_block);
+        let mut result: Vec<Block> = Vec::with_capacity(vec_block.len());
+        let mut join = false;
+        for mut block in vec_block.drain(..) {
+            let joins_next =
+                paragraph_inlines(&mut block).is_some_and(|inlines| self.take_marks(inlines));
+            if join {
+                let previous = result.last_mut().and_then(paragraph_inlines);
+                if let (Some(previous), Some(inlines)) = (previous, paragraph_inlines(&mut block)) {
+                    previous.append(inlines);
+                    join = joins_next;
+                    continue;
+                }
+            }
+            result.push(block);
+            join = joins_next;
+        }
+        *vec_block = result;
+    }
+}
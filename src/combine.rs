@@ -0,0 +1,154 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+/// How `Pandoc::concat` combines documents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// how metadata fields present in several documents are resolved,
+    /// going from the first document to the last
+    pub meta: MergeStrategy,
+    /// the amount to shift the headers of each document by, see
+    /// `transform::shift_headers`. Documents without an entry aren't shifted.
+    pub header_shifts: Vec<Int>,
+    /// whether to rename identifiers already used by an earlier document
+    pub unique_identifiers: bool,
+}
+
+impl Default for MergeOptions {
+    /// Keeps the metadata of the first document defining a field and makes
+    /// identifiers unique
+    fn default() -> Self {
+        MergeOptions {
+            meta: MergeStrategy::LeftBias,
+            header_shifts: Vec::new(),
+            unique_identifiers: true,
+        }
+    }
+}
+
+/// renames identifiers and the internal links pointing to them
+struct Renamer<'a>(&'a HashMap<String, String>);
+
+impl<'a> MutVisitor for Renamer<'a> {
+    fn visit_attr(&mut self, attr: &mut Attr) {
+        if let Some(renamed) = self.0.get(&attr.0) {
+            attr.0 = renamed.clone();
+        }
+    }
+    fn visit_inline(&mut self, inline: &mut Inline) {
+        if let Inline::Link(_, _, (ref mut url, _)) = *inline {
+            if let Some(renamed) = url.strip_prefix('#').and_then(|id| self.0.get(id)) {
+                *url = format!("#{}", renamed);
+            }
+        }
+        self.walk_inline(inline)
+    }
+}
+
+fn has_endnotes(doc: &Pandoc) -> bool {
+    doc.blocks.iter().any(|block| match *block {
+        Block::Div((_, ref classes, _), _) => classes.iter().any(|class| class == "footnotes"),
+        _ => false,
+    })
+}
+
+impl Pandoc {
+    /// Appends the blocks of `docs` and merges their metadata.
+    ///
+    /// Identifiers colliding with those of an earlier document get a number
+    /// appended like `ident::unique_identifier` does, with the links within
+    /// the document updated. Documents whose footnotes were turned into
+    /// endnotes with `notes::to_endnotes` get a single, renumbered list of
+    /// endnotes. The api version is taken from the first document.
+    pub fn concat(docs: Vec<Pandoc>, options: MergeOptions) -> Pandoc {
+        let mut result = Pandoc {
+            meta: Meta::new(),
+            blocks: Vec::new(),
+            pandoc_api_version: match docs.first() {
+                Some(doc) => doc.pandoc_api_version.clone(),
+                None => API_VERSION.to_vec(),
+            },
+        };
+        let mut used = HashSet::new();
+        let mut endnotes = false;
+        for (i, mut doc) in docs.into_iter().enumerate() {
+            if has_endnotes(&doc) {
+                notes::from_endnotes(&mut doc);
+                endnotes = true;
+            }
+            if let Some(&delta) = options.header_shifts.get(i) {
+                transform::shift_headers(&mut doc, delta);
+            }
+            if options.unique_identifiers {
+                let own = ident::used_identifiers(&doc);
+                let mut taken: HashSet<_> = used.union(&own).cloned().collect();
+                let renamed: HashMap<_, _> = own
+                    .iter()
+                    .filter(|&id| used.contains(id))
+                    .map(|id| (id.clone(), ident::unique_identifier(id.clone(), &mut taken)))
+                    .collect();
+                doc.accept_mut(&mut Renamer(&renamed));
+                used.extend(ident::used_identifiers(&doc));
+            }
+            if i == 0 {
+                result.meta = doc.meta;
+            } else {
+                result.meta.merge_with(doc.meta, options.meta);
+            }
+            result.blocks.extend(doc.blocks);
+        }
+        if endnotes {
+            notes::to_endnotes(&mut result);
+        }
+        result
+    }
+
+    /// Splits the document before every header of level `level` or lower,
+    /// the inverse of `concat`. Every part gets the metadata of the document
+    /// with the `title` replaced by the text of the header it starts with.
+    /// Blocks before the first such header form a part with the original
+    /// metadata if there are any. Endnotes, see `notes::to_endnotes`, are
+    /// distributed to the parts referencing them.
+    pub fn split_at_level(mut self, level: Int) -> Vec<Pandoc> {
+        let endnotes = has_endnotes(&self);
+        if endnotes {
+            notes::from_endnotes(&mut self);
+        }
+        let mut parts: Vec<Vec<Block>> = vec![Vec::new()];
+        for block in self.blocks {
+            if let Block::Header(l, ..) = block {
+                if l <= level {
+                    parts.push(Vec::new());
+                }
+            }
+            parts
+                .last_mut()
+                .expect("there is always a part")
+                .push(block);
+        }
+        if parts[0].is_empty() {
+            parts.remove(0);
+        }
+        let (meta, version) = (self.meta, self.pandoc_api_version);
+        parts
+            .into_iter()
+            .map(|blocks| {
+                let mut meta = meta.clone();
+                if let Some(Block::Header(l, _, ref title)) = blocks.first() {
+                    if *l <= level {
+                        meta.insert("title".to_owned(), MetaValue::MetaInlines(title.clone()));
+                    }
+                }
+                let mut part = Pandoc {
+                    meta,
+                    blocks,
+                    pandoc_api_version: version.clone(),
+                };
+                if endnotes {
+                    notes::to_endnotes(&mut part);
+                }
+                part
+            })
+            .collect()
+    }
+}
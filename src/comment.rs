@@ -0,0 +1,136 @@
+//! Review comments written as html comments
+//!
+//! Raw html blocks and inlines may hold comments like
+//! `<!-- TODO(alice): check the numbers -->`. `collect` parses them into
+//! `Comment`s for a report, `strip` removes them for a clean copy. Comments
+//! start with an optional tag of uppercase letters like `TODO` or `FIXME`,
+//! optionally followed by the author in parentheses, and a colon.
+//!
+//! ```
+//! # use pandoc_ast::*;
+//! let mut doc = Pandoc::new();
+//! let html = "<!-- FIXME(bob): wrong year -->".to_owned();
+//! doc.blocks.push(Block::RawBlock(Format("html".to_owned()), html));
+//! let comments = comment::collect(&doc);
+//! assert_eq!(comments[0].tag, "FIXME");
+//! assert_eq!(comments[0].author.as_deref(), Some("bob"));
+//! comment::strip(&mut doc);
+//! assert!(doc.blocks.is_empty());
+//! ```
+
+use super::*;
+use path::walk_with_paths;
+use transform::{Action, TraversalOrder};
+
+/// A comment and the path of the raw node holding it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Comment {
+    pub author: Option<String>,
+    /// the tag like `TODO`, empty for comments without one
+    pub tag: String,
+    /// the trimmed text after the tag
+    pub text: String,
+    pub path: AstPath,
+}
+
+impl Comment {
+    /// Parses the content of a comment, between `<!--` and `-->`
+    fn parse(content: &str, path: &AstPath) -> Comment {
+        let content = content.trim();
+        let tag_len = content
+            .find(|c: char| !c.is_ascii_uppercase())
+            .unwrap_or(content.len());
+        let (tag, rest) = content.split_at(tag_len);
+        let (author, rest) = match rest.strip_prefix('(').and_then(|rest| {
+            let close = rest.find(')')?;
+            Some((rest[..close].trim(), &rest[close + 1..]))
+        }) {
+            Some((author, rest)) => (Some(author.to_owned()), rest),
+            None => (None, rest),
+        };
+        match rest.strip_prefix(':') {
+            Some(text) if !tag.is_empty() => Comment {
+                author: author.filter(|author| !author.is_empty()),
+                tag: tag.to_owned(),
+                text: text.trim().to_owned(),
+                path: path.clone(),
+            },
+            _ => Comment {
+                author: None,
+                tag: String::new(),
+                text: content.to_owned(),
+                path: path.clone(),
+            },
+        }
+    }
+}
+
+/// The contents of the comments in `html` and the html around them.
+/// Unterminated comments are not comments.
+fn split(html: &str) -> (Vec<&str>, String) {
+    let mut comments = Vec::new();
+    let mut rest = String::new();
+    let mut html = html;
+    while let Some(start) = html.find("<!--") {
+        let end = match html[start + 4..].find("-->") {
+            Some(end) => start + 4 + end,
+            None => break,
+        };
+        comments.push(&html[start + 4..end]);
+        rest.push_str(&html[..start]);
+        html = &html[end + 3..];
+    }
+    rest.push_str(html);
+    (comments, rest)
+}
+
+/// All comments in raw html blocks and inlines of `doc`, in document order
+pub fn collect(doc: &Pandoc) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    walk_with_paths(doc, |path, node| {
+        let html = match node {
+            Node::Block(&Block::RawBlock(ref format, ref html))
+            | Node::Inline(&Inline::RawInline(ref format, ref html))
+                if format.is_html() =>
+            {
+                html
+            }
+            _ => return,
+        };
+        for content in split(html).0 {
+            comments.push(Comment::parse(content, path));
+        }
+    });
+    comments
+}
+
+/// Removes the comments from the content of a raw node, deleting the node
+/// if only whitespace is left
+fn strip_raw<T>(format: &Format, html: &mut String) -> Action<T> {
+    if !format.is_html() {
+        return Action::Keep;
+    }
+    let (comments, rest) = split(html);
+    if comments.is_empty() {
+        Action::Keep
+    } else if rest.trim().is_empty() {
+        Action::Delete
+    } else {
+        *html = rest;
+        Action::Keep
+    }
+}
+
+/// Removes all comments from raw html blocks and inlines of `doc`. Raw
+/// nodes left with nothing but whitespace are deleted, which changes the
+/// paths of the nodes after them, so `collect` before stripping.
+pub fn strip(doc: &mut Pandoc) {
+    transform::inlines(doc, TraversalOrder::BottomUp, |inline| match *inline {
+        Inline::RawInline(ref format, ref mut html) => strip_raw(format, html),
+        _ => Action::Keep,
+    });
+    transform::blocks(doc, TraversalOrder::BottomUp, |block| match *block {
+        Block::RawBlock(ref format, ref mut html) => strip_raw(format, html),
+        _ => Action::Keep,
+    });
+}
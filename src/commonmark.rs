@@ -0,0 +1,402 @@
+//! Conversion of CommonMark parsed by pulldown-cmark into pandoc blocks
+//!
+//! Markdown read this way can be handed to pandoc for writing, or spliced
+//! into a document from a filter:
+//!
+//! ```
+//! # #[macro_use] extern crate pandoc_ast;
+//! # use pandoc_ast::commonmark;
+//! # fn main() {
+//! let blocks = commonmark::from_markdown("Hello *world*");
+//! assert_eq!(blocks, vec![para!["Hello ", emph!["world"]]]);
+//! # }
+//! ```
+//!
+//! The events have to come from the pulldown-cmark version this crate
+//! depends on. Footnote references are replaced by a `Note` with the
+//! content of their definition, references without definition are kept as
+//! text. Metadata blocks are skipped.
+
+use super::*;
+use pulldown_cmark::{
+    BlockQuoteKind, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd,
+};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::slice;
+use table::TableBuilder;
+
+type Events<'e, 'a> = slice::Iter<'e, Event<'a>>;
+
+struct Converter<'e, 'a> {
+    events: &'e [Event<'a>],
+    /// ranges of the events between the start and end of each footnote
+    /// definition, by label
+    notes: HashMap<String, Range<usize>>,
+    /// labels of the footnotes being converted, against self references
+    active: Vec<String>,
+}
+
+/// Inlines waiting for the next block, for content of tight list items and
+/// table cells that is not wrapped in paragraphs
+#[derive(Default)]
+struct Pending {
+    inlines: Vec<Inline>,
+    text: String,
+}
+
+impl Pending {
+    fn flush_text(&mut self) {
+        if !self.text.is_empty() {
+            self.inlines.extend(inlines_from_str(&self.text));
+            self.text.clear();
+        }
+    }
+
+    fn push(&mut self, inline: Inline) {
+        self.flush_text();
+        self.inlines.push(inline);
+    }
+
+    fn take(&mut self) -> Vec<Inline> {
+        self.flush_text();
+        std::mem::take(&mut self.inlines)
+    }
+
+    fn flush_plain(&mut self, blocks: &mut Vec<Block>) {
+        let inlines = self.take();
+        if !inlines.is_empty() {
+            blocks.push(Block::Plain(inlines));
+        }
+    }
+}
+
+fn text(events: &mut Events) -> String {
+    let mut text = String::new();
+    for event in events {
+        match *event {
+            Event::End(_) => break,
+            Event::Text(ref s) | Event::Html(ref s) => text.push_str(s),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn attr(id: Option<&str>, classes: Vec<String>, attributes: Vec<(String, String)>) -> Attr {
+    (id.unwrap_or_default().to_owned(), classes, attributes)
+}
+
+impl<'e, 'a> Converter<'e, 'a> {
+    fn new(events: &'e [Event<'a>]) -> Self {
+        let mut notes = HashMap::new();
+        let mut start = None;
+        for (i, event) in events.iter().enumerate() {
+            match *event {
+                Event::Start(Tag::FootnoteDefinition(ref label)) => {
+                    start = Some((label.to_string(), i + 1))
+                }
+                Event::End(TagEnd::FootnoteDefinition) => {
+                    if let Some((label, start)) = start.take() {
+                        notes.entry(label).or_insert(start..i);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Converter {
+            events,
+            notes,
+            active: Vec::new(),
+        }
+    }
+
+    fn note(&mut self, label: &str) -> Option<Inline> {
+        let range = self.notes.get(label)?.clone();
+        if self.active.iter().any(|active| active == label) {
+            return None;
+        }
+        self.active.push(label.to_owned());
+        let events = self.events;
+        let blocks = self.blocks(&mut events[range].iter());
+        self.active.pop();
+        Some(Inline::Note(blocks))
+    }
+
+    /// Converts inline events up to the end of the enclosing tag
+    fn inlines(&mut self, events: &mut Events<'e, 'a>) -> Vec<Inline> {
+        let mut pending = Pending::default();
+        while let Some(event) = events.next() {
+            if let Event::End(_) = *event {
+                break;
+            }
+            self.inline(event, events, &mut pending);
+        }
+        pending.take()
+    }
+
+    fn inline(&mut self, event: &Event, events: &mut Events<'e, 'a>, pending: &mut Pending) {
+        match *event {
+            Event::Text(ref s) => pending.text.push_str(s),
+            Event::Code(ref s) => pending.push(Inline::Code(Attr::default(), s.to_string())),
+            Event::InlineMath(ref s) => {
+                pending.push(Inline::Math(MathType::InlineMath, s.to_string()))
+            }
+            Event::DisplayMath(ref s) => {
+                pending.push(Inline::Math(MathType::DisplayMath, s.to_string()))
+            }
+            Event::Html(ref s) | Event::InlineHtml(ref s) => {
+                pending.push(Inline::RawInline(Format("html".to_owned()), s.to_string()))
+            }
+            Event::FootnoteReference(ref label) => match self.note(label) {
+                Some(note) => pending.push(note),
+                None => pending.text.push_str(&format!("[^{}]", label)),
+            },
+            Event::SoftBreak => pending.push(Inline::SoftBreak),
+            Event::HardBreak => pending.push(Inline::LineBreak),
+            Event::TaskListMarker(checked) => {
+                pending.text.push_str(if checked { "☒ " } else { "☐ " })
+            }
+            Event::Start(ref tag) => {
+                let inline = match *tag {
+                    Tag::Emphasis => Inline::Emph(self.inlines(events)),
+                    Tag::Strong => Inline::Strong(self.inlines(events)),
+                    Tag::Strikethrough => Inline::Strikeout(self.inlines(events)),
+                    Tag::Link {
+                        ref dest_url,
+                        ref title,
+                        ..
+                    } => Inline::Link(
+                        Attr::default(),
+                        self.inlines(events),
+                        (dest_url.to_string(), title.to_string()),
+                    ),
+                    Tag::Image {
+                        ref dest_url,
+                        ref title,
+                        ..
+                    } => Inline::Image(
+                        Attr::default(),
+                        self.inlines(events),
+                        (dest_url.to_string(), title.to_string()),
+                    ),
+                    // block level tags are handled by `blocks`
+                    _ => {
+                        self.inlines(events);
+                        return;
+                    }
+                };
+                pending.push(inline);
+            }
+            Event::End(_) | Event::Rule => {}
+        }
+    }
+
+    /// Converts block events up to the end of the enclosing tag
+    fn blocks(&mut self, events: &mut Events<'e, 'a>) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut pending = Pending::default();
+        while let Some(event) = events.next() {
+            let tag = match *event {
+                Event::End(_) => break,
+                Event::Rule => {
+                    pending.flush_plain(&mut blocks);
+                    blocks.push(Block::HorizontalRule);
+                    continue;
+                }
+                Event::Start(ref tag) if Self::is_block(tag) => tag,
+                _ => {
+                    self.inline(event, events, &mut pending);
+                    continue;
+                }
+            };
+            pending.flush_plain(&mut blocks);
+            if let Some(block) = self.block(tag, events) {
+                blocks.push(block);
+            }
+        }
+        pending.flush_plain(&mut blocks);
+        blocks
+    }
+
+    fn is_block(tag: &Tag) -> bool {
+        !matches!(
+            *tag,
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link { .. } | Tag::Image { .. }
+        )
+    }
+
+    fn list_items(&mut self, events: &mut Events<'e, 'a>) -> Vec<Vec<Block>> {
+        let mut items = Vec::new();
+        while let Some(event) = events.next() {
+            match *event {
+                Event::Start(Tag::Item) => items.push(self.blocks(events)),
+                Event::End(_) => break,
+                _ => {}
+            }
+        }
+        items
+    }
+
+    fn cells(&mut self, events: &mut Events<'e, 'a>) -> Vec<Vec<Inline>> {
+        let mut cells = Vec::new();
+        while let Some(event) = events.next() {
+            match *event {
+                Event::Start(Tag::TableCell) => cells.push(self.inlines(events)),
+                Event::End(_) => break,
+                _ => {}
+            }
+        }
+        cells
+    }
+
+    fn block(&mut self, tag: &Tag, events: &mut Events<'e, 'a>) -> Option<Block> {
+        let block = match *tag {
+            Tag::Paragraph => Block::Para(self.inlines(events)),
+            Tag::Heading {
+                level,
+                ref id,
+                ref classes,
+                ref attrs,
+            } => {
+                let level = match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                let attr = attr(
+                    id.as_deref(),
+                    classes.iter().map(|class| class.to_string()).collect(),
+                    attrs
+                        .iter()
+                        .map(|(key, value)| {
+                            (key.to_string(), value.as_deref().unwrap_or("").to_owned())
+                        })
+                        .collect(),
+                );
+                Block::Header(level, attr, self.inlines(events))
+            }
+            Tag::BlockQuote(kind) => {
+                let content = self.blocks(events);
+                let class = match kind {
+                    None => return Some(Block::BlockQuote(content)),
+                    Some(BlockQuoteKind::Note) => "note",
+                    Some(BlockQuoteKind::Tip) => "tip",
+                    Some(BlockQuoteKind::Important) => "important",
+                    Some(BlockQuoteKind::Warning) => "warning",
+                    Some(BlockQuoteKind::Caution) => "caution",
+                };
+                Block::div(class, content)
+            }
+            Tag::CodeBlock(ref kind) => {
+                let classes = match *kind {
+                    CodeBlockKind::Fenced(ref info) => info
+                        .split_whitespace()
+                        .next()
+                        .map(|language| vec![language.to_owned()])
+                        .unwrap_or_default(),
+                    CodeBlockKind::Indented => Vec::new(),
+                };
+                let mut code = text(events);
+                if code.ends_with('\n') {
+                    code.pop();
+                }
+                Block::CodeBlock(attr(None, classes, Vec::new()), code)
+            }
+            Tag::HtmlBlock => Block::RawBlock(Format("html".to_owned()), text(events)),
+            Tag::List(None) => Block::BulletList(self.list_items(events)),
+            Tag::List(Some(start)) => Block::OrderedList(
+                ListAttributes {
+                    start: start as Int,
+                    style: ListNumberStyle::Decimal,
+                    delim: ListNumberDelim::Period,
+                },
+                self.list_items(events),
+            ),
+            Tag::DefinitionList => {
+                let mut items: Vec<(Vec<Inline>, Vec<Vec<Block>>)> = Vec::new();
+                while let Some(event) = events.next() {
+                    match *event {
+                        Event::Start(Tag::DefinitionListTitle) => {
+                            items.push((self.inlines(events), Vec::new()))
+                        }
+                        Event::Start(Tag::DefinitionListDefinition) => {
+                            let definition = self.blocks(events);
+                            match items.last_mut() {
+                                Some(item) => item.1.push(definition),
+                                None => items.push((Vec::new(), vec![definition])),
+                            }
+                        }
+                        Event::End(_) => break,
+                        _ => {}
+                    }
+                }
+                Block::DefinitionList(items)
+            }
+            Tag::Table(ref alignments) => {
+                let alignments = alignments
+                    .iter()
+                    .map(|alignment| match *alignment {
+                        pulldown_cmark::Alignment::None => Alignment::AlignDefault,
+                        pulldown_cmark::Alignment::Left => Alignment::AlignLeft,
+                        pulldown_cmark::Alignment::Center => Alignment::AlignCenter,
+                        pulldown_cmark::Alignment::Right => Alignment::AlignRight,
+                    })
+                    .collect();
+                let mut table = TableBuilder::new().alignments(alignments);
+                while let Some(event) = events.next() {
+                    match *event {
+                        Event::Start(Tag::TableHead) => table = table.header(self.cells(events)),
+                        Event::Start(Tag::TableRow) => table = table.row(self.cells(events)),
+                        Event::End(_) => break,
+                        _ => {}
+                    }
+                }
+                table.build()
+            }
+            // parts of lists and tables only appear inside of them in
+            // balanced events
+            Tag::Item
+            | Tag::TableHead
+            | Tag::TableRow
+            | Tag::TableCell
+            | Tag::DefinitionListTitle
+            | Tag::DefinitionListDefinition => Block::Div(Attr::default(), self.blocks(events)),
+            // definitions are inserted where they are referenced
+            Tag::FootnoteDefinition(_) | Tag::MetadataBlock(_) => {
+                text(events);
+                return None;
+            }
+            Tag::Emphasis
+            | Tag::Strong
+            | Tag::Strikethrough
+            | Tag::Link { .. }
+            | Tag::Image { .. } => Block::Plain(self.inlines(events)),
+        };
+        Some(block)
+    }
+}
+
+/// Converts the events of a markdown document into blocks. The events have
+/// to be balanced, as they come from a `pulldown_cmark::Parser`.
+pub fn from_events<'a, I: IntoIterator<Item = Event<'a>>>(events: I) -> Vec<Block> {
+    let events: Vec<Event> = events.into_iter().collect();
+    Converter::new(&events).blocks(&mut events.iter())
+}
+
+/// Parses markdown with tables, footnotes, strikethrough, task lists, math,
+/// definition lists and heading attributes enabled, and converts it with
+/// `from_events`.
+pub fn from_markdown(markdown: &str) -> Vec<Block> {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_MATH
+        | Options::ENABLE_DEFINITION_LIST
+        | Options::ENABLE_HEADING_ATTRIBUTES;
+    from_events(Parser::new_ext(markdown, options))
+}
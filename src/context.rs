@@ -0,0 +1,156 @@
+//! Cancellation and progress reporting for long traversals
+//!
+//! Visitors return a `Context` from `MutVisitor::context` or
+//! `Visitor::context` to have the traversal count the blocks and inlines it
+//! passes. Every few nodes the context reports the progress and polls its
+//! `CancellationToken`; once that is cancelled the traversal stops
+//! descending into further nodes. Editors and GUIs keep a clone of the token
+//! to abort from another thread:
+//!
+//! ```
+//! # use pandoc_ast::*;
+//! # use pandoc_ast::context::Context;
+//! # use pandoc_ast::transform::{Action, TraversalOrder};
+//! let mut doc = Pandoc::new();
+//! doc.blocks = vec![para!["a", "b"]; 1000];
+//! let total = doc.descendants().count();
+//! let mut context = Context::new()
+//!     .total_estimate(total)
+//!     .on_progress(|done, total| eprintln!("{}/{}", done, total));
+//! let token = context.token();
+//! let result = transform::inlines_with_context(
+//!     &mut doc,
+//!     TraversalOrder::BottomUp,
+//!     &mut context,
+//!     |_| {
+//!         token.cancel();
+//!         Action::Delete
+//!     },
+//! );
+//! assert!(result.is_err());
+//! ```
+
+use super::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag shared between the clones of the token, to cancel a traversal
+/// from another thread
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Polls a `CancellationToken` and reports progress while a visitor runs
+pub struct Context {
+    token: CancellationToken,
+    progress: Option<Box<dyn FnMut(usize, usize) + Send>>,
+    interval: usize,
+    total: usize,
+    done: usize,
+    cancelled: bool,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context {
+            token: CancellationToken::new(),
+            progress: None,
+            interval: 1024,
+            total: 0,
+            done: 0,
+            cancelled: false,
+        }
+    }
+}
+
+impl Context {
+    /// A context with its own token that polls every 1024 nodes
+    pub fn new() -> Self {
+        Context::default()
+    }
+
+    /// Uses `token` instead of the context's own one
+    pub fn cancelled_by(mut self, token: CancellationToken) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Calls `f` with the number of nodes done and the estimated total
+    /// every `interval` nodes
+    pub fn on_progress<F: FnMut(usize, usize) + Send + 'static>(mut self, f: F) -> Self {
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    /// The number of nodes between two polls of the token, at least 1
+    pub fn interval(mut self, interval: usize) -> Self {
+        self.interval = interval.max(1);
+        self
+    }
+
+    /// The expected number of nodes, e.g. the `descendants` of the document.
+    /// Without one the number of nodes done so far is reported as total.
+    pub fn total_estimate(mut self, total: usize) -> Self {
+        self.total = total;
+        self
+    }
+
+    /// A clone of the token, for cancelling from elsewhere
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// The number of nodes counted by `tick`
+    pub fn nodes_done(&self) -> usize {
+        self.done
+    }
+
+    /// `Error::Cancelled` if the token has been cancelled
+    pub fn check_cancelled(&self) -> Result<(), Error> {
+        if self.cancelled || self.token.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Passes the progress to the callback set by `on_progress`
+    pub fn report_progress(&mut self, nodes_done: usize, total_estimate: usize) {
+        if let Some(ref mut progress) = self.progress {
+            progress(nodes_done, total_estimate);
+        }
+    }
+
+    /// Counts a node and every `interval` nodes reports the progress and
+    /// polls the token. The token is also polled for the first node, so a
+    /// traversal with an already cancelled token stops right away. Returns
+    /// whether the traversal has been cancelled, which the `walk_*` methods
+    /// of the visitors check before descending.
+    pub fn tick(&mut self) -> bool {
+        if self.cancelled {
+            return true;
+        }
+        self.done += 1;
+        if self.done % self.interval == 0 {
+            let (done, total) = (self.done, self.total.max(self.done));
+            self.report_progress(done, total);
+            self.cancelled = self.token.is_cancelled();
+        } else if self.done == 1 {
+            self.cancelled = self.token.is_cancelled();
+        }
+        self.cancelled
+    }
+}
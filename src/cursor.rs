@@ -0,0 +1,115 @@
+use super::*;
+
+/// A position in a document that can be moved around the tree and edit the
+/// node it is on, the focus. Unlike visitors, a cursor can look at the
+/// parents and siblings of the focus:
+///
+/// ```
+/// # use pandoc_ast::{Block, Cursor, Node, Pandoc};
+/// # let mut doc = Pandoc::new();
+/// # doc.blocks.push(Block::BlockQuote(vec![Block::Para(vec![])]));
+/// let mut cursor = Cursor::new(&mut doc).unwrap();
+/// assert!(cursor.first_child());
+/// if let Some(Node::Block(&Block::BlockQuote(_))) = cursor.parent_node() {
+///     // a paragraph directly inside a block quote
+/// }
+/// ```
+///
+/// Siblings are the nodes of the same list, e.g. the blocks of one item of
+/// a list, so the cursor doesn't move from one item to the next.
+#[derive(Debug)]
+pub struct Cursor<'a> {
+    doc: &'a mut Pandoc,
+    path: AstPath,
+}
+
+impl<'a> Cursor<'a> {
+    /// A cursor on the first block of `doc`, `None` if it has no blocks
+    pub fn new(doc: &'a mut Pandoc) -> Option<Self> {
+        Self::at(doc, AstPath::block(0))
+    }
+
+    /// A cursor on the node at `path`, `None` if there is no such node
+    pub fn at(doc: &'a mut Pandoc, path: AstPath) -> Option<Self> {
+        doc.get(&path)?;
+        Some(Cursor { doc, path })
+    }
+
+    /// the path of the focus
+    pub fn path(&self) -> &AstPath {
+        &self.path
+    }
+
+    pub fn node(&self) -> Node<'_> {
+        self.doc.get(&self.path).expect("the focus exists")
+    }
+
+    pub fn node_mut(&mut self) -> NodeMut<'_> {
+        self.doc.get_mut(&self.path).expect("the focus exists")
+    }
+
+    /// the node containing the focus, `None` for top-level blocks
+    pub fn parent_node(&self) -> Option<Node<'_>> {
+        self.doc.get(&self.path.parent()?)
+    }
+
+    /// Moves to the node containing the focus, returns `false` and stays
+    /// for top-level blocks
+    pub fn parent(&mut self) -> bool {
+        match self.path.parent() {
+            Some(parent) => {
+                self.path = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to the previous node of the same list, returns `false` and stays
+    /// on the first one
+    pub fn prev_sibling(&mut self) -> bool {
+        match self.path.steps.last() {
+            Some(step) if step.index > 0 => {}
+            _ => return false,
+        }
+        self.path.steps.last_mut().unwrap().index -= 1;
+        true
+    }
+
+    /// Moves to the next node of the same list, returns `false` and stays on
+    /// the last one
+    pub fn next_sibling(&mut self) -> bool {
+        let len = match path::list(self.doc, &self.path) {
+            Some((list, _)) => list.len(),
+            None => return false,
+        };
+        match self.path.steps.last_mut() {
+            Some(step) if step.index + 1 < len => {
+                step.index += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves to the first node below the focus in document order, returns
+    /// `false` and stays if there is none
+    pub fn first_child(&mut self) -> bool {
+        let lists = self.node().child_lists();
+        match lists.iter().position(|list| list.len() > 0) {
+            Some(list) => {
+                self.path = self.path.child(list, 0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the focus by `node` and returns the old node. The focus
+    /// stays on the new node. A block can't replace an inline or vice versa,
+    /// that returns `Err(node)`.
+    #[allow(clippy::result_large_err)]
+    pub fn replace(&mut self, node: OwnedNode) -> Result<OwnedNode, OwnedNode> {
+        self.doc.replace(&self.path, node)
+    }
+}
@@ -0,0 +1,171 @@
+//! Construction and manipulation of definition lists without spelling out
+//! pandoc's nested tuples
+
+use super::*;
+use table::Table;
+
+fn inlines<I: IntoInlines>(content: I) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    content.extend_inlines(&mut inlines);
+    inlines
+}
+
+/// the inlines of a single `Plain` or `Para`, otherwise the text of the blocks
+fn term_inlines(mut blocks: Vec<Block>) -> Vec<Inline> {
+    if blocks.len() == 1 {
+        match blocks.pop() {
+            Some(Block::Plain(inlines)) | Some(Block::Para(inlines)) => return inlines,
+            Some(block) => blocks.push(block),
+            None => {}
+        }
+    }
+    inlines_from_str(&stringify(&blocks))
+}
+
+/// A term and its definitions, each a list of blocks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Entry {
+    pub term: Vec<Inline>,
+    pub definitions: Vec<Vec<Block>>,
+}
+
+impl Entry {
+    /// An entry with a single definition holding a `Plain` block. Term and
+    /// definition are anything `inlines!` accepts.
+    pub fn new<T: IntoInlines, D: IntoInlines>(term: T, definition: D) -> Self {
+        Entry {
+            term: inlines(term),
+            definitions: vec![vec![Block::Plain(inlines(definition))]],
+        }
+    }
+}
+
+/// The entries of a `Block::DefinitionList`
+///
+/// ```
+/// # use pandoc_ast::definition::DefinitionList;
+/// let mut list = DefinitionList::from_pairs(vec![("pear", "a fruit"), ("apple", "a fruit too")]);
+/// list.sort_by_term();
+/// let block = list.into_block();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DefinitionList {
+    pub entries: Vec<Entry>,
+}
+
+impl DefinitionList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A list with one entry per pair of term and definition, see `Entry::new`
+    pub fn from_pairs<I, T, D>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (T, D)>,
+        T: IntoInlines,
+        D: IntoInlines,
+    {
+        DefinitionList {
+            entries: pairs
+                .into_iter()
+                .map(|(term, definition)| Entry::new(term, definition))
+                .collect(),
+        }
+    }
+
+    /// Appends an entry, see `Entry::new`
+    pub fn entry<T: IntoInlines, D: IntoInlines>(mut self, term: T, definition: D) -> Self {
+        self.entries.push(Entry::new(term, definition));
+        self
+    }
+
+    /// Takes the list apart, handing other blocks back as the error
+    #[allow(clippy::result_large_err)]
+    pub fn from_block(block: Block) -> Result<DefinitionList, Block> {
+        match block {
+            Block::DefinitionList(items) => Ok(DefinitionList {
+                entries: items
+                    .into_iter()
+                    .map(|(term, definitions)| Entry { term, definitions })
+                    .collect(),
+            }),
+            other => Err(other),
+        }
+    }
+
+    pub fn into_block(self) -> Block {
+        Block::DefinitionList(
+            self.entries
+                .into_iter()
+                .map(|entry| (entry.term, entry.definitions))
+                .collect(),
+        )
+    }
+
+    /// Sorts the entries by the text of their terms, case-insensitively.
+    /// Entries with the same term keep their order.
+    pub fn sort_by_term(&mut self) {
+        self.entries
+            .sort_by_cached_key(|entry| stringify(&entry.term).to_lowercase());
+    }
+
+    /// Converts a table with two columns, terms and definitions, into a list
+    /// with one entry per body row. Header and foot rows are dropped. A term
+    /// cell holding a single `Plain` or `Para` becomes its inlines, any other
+    /// content its text. Empty definition cells become entries without
+    /// definitions. Tables with a different number of columns are
+    /// handed back as the error.
+    #[allow(clippy::result_large_err)]
+    pub fn from_table(table: Table) -> Result<DefinitionList, Table> {
+        if table.col_specs.len() != 2 {
+            return Err(table);
+        }
+        let mut entries = Vec::new();
+        for body in table.bodies {
+            for (_, cells) in body.2.into_iter().chain(body.3) {
+                let mut cells = cells.into_iter().map(|cell| cell.4);
+                let term = match cells.next() {
+                    Some(term) => term,
+                    None => continue,
+                };
+                entries.push(Entry {
+                    term: term_inlines(term),
+                    definitions: cells
+                        .next()
+                        .into_iter()
+                        .filter(|blocks| !blocks.is_empty())
+                        .collect(),
+                });
+            }
+        }
+        Ok(DefinitionList { entries })
+    }
+
+    /// Converts the list into a table with a column of terms and one of
+    /// definitions, without header. Several definitions of a term end up in
+    /// the same cell.
+    pub fn into_table(self) -> Table {
+        let cell = |blocks| (Attr::default(), Alignment::AlignDefault, 1, 1, blocks);
+        let rows = self
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let term = if entry.term.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![Block::Plain(entry.term)]
+                };
+                let definition = entry.definitions.into_iter().flatten().collect();
+                (Attr::default(), vec![cell(term), cell(definition)])
+            })
+            .collect();
+        Table {
+            attr: Attr::default(),
+            caption: (None, Vec::new()),
+            col_specs: vec![(Alignment::AlignDefault, ColWidth::ColWidthDefault); 2],
+            head: (Attr::default(), Vec::new()),
+            bodies: vec![(Attr::default(), 0, Vec::new(), rows)],
+            foot: (Attr::default(), Vec::new()),
+        }
+    }
+}
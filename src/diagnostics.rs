@@ -0,0 +1,247 @@
+//! Reporting problems found in a document to its authors
+//!
+//! A `Diagnostic` points at a node by its path and, if the document went
+//! through `sourcepos::lift`, at the line of the input it came from. The
+//! `Reporter` collects them and renders them like rustc does:
+//!
+//! ```text
+//! warning: image without alt text
+//!  --> notes.md:3:6
+//!   |
+//! 3 | Some ![](cat.png) text
+//!   |      ^
+//!   = at 1/2
+//! ```
+//!
+//! or as a json array for editors and CI tools.
+
+use super::*;
+use sourcepos::{SourceMap, SourcePos};
+use std::fmt;
+use recover::NodeError;
+use validate::{AccessibilityIssue, LinkIssue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A problem with the node at `path`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub path: AstPath,
+    /// where the node starts in the input, filled in by the `Reporter` if
+    /// it has a source map
+    pub source_pos: Option<SourcePos>,
+}
+
+impl Diagnostic {
+    pub fn new<S: Into<String>>(severity: Severity, path: AstPath, message: S) -> Self {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            path,
+            source_pos: None,
+        }
+    }
+
+    pub fn error<S: Into<String>>(path: AstPath, message: S) -> Self {
+        Self::new(Severity::Error, path, message)
+    }
+
+    pub fn warning<S: Into<String>>(path: AstPath, message: S) -> Self {
+        Self::new(Severity::Warning, path, message)
+    }
+
+    pub fn note<S: Into<String>>(path: AstPath, message: S) -> Self {
+        Self::new(Severity::Note, path, message)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let source_pos = self.source_pos.as_ref().map(|pos| {
+            serde_json::json!({
+                "file": pos.file,
+                "line": pos.line,
+                "column": pos.column,
+            })
+        });
+        serde_json::json!({
+            "severity": self.severity.as_str(),
+            "message": self.message,
+            "path": self.path.to_string(),
+            "source_pos": source_pos,
+        })
+    }
+}
+
+/// A single line like `notes.md:3:6: warning: image without alt text`, with
+/// the path instead of the position if there is none
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.source_pos {
+            Some(ref pos) => write!(f, "{}: ", pos)?,
+            None => write!(f, "{}: ", self.path)?,
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Broken links are errors, duplicate identifiers and empty targets warnings
+impl From<LinkIssue> for Diagnostic {
+    fn from(issue: LinkIssue) -> Self {
+        let severity = match issue {
+            LinkIssue::BrokenLink { .. } => Severity::Error,
+            _ => Severity::Warning,
+        };
+        Diagnostic::new(severity, issue.path().clone(), issue.message())
+    }
+}
+
+impl From<AccessibilityIssue> for Diagnostic {
+    fn from(issue: AccessibilityIssue) -> Self {
+        Diagnostic::warning(issue.path().clone(), issue.message())
+    }
+}
+
+/// Errors of a `recover::try_walk` visitor are errors
+impl<E: fmt::Display> From<NodeError<E>> for Diagnostic {
+    fn from(error: NodeError<E>) -> Self {
+        Diagnostic::error(error.path, error.error.to_string())
+    }
+}
+
+/// Collects diagnostics and renders them
+#[derive(Debug, Clone, Default)]
+pub struct Reporter {
+    diagnostics: Vec<Diagnostic>,
+    source_map: Option<SourceMap>,
+    source: Option<String>,
+}
+
+impl Reporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locates the reported nodes in the input with the positions returned
+    /// by `sourcepos::lift`. Nodes without a position of their own get the
+    /// one of the closest node containing them.
+    pub fn source_map(mut self, map: SourceMap) -> Self {
+        self.source_map = Some(map);
+        self
+    }
+
+    /// The text of the input, to show the lines of the reported nodes
+    pub fn source<S: Into<String>>(mut self, text: S) -> Self {
+        self.source = Some(text.into());
+        self
+    }
+
+    pub fn report<D: Into<Diagnostic>>(&mut self, diagnostic: D) {
+        let mut diagnostic = diagnostic.into();
+        if diagnostic.source_pos.is_none() {
+            if let Some(ref map) = self.source_map {
+                diagnostic.source_pos = map.find(&diagnostic.path).cloned();
+            }
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
+    pub fn error<S: Into<String>>(&mut self, path: &AstPath, message: S) {
+        self.report(Diagnostic::error(path.clone(), message));
+    }
+
+    pub fn warning<S: Into<String>>(&mut self, path: &AstPath, message: S) {
+        self.report(Diagnostic::warning(path.clone(), message));
+    }
+
+    pub fn note<S: Into<String>>(&mut self, path: &AstPath, message: S) {
+        self.report(Diagnostic::note(path.clone(), message));
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// All diagnostics in the order they were reported, in the format shown
+    /// in the module documentation, separated by empty lines
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            self.render_one(diagnostic, &mut out);
+        }
+        out
+    }
+
+    fn render_one(&self, diagnostic: &Diagnostic, out: &mut String) {
+        out.push_str(&format!(
+            "{}: {}\n",
+            diagnostic.severity, diagnostic.message
+        ));
+        let pos = match diagnostic.source_pos {
+            Some(ref pos) => pos,
+            None => {
+                out.push_str(&format!("  = at {}\n", diagnostic.path));
+                return;
+            }
+        };
+        let line = self
+            .source
+            .as_ref()
+            .and_then(|source| source.lines().nth(pos.line.saturating_sub(1)));
+        let number = pos.line.to_string();
+        let pad = " ".repeat(number.len());
+        out.push_str(&format!("{}--> {}\n", pad, pos));
+        if let Some(line) = line {
+            let indent: String = line
+                .chars()
+                .take(pos.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            out.push_str(&format!("{} |\n", pad));
+            out.push_str(&format!("{} | {}\n", number, line));
+            out.push_str(&format!("{} | {}^\n", pad, indent));
+        }
+        out.push_str(&format!("{} = at {}\n", pad, diagnostic.path));
+    }
+
+    /// All diagnostics as a json array of objects with the fields of
+    /// `Diagnostic`, the path as a string and `source_pos` possibly `null`
+    pub fn to_json(&self) -> String {
+        let diagnostics: Vec<_> = self.diagnostics.iter().map(Diagnostic::to_json).collect();
+        serde_json::Value::Array(diagnostics).to_string()
+    }
+}
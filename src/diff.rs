@@ -0,0 +1,272 @@
+//! Structural comparison of documents.
+//!
+//! `diff` compares two documents and returns the edits turning the first into
+//! the second. Lists of blocks and inlines are aligned on their longest common
+//! subsequence. Nodes that take each other's place and only differ in their
+//! children are descended into, so that a changed word shows up as a changed
+//! `Str` rather than a changed paragraph.
+//!
+//! The paths of the edits are meant to be applied in order: each one refers to
+//! the document with all previous edits already applied.
+
+use super::*;
+use node::{ChildList, ChildListMut};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A change to a document
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Edit {
+    /// `node` is inserted at `path`, shifting the following siblings
+    Insert { path: AstPath, node: OwnedNode },
+    /// `node` is removed from `path`, shifting the following siblings
+    Remove { path: AstPath, node: OwnedNode },
+    /// the node at `path` is replaced
+    Replace {
+        path: AstPath,
+        old: OwnedNode,
+        new: OwnedNode,
+    },
+    /// a metadata field is added (`old` is `None`), removed (`new` is `None`)
+    /// or changed
+    Meta {
+        key: String,
+        old: Option<MetaValue>,
+        new: Option<MetaValue>,
+    },
+}
+
+/// The edits turning one document into another, see `diff`.
+///
+/// Its `Display` implementation renders one edit per line, with `+`, `-` and
+/// `~` marking insertions, removals and replacements.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditScript {
+    pub edits: Vec<Edit>,
+}
+
+impl EditScript {
+    /// whether the documents are equal
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+}
+
+/// text longer than this is cut off when rendering edits
+const MAX_TEXT: usize = 40;
+
+fn describe(node: Node) -> String {
+    let text = match node {
+        Node::Block(block) => stringify(block),
+        Node::Inline(inline) => stringify(inline),
+    };
+    match text.char_indices().nth(MAX_TEXT) {
+        Some((end, _)) => format!("{} {:?}…", node.name(), &text[..end]),
+        None if text.trim().is_empty() => node.name().to_owned(),
+        None => format!("{} {:?}", node.name(), text),
+    }
+}
+
+fn describe_meta(value: &Option<MetaValue>) -> String {
+    match *value {
+        None => "nothing".to_owned(),
+        Some(ref value) => match value.text() {
+            Some(text) => format!("{:?}", text),
+            None => format!("{:?}", value),
+        },
+    }
+}
+
+impl fmt::Display for EditScript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for edit in &self.edits {
+            match *edit {
+                Edit::Insert { ref path, ref node } => {
+                    writeln!(f, "+ {} {}", path, describe(node.as_node()))?
+                }
+                Edit::Remove { ref path, ref node } => {
+                    writeln!(f, "- {} {}", path, describe(node.as_node()))?
+                }
+                Edit::Replace {
+                    ref path,
+                    ref old,
+                    ref new,
+                } => writeln!(
+                    f,
+                    "~ {} {} -> {}",
+                    path,
+                    describe(old.as_node()),
+                    describe(new.as_node())
+                )?,
+                Edit::Meta {
+                    ref key,
+                    ref old,
+                    ref new,
+                } => writeln!(
+                    f,
+                    "~ meta {}: {} -> {}",
+                    key,
+                    describe_meta(old),
+                    describe_meta(new)
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares two documents, see the module documentation
+pub fn diff(old: &Pandoc, new: &Pandoc) -> EditScript {
+    let mut edits = Vec::new();
+    let keys: BTreeSet<&String> = old.meta.keys().chain(new.meta.keys()).collect();
+    for key in keys {
+        let (old, new) = (old.meta.get(key), new.meta.get(key));
+        if old != new {
+            edits.push(Edit::Meta {
+                key: key.clone(),
+                old: old.cloned(),
+                new: new.cloned(),
+            });
+        }
+    }
+    diff_lists(
+        &AstPath::default(),
+        0,
+        ChildList::Blocks(&old.blocks),
+        ChildList::Blocks(&new.blocks),
+        &mut edits,
+    );
+    EditScript { edits }
+}
+
+/// the node with all its children removed
+fn shell(node: Node) -> OwnedNode {
+    let mut shell = node.to_owned_node();
+    for list in shell.as_node_mut().child_lists_mut() {
+        match list {
+            ChildListMut::Blocks(blocks) => blocks.clear(),
+            ChildListMut::Inlines(inlines) => inlines.clear(),
+        }
+    }
+    shell
+}
+
+/// whether two nodes only differ in their children
+fn same_shape(old: Node, new: Node) -> bool {
+    old.name() == new.name() && shell(old) == shell(new)
+}
+
+fn nodes(list: ChildList) -> Vec<Node> {
+    match list {
+        ChildList::Blocks(blocks) => blocks.iter().map(Node::Block).collect(),
+        ChildList::Inlines(inlines) => inlines.iter().map(Node::Inline).collect(),
+    }
+}
+
+/// pairs of indices of a longest common subsequence
+fn common_subsequence(old: &[Node], new: &[Node]) -> Vec<(usize, usize)> {
+    // lengths[i][j] is the length of the lcs of old[i..] and new[j..]
+    let mut lengths = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+fn diff_lists(
+    parent: &AstPath,
+    list: usize,
+    old: ChildList,
+    new: ChildList,
+    edits: &mut Vec<Edit>,
+) {
+    let (old, new) = (nodes(old), nodes(new));
+    // the common prefix and suffix don't need the quadratic alignment
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut pairs: Vec<_> = (0..prefix).map(|i| (i, i)).collect();
+    pairs.extend(
+        common_subsequence(
+            &old[prefix..old.len() - suffix],
+            &new[prefix..new.len() - suffix],
+        )
+        .into_iter()
+        .map(|(i, j)| (i + prefix, j + prefix)),
+    );
+    pairs.extend((0..suffix).map(|k| (old.len() - suffix + k, new.len() - suffix + k)));
+    pairs.push((old.len(), new.len()));
+
+    // the position in the list as changed by the edits so far
+    let mut current = 0;
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in pairs {
+        let paired = (next_i - i).min(next_j - j);
+        for k in 0..paired {
+            let (a, b) = (old[i + k], new[j + k]);
+            let path = parent.child(list, current);
+            if same_shape(a, b) {
+                diff_children(&path, a, b, edits);
+            } else {
+                edits.push(Edit::Replace {
+                    path,
+                    old: a.to_owned_node(),
+                    new: b.to_owned_node(),
+                });
+            }
+            current += 1;
+        }
+        for a in &old[i + paired..next_i] {
+            edits.push(Edit::Remove {
+                path: parent.child(list, current),
+                node: a.to_owned_node(),
+            });
+        }
+        for b in &new[j + paired..next_j] {
+            edits.push(Edit::Insert {
+                path: parent.child(list, current),
+                node: b.to_owned_node(),
+            });
+            current += 1;
+        }
+        // the matched pair
+        current += 1;
+        i = next_i + 1;
+        j = next_j + 1;
+    }
+}
+
+fn diff_children(path: &AstPath, old: Node, new: Node, edits: &mut Vec<Edit>) {
+    for (list, (a, b)) in old
+        .child_lists()
+        .into_iter()
+        .zip(new.child_lists())
+        .enumerate()
+    {
+        diff_lists(path, list, a, b, edits);
+    }
+}
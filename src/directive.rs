@@ -0,0 +1,199 @@
+//! Custom directives written as attributes
+//!
+//! A directive is a `Div`, `CodeBlock`, `Span` or `Code` whose attribute
+//! with a registered key names a handler and its arguments:
+//!
+//! ```markdown
+//! ::: {data-macro="youtube dQw4w9WgXcQ width=560 title=\"A video\""}
+//! :::
+//! ```
+//!
+//! The first word of the value is the name of the directive, the following
+//! ones are its arguments, `key=value` for named and plain words for
+//! positional ones. Double quotes group words, backslashes escape quotes.
+//!
+//! ```
+//! # use pandoc_ast::directive::Directives;
+//! # use pandoc_ast::transform::Action;
+//! # use pandoc_ast::{Block, Format, Pandoc};
+//! # let mut doc = Pandoc::new();
+//! Directives::new("data-macro")
+//!     .block("youtube", |args, _| {
+//!         let html = format!(
+//!             r#"<iframe src="https://www.youtube.com/embed/{}"></iframe>"#,
+//!             args.positional.first().map_or("", |id| id.as_str())
+//!         );
+//!         Action::Replace(Block::RawBlock(Format("html".to_owned()), html))
+//!     })
+//!     .apply(&mut doc)
+//!     .unwrap();
+//! ```
+//!
+//! Handlers see the node without the directive's attribute. Nodes naming a
+//! directive without a handler are left alone, so several sets of directives
+//! can share one key.
+
+use super::*;
+use std::collections::HashMap;
+use transform::{Action, TraversalOrder};
+
+/// The name and arguments of a directive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Args {
+    pub name: String,
+    pub positional: Vec<String>,
+    pub named: Map<String, String>,
+}
+
+impl Args {
+    /// Parses the value of a directive's attribute, `None` for an empty value
+    /// or an unterminated quote
+    pub fn parse(value: &str) -> Option<Args> {
+        let mut words = split(value)?.into_iter();
+        let mut args = Args {
+            name: words.next()?,
+            ..Args::default()
+        };
+        for word in words {
+            match word.find('=') {
+                Some(eq) => {
+                    args.named
+                        .insert(word[..eq].to_owned(), word[eq + 1..].to_owned());
+                }
+                None => args.positional.push(word),
+            }
+        }
+        Some(args)
+    }
+
+    /// the named argument `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.named.get(key).map(String::as_str)
+    }
+}
+
+/// the words of `value`, with quotes removed
+fn split(value: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = None::<String>;
+    let mut quoted = false;
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
+            }
+            '\\' if quoted => word.get_or_insert_with(String::new).push(chars.next()?),
+            c if c.is_whitespace() && !quoted => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    words.extend(word);
+    Some(words)
+}
+
+type Handler<'f, T> = Box<dyn FnMut(&Args, &mut T) -> Action<T> + 'f>;
+
+/// Handlers for the directives of one attribute key
+pub struct Directives<'f> {
+    key: String,
+    block: HashMap<String, Handler<'f, Block>>,
+    inline: HashMap<String, Handler<'f, Inline>>,
+}
+
+impl<'f> Directives<'f> {
+    pub fn new<S: Into<String>>(key: S) -> Self {
+        Directives {
+            key: key.into(),
+            block: HashMap::new(),
+            inline: HashMap::new(),
+        }
+    }
+
+    /// Registers the handler for `Div`s and `CodeBlock`s naming `name`
+    pub fn block<F>(mut self, name: &str, f: F) -> Self
+    where
+        F: FnMut(&Args, &mut Block) -> Action<Block> + 'f,
+    {
+        self.block.insert(name.to_owned(), Box::new(f));
+        self
+    }
+
+    /// Registers the handler for `Span`s and `Code`s naming `name`
+    pub fn inline<F>(mut self, name: &str, f: F) -> Self
+    where
+        F: FnMut(&Args, &mut Inline) -> Action<Inline> + 'f,
+    {
+        self.inline.insert(name.to_owned(), Box::new(f));
+        self
+    }
+
+    /// Runs the handlers on the directives in `node`, innermost first, and
+    /// splices their results into the parent lists. The results are not
+    /// searched for directives. Inline directives are handled before block
+    /// directives.
+    ///
+    /// Directives with arguments that can't be parsed are left alone and
+    /// the first of them is returned as `Error::InvalidAttribute` after all
+    /// other directives are handled.
+    pub fn apply<T: MutVisitable>(&mut self, node: &mut T) -> Result<(), Error> {
+        let key = &self.key;
+        let mut error = None;
+        if !self.inline.is_empty() {
+            let handlers = &mut self.inline;
+            transform::inlines(node, TraversalOrder::BottomUp, |inline| {
+                let attr = match *inline {
+                    Inline::Span(ref mut attr, _) | Inline::Code(ref mut attr, _) => attr,
+                    _ => return Action::Keep,
+                };
+                dispatch(key, attr, handlers, &mut error)
+                    .map_or(Action::Keep, |(f, args)| f(&args, inline))
+            });
+        }
+        if !self.block.is_empty() {
+            let handlers = &mut self.block;
+            transform::blocks(node, TraversalOrder::BottomUp, |block| {
+                let attr = match *block {
+                    Block::Div(ref mut attr, _) | Block::CodeBlock(ref mut attr, _) => attr,
+                    _ => return Action::Keep,
+                };
+                dispatch(key, attr, handlers, &mut error)
+                    .map_or(Action::Keep, |(f, args)| f(&args, block))
+            });
+        }
+        match error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The handler for the directive in `attr` and its arguments, removing the
+/// directive's attribute if there is a handler
+fn dispatch<'h, 'f, T>(
+    key: &str,
+    attr: &mut Attr,
+    handlers: &'h mut HashMap<String, Handler<'f, T>>,
+    error: &mut Option<Error>,
+) -> Option<(&'h mut Handler<'f, T>, Args)> {
+    let index = attr.2.iter().position(|(k, _)| k == key)?;
+    let args = match Args::parse(&attr.2[index].1) {
+        Some(args) => args,
+        None => {
+            if error.is_none() {
+                *error = Some(Error::InvalidAttribute {
+                    key: key.to_owned(),
+                    value: attr.2[index].1.clone(),
+                });
+            }
+            return None;
+        }
+    };
+    let f = handlers.get_mut(&args.name)?;
+    attr.2.remove(index);
+    Some((f, args))
+}
@@ -0,0 +1,204 @@
+use super::*;
+use node::ChildList;
+use std::fmt::Write;
+
+/// text longer than this is cut off in dumps
+const MAX_TEXT: usize = 40;
+
+struct Dumper {
+    out: String,
+    colored: bool,
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_TEXT) {
+        Some((end, _)) => format!("{:?}…", &text[..end]),
+        None => format!("{:?}", text),
+    }
+}
+
+fn attr_summary(attr: &Attr) -> String {
+    let (ref id, ref classes, ref attributes) = *attr;
+    let mut parts = Vec::new();
+    if !id.is_empty() {
+        parts.push(format!("#{}", id));
+    }
+    parts.extend(classes.iter().map(|class| format!(".{}", class)));
+    parts.extend(
+        attributes
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, truncate(value))),
+    );
+    parts.join(" ")
+}
+
+/// the scalar fields of a node, shown after its name
+fn details(node: Node) -> Option<String> {
+    match node {
+        Node::Block(block) => match *block {
+            Block::CodeBlock(_, ref text) => Some(truncate(text)),
+            Block::RawBlock(ref format, ref text) => {
+                Some(format!("{} {}", format.0, truncate(text)))
+            }
+            Block::OrderedList(ref attributes, _) => Some(attributes.start.to_string()),
+            Block::Header(level, ..) => Some(level.to_string()),
+            Block::Unknown(ref name, _) => Some(name.clone()),
+            Block::Unparsed(ref unparsed) => Some(truncate(unparsed.json())),
+            _ => None,
+        },
+        Node::Inline(inline) => match *inline {
+            Inline::Str(ref text) | Inline::Code(_, ref text) => Some(truncate(text)),
+            Inline::Math(ref math_type, ref text) => {
+                Some(format!("{:?} {}", math_type, truncate(text)))
+            }
+            Inline::RawInline(ref format, ref text) => {
+                Some(format!("{} {}", format.0, truncate(text)))
+            }
+            Inline::Quoted(ref quote_type, _) => Some(format!("{:?}", quote_type)),
+            Inline::Unknown(ref name, _) => Some(name.clone()),
+            Inline::Link(_, _, (ref url, _)) | Inline::Image(_, _, (ref url, _)) => {
+                Some(truncate(url))
+            }
+            Inline::Cite(ref citations, _) => Some(
+                citations
+                    .iter()
+                    .map(|citation| format!("@{}", citation.citationId))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            _ => None,
+        },
+    }
+}
+
+impl Dumper {
+    fn paint(&mut self, code: &str, text: &str) {
+        if self.colored {
+            write!(self.out, "\x1b[{}m{}\x1b[0m", code, text).expect("writing to a string failed");
+        } else {
+            self.out.push_str(text);
+        }
+    }
+
+    fn line(&mut self, depth: usize, name: &str, attr: Option<&Attr>, details: Option<String>) {
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+        self.paint("1;34", name);
+        if let Some(details) = details {
+            self.out.push(' ');
+            self.paint("32", &details);
+        }
+        let attr = attr.map(attr_summary).unwrap_or_default();
+        if !attr.is_empty() {
+            self.out.push(' ');
+            self.paint("33", &attr);
+        }
+        self.out.push('\n');
+    }
+
+    fn list(&mut self, depth: usize, list: ChildList) {
+        match list {
+            ChildList::Blocks(blocks) => {
+                for block in blocks {
+                    self.node(depth, Node::Block(block));
+                }
+            }
+            ChildList::Inlines(inlines) => {
+                for inline in inlines {
+                    self.node(depth, Node::Inline(inline));
+                }
+            }
+        }
+    }
+
+    fn node(&mut self, depth: usize, node: Node) {
+        self.line(depth, node.name(), node.attr(), details(node));
+        let lists = node.child_lists();
+        // list items, table cells and the like get a line of their own
+        if lists.len() > 1 {
+            for list in lists {
+                self.line(depth + 1, "-", None, None);
+                self.list(depth + 2, list);
+            }
+        } else {
+            for list in lists {
+                self.list(depth + 1, list);
+            }
+        }
+    }
+
+    fn meta_value(&mut self, depth: usize, key: &str, value: &MetaValue) {
+        let name = match *value {
+            MetaValue::MetaMap(_) => "MetaMap",
+            MetaValue::MetaList(_) => "MetaList",
+            MetaValue::MetaBool(_) => "MetaBool",
+            MetaValue::MetaString(_) => "MetaString",
+            MetaValue::MetaNumber(_) => "MetaNumber",
+            MetaValue::MetaInlines(_) => "MetaInlines",
+            MetaValue::MetaBlocks(_) => "MetaBlocks",
+        };
+        let details = match *value {
+            MetaValue::MetaBool(b) => Some(b.to_string()),
+            MetaValue::MetaString(ref s) => Some(truncate(s)),
+            MetaValue::MetaNumber(ref n) => Some(n.to_string()),
+            _ => None,
+        };
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+        self.paint("35", key);
+        self.out.push(' ');
+        self.line(0, name, None, details);
+        match *value {
+            MetaValue::MetaMap(ref map) => {
+                for (key, value) in map {
+                    self.meta_value(depth + 1, key, value);
+                }
+            }
+            MetaValue::MetaList(ref list) => {
+                for value in list {
+                    self.meta_value(depth + 1, "-", value);
+                }
+            }
+            MetaValue::MetaInlines(ref inlines) => {
+                self.list(depth + 1, ChildList::Inlines(inlines))
+            }
+            MetaValue::MetaBlocks(ref blocks) => self.list(depth + 1, ChildList::Blocks(blocks)),
+            MetaValue::MetaBool(_) | MetaValue::MetaString(_) | MetaValue::MetaNumber(_) => {}
+        }
+    }
+
+    fn document(mut self, doc: &Pandoc) -> String {
+        self.line(0, "Pandoc", None, None);
+        for (key, value) in &doc.meta {
+            self.meta_value(1, key, value);
+        }
+        self.list(1, ChildList::Blocks(&doc.blocks));
+        self.out
+    }
+}
+
+impl Pandoc {
+    /// An indented tree of the document with one node per line, for debugging.
+    /// Constructor names are followed by their text (cut off after a few words)
+    /// or other scalar fields and the identifier, classes and attributes. The
+    /// metadata comes first, with keys in front of the values.
+    pub fn dump_tree(&self) -> String {
+        Dumper {
+            out: String::new(),
+            colored: false,
+        }
+        .document(self)
+    }
+
+    /// `dump_tree` highlighted with ansi escape codes, for printing to terminals
+    #[cfg(feature = "cli")]
+    pub fn dump_tree_colored(&self) -> String {
+        Dumper {
+            out: String::new(),
+            colored: true,
+        }
+        .document(self)
+    }
+}
@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+use std::{error, fmt, io};
+
+/// Errors that can occur while reading, writing or processing a document
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// the input is not valid json or not in the pandoc format
+    Json(serde_json::Error),
+    /// the document was produced by a pandoc version that is not supported
+    VersionMismatch {
+        major: i64,
+        minor: i64,
+    },
+    /// the document does not specify a `pandoc-api-version`
+    MissingVersion,
+    /// a node selector could not be parsed
+    InvalidSelector(String),
+    /// metadata does not have the shape of the type it is converted to
+    Metadata(String),
+    /// a file referenced by the document could not be read or written
+    File {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// a key-value attribute has a value that can't be used
+    InvalidAttribute {
+        key: String,
+        value: String,
+    },
+    /// a document could not be encoded into or decoded from a binary format
+    Binary(String),
+    /// the input is not in pandoc's native format
+    Native(String),
+    /// the edit with the given index in an edit script doesn't fit the document
+    Patch {
+        edit: usize,
+        message: String,
+    },
+    /// the json does not match the pandoc types, see `Pandoc::from_json_validated`
+    Schema {
+        /// where the problem is, e.g. `blocks[412].c[1][3]`
+        path: String,
+        /// the constructor of the innermost node containing the problem
+        constructor: Option<String>,
+        message: String,
+        /// the `pandoc-api-version` of the document
+        version: Vec<u32>,
+    },
+    /// the pandoc executable failed, see the `exec` module
+    PandocFailed {
+        /// the exit status, `None` if pandoc was killed by a signal
+        status: Option<i32>,
+        stderr: String,
+    },
+    /// a traversal was cancelled, see the `context` module
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => write!(f, "io error: {}", err),
+            Error::Json(ref err) => write!(f, "json is not in the pandoc format: {}", err),
+            Error::VersionMismatch { major, minor } => write!(
+                f,
+                "Pandoc version mismatch: \
+                 `pandoc-ast` expects pandoc version 1.20 or newer, got {}.{}",
+                major, minor
+            ),
+            Error::MissingVersion => write!(
+                f,
+                "Unable to parse pandoc version from JSON. \
+                 Please update your pandoc to at least version 1.18 or use an older version of `pandoc-ast`"
+            ),
+            Error::InvalidSelector(ref msg) => write!(f, "invalid selector: {}", msg),
+            Error::Metadata(ref msg) => write!(f, "invalid metadata: {}", msg),
+            Error::File {
+                ref path,
+                ref source,
+            } => write!(f, "{}: {}", path.display(), source),
+            Error::InvalidAttribute { ref key, ref value } => {
+                write!(f, "invalid value `{}` for attribute `{}`", value, key)
+            }
+            Error::Binary(ref msg) => write!(f, "binary encoding error: {}", msg),
+            Error::Native(ref msg) => write!(f, "invalid native format: {}", msg),
+            Error::Patch { edit, ref message } => {
+                write!(f, "edit {} does not apply: {}", edit, message)
+            }
+            Error::Schema {
+                ref path,
+                ref constructor,
+                ref message,
+                ref version,
+            } => {
+                write!(f, "{}", path)?;
+                if let Some(ref constructor) = *constructor {
+                    write!(f, " (in {})", constructor)?;
+                }
+                write!(f, ": {}", message)?;
+                if version.len() >= 2 && version[..2] != ::API_VERSION[..] {
+                    let newer = version[..2] > ::API_VERSION[..];
+                    write!(
+                        f,
+                        "; the document uses pandoc-api-version {}.{}, which is {} than the {}.{} \
+                         supported here",
+                        version[0],
+                        version[1],
+                        if newer { "newer" } else { "older" },
+                        ::API_VERSION[0],
+                        ::API_VERSION[1]
+                    )?;
+                }
+                Ok(())
+            }
+            Error::PandocFailed { status, ref stderr } => {
+                match status {
+                    Some(status) => write!(f, "pandoc exited with status {}", status)?,
+                    None => write!(f, "pandoc was terminated")?,
+                }
+                if !stderr.trim().is_empty() {
+                    write!(f, ": {}", stderr.trim())?;
+                }
+                Ok(())
+            }
+            Error::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Json(ref err) => Some(err),
+            Error::File { ref source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
@@ -0,0 +1,200 @@
+//! Reading and writing other formats by running the `pandoc` executable
+//!
+//! Filters often need to turn a snippet of markdown, e.g. from an attribute,
+//! into inlines:
+//!
+//! ```no_run
+//! # use pandoc_ast::exec;
+//! let doc = exec::read("Some *markdown*", "markdown", &[] as &[&str]).unwrap();
+//! let html = exec::write(&doc, "html").unwrap();
+//! ```
+//!
+//! Documents are exchanged as json. Output of pandoc versions with an older
+//! document model is converted to the current one, and documents are
+//! converted down to the version of the executable before writing, like
+//! `filter` does.
+
+use super::*;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+
+/// What pandoc reads: a file or text passed on stdin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input<'a> {
+    Path(&'a Path),
+    Text(&'a str),
+}
+
+impl<'a> From<&'a str> for Input<'a> {
+    fn from(text: &'a str) -> Self {
+        Input::Text(text)
+    }
+}
+
+impl<'a> From<&'a String> for Input<'a> {
+    fn from(text: &'a String) -> Self {
+        Input::Text(text)
+    }
+}
+
+impl<'a> From<&'a Path> for Input<'a> {
+    fn from(path: &'a Path) -> Self {
+        Input::Path(path)
+    }
+}
+
+impl<'a> From<&'a PathBuf> for Input<'a> {
+    fn from(path: &'a PathBuf) -> Self {
+        Input::Path(path)
+    }
+}
+
+/// A pandoc executable. The `pandoc-api-version` it uses is detected on
+/// first use and remembered.
+#[derive(Debug, Default)]
+pub struct Executable {
+    program: Option<PathBuf>,
+    api_version: OnceLock<Vec<u32>>,
+}
+
+impl Executable {
+    /// The `pandoc` found in `PATH`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The executable at `program`
+    pub fn with_program<P: Into<PathBuf>>(program: P) -> Self {
+        Executable {
+            program: Some(program.into()),
+            api_version: OnceLock::new(),
+        }
+    }
+
+    fn command(&self) -> Command {
+        let program = self
+            .program
+            .as_ref()
+            .map_or(OsStr::new("pandoc"), |program| program.as_os_str());
+        Command::new(program)
+    }
+
+    /// Runs pandoc with `args` and `stdin` as input, returning stdout
+    fn run<I, S>(&self, args: I, stdin: Option<String>) -> Result<Vec<u8>, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut child = self
+            .command()
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut pipe = child.stdin.take().expect("stdin is piped");
+        // written from a separate thread so pandoc can't block on a full
+        // stdout while we are still writing
+        let writer = thread::spawn(move || -> io::Result<()> {
+            if let Some(stdin) = stdin {
+                pipe.write_all(stdin.as_bytes())?;
+            }
+            Ok(())
+        });
+        let output = child.wait_with_output()?;
+        let written = writer.join().expect("writing to pandoc panicked");
+        if !output.status.success() {
+            return Err(Error::PandocFailed {
+                status: output.status.code(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+        written?;
+        Ok(output.stdout)
+    }
+
+    /// The `pandoc-api-version` of the executable's json
+    pub fn api_version(&self) -> Result<&[u32], Error> {
+        if let Some(version) = self.api_version.get() {
+            return Ok(version);
+        }
+        let json = self.run(["-f", "markdown", "-t", "json"], Some(String::new()))?;
+        let value: serde_json::Value = serde_json::from_slice(&json)?;
+        let version = value
+            .get("pandoc-api-version")
+            .and_then(|version| serde_json::from_value(version.clone()).ok())
+            .ok_or(Error::MissingVersion)?;
+        Ok(self.api_version.get_or_init(|| version))
+    }
+
+    /// Reads `input` in the format `from` into a document. `options` are
+    /// passed to pandoc as additional arguments.
+    pub fn read<'i, T, I, S>(&self, input: T, from: &str, options: I) -> Result<Pandoc, Error>
+    where
+        T: Into<Input<'i>>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut args: Vec<&OsStr> =
+            vec!["-f".as_ref(), from.as_ref(), "-t".as_ref(), "json".as_ref()];
+        let options: Vec<S> = options.into_iter().collect();
+        args.extend(options.iter().map(AsRef::as_ref));
+        let input = input.into();
+        let stdin = match input {
+            Input::Path(path) => {
+                args.push(path.as_os_str());
+                None
+            }
+            Input::Text(text) => Some(text.to_owned()),
+        };
+        let json = self.run(args, stdin)?;
+        let (doc, version) = from_any_version(&json)?;
+        // remember the version, saving a run for `write`
+        let _ = self.api_version.set(version);
+        Ok(doc)
+    }
+
+    /// Writes the document in the format `to`. `options` are passed to pandoc
+    /// as additional arguments. Binary formats like docx have to be written
+    /// to a file with `-o`, the result is empty then.
+    pub fn write<I, S>(&self, doc: &Pandoc, to: &str, options: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let json = to_version(doc.clone(), self.api_version()?)?;
+        let mut args: Vec<&OsStr> =
+            vec!["-f".as_ref(), "json".as_ref(), "-t".as_ref(), to.as_ref()];
+        let options: Vec<S> = options.into_iter().collect();
+        args.extend(options.iter().map(AsRef::as_ref));
+        let output = self.run(args, Some(json))?;
+        let output = String::from_utf8(output)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(output)
+    }
+}
+
+fn pandoc() -> &'static Executable {
+    static PANDOC: OnceLock<Executable> = OnceLock::new();
+    PANDOC.get_or_init(Executable::new)
+}
+
+/// Reads `input` in the format `from` with the `pandoc` found in `PATH`,
+/// see `Executable::read`
+pub fn read<'i, T, I, S>(input: T, from: &str, options: I) -> Result<Pandoc, Error>
+where
+    T: Into<Input<'i>>,
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    pandoc().read(input, from, options)
+}
+
+/// Writes the document in the format `to` with the `pandoc` found in `PATH`,
+/// see `Executable::write`
+pub fn write(doc: &Pandoc, to: &str) -> Result<String, Error> {
+    pandoc().write(doc, to, &[] as &[&str])
+}
@@ -0,0 +1,513 @@
+//! A json writer for documents that bypasses serde, see `Pandoc::to_json_fast`.
+//!
+//! serde writes each node through the generic adjacently tagged enum code,
+//! which goes through several layers of `Serializer` calls per node. The
+//! writer here matches on the nodes itself and writes their
+//! `{"t": ..., "c": ...}` objects as bytes, producing exactly the output of
+//! `to_json`. Only the content of `Unknown` nodes goes through `serde_json`.
+//! `cargo bench --bench serialize` compares both.
+
+use super::*;
+
+impl Pandoc {
+    /// Serializes the document like `to_json`, but faster
+    pub fn to_json_fast(&self) -> String {
+        let mut out = Vec::new();
+        JsonWriter(&mut out)
+            .pandoc(self)
+            .expect("writing to a Vec failed");
+        String::from_utf8(out).expect("the json writer wrote invalid utf-8")
+    }
+
+    /// Serializes the document into a writer like `to_writer`, but faster
+    pub fn to_writer_fast<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let mut writer = io::BufWriter::new(writer);
+        JsonWriter(&mut writer).pandoc(self)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+struct JsonWriter<W>(W);
+
+impl<W: Write> JsonWriter<W> {
+    fn raw(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.0.write_all(bytes)
+    }
+
+    /// Writes `s` as a json string, escaped the way `serde_json` does
+    fn string(&mut self, s: &str) -> io::Result<()> {
+        self.raw(b"\"")?;
+        let bytes = s.as_bytes();
+        let mut start = 0;
+        for (i, &byte) in bytes.iter().enumerate() {
+            let escape: &[u8] = match byte {
+                b'"' => b"\\\"",
+                b'\\' => b"\\\\",
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                b'\t' => b"\\t",
+                0x08 => b"\\b",
+                0x0c => b"\\f",
+                0x00..=0x1f => {
+                    self.raw(&bytes[start..i])?;
+                    write!(self.0, "\\u{:04x}", byte)?;
+                    start = i + 1;
+                    continue;
+                }
+                _ => continue,
+            };
+            self.raw(&bytes[start..i])?;
+            self.raw(escape)?;
+            start = i + 1;
+        }
+        self.raw(&bytes[start..])?;
+        self.raw(b"\"")
+    }
+
+    fn int(&mut self, n: Int) -> io::Result<()> {
+        write!(self.0, "{}", n)
+    }
+
+    /// Writes `n` like `serde_json`, which writes non-finite numbers as `null`
+    fn float(&mut self, n: Double) -> io::Result<()> {
+        match serde_json::Number::from_f64(n) {
+            Some(n) => write!(self.0, "{}", n),
+            None => self.raw(b"null"),
+        }
+    }
+
+    fn bool(&mut self, b: bool) -> io::Result<()> {
+        self.raw(if b { b"true" } else { b"false" })
+    }
+
+    /// Writes a json array of `items`
+    fn seq<T, F>(&mut self, items: &[T], mut item: F) -> io::Result<()>
+    where
+        F: FnMut(&mut Self, &T) -> io::Result<()>,
+    {
+        self.raw(b"[")?;
+        for (i, value) in items.iter().enumerate() {
+            if i > 0 {
+                self.raw(b",")?;
+            }
+            item(self, value)?;
+        }
+        self.raw(b"]")
+    }
+
+    /// Writes a node without content like `{"t":"Space"}`, which is also how
+    /// the fieldless enums like `Alignment` are written
+    fn tag<T: ::std::fmt::Debug>(&mut self, tag: T) -> io::Result<()> {
+        write!(self.0, "{{\"t\":\"{:?}\"}}", tag)
+    }
+
+    /// Starts a node with content, which is closed by `end`
+    fn start(&mut self, tag: &str) -> io::Result<()> {
+        self.raw(b"{\"t\":\"")?;
+        self.raw(tag.as_bytes())?;
+        self.raw(b"\",\"c\":")
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        self.raw(b"}")
+    }
+
+    fn pandoc(&mut self, doc: &Pandoc) -> io::Result<()> {
+        self.raw(b"{\"meta\":")?;
+        self.meta_map(doc.meta.iter())?;
+        self.raw(b",\"blocks\":")?;
+        self.blocks(&doc.blocks)?;
+        self.raw(b",\"pandoc-api-version\":")?;
+        self.seq(&doc.pandoc_api_version, |w, &n| write!(w.0, "{}", n))?;
+        self.end()
+    }
+
+    fn meta_map<'a, I>(&mut self, entries: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (&'a String, &'a MetaValue)>,
+    {
+        self.raw(b"{")?;
+        for (i, (key, value)) in entries.enumerate() {
+            if i > 0 {
+                self.raw(b",")?;
+            }
+            self.string(key)?;
+            self.raw(b":")?;
+            self.meta_value(value)?;
+        }
+        self.raw(b"}")
+    }
+
+    fn meta_value(&mut self, value: &MetaValue) -> io::Result<()> {
+        match *value {
+            MetaValue::MetaMap(ref map) => {
+                self.start("MetaMap")?;
+                self.meta_map(map.iter().map(|(key, value)| (key, &**value)))?;
+            }
+            MetaValue::MetaList(ref list) => {
+                self.start("MetaList")?;
+                self.seq(list, |w, value| w.meta_value(value))?;
+            }
+            MetaValue::MetaBool(b) => {
+                self.start("MetaBool")?;
+                self.bool(b)?;
+            }
+            MetaValue::MetaString(ref s) => {
+                self.start("MetaString")?;
+                self.string(s)?;
+            }
+            MetaValue::MetaNumber(ref n) => {
+                self.start("MetaString")?;
+                write!(self.0, "{}", n)?;
+            }
+            MetaValue::MetaInlines(ref inlines) => {
+                self.start("MetaInlines")?;
+                self.inlines(inlines)?;
+            }
+            MetaValue::MetaBlocks(ref blocks) => {
+                self.start("MetaBlocks")?;
+                self.blocks(blocks)?;
+            }
+        }
+        self.end()
+    }
+
+    fn attr(&mut self, attr: &Attr) -> io::Result<()> {
+        self.raw(b"[")?;
+        self.string(&attr.0)?;
+        self.raw(b",")?;
+        self.seq(&attr.1, |w, class| w.string(class))?;
+        self.raw(b",")?;
+        self.seq(&attr.2, |w, pair| w.pair(&pair.0, &pair.1))?;
+        self.raw(b"]")
+    }
+
+    /// Writes two strings as an array, e.g. a key-value pair or a `Target`
+    fn pair(&mut self, first: &str, second: &str) -> io::Result<()> {
+        self.raw(b"[")?;
+        self.string(first)?;
+        self.raw(b",")?;
+        self.string(second)?;
+        self.raw(b"]")
+    }
+
+    fn blocks(&mut self, blocks: &[Block]) -> io::Result<()> {
+        self.seq(blocks, |w, block| w.block(block))
+    }
+
+    fn inlines(&mut self, inlines: &[Inline]) -> io::Result<()> {
+        self.seq(inlines, |w, inline| w.inline(inline))
+    }
+
+    /// Writes a node whose content is a list of inlines
+    fn inlines_node(&mut self, tag: &str, inlines: &[Inline]) -> io::Result<()> {
+        self.start(tag)?;
+        self.inlines(inlines)?;
+        self.end()
+    }
+
+    /// Writes a node whose content is an attr and a list of blocks
+    fn attr_blocks_node(&mut self, tag: &str, attr: &Attr, blocks: &[Block]) -> io::Result<()> {
+        self.start(tag)?;
+        self.raw(b"[")?;
+        self.attr(attr)?;
+        self.raw(b",")?;
+        self.blocks(blocks)?;
+        self.raw(b"]")?;
+        self.end()
+    }
+
+    /// Writes a node whose content is an attr and a string
+    fn attr_text_node(&mut self, tag: &str, attr: &Attr, text: &str) -> io::Result<()> {
+        self.start(tag)?;
+        self.raw(b"[")?;
+        self.attr(attr)?;
+        self.raw(b",")?;
+        self.string(text)?;
+        self.raw(b"]")?;
+        self.end()
+    }
+
+    fn unknown(&mut self, tag: &str, content: &serde_json::Value) -> io::Result<()> {
+        self.raw(b"{\"t\":")?;
+        self.string(tag)?;
+        if !content.is_null() {
+            self.raw(b",\"c\":")?;
+            serde_json::to_writer(&mut self.0, content)?;
+        }
+        self.end()
+    }
+
+    fn block(&mut self, block: &Block) -> io::Result<()> {
+        match *block {
+            Block::Plain(ref inlines) => self.inlines_node("Plain", inlines),
+            Block::Para(ref inlines) => self.inlines_node("Para", inlines),
+            Block::LineBlock(ref lines) => {
+                self.start("LineBlock")?;
+                self.seq(lines, |w, line| w.inlines(line))?;
+                self.end()
+            }
+            Block::CodeBlock(ref attr, ref code) => self.attr_text_node("CodeBlock", attr, code),
+            Block::RawBlock(ref format, ref text) => {
+                self.start("RawBlock")?;
+                self.pair(&format.0, text)?;
+                self.end()
+            }
+            Block::BlockQuote(ref blocks) => {
+                self.start("BlockQuote")?;
+                self.blocks(blocks)?;
+                self.end()
+            }
+            Block::OrderedList(ref attributes, ref items) => {
+                self.start("OrderedList")?;
+                self.raw(b"[[")?;
+                self.int(attributes.start)?;
+                self.raw(b",")?;
+                self.tag(attributes.style)?;
+                self.raw(b",")?;
+                self.tag(attributes.delim)?;
+                self.raw(b"],")?;
+                self.seq(items, |w, item| w.blocks(item))?;
+                self.raw(b"]")?;
+                self.end()
+            }
+            Block::BulletList(ref items) => {
+                self.start("BulletList")?;
+                self.seq(items, |w, item| w.blocks(item))?;
+                self.end()
+            }
+            Block::DefinitionList(ref items) => {
+                self.start("DefinitionList")?;
+                self.seq(items, |w, (term, definitions)| {
+                    w.raw(b"[")?;
+                    w.inlines(term)?;
+                    w.raw(b",")?;
+                    w.seq(definitions, |w, definition| w.blocks(definition))?;
+                    w.raw(b"]")
+                })?;
+                self.end()
+            }
+            Block::Header(level, ref attr, ref inlines) => {
+                self.start("Header")?;
+                self.raw(b"[")?;
+                self.int(level)?;
+                self.raw(b",")?;
+                self.attr(attr)?;
+                self.raw(b",")?;
+                self.inlines(inlines)?;
+                self.raw(b"]")?;
+                self.end()
+            }
+            Block::HorizontalRule => self.raw(b"{\"t\":\"HorizontalRule\"}"),
+            Block::Table(ref attr, ref caption, ref specs, ref head, ref bodies, ref foot) => {
+                self.start("Table")?;
+                self.raw(b"[")?;
+                self.attr(attr)?;
+                self.raw(b",")?;
+                self.caption(caption)?;
+                self.raw(b",")?;
+                self.seq(specs, |w, spec| w.col_spec(spec))?;
+                self.raw(b",")?;
+                self.rows_with_attr(&head.0, &head.1)?;
+                self.raw(b",")?;
+                self.seq(bodies, |w, body| w.table_body(body))?;
+                self.raw(b",")?;
+                self.rows_with_attr(&foot.0, &foot.1)?;
+                self.raw(b"]")?;
+                self.end()
+            }
+            Block::Figure(ref attr, ref caption, ref blocks) => {
+                self.start("Figure")?;
+                self.raw(b"[")?;
+                self.attr(attr)?;
+                self.raw(b",")?;
+                self.caption(caption)?;
+                self.raw(b",")?;
+                self.blocks(blocks)?;
+                self.raw(b"]")?;
+                self.end()
+            }
+            Block::Div(ref attr, ref blocks) => self.attr_blocks_node("Div", attr, blocks),
+            Block::Null => self.raw(b"{\"t\":\"Null\"}"),
+            Block::Unknown(ref tag, ref content) => self.unknown(tag, content),
+            Block::Unparsed(ref unparsed) => self.raw(unparsed.json().as_bytes()),
+        }
+    }
+
+    fn caption(&mut self, caption: &Caption) -> io::Result<()> {
+        self.raw(b"[")?;
+        match caption.0 {
+            Some(ref short) => self.inlines(short)?,
+            None => self.raw(b"null")?,
+        }
+        self.raw(b",")?;
+        self.blocks(&caption.1)?;
+        self.raw(b"]")
+    }
+
+    fn col_spec(&mut self, spec: &ColSpec) -> io::Result<()> {
+        self.raw(b"[")?;
+        self.tag(spec.0)?;
+        self.raw(b",")?;
+        match spec.1 {
+            ColWidth::ColWidth(width) => {
+                self.start("ColWidth")?;
+                self.float(width)?;
+                self.end()?;
+            }
+            ColWidth::ColWidthDefault => self.raw(b"{\"t\":\"ColWidthDefault\"}")?,
+        }
+        self.raw(b"]")
+    }
+
+    /// Writes a `TableHead` or `TableFoot`
+    fn rows_with_attr(&mut self, attr: &Attr, rows: &[Row]) -> io::Result<()> {
+        self.raw(b"[")?;
+        self.attr(attr)?;
+        self.raw(b",")?;
+        self.seq(rows, |w, row| w.row(row))?;
+        self.raw(b"]")
+    }
+
+    fn table_body(&mut self, body: &TableBody) -> io::Result<()> {
+        self.raw(b"[")?;
+        self.attr(&body.0)?;
+        self.raw(b",")?;
+        self.int(body.1)?;
+        self.raw(b",")?;
+        self.seq(&body.2, |w, row| w.row(row))?;
+        self.raw(b",")?;
+        self.seq(&body.3, |w, row| w.row(row))?;
+        self.raw(b"]")
+    }
+
+    fn row(&mut self, row: &Row) -> io::Result<()> {
+        self.raw(b"[")?;
+        self.attr(&row.0)?;
+        self.raw(b",")?;
+        self.seq(&row.1, |w, cell| {
+            w.raw(b"[")?;
+            w.attr(&cell.0)?;
+            w.raw(b",")?;
+            w.tag(cell.1)?;
+            w.raw(b",")?;
+            w.int(cell.2)?;
+            w.raw(b",")?;
+            w.int(cell.3)?;
+            w.raw(b",")?;
+            w.blocks(&cell.4)?;
+            w.raw(b"]")
+        })?;
+        self.raw(b"]")
+    }
+
+    fn citation(&mut self, citation: &Citation) -> io::Result<()> {
+        self.raw(b"{\"citationId\":")?;
+        self.string(&citation.citationId)?;
+        self.raw(b",\"citationPrefix\":")?;
+        self.inlines(&citation.citationPrefix)?;
+        self.raw(b",\"citationSuffix\":")?;
+        self.inlines(&citation.citationSuffix)?;
+        self.raw(b",\"citationMode\":")?;
+        self.tag(citation.citationMode)?;
+        self.raw(b",\"citationNoteNum\":")?;
+        self.int(citation.citationNoteNum)?;
+        self.raw(b",\"citationHash\":")?;
+        self.int(citation.citationHash)?;
+        self.end()
+    }
+
+    fn inline(&mut self, inline: &Inline) -> io::Result<()> {
+        match *inline {
+            Inline::Str(ref s) => {
+                self.start("Str")?;
+                self.string(s)?;
+                self.end()
+            }
+            Inline::Emph(ref inlines) => self.inlines_node("Emph", inlines),
+            Inline::Underline(ref inlines) => self.inlines_node("Underline", inlines),
+            Inline::Strong(ref inlines) => self.inlines_node("Strong", inlines),
+            Inline::Strikeout(ref inlines) => self.inlines_node("Strikeout", inlines),
+            Inline::Superscript(ref inlines) => self.inlines_node("Superscript", inlines),
+            Inline::Subscript(ref inlines) => self.inlines_node("Subscript", inlines),
+            Inline::SmallCaps(ref inlines) => self.inlines_node("SmallCaps", inlines),
+            Inline::Quoted(quote, ref inlines) => {
+                self.start("Quoted")?;
+                self.raw(b"[")?;
+                self.tag(quote)?;
+                self.raw(b",")?;
+                self.inlines(inlines)?;
+                self.raw(b"]")?;
+                self.end()
+            }
+            Inline::Cite(ref citations, ref inlines) => {
+                self.start("Cite")?;
+                self.raw(b"[")?;
+                self.seq(citations, |w, citation| w.citation(citation))?;
+                self.raw(b",")?;
+                self.inlines(inlines)?;
+                self.raw(b"]")?;
+                self.end()
+            }
+            Inline::Code(ref attr, ref code) => self.attr_text_node("Code", attr, code),
+            Inline::Space => self.raw(b"{\"t\":\"Space\"}"),
+            Inline::SoftBreak => self.raw(b"{\"t\":\"SoftBreak\"}"),
+            Inline::LineBreak => self.raw(b"{\"t\":\"LineBreak\"}"),
+            Inline::Math(math, ref tex) => {
+                self.start("Math")?;
+                self.raw(b"[")?;
+                self.tag(math)?;
+                self.raw(b",")?;
+                self.string(tex)?;
+                self.raw(b"]")?;
+                self.end()
+            }
+            Inline::RawInline(ref format, ref text) => {
+                self.start("RawInline")?;
+                self.pair(&format.0, text)?;
+                self.end()
+            }
+            Inline::Link(ref attr, ref inlines, ref target) => {
+                self.target_node("Link", attr, inlines, target)
+            }
+            Inline::Image(ref attr, ref inlines, ref target) => {
+                self.target_node("Image", attr, inlines, target)
+            }
+            Inline::Note(ref blocks) => {
+                self.start("Note")?;
+                self.blocks(blocks)?;
+                self.end()
+            }
+            Inline::Span(ref attr, ref inlines) => {
+                self.start("Span")?;
+                self.raw(b"[")?;
+                self.attr(attr)?;
+                self.raw(b",")?;
+                self.inlines(inlines)?;
+                self.raw(b"]")?;
+                self.end()
+            }
+            Inline::Unknown(ref tag, ref content) => self.unknown(tag, content),
+        }
+    }
+
+    /// Writes a `Link` or `Image`
+    fn target_node(
+        &mut self,
+        tag: &str,
+        attr: &Attr,
+        inlines: &[Inline],
+        target: &Target,
+    ) -> io::Result<()> {
+        self.start(tag)?;
+        self.raw(b"[")?;
+        self.attr(attr)?;
+        self.raw(b",")?;
+        self.inlines(inlines)?;
+        self.raw(b",")?;
+        self.pair(&target.0, &target.1)?;
+        self.raw(b"]")?;
+        self.end()
+    }
+}
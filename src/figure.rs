@@ -0,0 +1,146 @@
+//! Figures in both of pandoc's representations.
+//!
+//! Before pandoc 3 (API 1.23), a figure is an implicit figure: a paragraph
+//! containing nothing but an image whose title starts with `fig:`, the alt
+//! text being the caption. Newer versions use `Block::Figure`.
+
+use super::*;
+use std::mem;
+
+/// Whether `block` is a paragraph with a single image titled `fig:...`
+pub fn is_implicit_figure(block: &Block) -> bool {
+    match *block {
+        Block::Para(ref content) => match content[..] {
+            [Inline::Image(_, _, ref target)] => target.1.starts_with("fig:"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether `block` is a figure in either representation
+pub fn is_figure(block: &Block) -> bool {
+    matches!(*block, Block::Figure(..)) || is_implicit_figure(block)
+}
+
+/// The caption of a figure in either representation. The caption blocks of
+/// a `Figure` are joined into a single list of inlines.
+pub fn caption(block: &Block) -> Option<Vec<Inline>> {
+    match *block {
+        Block::Figure(_, ref caption, _) => Some(
+            caption
+                .1
+                .iter()
+                .flat_map(|block| match *block {
+                    Block::Plain(ref content) | Block::Para(ref content) => content.clone(),
+                    _ => Vec::new(),
+                })
+                .collect(),
+        ),
+        Block::Para(ref content) if is_implicit_figure(block) => match content[0] {
+            Inline::Image(_, ref alt, _) => Some(alt.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Replaces the caption of a figure in either representation, returning
+/// whether `block` is a figure
+pub fn set_caption(block: &mut Block, text: Vec<Inline>) -> bool {
+    if !is_figure(block) {
+        return false;
+    }
+    match *block {
+        Block::Figure(_, ref mut caption, _) => caption.1 = vec![Block::Plain(text)],
+        Block::Para(ref mut content) => {
+            if let Inline::Image(_, ref mut alt, _) = content[0] {
+                *alt = text;
+            }
+        }
+        _ => {}
+    }
+    true
+}
+
+/// Turns an implicit figure into a `Figure` the way pandoc 3 reads it: the
+/// figure takes the identifier of the image, the image keeps its other
+/// attributes and loses the `fig:` prefix of its title. Returns whether
+/// `block` was converted.
+pub fn to_figure(block: &mut Block) -> bool {
+    if !is_implicit_figure(block) {
+        return false;
+    }
+    let (mut attr, alt, target) = match mem::replace(block, Block::Null) {
+        Block::Para(mut content) => match content.pop() {
+            Some(Inline::Image(attr, alt, target)) => (attr, alt, target),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    let id = mem::take(&mut attr.0);
+    let caption = if alt.is_empty() {
+        Vec::new()
+    } else {
+        vec![Block::Plain(alt.clone())]
+    };
+    let title = target.1["fig:".len()..].to_owned();
+    *block = Block::Figure(
+        (id, Vec::new(), Vec::new()),
+        (None, caption),
+        vec![Block::Plain(vec![Inline::Image(
+            attr,
+            alt,
+            (target.0, title),
+        )])],
+    );
+    true
+}
+
+/// Turns a `Figure` containing a single image into an implicit figure, the
+/// inverse of `to_figure`. Other figures can't be represented by older
+/// pandoc versions and are left alone. Returns whether `block` was converted.
+pub fn to_implicit_figure(block: &mut Block) -> bool {
+    let image = match *block {
+        Block::Figure(ref attr, ref caption, ref content) => match content[..] {
+            [Block::Plain(ref inlines)] | [Block::Para(ref inlines)] => match inlines[..] {
+                [Inline::Image(ref image_attr, _, ref target)] => {
+                    let mut image_attr = image_attr.clone();
+                    if image_attr.0.is_empty() {
+                        image_attr.0 = attr.0.clone();
+                    }
+                    let alt = caption
+                        .1
+                        .iter()
+                        .flat_map(|block| match *block {
+                            Block::Plain(ref content) | Block::Para(ref content) => content.clone(),
+                            _ => Vec::new(),
+                        })
+                        .collect();
+                    let target = (target.0.clone(), format!("fig:{}", target.1));
+                    Inline::Image(image_attr, alt, target)
+                }
+                _ => return false,
+            },
+            _ => return false,
+        },
+        _ => return false,
+    };
+    *block = Block::Para(vec![image]);
+    true
+}
+
+/// Converts every implicit figure below `node` into a `Figure`
+pub fn to_figures<T: MutVisitable>(node: &mut T) {
+    node.for_each_block_mut(|block| {
+        to_figure(block);
+    })
+}
+
+/// Converts every `Figure` below `node` that holds a single image into an
+/// implicit figure, for writers of pandoc versions before 3
+pub fn to_implicit_figures<T: MutVisitable>(node: &mut T) {
+    node.for_each_block_mut(|block| {
+        to_implicit_figure(block);
+    })
+}
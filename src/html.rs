@@ -0,0 +1,429 @@
+//! A small html writer for previews.
+//!
+//! Only the body is written, without templates, styling or metadata. Math is
+//! left as tex in `span.math` elements, raw content in other formats than
+//! html is dropped and footnotes are collected in a `section.footnotes` at
+//! the end.
+
+use super::*;
+
+fn escape(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+struct Writer {
+    out: String,
+    notes: Vec<String>,
+}
+
+impl Writer {
+    fn text(&mut self, text: &str) {
+        escape(text, &mut self.out);
+    }
+
+    /// ` id="…" class="…" data-key="…"`, with `extra` added to the classes
+    fn attr(&mut self, attr: &Attr, extra: Option<&str>) {
+        let (ref id, ref classes, ref attributes) = *attr;
+        if !id.is_empty() {
+            self.out.push_str(" id=\"");
+            self.text(id);
+            self.out.push('"');
+        }
+        let classes: Vec<&str> = extra
+            .into_iter()
+            .chain(classes.iter().map(String::as_str))
+            .collect();
+        if !classes.is_empty() {
+            self.out.push_str(" class=\"");
+            self.text(&classes.join(" "));
+            self.out.push('"');
+        }
+        for (key, value) in attributes {
+            self.out.push_str(" data-");
+            self.text(key);
+            self.out.push_str("=\"");
+            self.text(value);
+            self.out.push('"');
+        }
+    }
+
+    fn tag(&mut self, name: &str, attr: Option<&Attr>) {
+        self.out.push('<');
+        self.out.push_str(name);
+        if let Some(attr) = attr {
+            self.attr(attr, None);
+        }
+        self.out.push('>');
+    }
+
+    fn wrap_inlines(&mut self, name: &str, attr: Option<&Attr>, content: &[Inline]) {
+        self.tag(name, attr);
+        self.inlines(content);
+        self.out.push_str("</");
+        self.out.push_str(name);
+        self.out.push('>');
+    }
+
+    fn inlines(&mut self, inlines: &[Inline]) {
+        for inline in inlines {
+            self.inline(inline);
+        }
+    }
+
+    fn inline(&mut self, inline: &Inline) {
+        match *inline {
+            Inline::Str(ref s) => self.text(s),
+            Inline::Emph(ref c) => self.wrap_inlines("em", None, c),
+            Inline::Underline(ref c) => self.wrap_inlines("u", None, c),
+            Inline::Strong(ref c) => self.wrap_inlines("strong", None, c),
+            Inline::Strikeout(ref c) => self.wrap_inlines("del", None, c),
+            Inline::Superscript(ref c) => self.wrap_inlines("sup", None, c),
+            Inline::Subscript(ref c) => self.wrap_inlines("sub", None, c),
+            Inline::SmallCaps(ref c) => {
+                self.out.push_str("<span class=\"smallcaps\">");
+                self.inlines(c);
+                self.out.push_str("</span>");
+            }
+            Inline::Quoted(ref quote_type, ref c) => {
+                let (open, close) = match *quote_type {
+                    QuoteType::SingleQuote => ("‘", "’"),
+                    QuoteType::DoubleQuote => ("“", "”"),
+                };
+                self.out.push_str(open);
+                self.inlines(c);
+                self.out.push_str(close);
+            }
+            Inline::Cite(_, ref c) => {
+                self.out.push_str("<span class=\"citation\">");
+                self.inlines(c);
+                self.out.push_str("</span>");
+            }
+            Inline::Code(ref attr, ref code) => {
+                self.tag("code", Some(attr));
+                self.text(code);
+                self.out.push_str("</code>");
+            }
+            Inline::Space => self.out.push(' '),
+            Inline::SoftBreak => self.out.push('\n'),
+            Inline::LineBreak => self.out.push_str("<br />\n"),
+            Inline::Math(ref math_type, ref tex) => {
+                let (class, open, close) = match *math_type {
+                    MathType::InlineMath => ("inline", "\\(", "\\)"),
+                    MathType::DisplayMath => ("display", "\\[", "\\]"),
+                };
+                self.out.push_str("<span class=\"math ");
+                self.out.push_str(class);
+                self.out.push_str("\">");
+                self.out.push_str(open);
+                self.text(tex);
+                self.out.push_str(close);
+                self.out.push_str("</span>");
+            }
+            Inline::RawInline(ref format, ref raw) => {
+                if format.is_html() {
+                    self.out.push_str(raw);
+                }
+            }
+            Inline::Link(ref attr, ref c, (ref url, ref title)) => {
+                self.out.push_str("<a");
+                self.attr(attr, None);
+                self.out.push_str(" href=\"");
+                self.text(url);
+                self.out.push('"');
+                if !title.is_empty() {
+                    self.out.push_str(" title=\"");
+                    self.text(title);
+                    self.out.push('"');
+                }
+                self.out.push('>');
+                self.inlines(c);
+                self.out.push_str("</a>");
+            }
+            Inline::Image(ref attr, ref c, (ref url, ref title)) => {
+                self.out.push_str("<img");
+                self.attr(attr, None);
+                self.out.push_str(" src=\"");
+                self.text(url);
+                self.out.push_str("\" alt=\"");
+                self.text(&inlines_to_string(c));
+                self.out.push('"');
+                if !title.is_empty() {
+                    self.out.push_str(" title=\"");
+                    self.text(title);
+                    self.out.push('"');
+                }
+                self.out.push_str(" />");
+            }
+            Inline::Note(ref content) => {
+                let number = self.notes.len() + 1;
+                // notes inside notes are numbered after the outer one
+                self.notes.push(String::new());
+                let mut writer = Writer {
+                    out: String::new(),
+                    notes: std::mem::take(&mut self.notes),
+                };
+                writer.blocks(content);
+                self.notes = writer.notes;
+                self.notes[number - 1] = writer.out;
+                self.out.push_str(&format!(
+                    "<a href=\"#fn{0}\" class=\"footnote-ref\" id=\"fnref{0}\"><sup>{0}</sup></a>",
+                    number
+                ));
+            }
+            Inline::Span(ref attr, ref c) => self.wrap_inlines("span", Some(attr), c),
+            Inline::Unknown(ref name, _) => {
+                self.out.push_str("<!-- ");
+                self.text(name);
+                self.out.push_str(" -->");
+            }
+        }
+    }
+
+    fn blocks(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            self.block(block);
+        }
+    }
+
+    fn list_items(&mut self, items: &[Vec<Block>]) {
+        for item in items {
+            self.out.push_str("<li>");
+            match item[..] {
+                [Block::Plain(ref c)] => self.inlines(c),
+                _ => {
+                    self.out.push('\n');
+                    self.blocks(item);
+                }
+            }
+            self.out.push_str("</li>\n");
+        }
+    }
+
+    fn cells(&mut self, rows: &[Row], tag: &str) {
+        for row in rows {
+            self.tag("tr", Some(&row.0));
+            self.out.push('\n');
+            for cell in &row.1 {
+                let (ref attr, ref alignment, row_span, col_span, ref content) = *cell;
+                self.out.push('<');
+                self.out.push_str(tag);
+                self.attr(attr, None);
+                let align = match *alignment {
+                    Alignment::AlignLeft => Some("left"),
+                    Alignment::AlignRight => Some("right"),
+                    Alignment::AlignCenter => Some("center"),
+                    Alignment::AlignDefault => None,
+                };
+                if let Some(align) = align {
+                    self.out
+                        .push_str(&format!(" style=\"text-align: {}\"", align));
+                }
+                if row_span > 1 {
+                    self.out.push_str(&format!(" rowspan=\"{}\"", row_span));
+                }
+                if col_span > 1 {
+                    self.out.push_str(&format!(" colspan=\"{}\"", col_span));
+                }
+                self.out.push('>');
+                match content[..] {
+                    [Block::Plain(ref c)] => self.inlines(c),
+                    _ => self.blocks(content),
+                }
+                self.out.push_str("</");
+                self.out.push_str(tag);
+                self.out.push_str(">\n");
+            }
+            self.out.push_str("</tr>\n");
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        match *block {
+            Block::Plain(ref c) => {
+                self.inlines(c);
+                self.out.push('\n');
+            }
+            Block::Para(ref c) => {
+                self.wrap_inlines("p", None, c);
+                self.out.push('\n');
+            }
+            Block::LineBlock(ref lines) => {
+                self.out.push_str("<div class=\"line-block\">");
+                for (i, line) in lines.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str("<br />\n");
+                    }
+                    self.inlines(line);
+                }
+                self.out.push_str("</div>\n");
+            }
+            Block::CodeBlock(ref attr, ref code) => {
+                self.out.push_str("<pre");
+                self.attr(attr, None);
+                self.out.push_str("><code>");
+                self.text(code);
+                self.out.push_str("</code></pre>\n");
+            }
+            Block::RawBlock(ref format, ref raw) => {
+                if format.is_html() {
+                    self.out.push_str(raw);
+                    self.out.push('\n');
+                }
+            }
+            Block::BlockQuote(ref content) => {
+                self.out.push_str("<blockquote>\n");
+                self.blocks(content);
+                self.out.push_str("</blockquote>\n");
+            }
+            Block::OrderedList(
+                ListAttributes {
+                    start, ref style, ..
+                },
+                ref items,
+            ) => {
+                self.out.push_str("<ol");
+                if start != 1 {
+                    self.out.push_str(&format!(" start=\"{}\"", start));
+                }
+                let kind = match *style {
+                    ListNumberStyle::LowerRoman => Some("i"),
+                    ListNumberStyle::UpperRoman => Some("I"),
+                    ListNumberStyle::LowerAlpha => Some("a"),
+                    ListNumberStyle::UpperAlpha => Some("A"),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    self.out.push_str(&format!(" type=\"{}\"", kind));
+                }
+                self.out.push_str(">\n");
+                self.list_items(items);
+                self.out.push_str("</ol>\n");
+            }
+            Block::BulletList(ref items) => {
+                self.out.push_str("<ul>\n");
+                self.list_items(items);
+                self.out.push_str("</ul>\n");
+            }
+            Block::DefinitionList(ref items) => {
+                self.out.push_str("<dl>\n");
+                for (term, definitions) in items {
+                    self.wrap_inlines("dt", None, term);
+                    self.out.push('\n');
+                    for definition in definitions {
+                        self.out.push_str("<dd>");
+                        self.blocks(definition);
+                        self.out.push_str("</dd>\n");
+                    }
+                }
+                self.out.push_str("</dl>\n");
+            }
+            Block::Header(level, ref attr, ref c) => {
+                let tag = format!("h{}", level.clamp(1, 6));
+                self.wrap_inlines(&tag, Some(attr), c);
+                self.out.push('\n');
+            }
+            Block::HorizontalRule => self.out.push_str("<hr />\n"),
+            Block::Table(ref attr, ref caption, _, ref head, ref bodies, ref foot) => {
+                self.tag("table", Some(attr));
+                self.out.push('\n');
+                if !caption.1.is_empty() {
+                    self.out.push_str("<caption>");
+                    match caption.1[..] {
+                        [Block::Plain(ref c)] | [Block::Para(ref c)] => self.inlines(c),
+                        _ => self.blocks(&caption.1),
+                    }
+                    self.out.push_str("</caption>\n");
+                }
+                if !head.1.is_empty() {
+                    self.tag("thead", Some(&head.0));
+                    self.out.push('\n');
+                    self.cells(&head.1, "th");
+                    self.out.push_str("</thead>\n");
+                }
+                for body in bodies {
+                    self.tag("tbody", Some(&body.0));
+                    self.out.push('\n');
+                    self.cells(&body.2, "th");
+                    self.cells(&body.3, "td");
+                    self.out.push_str("</tbody>\n");
+                }
+                if !foot.1.is_empty() {
+                    self.tag("tfoot", Some(&foot.0));
+                    self.out.push('\n');
+                    self.cells(&foot.1, "td");
+                    self.out.push_str("</tfoot>\n");
+                }
+                self.out.push_str("</table>\n");
+            }
+            Block::Figure(ref attr, ref caption, ref content) => {
+                self.tag("figure", Some(attr));
+                self.out.push('\n');
+                self.blocks(content);
+                if !caption.1.is_empty() {
+                    self.out.push_str("<figcaption>");
+                    self.blocks(&caption.1);
+                    self.out.push_str("</figcaption>\n");
+                }
+                self.out.push_str("</figure>\n");
+            }
+            Block::Div(ref attr, ref content) => {
+                self.tag("div", Some(attr));
+                self.out.push('\n');
+                self.blocks(content);
+                self.out.push_str("</div>\n");
+            }
+            Block::Null => {}
+            Block::Unknown(ref name, _) => {
+                self.out.push_str("<!-- ");
+                self.text(name);
+                self.out.push_str(" -->\n");
+            }
+            Block::Unparsed(ref unparsed) => {
+                if let Ok(block) = unparsed.parse() {
+                    self.block(&block);
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if self.notes.is_empty() {
+            return self.out;
+        }
+        self.out
+            .push_str("<section class=\"footnotes\">\n<hr />\n<ol>\n");
+        for (i, note) in self.notes.iter().enumerate() {
+            self.out.push_str(&format!(
+                "<li id=\"fn{0}\">\n{1}<a href=\"#fnref{0}\" class=\"footnote-back\">↩︎</a></li>\n",
+                i + 1,
+                note
+            ));
+        }
+        self.out.push_str("</ol>\n</section>\n");
+        self.out
+    }
+}
+
+impl Pandoc {
+    /// Renders the blocks as an html fragment for previews. There is no
+    /// `<html>` or `<body>` element around it and the metadata is ignored.
+    /// Key-value attributes become `data-` attributes, math is kept as tex
+    /// and raw content in formats other than html is dropped. This is not
+    /// meant to replace pandoc's html writer.
+    pub fn to_html_fragment(&self) -> String {
+        let mut writer = Writer {
+            out: String::new(),
+            notes: Vec::new(),
+        };
+        writer.blocks(&self.blocks);
+        writer.finish()
+    }
+}
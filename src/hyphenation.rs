@@ -0,0 +1,185 @@
+//! Hyphenation of long words with TeX patterns
+//!
+//! `Hyphenator` implements Liang's algorithm as used by TeX, with patterns
+//! like `hy3ph` in the format of the `hyph-*.pat.txt` files of the
+//! `hyph-utf8` project. `hyphenate` inserts the break opportunities it finds
+//! into the `Str`s of a document, for writers and typesetters that can't
+//! hyphenate by themselves, e.g. for narrow columns in pdf output.
+//!
+//! ```
+//! # use pandoc_ast::hyphenation::{Break, Hyphenator};
+//! # use pandoc_ast::*;
+//! let hyphenator = Hyphenator::new("hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n");
+//! assert_eq!(hyphenator.breaks("hyphenation"), vec![2, 6]);
+//! let mut inlines = inlines!["hyphenation"];
+//! hyphenation::hyphenate(&mut inlines, &hyphenator, Break::SoftHyphen);
+//! assert_eq!(inlines, inlines!["hy\u{ad}phen\u{ad}ation"]);
+//! ```
+
+use super::*;
+use std::collections::HashMap;
+
+/// What `hyphenate` inserts where a word may be broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Break {
+    /// U+00AD, which renders as a hyphen only if the line is broken there
+    SoftHyphen,
+    /// a `SoftBreak` inline between the parts of the word. Writers treat it
+    /// like a newline in the source, which most outputs render as a space,
+    /// so this is for formats that join lines without one.
+    SoftBreak,
+}
+
+/// Liang's hyphenation algorithm with a set of patterns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hyphenator {
+    /// the letters of each pattern and the values between them
+    patterns: HashMap<String, Vec<u8>>,
+    longest: usize,
+    left_min: usize,
+    right_min: usize,
+    min_length: usize,
+}
+
+impl Hyphenator {
+    /// A hyphenator with the whitespace separated `patterns`. Words are
+    /// broken at least two letters from their start and three from their
+    /// end, words shorter than six letters are left alone.
+    pub fn new(patterns: &str) -> Self {
+        let mut hyphenator = Hyphenator {
+            left_min: 2,
+            right_min: 3,
+            min_length: 6,
+            ..Hyphenator::default()
+        };
+        for pattern in patterns.split_whitespace() {
+            hyphenator.add_pattern(pattern);
+        }
+        hyphenator
+    }
+
+    /// Adds a pattern like `.ach4` or `hy3ph`
+    pub fn add_pattern(&mut self, pattern: &str) {
+        let mut letters = String::new();
+        let mut values = vec![0];
+        for c in pattern.chars() {
+            match c.to_digit(10) {
+                Some(digit) => *values.last_mut().unwrap() = digit as u8,
+                None => {
+                    letters.extend(c.to_lowercase());
+                    values.push(0);
+                }
+            }
+        }
+        self.longest = self.longest.max(letters.chars().count());
+        self.patterns.insert(letters, values);
+    }
+
+    /// Sets how many letters must stay before the first and after the last
+    /// break of a word
+    pub fn margins(mut self, left_min: usize, right_min: usize) -> Self {
+        self.left_min = left_min.max(1);
+        self.right_min = right_min.max(1);
+        self
+    }
+
+    /// Sets the number of letters below which words are not hyphenated
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    /// The character offsets in `word` where it may be broken
+    pub fn breaks(&self, word: &str) -> Vec<usize> {
+        let letters: Vec<char> = word.chars().collect();
+        let n = letters.len();
+        if n < self.min_length || n < self.left_min + self.right_min {
+            return Vec::new();
+        }
+        let padded: Vec<char> = Some('.')
+            .into_iter()
+            .chain(letters.iter().flat_map(|c| c.to_lowercase()))
+            .chain(Some('.'))
+            .collect();
+        // lowercasing may change the length, in which case the positions
+        // wouldn't fit the word
+        if padded.len() != n + 2 {
+            return Vec::new();
+        }
+        let mut points = vec![0; padded.len() + 1];
+        for start in 0..padded.len() {
+            let mut key = String::new();
+            for &c in padded[start..].iter().take(self.longest) {
+                key.push(c);
+                if let Some(values) = self.patterns.get(&key) {
+                    for (point, &value) in points[start..].iter_mut().zip(values) {
+                        *point = (*point).max(value);
+                    }
+                }
+            }
+        }
+        (self.left_min..=n - self.right_min)
+            .filter(|&k| points[k + 1] % 2 == 1)
+            .collect()
+    }
+}
+
+/// Whether the text of a `Str` is a url or an email address, which must not
+/// be broken
+fn is_address(text: &str) -> bool {
+    text.contains("://") || text.starts_with("www.") || text.contains('@')
+}
+
+/// Appends the letters of `word` to `parts`, starting a new part at every
+/// break, and clears it
+fn flush(word: &mut String, parts: &mut Vec<String>, hyphenator: &Hyphenator) {
+    let breaks = hyphenator.breaks(word);
+    for (i, c) in word.chars().enumerate() {
+        if breaks.contains(&i) {
+            parts.push(String::new());
+        }
+        parts.last_mut().unwrap().push(c);
+    }
+    word.clear();
+}
+
+/// The parts of `text` between the breaks of its words
+fn split(text: &str, hyphenator: &Hyphenator) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_alphabetic() {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut parts, hyphenator);
+            parts.last_mut().unwrap().push(c);
+        }
+    }
+    flush(&mut word, &mut parts, hyphenator);
+    parts
+}
+
+/// Inserts `mark` at the breaks the hyphenator finds in the words of all
+/// `Str`s below `node`. Code, math and raw content are not `Str`s and stay
+/// as they are, `Str`s that look like urls or email addresses are skipped.
+pub fn hyphenate<T: MutVisitable>(node: &mut T, hyphenator: &Hyphenator, mark: Break) {
+    transform::inlines(node, transform::TraversalOrder::BottomUp, |inline| {
+        let parts = match *inline {
+            Inline::Str(ref text) if !is_address(text) => split(text, hyphenator),
+            _ => return transform::Action::Keep,
+        };
+        match mark {
+            Break::SoftHyphen => transform::Action::Replace(Inline::Str(parts.join("\u{ad}"))),
+            Break::SoftBreak => {
+                let mut inlines = Vec::with_capacity(parts.len() * 2);
+                for part in parts {
+                    if !inlines.is_empty() {
+                        inlines.push(Inline::SoftBreak);
+                    }
+                    inlines.push(Inline::Str(part));
+                }
+                transform::Action::ReplaceMany(inlines)
+            }
+        }
+    });
+}
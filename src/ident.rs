@@ -0,0 +1,111 @@
+//! Identifier generation following pandoc's `auto_identifiers` extension
+
+use super::*;
+use std::collections::HashSet;
+
+fn slug_with(inlines: &[Inline], ascii: bool) -> String {
+    let text = stringify(inlines).to_lowercase();
+    let allowed: String = text
+        .chars()
+        .filter_map(|c| if ascii { to_ascii(c) } else { Some(c) })
+        .filter(|&c| c.is_whitespace() || c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+        .collect();
+    let ident = allowed.split_whitespace().collect::<Vec<_>>().join("-");
+    ident
+        .trim_start_matches(|c: char| !c.is_alphabetic())
+        .to_owned()
+}
+
+const ACCENTED: &[(&str, char)] = &[
+    ("àáâãäåāăą", 'a'),
+    ("çćĉċč", 'c'),
+    ("ď", 'd'),
+    ("èéêëēĕėęě", 'e'),
+    ("ĝğġģ", 'g'),
+    ("ĥ", 'h'),
+    ("ìíîïĩīĭį", 'i'),
+    ("ĵ", 'j'),
+    ("ķ", 'k'),
+    ("ĺļľ", 'l'),
+    ("ñńņň", 'n'),
+    ("òóôõöōŏő", 'o'),
+    ("ŕŗř", 'r'),
+    ("śŝşš", 's'),
+    ("ţť", 't'),
+    ("ùúûüũūŭůűų", 'u'),
+    ("ŵ", 'w'),
+    ("ýÿŷ", 'y'),
+    ("źżž", 'z'),
+];
+
+/// strips diacritics from lowercase latin letters and drops any other non-ascii character
+fn to_ascii(c: char) -> Option<char> {
+    if c.is_ascii() {
+        return Some(c);
+    }
+    ACCENTED
+        .iter()
+        .find(|&&(accented, _)| accented.contains(c))
+        .map(|&(_, plain)| plain)
+}
+
+/// The identifier pandoc derives from a header text: the lowercased text
+/// without formatting, footnotes and punctuation (except `_`, `-` and `.`),
+/// with hyphens instead of spaces and everything before the first letter
+/// removed. May be empty, see `unique_identifier`.
+pub fn slugify(inlines: &[Inline]) -> String {
+    slug_with(inlines, false)
+}
+
+/// `slugify` with the `ascii_identifiers` extension: accents are removed
+/// from latin letters and all other non-ascii characters are dropped.
+pub fn slugify_ascii(inlines: &[Inline]) -> String {
+    slug_with(inlines, true)
+}
+
+/// pandoc's `uniqueIdent`: an empty identifier becomes `section`, and
+/// `-1`, `-2`, ... is appended if the identifier is already in `used`.
+/// The result is added to `used`.
+pub fn unique_identifier(base: String, used: &mut HashSet<String>) -> String {
+    let base = if base.is_empty() {
+        "section".to_owned()
+    } else {
+        base
+    };
+    let ident = if used.contains(&base) {
+        (1..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|ident| !used.contains(ident))
+            .expect("there are infinitely many numbers")
+    } else {
+        base
+    };
+    used.insert(ident.clone());
+    ident
+}
+
+/// All non-empty identifiers below a node
+pub fn used_identifiers<T: Visitable + ?Sized>(node: &T) -> HashSet<String> {
+    node.descendants()
+        .filter_map(|node| node.attr())
+        .filter(|attr| !attr.0.is_empty())
+        .map(|attr| attr.0.clone())
+        .collect()
+}
+
+/// Gives every header and `Div` without an identifier a unique one, in
+/// document order. Headers use their text, `Div`s the text of their first
+/// block. Existing identifiers are never changed.
+pub fn assign_identifiers(doc: &mut Pandoc) {
+    let mut used = used_identifiers(doc);
+    doc.blocks.for_each_block_mut(|block| match *block {
+        Block::Header(_, ref mut attr, ref text) if attr.0.is_empty() => {
+            attr.0 = unique_identifier(slugify(text), &mut used);
+        }
+        Block::Div(ref mut attr, ref content) if attr.0.is_empty() => {
+            let text = content.first().map(stringify).unwrap_or_default();
+            attr.0 = unique_identifier(slugify(&inlines_from_str(&text)), &mut used);
+        }
+        _ => {}
+    });
+}
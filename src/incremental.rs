@@ -0,0 +1,163 @@
+//! Incremental filtering, reusing the results of earlier runs for unchanged
+//! blocks.
+//!
+//! `Cache::map_blocks` applies a function to the top-level blocks of a
+//! document like `Pandoc::par_map_blocks`, but first looks up the
+//! `content_hash` of each block in the cache and reuses the stored result.
+//! The cache keeps the input blocks as well, so a block whose hash merely
+//! collides with a cached one is filtered again.
+//! Watch-mode builds re-running a filter on every change then only filter
+//! the blocks that were edited. The cache is written and read as json by
+//! the caller, e.g. to a file next to the output:
+//!
+//! ```no_run
+//! # use pandoc_ast::*;
+//! # use pandoc_ast::incremental::Cache;
+//! # use std::fs;
+//! # let mut doc = Pandoc::new();
+//! let key = "my-filter 1.0";
+//! let mut cache = fs::read_to_string("filter-cache.json")
+//!     .ok()
+//!     .and_then(|json| Cache::from_json(&json, key).ok())
+//!     .unwrap_or_else(|| Cache::new(key));
+//! cache.map_blocks(&mut doc, |block| block);
+//! cache.prune();
+//! fs::write("filter-cache.json", cache.to_json()).unwrap();
+//! ```
+//!
+//! The results only depend on the block, so filters that also look at the
+//! metadata or other blocks have to include these in the key, e.g. via
+//! `content_hash(&doc.meta)`.
+
+use super::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+/// FNV-1a, fed with the json of a node
+struct Fnv(u64);
+
+impl Write for Fnv {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A hash of the content of a node that stays the same across runs and
+/// platforms, unlike the one of `Hash`. It is computed from the json of
+/// the node.
+pub fn content_hash<T: Serialize>(node: &T) -> u64 {
+    let mut hasher = Fnv(0xcbf2_9ce4_8422_2325);
+    serde_json::to_writer(&mut hasher, node).expect("serialization failed");
+    hasher.0
+}
+
+/// A block and the result of the filter for it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Entry {
+    input: Block,
+    output: Vec<Block>,
+}
+
+/// Results of a filter for blocks, by their `content_hash`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Cache {
+    key: String,
+    /// the entries of all blocks with a hash, usually just one
+    entries: BTreeMap<u64, Vec<Entry>>,
+    /// the entries looked up since the cache was created or read
+    #[serde(skip)]
+    used: BTreeSet<u64>,
+    #[serde(skip)]
+    hits: usize,
+    #[serde(skip)]
+    misses: usize,
+}
+
+impl Cache {
+    /// An empty cache for the filter identified by `key`, which should
+    /// change whenever the filter or its configuration does
+    pub fn new(key: &str) -> Self {
+        Cache {
+            key: key.to_owned(),
+            ..Cache::default()
+        }
+    }
+
+    /// Reads a cache written by `to_json`. A cache written with a different
+    /// key is discarded and an empty one returned.
+    pub fn from_json(json: &str, key: &str) -> Result<Self, Error> {
+        let cache: Cache = serde_json::from_str(json)?;
+        if cache.key == key {
+            Ok(cache)
+        } else {
+            Ok(Cache::new(key))
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("serialization failed")
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of blocks whose result was taken from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of blocks that had to be filtered
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Applies `f` to every top-level block of `doc` whose result isn't in
+    /// the cache yet and stores the result. `f` may return a single block or
+    /// a `Vec<Block>` to replace the block with several or none.
+    pub fn map_blocks<F, R>(&mut self, doc: &mut Pandoc, mut f: F)
+    where
+        F: FnMut(Block) -> R,
+        R: IntoBlocks,
+    {
+        for block in mem::take(&mut doc.blocks) {
+            let hash = content_hash(&block);
+            self.used.insert(hash);
+            let entries = self.entries.entry(hash).or_default();
+            if let Some(entry) = entries.iter().find(|entry| entry.input == block) {
+                self.hits += 1;
+                doc.blocks.extend(entry.output.iter().cloned());
+                continue;
+            }
+            self.misses += 1;
+            let input = block.clone();
+            let mut output = Vec::new();
+            f(block).extend_blocks(&mut output);
+            doc.blocks.extend(output.iter().cloned());
+            entries.push(Entry { input, output });
+        }
+    }
+
+    /// Removes the entries that were not used since the cache was created
+    /// or read, e.g. those of blocks that have since been edited
+    pub fn prune(&mut self) {
+        let used = &self.used;
+        self.entries.retain(|hash, _| used.contains(hash));
+    }
+}
@@ -0,0 +1,211 @@
+//! Back-of-book index generation from marked spans
+//!
+//! Index entries are `Span`s with the class `index`, the term being the
+//! text of the span or its `term` attribute, and a sub-term in its `sub`
+//! attribute:
+//!
+//! ```markdown
+//! The [borrow checker]{.index} enforces [ownership]{.index sub="moves"}.
+//! ```
+//!
+//! `Index::build` gives every marked span an identifier to link to and
+//! returns the index as a `Div` with the class `index`, holding a
+//! definition list of terms and links to their occurrences per initial
+//! letter.
+//!
+//! ```
+//! # use pandoc_ast::index::Index;
+//! # use pandoc_ast::*;
+//! let mut doc = Pandoc::new();
+//! doc.blocks.push(Block::Para(vec![Inline::span("index", inlines!["ownership"])]));
+//! let index = Index::new().build(&mut doc);
+//! doc.blocks.push(index);
+//! ```
+
+use super::*;
+use definition::{DefinitionList, Entry};
+use std::collections::BTreeMap;
+
+/// A term of the index with the identifiers of its occurrences, in
+/// document order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Term {
+    pub name: String,
+    pub targets: Vec<String>,
+    /// sorted like the terms
+    pub sub_terms: Vec<Term>,
+}
+
+impl Term {
+    fn new(name: String) -> Self {
+        Term {
+            name,
+            ..Term::default()
+        }
+    }
+
+    /// The `Plain` block of links to the occurrences, numbered from one
+    fn links(&self) -> Block {
+        let mut inlines = Vec::new();
+        for (i, target) in self.targets.iter().enumerate() {
+            if i > 0 {
+                inlines.push(Inline::Str(",".to_owned()));
+                inlines.push(Inline::Space);
+            }
+            let number = vec![Inline::Str((i + 1).to_string())];
+            inlines.push(Inline::link(number, format!("#{}", target)));
+        }
+        Block::Plain(inlines)
+    }
+
+    fn entry(&self) -> Entry {
+        let mut definition = Vec::new();
+        if !self.targets.is_empty() {
+            definition.push(self.links());
+        }
+        if !self.sub_terms.is_empty() {
+            definition.push(definition_list(&self.sub_terms).into_block());
+        }
+        Entry {
+            term: inlines_from_str(&self.name),
+            definitions: vec![definition],
+        }
+    }
+}
+
+fn definition_list(terms: &[Term]) -> DefinitionList {
+    DefinitionList {
+        entries: terms.iter().map(Term::entry).collect(),
+    }
+}
+
+/// the term with `name` in `terms`, added if missing
+fn term<'t>(terms: &'t mut Vec<Term>, name: &str) -> &'t mut Term {
+    match terms.iter().position(|term| term.name == name) {
+        Some(i) => &mut terms[i],
+        None => {
+            terms.push(Term::new(name.to_owned()));
+            terms.last_mut().unwrap()
+        }
+    }
+}
+
+fn sort(terms: &mut [Term]) {
+    terms.sort_by_cached_key(|term| (term.name.to_lowercase(), term.name.clone()));
+    for term in terms {
+        sort(&mut term.sub_terms);
+    }
+}
+
+/// The letter a term is filed under, `#` for terms not starting with one,
+/// which come first
+fn initial(name: &str) -> String {
+    match name.chars().next() {
+        Some(c) if c.is_alphabetic() => c.to_uppercase().collect(),
+        _ => "#".to_owned(),
+    }
+}
+
+/// Settings for collecting and emitting the index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index {
+    class: String,
+    sub_key: String,
+    prefix: String,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Index {
+            class: "index".to_owned(),
+            sub_key: "sub".to_owned(),
+            prefix: "index".to_owned(),
+        }
+    }
+}
+
+impl Index {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the class marking index entries
+    pub fn class<S: Into<String>>(mut self, class: S) -> Self {
+        self.class = class.into();
+        self
+    }
+
+    /// Sets the attribute holding the sub-term of an entry
+    pub fn sub_term_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.sub_key = key.into();
+        self
+    }
+
+    /// Sets the prefix of the identifiers generated for entries, followed
+    /// by a hyphen and a number
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// The sorted terms of all entries in `doc`. Entries without an
+    /// identifier are given one, existing identifiers are kept. Terms are
+    /// sorted case-insensitively, entries with an empty term are ignored.
+    pub fn collect(&self, doc: &mut Pandoc) -> Vec<Term> {
+        let mut used = ident::used_identifiers(doc);
+        let mut terms = Vec::new();
+        let mut count = 0;
+        doc.for_each_inline_mut(|inline| {
+            let (attr, content) = match *inline {
+                Inline::Span(ref mut attr, ref content) if attr.1.contains(&self.class) => {
+                    (attr, content)
+                }
+                _ => return,
+            };
+            let value = |key: &str| {
+                attr.2
+                    .iter()
+                    .find(|&(k, _)| k == key)
+                    .map(|(_, value)| value.trim().to_owned())
+            };
+            let name = value("term").unwrap_or_else(|| stringify(content).trim().to_owned());
+            if name.is_empty() {
+                return;
+            }
+            let sub = value(&self.sub_key).filter(|sub| !sub.is_empty());
+            if attr.0.is_empty() {
+                count += 1;
+                let base = format!("{}-{}", self.prefix, count);
+                attr.0 = ident::unique_identifier(base, &mut used);
+            }
+            let mut entry = term(&mut terms, &name);
+            if let Some(sub) = sub {
+                entry = term(&mut entry.sub_terms, &sub);
+            }
+            entry.targets.push(attr.0.clone());
+        });
+        sort(&mut terms);
+        terms
+    }
+
+    /// `collect`s the terms and emits them as a `Div` with the class `index`
+    /// that holds a `Div` with the class `letter` per initial letter of the
+    /// terms, each with a header of level 2 and a definition list of the
+    /// terms. Sub-terms are nested definition lists below the links of their
+    /// term.
+    pub fn build(&self, doc: &mut Pandoc) -> Block {
+        let terms = self.collect(doc);
+        let mut groups: BTreeMap<String, Vec<Term>> = BTreeMap::new();
+        for term in terms {
+            groups.entry(initial(&term.name)).or_default().push(term);
+        }
+        let letters = groups
+            .into_iter()
+            .map(|(letter, terms)| {
+                let header = Block::Header(2, Attr::default(), vec![Inline::Str(letter)]);
+                Block::div("letter", vec![header, definition_list(&terms).into_block()])
+            })
+            .collect();
+        Block::div("index", letters)
+    }
+}
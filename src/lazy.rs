@@ -0,0 +1,112 @@
+use super::*;
+use serde_json::value::RawValue;
+use std::hash::{Hash, Hasher};
+
+/// The json of a block that has not been parsed yet
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Unparsed(Box<RawValue>);
+
+impl Unparsed {
+    /// the json of the block
+    pub fn json(&self) -> &str {
+        self.0.get()
+    }
+
+    /// the constructor name of the block, without parsing its content
+    pub fn name(&self) -> Result<String, Error> {
+        #[derive(Deserialize)]
+        struct Tag {
+            t: String,
+        }
+        let tag: Tag = from_str(self.json())?;
+        Ok(tag.t)
+    }
+
+    pub fn parse(&self) -> Result<Block, Error> {
+        Ok(from_str(self.json())?)
+    }
+}
+
+impl PartialEq for Unparsed {
+    fn eq(&self, other: &Self) -> bool {
+        self.json() == other.json()
+    }
+}
+
+impl Eq for Unparsed {}
+
+impl Hash for Unparsed {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.json().hash(state)
+    }
+}
+
+/// A document whose metadata stays raw json
+#[derive(Serialize, Deserialize)]
+struct RawMetaPandoc<'a> {
+    #[serde(borrow)]
+    meta: &'a RawValue,
+    blocks: Vec<Block>,
+    #[serde(rename = "pandoc-api-version")]
+    pandoc_api_version: Vec<u32>,
+}
+
+/// Like `filter`, but only the blocks are decoded and handed to the
+/// closure/function. The metadata is copied to the output as it was read,
+/// which saves decoding and encoding it for filters that only change the body.
+pub fn filter_blocks<F: FnOnce(Vec<Block>) -> Vec<Block>>(json: String, f: F) -> String {
+    let doc: RawMetaPandoc = from_str(&json).unwrap_or_else(|err| panic!("{}", Error::from(err)));
+    if let Err(err) = check_api_version(&doc.pandoc_api_version) {
+        panic!("{}", err);
+    }
+    let doc = RawMetaPandoc {
+        blocks: f(doc.blocks),
+        ..doc
+    };
+    to_string(&doc).expect("serialization failed")
+}
+
+#[derive(Deserialize)]
+struct LazyPandoc {
+    meta: Meta,
+    blocks: Vec<Box<RawValue>>,
+    #[serde(rename = "pandoc-api-version")]
+    pandoc_api_version: Vec<u32>,
+}
+
+impl Pandoc {
+    /// Parses a document, leaving its top-level blocks as `Block::Unparsed`.
+    /// Filters that only touch a few blocks can parse just those with
+    /// `Block::ensure_parsed` and write the rest back without ever decoding them.
+    pub fn from_json_lazy(json: &str) -> Result<Self, Error> {
+        let doc: LazyPandoc = from_str(json)?;
+        check_api_version(&doc.pandoc_api_version)?;
+        Ok(Pandoc {
+            meta: doc.meta,
+            blocks: doc
+                .blocks
+                .into_iter()
+                .map(|raw| Block::Unparsed(Unparsed(raw)))
+                .collect(),
+            pandoc_api_version: doc.pandoc_api_version,
+        })
+    }
+
+    /// Parses all top-level blocks left unparsed by `from_json_lazy`
+    pub fn ensure_parsed(&mut self) -> Result<(), Error> {
+        for block in &mut self.blocks {
+            block.ensure_parsed()?;
+        }
+        Ok(())
+    }
+}
+
+impl Block {
+    /// Replaces a `Block::Unparsed` with the block it contains
+    pub fn ensure_parsed(&mut self) -> Result<&mut Block, Error> {
+        if let Block::Unparsed(ref unparsed) = *self {
+            *self = unparsed.parse()?;
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,589 @@
+//! The `Deserialize` impls of `Block` and `Inline`, and `Lenient`.
+//!
+//! Deriving them would make serde buffer every node to fall back to the
+//! untagged `Unknown` and `Unparsed` variants, which also replaces any error
+//! inside of a node with a generic one. Remote definitions without these
+//! variants are used instead.
+//!
+//! Lenient parsing wraps the deserializer in `Wrap`, which wraps everything
+//! it hands out in turn, so it reaches every nested node. Nodes ask for a
+//! struct with a name of their own, which `Wrap` answers with
+//! `visit_newtype_struct` instead of `visit_map`, and which other
+//! deserializers ignore.
+
+use super::*;
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqAccessDeserializer};
+use serde::de::Error as _;
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IgnoredAny, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A value parsed leniently: blocks and inlines with constructors this crate
+/// doesn't know become `Block::Unknown` and `Inline::Unknown` instead of
+/// errors, wherever they are in `T`. Works with any serde format:
+///
+/// ```
+/// # use pandoc_ast::*;
+/// let json = r#"[{"t":"Para","c":[{"t":"Tab"}]}]"#;
+/// let Lenient(blocks): Lenient<Vec<Block>> = serde_json::from_str(json).unwrap();
+/// assert_eq!(blocks, [Block::Para(vec![Inline::Unknown("Tab".into(), serde_json::Value::Null)])]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lenient<T>(pub T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Lenient<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(Wrap(deserializer)).map(Lenient)
+    }
+}
+
+/// The struct names `Block` and `Inline` deserialize with
+const BLOCK: &str = "$pandoc_ast::Block";
+const INLINE: &str = "$pandoc_ast::Inline";
+const FIELDS: &[&str] = &["t", "c"];
+
+#[derive(Deserialize)]
+#[serde(remote = "Block", tag = "t", content = "c")]
+#[allow(clippy::large_enum_variant)]
+enum BlockDef {
+    Plain(Vec<Inline>),
+    Para(Vec<Inline>),
+    LineBlock(Vec<Vec<Inline>>),
+    CodeBlock(Attr, String),
+    RawBlock(Format, String),
+    BlockQuote(Vec<Block>),
+    OrderedList(ListAttributes, Vec<Vec<Block>>),
+    BulletList(Vec<Vec<Block>>),
+    DefinitionList(Vec<(Vec<Inline>, Vec<Vec<Block>>)>),
+    Header(Int, Attr, Vec<Inline>),
+    #[serde(deserialize_with = "nullary")]
+    HorizontalRule,
+    Table(
+        Attr,
+        Caption,
+        Vec<ColSpec>,
+        TableHead,
+        Vec<TableBody>,
+        TableFoot,
+    ),
+    Figure(Attr, Caption, Vec<Block>),
+    Div(Attr, Vec<Block>),
+    #[serde(deserialize_with = "nullary")]
+    Null,
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "Inline", tag = "t", content = "c")]
+enum InlineDef {
+    Str(String),
+    Emph(Vec<Inline>),
+    Underline(Vec<Inline>),
+    Strong(Vec<Inline>),
+    Strikeout(Vec<Inline>),
+    Superscript(Vec<Inline>),
+    Subscript(Vec<Inline>),
+    SmallCaps(Vec<Inline>),
+    Quoted(QuoteType, Vec<Inline>),
+    Cite(Vec<Citation>, Vec<Inline>),
+    Code(Attr, String),
+    #[serde(deserialize_with = "nullary")]
+    Space,
+    #[serde(deserialize_with = "nullary")]
+    SoftBreak,
+    #[serde(deserialize_with = "nullary")]
+    LineBreak,
+    Math(MathType, String),
+    RawInline(Format, String),
+    Link(Attr, Vec<Inline>, Target),
+    Image(Attr, Vec<Inline>, Target),
+    Note(Vec<Block>),
+    Span(Attr, Vec<Inline>),
+}
+
+/// The content of a constructor without arguments, like `Space`. Pandoc
+/// omits it, but older versions and other tools write `"c": []`, which
+/// serde's adjacently tagged enums would reject.
+pub(crate) fn nullary<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(), D::Error> {
+    struct Nothing;
+
+    impl<'de> Visitor<'de> for Nothing {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("no content or an empty array")
+        }
+
+        fn visit_unit<E>(self) -> Result<(), E> {
+            Ok(())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+            match seq.next_element::<IgnoredAny>()? {
+                None => Ok(()),
+                Some(_) => Err(A::Error::invalid_length(1, &self)),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(Nothing)
+}
+
+/// The constructors of `BlockDef`
+const BLOCKS: &[&str] = &[
+    "Plain",
+    "Para",
+    "LineBlock",
+    "CodeBlock",
+    "RawBlock",
+    "BlockQuote",
+    "OrderedList",
+    "BulletList",
+    "DefinitionList",
+    "Header",
+    "HorizontalRule",
+    "Table",
+    "Figure",
+    "Div",
+    "Null",
+];
+
+/// The constructors of `InlineDef`
+const INLINES: &[&str] = &[
+    "Str",
+    "Emph",
+    "Underline",
+    "Strong",
+    "Strikeout",
+    "Superscript",
+    "Subscript",
+    "SmallCaps",
+    "Quoted",
+    "Cite",
+    "Code",
+    "Space",
+    "SoftBreak",
+    "LineBreak",
+    "Math",
+    "RawInline",
+    "Link",
+    "Image",
+    "Note",
+    "Span",
+];
+
+/// A node type with a strict remote definition and an `Unknown` variant
+trait LenientNode: Sized {
+    const NAME: &'static str;
+    const CONSTRUCTORS: &'static [&'static str];
+
+    fn strict<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+
+    fn unknown(tag: String, content: Value) -> Self;
+}
+
+impl LenientNode for Block {
+    const NAME: &'static str = BLOCK;
+    const CONSTRUCTORS: &'static [&'static str] = BLOCKS;
+
+    fn strict<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BlockDef::deserialize(deserializer)
+    }
+
+    fn unknown(tag: String, content: Value) -> Self {
+        Block::Unknown(tag, content)
+    }
+}
+
+impl LenientNode for Inline {
+    const NAME: &'static str = INLINE;
+    const CONSTRUCTORS: &'static [&'static str] = INLINES;
+
+    fn strict<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        InlineDef::deserialize(deserializer)
+    }
+
+    fn unknown(tag: String, content: Value) -> Self {
+        Inline::Unknown(tag, content)
+    }
+}
+
+/// The entries of a node whose tag has already been read, with the tag put
+/// back in front of the remaining ones
+struct TagFirst<A> {
+    key: Option<String>,
+    value: Option<String>,
+    rest: A,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for TagFirst<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        match self.key.take() {
+            Some(tag) => {
+                self.value = Some(tag);
+                seed.deserialize("t".into_deserializer()).map(Some)
+            }
+            None => self.rest.next_key_seed(seed),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+        match self.value.take() {
+            Some(tag) => seed.deserialize(tag.into_deserializer()),
+            None => self.rest.next_value_seed(seed),
+        }
+    }
+}
+
+/// Parses a node strictly, or leniently if `Wrap` calls
+/// `visit_newtype_struct`
+struct NodeVisitor<T>(PhantomData<T>);
+
+impl<'de, T: LenientNode> Visitor<'de> for NodeVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a pandoc node")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<T, A::Error> {
+        T::strict(MapAccessDeserializer::new(map))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<T, A::Error> {
+        T::strict(SeqAccessDeserializer::new(seq))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        deserializer.deserialize_map(LenientVisitor(PhantomData))
+    }
+}
+
+/// Parses a node leniently: constructors this crate knows are parsed
+/// strictly, so errors in their content are reported, and only unknown ones
+/// become `Unknown`. The content isn't buffered if the tag comes first, as
+/// pandoc writes it.
+struct LenientVisitor<T>(PhantomData<T>);
+
+impl<'de, T: LenientNode> Visitor<'de> for LenientVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a pandoc node")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let mut content = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match &key[..] {
+                "t" => {
+                    let tag: String = map.next_value()?;
+                    let known = T::CONSTRUCTORS.contains(&&tag[..]);
+                    if known && content.is_none() {
+                        return T::strict(MapAccessDeserializer::new(TagFirst {
+                            key: Some(tag),
+                            value: None,
+                            rest: map,
+                        }));
+                    }
+                    while let Some(key) = map.next_key::<String>()? {
+                        if key == "c" && content.is_none() {
+                            content = Some(map.next_value()?);
+                        } else {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                    let content = content.unwrap_or(Value::Null);
+                    if !known {
+                        return Ok(T::unknown(tag, content));
+                    }
+                    // the tag goes first again, so the content isn't
+                    // buffered by serde, which would lose the `Wrap`
+                    let mut node = vec![("t", Value::String(tag))];
+                    if !content.is_null() {
+                        node.push(("c", content));
+                    }
+                    let node = MapDeserializer::<_, serde_json::Error>::new(node.into_iter());
+                    return T::strict(Wrap(node)).map_err(A::Error::custom);
+                }
+                "c" if content.is_none() => content = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Err(A::Error::missing_field("t"))
+    }
+}
+
+fn deserialize_node<'de, T: LenientNode, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    deserializer.deserialize_struct(T::NAME, FIELDS, NodeVisitor(PhantomData))
+}
+
+impl<'de> Deserialize<'de> for Block {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_node(deserializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Inline {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_node(deserializer)
+    }
+}
+
+/// Passes lenient parsing on to everything below a deserializer, see the
+/// module documentation. It wraps deserializers, visitors, seeds and the
+/// accessors of sequences, maps and enums alike.
+struct Wrap<T>(T);
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {$(
+        fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error> {
+            self.0.$method($($arg,)* Wrap(visitor))
+        }
+    )*};
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Wrap<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        if name == BLOCK || name == INLINE {
+            visitor.visit_newtype_struct(self)
+        } else {
+            self.0.deserialize_struct(name, fields, Wrap(visitor))
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {$(
+        fn $method<E: de::Error>(self, value: $ty) -> Result<V::Value, E> {
+            self.0.$method(value)
+        }
+    )*};
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Wrap<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.0.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.0.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        self.0.visit_some(Wrap(deserializer))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        self.0.visit_newtype_struct(Wrap(deserializer))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.0.visit_seq(Wrap(seq))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.0.visit_map(Wrap(map))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.0.visit_enum(Wrap(data))
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Wrap<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        self.0.deserialize(Wrap(deserializer))
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, A::Error> {
+        self.0.next_element_seed(Wrap(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.0.next_key_seed(Wrap(seed))
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        self.0.next_value_seed(Wrap(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+    type Variant = Wrap<A::Variant>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Wrap<A::Variant>), A::Error> {
+        let (value, variant) = self.0.variant_seed(Wrap(seed))?;
+        Ok((value, Wrap(variant)))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.0.unit_variant()
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, A::Error> {
+        self.0.newtype_variant_seed(Wrap(seed))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        self.0.tuple_variant(len, Wrap(visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.0.struct_variant(fields, Wrap(visitor))
+    }
+}
+
+/// writes an `Unknown` node the way it was read
+pub(crate) fn serialize_unknown<S: Serializer>(
+    tag: &str,
+    content: &Value,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut node = serializer.serialize_map(None)?;
+    node.serialize_entry("t", tag)?;
+    if !content.is_null() {
+        node.serialize_entry("c", content)?;
+    }
+    node.end()
+}
+
+impl Pandoc {
+    /// Parses a document like `try_from_json`, but turns blocks and inlines
+    /// with constructors this crate doesn't know into `Block::Unknown` and
+    /// `Inline::Unknown`, e.g. those added by newer pandoc versions. Filters
+    /// can then handle the rest of the document and write the unknown nodes
+    /// back unchanged. Use `Lenient` to parse leniently from other formats,
+    /// or parts of a document.
+    pub fn from_json_lenient(json: &str) -> Result<Self, Error> {
+        check_api_version(&api_version(json.as_bytes())?)?;
+        let Lenient(doc) = serde_json::from_str(json)?;
+        Ok(doc)
+    }
+}
@@ -1,71 +1,263 @@
+#[macro_use]
+extern crate serde;
 extern crate serde_json;
+#[cfg(feature = "arena")]
+extern crate bumpalo;
+#[cfg(feature = "cbor")]
+extern crate ciborium;
+#[cfg(feature = "commonmark")]
+extern crate pulldown_cmark;
+#[cfg(feature = "quickcheck")]
+extern crate quickcheck;
+#[cfg(feature = "csv")]
+extern crate csv;
+#[cfg(feature = "preserve-order")]
+extern crate indexmap;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
+#[cfg(feature = "yaml")]
+extern crate serde_yaml;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
 #[macro_use]
 extern crate serde_derive;
 
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "quickcheck")]
+mod arbitrary;
+#[cfg(feature = "tokio")]
+mod async_filter;
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+mod binary;
+mod builder;
+mod combine;
+mod cursor;
+mod dump;
+mod error;
+mod fast;
+#[cfg(feature = "html")]
+mod html;
+mod lazy;
+mod lenient;
+mod markdown;
+pub mod map;
+mod meta;
+mod node;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod path;
+mod schema;
+mod stringify;
 mod visitor;
 
-use serde_json::{from_str, to_string};
+pub mod acronym;
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "borrowed")]
+pub mod borrowed;
+pub mod changes;
+pub mod comment;
+pub mod context;
+#[cfg(feature = "commonmark")]
+pub mod commonmark;
+pub mod definition;
+pub mod diagnostics;
+pub mod diff;
+pub mod directive;
+#[cfg(feature = "exec")]
+pub mod exec;
+pub mod figure;
+pub mod hyphenation;
+pub mod ident;
+pub mod incremental;
+pub mod index;
+pub mod list;
+pub mod literate;
+pub mod lua_compat;
+pub mod native;
+pub mod notes;
+pub mod patch;
+pub mod pipeline;
+pub mod query;
+pub mod recover;
+pub mod select;
+pub mod smart;
+pub mod sourcepos;
+pub mod stats;
+pub mod structure;
+pub mod table;
+pub mod testing;
+#[cfg(feature = "regex")]
+pub mod text;
+pub mod toc;
+pub mod transform;
+pub mod v1_20;
+pub mod v1_22;
+pub mod v1_23;
+pub mod validate;
+pub mod variables;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use std::collections::BTreeMap as Map;
+use serde_json::{from_str, to_string};
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write};
+use std::{env, process};
+
+#[cfg(feature = "tokio")]
+pub use async_filter::{run_filter_async, RunFilterAsync};
+pub use builder::{inlines_from_str, inlines_to_string, IntoBlocks, IntoInlines, PandocBuilder};
+pub use combine::MergeOptions;
+pub use cursor::Cursor;
+pub use error::Error;
+pub use lazy::{filter_blocks, Unparsed};
+pub use lenient::Lenient;
+pub use meta::{MergeStrategy, Meta, MetaExt};
+pub use node::{Blocks, Descendants, Inlines, Node, NodeMut, OwnedNode};
+pub use path::{AstPath, Step};
+pub use map::Map;
+pub use stringify::*;
 pub use visitor::*;
 pub type Int = i64;
 pub type Double = f64;
 
 /// the root object of a pandoc document
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Pandoc {
-    pub meta: Map<String, MetaValue>,
+    pub meta: Meta,
     pub blocks: Vec<Block>,
     #[serde(rename = "pandoc-api-version")]
     pub pandoc_api_version: Vec<u32>,
 }
 
 impl Pandoc {
+    /// Parses a document, panicking if it isn't valid pandoc json
     pub fn from_json(json: &str) -> Self {
-        let v: serde_json::Value = from_str(json).unwrap();
-        let obj = v.as_object().expect("broken pandoc json");
-        fn pandoc_version(obj: &serde_json::Map<String, serde_json::Value>) -> Option<(i64, i64)> {
-            let version = obj
-                .get("pandoc-api-version")?
-                .as_array()?
-                .into_iter()
-                .map(|v| v.as_i64())
-                .collect::<Vec<_>>();
-            match version[..] {
-                [Some(major), Some(minor), ..] => Some((major, minor)),
-                _ => None,
+        Self::try_from_json(json).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Parses a document, checking that it was produced by a supported pandoc version
+    pub fn try_from_json(json: &str) -> Result<Self, Error> {
+        check_api_version(&api_version(json.as_bytes())?)?;
+        Ok(from_str(json)?)
+    }
+
+    /// Parses a document from a reader without buffering the json. Unlike
+    /// `try_from_json`, input in an old format fails with a json error rather
+    /// than a version error, as the version is only known after parsing.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let doc: Pandoc = serde_json::from_reader(io::BufReader::new(reader))?;
+        check_api_version(&doc.pandoc_api_version)?;
+        Ok(doc)
+    }
+
+    /// Serializes the document as json.
+    ///
+    /// Reading the json back with `from_json` gives a document equal to this
+    /// one, attributes, empty strings and column widths of `0.0` or `-0.0`
+    /// included. The only exception are `NaN` and infinite column widths,
+    /// which json can't represent and which are written as `null`.
+    /// Constructors without content like `HorizontalRule` are written as
+    /// `{"t":"HorizontalRule"}`, without a `"c"`, as pandoc does. The
+    /// `{"t":"HorizontalRule","c":[]}` of older tools is read as well.
+    ///
+    /// Some values pandoc itself never writes, like `MetaNumber`s and widths
+    /// of `NaN`, are rejected by pandoc or mean something else to it.
+    /// `transform::canonicalize` replaces them with their equivalent in
+    /// pandoc's own output.
+    pub fn to_json(&self) -> String {
+        to_string(self).expect("serialization failed")
+    }
+
+    /// Serializes the document as indented json
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).expect("serialization failed")
+    }
+
+    /// Serializes the document as indented json with the keys of all objects
+    /// sorted and a trailing newline. The output only depends on the document,
+    /// which makes it suitable for snapshots kept in version control.
+    pub fn to_json_canonical(&self) -> String {
+        fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+            use serde_json::Value;
+            match value {
+                Value::Object(map) => {
+                    let mut entries: Vec<_> = map.into_iter().collect();
+                    entries.sort_by(|a, b| a.0.cmp(&b.0));
+                    Value::Object(
+                        entries
+                            .into_iter()
+                            .map(|(k, v)| (k, sort_keys(v)))
+                            .collect(),
+                    )
+                }
+                Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+                other => other,
             }
         }
-        // test pandoc version
-        if let Some((major, minor)) = pandoc_version(&obj) {
-            if !(major == 1 && minor >= 20) {
-                panic!(
-                    "Pandoc version mismatch: \
-                    `pandoc-ast` expects pandoc version 1.20 or newer, got {}.{}",
-                    major, minor
-                );
-            }
-        } else {
-            panic!(
-                "Unable to parse pandoc version from JSON. \
-                Please update your pandoc to at least version 1.18 or use an older version of `pandoc-ast`"
-            );
+        let value = serde_json::to_value(self).expect("serialization failed");
+        let mut json =
+            serde_json::to_string_pretty(&sort_keys(value)).expect("serialization failed");
+        json.push('\n');
+        json
+    }
+
+    /// Serializes the document into a writer without building a string
+    pub fn to_writer<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let mut writer = io::BufWriter::new(writer);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// the api version of documents created from scratch
+const API_VERSION: [u32; 2] = [1, 23];
+
+fn check_version(version: Option<(i64, i64)>) -> Result<(), Error> {
+    match version {
+        Some((major, minor)) if !(major == 1 && minor >= 20) => {
+            Err(Error::VersionMismatch { major, minor })
         }
-        let s = serde_json::to_string_pretty(&v).unwrap();
-        let data: Self = match from_str(&s) {
-            Ok(data) => data,
-            Err(err) => panic!("json is not in the pandoc format: {:?}\n{}", err, s),
-        };
-        data
+        Some(_) => Ok(()),
+        None => Err(Error::MissingVersion),
     }
+}
 
-    pub fn to_json(&self) -> String {
-        to_string(self).expect("serialization failed")
+/// Only the version of a document, to check it before parsing the rest
+#[derive(Deserialize)]
+struct Version {
+    #[serde(rename = "pandoc-api-version")]
+    pandoc_api_version: Option<Vec<u32>>,
+}
+
+/// The `pandoc-api-version` of a json document, empty if it has none. The
+/// rest of the document is skipped, so it can be parsed from the json text
+/// afterwards, rather than from a `serde_json::Value` that sorts the keys of
+/// the metadata.
+fn api_version(json: &[u8]) -> Result<Vec<u32>, Error> {
+    let version: Version = serde_json::from_slice(json)?;
+    Ok(version.pandoc_api_version.unwrap_or_default())
+}
+
+fn check_api_version(version: &[u32]) -> Result<(), Error> {
+    match *version {
+        [major, minor, ..] => check_version(Some((major.into(), minor.into()))),
+        _ => check_version(None),
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(tag = "t", content = "c")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "t", content = "c", from = "meta::MetaValueDef")]
 pub enum MetaValue {
     MetaMap(Map<String, Box<MetaValue>>),
     MetaList(Vec<MetaValue>),
@@ -73,11 +265,22 @@ pub enum MetaValue {
     MetaString(String),
     MetaInlines(Vec<Inline>),
     MetaBlocks(Vec<Block>),
+    /// A number in place of the string of a `MetaString`. Pandoc itself
+    /// never writes these, but tools producing json metadata do, and they
+    /// are written back as the same number.
+    ///
+    /// The numbers are those of `serde_json::Number`: integers beyond the
+    /// range of `i64`/`u64`, like `12345678901234567890123`, are rounded to
+    /// the nearest `f64`, and numbers beyond the range of `f64`, like
+    /// `1e400`, are rejected with an error for the whole document.
+    #[serde(rename = "MetaString")]
+    MetaNumber(serde_json::Number),
 }
 
 /// Structured text like tables and lists
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(tag = "t", content = "c")]
+#[allow(clippy::large_enum_variant)]
 pub enum Block {
     /// Plain text, not a paragraph
     Plain(Vec<Inline>),
@@ -110,14 +313,28 @@ pub enum Block {
         Vec<TableBody>,
         TableFoot
     ),
+    /// Figure, with attributes, caption, and content (pandoc 3 / API 1.23)
+    Figure(Attr, Caption, Vec<Block>),
     /// Generic block container with attributes
     Div(Attr, Vec<Block>),
     /// Nothing
     Null,
+    /// A block with a constructor unknown to this crate and its content,
+    /// see `Pandoc::from_json_lenient`. It is written back unchanged.
+    #[serde(untagged, serialize_with = "lenient::serialize_unknown")]
+    Unknown(String, serde_json::Value),
+    /// A block whose json has not been parsed yet, see `Pandoc::from_json_lazy`.
+    /// It is written back unchanged.
+    #[serde(untagged)]
+    Unparsed(Unparsed),
 }
 
 /// a single formatting item like bold, italic or hyperlink
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+///
+/// Nested inlines are kept in plain `Vec`s. Storing a few of them inline
+/// (e.g. in a `SmallVec<[Inline; 4]>`) would make `Inline` contain itself
+/// and thus have infinite size.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(tag = "t", content = "c")]
 pub enum Inline {
     /// Text
@@ -160,6 +377,10 @@ pub enum Inline {
     Note(Vec<Block>),
     /// Generic inline container with attributes
     Span(Attr, Vec<Inline>),
+    /// An inline with a constructor unknown to this crate and its content,
+    /// see `Pandoc::from_json_lenient`. It is written back unchanged.
+    #[serde(untagged, serialize_with = "lenient::serialize_unknown")]
+    Unknown(String, serde_json::Value),
 }
 
 /// Alignment of a table column.
@@ -172,7 +393,45 @@ pub enum Alignment {
     AlignDefault,
 }
 
-pub type ListAttributes = (Int, ListNumberStyle, ListNumberDelim);
+/// Numbering of an ordered list, written as `[start, style, delim]` in json
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(
+    from = "(Int, ListNumberStyle, ListNumberDelim)",
+    into = "(Int, ListNumberStyle, ListNumberDelim)"
+)]
+pub struct ListAttributes {
+    /// the number of the first item
+    pub start: Int,
+    pub style: ListNumberStyle,
+    pub delim: ListNumberDelim,
+}
+
+/// Starting at 1, with the default style and delimiter
+impl Default for ListAttributes {
+    fn default() -> Self {
+        ListAttributes {
+            start: 1,
+            style: ListNumberStyle::DefaultStyle,
+            delim: ListNumberDelim::DefaultDelim,
+        }
+    }
+}
+
+impl From<(Int, ListNumberStyle, ListNumberDelim)> for ListAttributes {
+    fn from((start, style, delim): (Int, ListNumberStyle, ListNumberDelim)) -> Self {
+        ListAttributes {
+            start,
+            style,
+            delim,
+        }
+    }
+}
+
+impl From<ListAttributes> for (Int, ListNumberStyle, ListNumberDelim) {
+    fn from(attributes: ListAttributes) -> Self {
+        (attributes.start, attributes.style, attributes.delim)
+    }
+}
 
 /// Style of list numbers.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -201,6 +460,50 @@ pub enum ListNumberDelim {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Format(pub String);
 
+impl Format {
+    /// Whether this is the format `name`, or one of them is an alias of
+    /// the other, following pandoc's rules: names are compared ignoring
+    /// case, raw `tex` is included in `latex`, `beamer` and `context`
+    /// output, raw `latex` in `beamer` output, and raw `html` in `html4` and
+    /// `html5` output.
+    pub fn matches(&self, name: &str) -> bool {
+        fn includes(general: &str, specific: &str) -> bool {
+            match general {
+                "tex" => specific == "latex" || specific == "beamer" || specific == "context",
+                "latex" => specific == "beamer",
+                "html" => specific == "html4" || specific == "html5",
+                _ => false,
+            }
+        }
+        let (format, name) = (self.0.to_ascii_lowercase(), name.to_ascii_lowercase());
+        format == name || includes(&format, &name) || includes(&name, &format)
+    }
+
+    /// Whether this is `latex`, `beamer`, `tex` or `context`, ignoring case
+    pub fn is_tex(&self) -> bool {
+        self.matches("tex")
+    }
+
+    /// Whether this is `html`, `html4` or `html5`, ignoring case
+    pub fn is_html(&self) -> bool {
+        self.matches("html")
+    }
+}
+
+/// Compares the name ignoring case, like pandoc does. Unlike `matches`,
+/// aliases are different formats.
+impl PartialEq<str> for Format {
+    fn eq(&self, other: &str) -> bool {
+        self.0.eq_ignore_ascii_case(other)
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Format {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.0.eq_ignore_ascii_case(other)
+    }
+}
+
 /// Attributes: identifier, classes, key-value pairs
 pub type Attr = (String, Vec<String>, Vec<(String, String)>);
 
@@ -223,13 +526,42 @@ pub type ShortCaption = Vec<Inline>;
 
 pub type RowHeadColumns = Int;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+/// Width of a table column, as a fraction of the text width.
+///
+/// Widths compare and hash by their bit pattern, which makes them usable
+/// as part of `Eq` and `Hash` AST nodes (`NaN == NaN`, but `0.0 != -0.0`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(tag = "t", content = "c")]
 pub enum ColWidth {
     ColWidth(Double),
+    #[serde(deserialize_with = "lenient::nullary")]
     ColWidthDefault
 }
 
+impl PartialEq for ColWidth {
+    fn eq(&self, other: &Self) -> bool {
+        match (*self, *other) {
+            (ColWidth::ColWidth(a), ColWidth::ColWidth(b)) => a.to_bits() == b.to_bits(),
+            (ColWidth::ColWidthDefault, ColWidth::ColWidthDefault) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ColWidth {}
+
+impl Hash for ColWidth {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            ColWidth::ColWidth(w) => {
+                0u8.hash(state);
+                w.to_bits().hash(state);
+            }
+            ColWidth::ColWidthDefault => 1u8.hash(state),
+        }
+    }
+}
+
 pub type ColSpec = (Alignment, ColWidth);
 
 pub type Row = (Attr, Vec<Cell>);
@@ -256,7 +588,7 @@ pub enum MathType {
     InlineMath,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(non_snake_case)]
 pub struct Citation {
     pub citationId: String,
@@ -277,6 +609,127 @@ pub enum CitationMode {
 
 /// deserialized a json string to a Pandoc object, passes it to the closure/function
 /// and serializes the result back into a string
+///
+/// Documents of older pandoc versions are converted to the current model
+/// for the closure and back to their version afterwards, see the `v1_20`
+/// and `v1_22` modules.
 pub fn filter<F: FnOnce(Pandoc) -> Pandoc>(json: String, f: F) -> String {
-    f(Pandoc::from_json(&json)).to_json()
+    filter_json(&json, f).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Like `filter`, but returns errors instead of panicking. It does not use
+/// stdin, stdout or the environment, so it also works in webassembly, see
+/// the `wasm` module.
+pub fn filter_json<F: FnOnce(Pandoc) -> Pandoc>(json: &str, f: F) -> Result<String, Error> {
+    let (doc, version) = from_any_version(json.as_bytes())?;
+    to_version(f(doc), &version)
+}
+
+/// Parses a document from untrusted input, e.g. json received by a web
+/// service. Invalid input results in an error and never in a panic, which
+/// makes this the entry point for fuzzing:
+///
+/// ```ignore
+/// fuzz_target!(|data: &[u8]| {
+///     let _ = pandoc_ast::parse_untrusted(data);
+/// });
+/// ```
+///
+/// Nesting deeper than 128 arrays and objects is rejected, which bounds the
+/// recursion of parsing and dropping the document. Documents of older pandoc
+/// versions are converted like by `filter`.
+pub fn parse_untrusted(bytes: &[u8]) -> Result<Pandoc, Error> {
+    Ok(from_any_version(bytes)?.0)
+}
+
+/// Parses a document of any supported pandoc version into the current model,
+/// returning the `pandoc-api-version` it had
+pub(crate) fn from_any_version(json: &[u8]) -> Result<(Pandoc, Vec<u32>), Error> {
+    let version = api_version(json)?;
+    let doc = match version[..] {
+        [1, 17..=20, ..] => {
+            let doc: v1_20::Pandoc = serde_json::from_slice(json)?;
+            v1_22::Pandoc::from(doc).into()
+        }
+        [1, 21..=22, ..] => v1_22::Pandoc(serde_json::from_slice(json)?).into(),
+        _ => {
+            check_api_version(&version)?;
+            serde_json::from_slice(json)?
+        }
+    };
+    Ok((doc, version))
+}
+
+/// Serializes a document of the current model as json of the given
+/// `pandoc-api-version`, converting it to an older model if needed
+pub(crate) fn to_version(doc: Pandoc, version: &[u32]) -> Result<String, Error> {
+    let json = match *version {
+        [1, 17..=20, ..] => {
+            let mut doc = v1_20::Pandoc::try_from(v1_22::Pandoc::from(doc))?;
+            doc.pandoc_api_version = version.to_vec();
+            to_string(&doc)?
+        }
+        [1, 21..=22, ..] => {
+            let mut doc = v1_22::Pandoc::from(doc).0;
+            doc.pandoc_api_version = version.to_vec();
+            doc.to_json()
+        }
+        _ => doc.to_json(),
+    };
+    Ok(json)
+}
+
+/// Runs a complete filter binary: reads the document from stdin, hands it to the
+/// closure together with the output format pandoc passes as the first argument,
+/// and writes the result to stdout. Any error is reported on stderr and exits
+/// the process with status 1. Documents of older pandoc versions are
+/// converted like by `filter`.
+pub fn run_filter<F: FnOnce(Option<&str>, Pandoc) -> Pandoc>(f: F) {
+    let format = env::args().nth(1);
+    let run = || -> Result<(), Error> {
+        let mut json = String::new();
+        io::stdin().read_to_string(&mut json)?;
+        let json = filter_json(&json, |doc| f(format.as_deref(), doc))?;
+        io::stdout().write_all(json.as_bytes())?;
+        Ok(())
+    };
+    if let Err(err) = run() {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+/// Reads a document from `reader`, passes it to the closure/function and
+/// writes the result to `writer`, without holding the json in memory. Unlike
+/// `filter`, only documents of the current model can be read.
+pub fn filter_io<R: Read, W: Write, F: FnOnce(Pandoc) -> Pandoc>(
+    reader: R,
+    writer: W,
+    f: F,
+) -> Result<(), Error> {
+    f(Pandoc::from_reader(reader)?).to_writer(writer)
+}
+
+/// Filters a stream of documents, for long-lived filter processes that are
+/// handed many documents. The input is json documents one after another,
+/// separated by newlines (json lines) or not separated at all. Every result
+/// is written on a line of its own and flushed right away, so the other end
+/// can read it before sending the next document. Documents of older pandoc
+/// versions are converted like by `filter`.
+pub fn filter_stream<R, W, F>(reader: R, mut writer: W, mut f: F) -> Result<(), Error>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(Pandoc) -> Pandoc,
+{
+    let documents =
+        serde_json::Deserializer::from_reader(reader).into_iter::<Box<serde_json::value::RawValue>>();
+    for json in documents {
+        let (doc, version) = from_any_version(json?.get().as_bytes())?;
+        let json = to_version(f(doc), &version)?;
+        writer.write_all(json.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
 }
@@ -0,0 +1,86 @@
+//! Numbering, styling and conversion of bullet and ordered lists
+
+use super::*;
+use std::mem;
+
+/// Makes every ordered list below `node` start at `start`
+pub fn restart_numbering<T: MutVisitable>(node: &mut T, start: Int) {
+    node.for_each_block_mut(|block| {
+        if let Block::OrderedList(ref mut attributes, _) = *block {
+            attributes.start = start;
+        }
+    });
+}
+
+/// Makes every ordered list in `blocks` continue the numbering of the
+/// previous one, like separate lists of a single enumeration. The first
+/// list keeps its start. Nested lists are left alone.
+pub fn continue_numbering(blocks: &mut [Block]) {
+    let mut next = None;
+    for block in blocks {
+        if let Block::OrderedList(ref mut attributes, ref items) = *block {
+            if let Some(next) = next {
+                attributes.start = next;
+            }
+            next = Some(attributes.start + items.len() as Int);
+        }
+    }
+}
+
+/// Sets the number style and delimiter of every ordered list below `node`
+pub fn set_style<T: MutVisitable>(node: &mut T, style: ListNumberStyle, delim: ListNumberDelim) {
+    node.for_each_block_mut(|block| {
+        if let Block::OrderedList(ref mut attributes, _) = *block {
+            attributes.style = style;
+            attributes.delim = delim;
+        }
+    });
+}
+
+/// Turns a `BulletList` into an `OrderedList` numbered by `attributes`.
+/// Other blocks are left alone.
+pub fn to_ordered(block: &mut Block, attributes: ListAttributes) {
+    if let Block::BulletList(ref mut items) = *block {
+        *block = Block::OrderedList(attributes, mem::take(items));
+    }
+}
+
+/// Turns an `OrderedList` into a `BulletList`. Other blocks are left alone.
+pub fn to_bullet(block: &mut Block) {
+    if let Block::OrderedList(_, ref mut items) = *block {
+        *block = Block::BulletList(mem::take(items));
+    }
+}
+
+/// Moves the items of lists nested directly in the items of the list
+/// `block` up to its level, each after the item that contained it, so all
+/// items end up in one flat list. Items that held nothing but lists are
+/// dropped. Lists inside other blocks of an item, e.g. block quotes, stay.
+pub fn flatten(block: &mut Block) {
+    match *block {
+        Block::BulletList(ref mut items) | Block::OrderedList(_, ref mut items) => {
+            let nested = mem::take(items);
+            flatten_items(nested, items);
+        }
+        _ => {}
+    }
+}
+
+fn flatten_items(items: Vec<Vec<Block>>, flat: &mut Vec<Vec<Block>>) {
+    for item in items {
+        let mut own = Vec::new();
+        let mut nested = Vec::new();
+        for block in item {
+            match block {
+                Block::BulletList(items) | Block::OrderedList(_, items) => nested.push(items),
+                block => own.push(block),
+            }
+        }
+        if !own.is_empty() {
+            flat.push(own);
+        }
+        for items in nested {
+            flatten_items(items, flat);
+        }
+    }
+}
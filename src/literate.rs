@@ -0,0 +1,112 @@
+//! Literate programming: extracting source files from code blocks and
+//! including files into them
+
+use super::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Concatenates the code blocks carrying a `file` attribute per file name,
+/// in document order. Each block ends with a newline in the output.
+pub fn tangle<T: Visitable + ?Sized>(node: &T) -> BTreeMap<String, String> {
+    let mut files = BTreeMap::new();
+    for (attr, code) in query::code_blocks(node) {
+        let file = attr.2.iter().find(|(key, _)| key == "file");
+        if let Some((_, name)) = file {
+            let contents: &mut String = files.entry(name.clone()).or_default();
+            contents.push_str(code);
+            if !code.ends_with('\n') {
+                contents.push('\n');
+            }
+        }
+    }
+    files
+}
+
+/// Writes the files `tangle` produces below `dir`, creating missing
+/// directories. Returns the names of the written files.
+pub fn tangle_to_dir<T, P>(node: &T, dir: P) -> Result<Vec<String>, Error>
+where
+    T: Visitable + ?Sized,
+    P: AsRef<Path>,
+{
+    let mut written = Vec::new();
+    for (name, contents) in tangle(node) {
+        let path = dir.as_ref().join(&name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, contents)?;
+        written.push(name);
+    }
+    Ok(written)
+}
+
+fn take_attribute(attr: &mut Attr, key: &str) -> Option<String> {
+    let index = attr.2.iter().position(|(k, _)| k == key)?;
+    Some(attr.2.remove(index).1)
+}
+
+fn number_attribute(attr: &mut Attr, key: &str) -> Result<Option<usize>, Error> {
+    match take_attribute(attr, key) {
+        Some(value) => match value.trim().parse() {
+            Ok(n) => Ok(Some(n)),
+            Err(_) => Err(Error::InvalidAttribute {
+                key: key.to_owned(),
+                value,
+            }),
+        },
+        None => Ok(None),
+    }
+}
+
+fn dedent(line: &str, amount: usize) -> &str {
+    let skip = line
+        .char_indices()
+        .take(amount)
+        .take_while(|&(_, c)| c.is_whitespace())
+        .last()
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    &line[skip..]
+}
+
+fn include(attr: &mut Attr, code: &mut String, base_dir: &Path) -> Result<(), Error> {
+    let path = match take_attribute(attr, "include") {
+        Some(file) => base_dir.join(file),
+        None => return Ok(()),
+    };
+    let start = number_attribute(attr, "startLine")?.unwrap_or(1);
+    let end = number_attribute(attr, "endLine")?.unwrap_or(usize::MAX);
+    let amount = number_attribute(attr, "dedent")?.unwrap_or(0);
+    let contents = fs::read_to_string(&path).map_err(|source| Error::File { path, source })?;
+    *code = contents
+        .lines()
+        .enumerate()
+        .filter(|&(i, _)| i + 1 >= start && i < end)
+        .map(|(_, line)| dedent(line, amount))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(())
+}
+
+/// Replaces the content of every code block with an `include` attribute by
+/// the file it names, relative to `base_dir`, following the conventions of
+/// `pandoc-include-code`: `startLine` and `endLine` select an inclusive,
+/// 1-based range of lines, `dedent` removes up to that many leading
+/// whitespace characters from each line. These attributes are removed from
+/// the block. Stops at the first file that can't be read.
+pub fn include_code<T, P>(node: &mut T, base_dir: P) -> Result<(), Error>
+where
+    T: MutVisitable,
+    P: AsRef<Path>,
+{
+    let mut result = Ok(());
+    node.for_each_block_mut(|block| {
+        if let Block::CodeBlock(ref mut attr, ref mut code) = *block {
+            if result.is_ok() {
+                result = include(attr, code, base_dir.as_ref());
+            }
+        }
+    });
+    result
+}
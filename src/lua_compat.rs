@@ -0,0 +1,371 @@
+//! Filters in the style of pandoc's lua filters
+//!
+//! Functions are registered by element name and applied with the same
+//! traversal order as in lua, so existing lua filters can be ported
+//! mechanically:
+//!
+//! ```lua
+//! function Str(s)
+//!   if s.text == "TODO" then return {} end
+//! end
+//! function CodeBlock(cb)
+//!   cb.classes:insert("numberLines")
+//!   return cb
+//! end
+//! ```
+//!
+//! becomes
+//!
+//! ```
+//! # use pandoc_ast::lua_compat::Filter;
+//! # use pandoc_ast::transform::Action;
+//! # use pandoc_ast::{Block, Inline, Pandoc};
+//! # let mut doc = Pandoc::new();
+//! Filter::new()
+//!     .on("Str", |s: &mut Inline| match *s {
+//!         Inline::Str(ref text) if text == "TODO" => Action::Delete,
+//!         _ => Action::Keep,
+//!     })
+//!     .on("CodeBlock", |cb: &mut Block| {
+//!         if let Block::CodeBlock(ref mut attr, _) = *cb {
+//!             attr.1.push("numberLines".to_owned());
+//!         }
+//!         Action::Keep
+//!     })
+//!     .apply(&mut doc);
+//! ```
+//!
+//! Returning nothing in lua corresponds to `Action::Keep`, an element to
+//! `Action::Replace`, a list to `Action::ReplaceMany` and an empty list to
+//! `Action::Delete`. The `Inlines`, `Blocks`, `Meta` and `Pandoc` functions
+//! modify their argument in place.
+
+use super::*;
+use std::collections::HashMap;
+use std::mem;
+use transform::Action;
+
+const INLINE_NAMES: &[&str] = &[
+    "Str",
+    "Emph",
+    "Underline",
+    "Strong",
+    "Strikeout",
+    "Superscript",
+    "Subscript",
+    "SmallCaps",
+    "Quoted",
+    "Cite",
+    "Code",
+    "Space",
+    "SoftBreak",
+    "LineBreak",
+    "Math",
+    "RawInline",
+    "Link",
+    "Image",
+    "Note",
+    "Span",
+];
+
+const BLOCK_NAMES: &[&str] = &[
+    "Plain",
+    "Para",
+    "LineBlock",
+    "CodeBlock",
+    "RawBlock",
+    "BlockQuote",
+    "OrderedList",
+    "BulletList",
+    "DefinitionList",
+    "Header",
+    "HorizontalRule",
+    "Table",
+    "Figure",
+    "Div",
+];
+
+/// How a filter walks the document, like the `traverse` field of lua filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Traverse {
+    /// One pass per kind of function, each bottom-up: first the inline
+    /// element functions (falling back to `Inline`), then `Inlines`, the
+    /// block element functions (falling back to `Block`), `Blocks`, and
+    /// finally `Meta` and `Pandoc`. Inline functions also see the inlines
+    /// in the metadata.
+    #[default]
+    Typewise,
+    /// A single pass in document order: `Pandoc` and `Meta` first, then
+    /// every node before its children and every list before its elements.
+    /// Returning `(action, false)` from an element function skips the
+    /// children of the resulting nodes.
+    Topdown,
+}
+
+/// What an element function returns: an `Action`, optionally paired with
+/// whether a `Topdown` traversal descends into the resulting nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Return<T> {
+    action: Action<T>,
+    descend: bool,
+}
+
+impl<T> From<Action<T>> for Return<T> {
+    fn from(action: Action<T>) -> Self {
+        Return {
+            action,
+            descend: true,
+        }
+    }
+}
+
+impl<T> From<(Action<T>, bool)> for Return<T> {
+    fn from((action, descend): (Action<T>, bool)) -> Self {
+        Return { action, descend }
+    }
+}
+
+type Function<'f, T> = Box<dyn FnMut(&mut T) -> Return<T> + 'f>;
+type InPlace<'f, T> = Option<Box<dyn FnMut(&mut T) + 'f>>;
+
+/// Elements that functions can be registered for, `Inline` and `Block`
+pub trait Element: Sized {
+    #[doc(hidden)]
+    const GENERIC: &'static str;
+    #[doc(hidden)]
+    const NAMES: &'static [&'static str];
+    #[doc(hidden)]
+    fn functions<'a, 'f>(filter: &'a mut Filter<'f>)
+        -> &'a mut HashMap<String, Function<'f, Self>>;
+    #[doc(hidden)]
+    fn element_name(&self) -> &'static str;
+}
+
+impl Element for Inline {
+    const GENERIC: &'static str = "Inline";
+    const NAMES: &'static [&'static str] = INLINE_NAMES;
+    fn functions<'a, 'f>(
+        filter: &'a mut Filter<'f>,
+    ) -> &'a mut HashMap<String, Function<'f, Self>> {
+        &mut filter.inline
+    }
+    fn element_name(&self) -> &'static str {
+        self.name()
+    }
+}
+
+impl Element for Block {
+    const GENERIC: &'static str = "Block";
+    const NAMES: &'static [&'static str] = BLOCK_NAMES;
+    fn functions<'a, 'f>(
+        filter: &'a mut Filter<'f>,
+    ) -> &'a mut HashMap<String, Function<'f, Self>> {
+        &mut filter.block
+    }
+    fn element_name(&self) -> &'static str {
+        self.name()
+    }
+}
+
+/// A set of functions by element name, see the module documentation
+#[derive(Default)]
+pub struct Filter<'f> {
+    inline: HashMap<String, Function<'f, Inline>>,
+    block: HashMap<String, Function<'f, Block>>,
+    inlines: InPlace<'f, Vec<Inline>>,
+    blocks: InPlace<'f, Vec<Block>>,
+    meta: InPlace<'f, Meta>,
+    pandoc: InPlace<'f, Pandoc>,
+    traverse: Traverse,
+}
+
+impl<'f> Filter<'f> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn traverse(mut self, traverse: Traverse) -> Self {
+        self.traverse = traverse;
+        self
+    }
+
+    /// Registers `f` for the elements named `name`, e.g. `"Str"`, or for all
+    /// elements without a function of their own with `"Inline"` or `"Block"`.
+    /// Panics if there is no such element of type `E`.
+    pub fn on<E, R, F>(mut self, name: &str, mut f: F) -> Self
+    where
+        E: Element,
+        R: Into<Return<E>>,
+        F: FnMut(&mut E) -> R + 'f,
+    {
+        assert!(
+            name == E::GENERIC || E::NAMES.contains(&name),
+            "there is no {} element called `{}`",
+            E::GENERIC,
+            name
+        );
+        E::functions(&mut self).insert(name.to_owned(), Box::new(move |e| f(e).into()));
+        self
+    }
+
+    /// Registers the `Inlines` function, called with every list of inlines
+    pub fn on_inlines<F: FnMut(&mut Vec<Inline>) + 'f>(mut self, f: F) -> Self {
+        self.inlines = Some(Box::new(f));
+        self
+    }
+
+    /// Registers the `Blocks` function, called with every list of blocks
+    pub fn on_blocks<F: FnMut(&mut Vec<Block>) + 'f>(mut self, f: F) -> Self {
+        self.blocks = Some(Box::new(f));
+        self
+    }
+
+    /// Registers the `Meta` function
+    pub fn on_meta<F: FnMut(&mut Meta) + 'f>(mut self, f: F) -> Self {
+        self.meta = Some(Box::new(f));
+        self
+    }
+
+    /// Registers the `Pandoc` function
+    pub fn on_pandoc<F: FnMut(&mut Pandoc) + 'f>(mut self, f: F) -> Self {
+        self.pandoc = Some(Box::new(f));
+        self
+    }
+
+    /// Runs the filter on the document
+    pub fn apply(&mut self, doc: &mut Pandoc) {
+        match self.traverse {
+            Traverse::Typewise => {
+                let passes = [
+                    (Pass::Inline, !self.inline.is_empty()),
+                    (Pass::Inlines, self.inlines.is_some()),
+                    (Pass::Block, !self.block.is_empty()),
+                    (Pass::Blocks, self.blocks.is_some()),
+                ];
+                for &(pass, needed) in &passes {
+                    if needed {
+                        doc.accept_mut(&mut Walker { filter: self, pass });
+                    }
+                }
+                if let Some(ref mut f) = self.meta {
+                    f(&mut doc.meta);
+                }
+                if let Some(ref mut f) = self.pandoc {
+                    f(doc);
+                }
+            }
+            Traverse::Topdown => {
+                if let Some(ref mut f) = self.pandoc {
+                    f(doc);
+                }
+                if let Some(ref mut f) = self.meta {
+                    f(&mut doc.meta);
+                }
+                doc.accept_mut(&mut Walker {
+                    filter: self,
+                    pass: Pass::Topdown,
+                });
+            }
+        }
+    }
+}
+
+fn call<E: Element>(functions: &mut HashMap<String, Function<E>>, element: &mut E) -> Return<E> {
+    let name = element.element_name();
+    let f = match functions.get_mut(name) {
+        Some(f) => f,
+        None => match functions.get_mut(E::GENERIC) {
+            Some(f) => f,
+            None => return Action::Keep.into(),
+        },
+    };
+    f(element)
+}
+
+/// Replaces the elements of `vec` by the results of the functions, returning
+/// whether to descend into each of the new elements
+fn splice<E: Element>(vec: &mut Vec<E>, functions: &mut HashMap<String, Function<E>>) -> Vec<bool> {
+    let old = mem::replace(vec, Vec::with_capacity(vec.len()));
+    let mut descend = Vec::with_capacity(old.len());
+    for mut element in old {
+        let result = call(functions, &mut element);
+        match result.action {
+            Action::Keep => vec.push(element),
+            Action::Replace(new) => vec.push(new),
+            Action::ReplaceMany(new) => vec.extend(new),
+            Action::Delete => {}
+        }
+        descend.resize(vec.len(), result.descend);
+    }
+    descend
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pass {
+    Inline,
+    Inlines,
+    Block,
+    Blocks,
+    Topdown,
+}
+
+struct Walker<'a, 'f: 'a> {
+    filter: &'a mut Filter<'f>,
+    pass: Pass,
+}
+
+impl<'a, 'f> MutVisitor for Walker<'a, 'f> {
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        match self.pass {
+            Pass::Inline => {
+                self.walk_vec_inline(vec_inline);
+                splice(vec_inline, &mut self.filter.inline);
+            }
+            Pass::Inlines => {
+                self.walk_vec_inline(vec_inline);
+                if let Some(ref mut f) = self.filter.inlines {
+                    f(vec_inline);
+                }
+            }
+            Pass::Topdown => {
+                if let Some(ref mut f) = self.filter.inlines {
+                    f(vec_inline);
+                }
+                let descend = splice(vec_inline, &mut self.filter.inline);
+                for (inline, descend) in vec_inline.iter_mut().zip(descend) {
+                    if descend {
+                        self.walk_inline(inline);
+                    }
+                }
+            }
+            Pass::Block | Pass::Blocks => self.walk_vec_inline(vec_inline),
+        }
+    }
+
+    fn visit_vec_block(&mut self, vec_block: &mut Vec<Block>) {
+        match self.pass {
+            Pass::Block => {
+                self.walk_vec_block(vec_block);
+                splice(vec_block, &mut self.filter.block);
+            }
+            Pass::Blocks => {
+                self.walk_vec_block(vec_block);
+                if let Some(ref mut f) = self.filter.blocks {
+                    f(vec_block);
+                }
+            }
+            Pass::Topdown => {
+                if let Some(ref mut f) = self.filter.blocks {
+                    f(vec_block);
+                }
+                let descend = splice(vec_block, &mut self.filter.block);
+                for (block, descend) in vec_block.iter_mut().zip(descend) {
+                    if descend {
+                        self.walk_block(block);
+                    }
+                }
+            }
+            Pass::Inline | Pass::Inlines => self.walk_vec_block(vec_block),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+/// Builds a `Vec<Inline>` from inlines, lists of inlines and strings.
+/// Strings are split into `Str` and `Space` inlines like pandoc does.
+///
+/// ```
+/// # #[macro_use] extern crate pandoc_ast;
+/// # use pandoc_ast::Inline;
+/// # fn main() {
+/// let inlines = inlines!["Hello ", strong!["world"]];
+/// assert_eq!(inlines, vec![
+///     Inline::str("Hello"),
+///     Inline::Space,
+///     Inline::Strong(vec![Inline::str("world")]),
+/// ]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! inlines {
+    ($($item:expr),* $(,)*) => {{
+        #[allow(unused_mut)]
+        let mut inlines = ::std::vec::Vec::new();
+        $($crate::IntoInlines::extend_inlines($item, &mut inlines);)*
+        inlines
+    }};
+}
+
+/// Builds a `Vec<Block>` from blocks and lists of blocks
+#[macro_export]
+macro_rules! blocks {
+    ($($item:expr),* $(,)*) => {{
+        #[allow(unused_mut)]
+        let mut blocks = ::std::vec::Vec::new();
+        $($crate::IntoBlocks::extend_blocks($item, &mut blocks);)*
+        blocks
+    }};
+}
+
+/// `Inline::Emph` with contents as accepted by `inlines!`
+#[macro_export]
+macro_rules! emph {
+    ($($item:expr),* $(,)*) => {
+        $crate::Inline::Emph($crate::inlines![$($item),*])
+    };
+}
+
+/// `Inline::Strong` with contents as accepted by `inlines!`
+#[macro_export]
+macro_rules! strong {
+    ($($item:expr),* $(,)*) => {
+        $crate::Inline::Strong($crate::inlines![$($item),*])
+    };
+}
+
+/// `Block::Para` with contents as accepted by `inlines!`
+#[macro_export]
+macro_rules! para {
+    ($($item:expr),* $(,)*) => {
+        $crate::Block::Para($crate::inlines![$($item),*])
+    };
+}
+
+/// `Block::Plain` with contents as accepted by `inlines!`
+#[macro_export]
+macro_rules! plain {
+    ($($item:expr),* $(,)*) => {
+        $crate::Block::Plain($crate::inlines![$($item),*])
+    };
+}
+
+/// Builds a `Meta` from keys and values convertible into `MetaValue`.
+/// Nested `meta!` invocations become `MetaMap`s.
+///
+/// ```
+/// # #[macro_use] extern crate pandoc_ast;
+/// # use pandoc_ast::{MetaExt, MetaValue};
+/// # fn main() {
+/// let meta = meta! {
+///     "title" => "Notes",
+///     "draft" => true,
+///     "author" => meta! { "name" => "Jane" },
+///     "tags" => vec![MetaValue::from("a"), MetaValue::from("b")],
+/// };
+/// assert_eq!(meta.get_string("author.name").unwrap(), "Jane");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! meta {
+    ($($key:expr => $value:expr),* $(,)*) => {{
+        #[allow(unused_mut)]
+        let mut meta = $crate::Meta::new();
+        $(meta.insert(::std::string::String::from($key), $crate::MetaValue::from($value));)*
+        meta
+    }};
+}
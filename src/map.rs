@@ -0,0 +1,288 @@
+//! The `Map` of metadata and other maps of the crate
+//!
+//! `Map` has the same API whether or not the `preserve-order` feature is
+//! enabled, only the order of its entries differs. By default it is sorted
+//! by key like a `BTreeMap`. With the feature it keeps the order in which the
+//! keys were inserted, so metadata read from json is written back with the
+//! keys in the order they were read, and `remove` keeps the order of the
+//! remaining entries. Either way, maps with the same entries are equal and
+//! hash the same, whatever their order.
+//!
+//! Metadata converted from a `serde_json::Value`, e.g. by
+//! `MetaExt::from_json_str`, has the order of the `Value`, which is sorted
+//! unless serde_json's own `preserve_order` feature is enabled.
+
+#[cfg(feature = "preserve-order")]
+use indexmap::{map as inner, IndexMap};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::BTreeMap as SortedMap;
+#[cfg(not(feature = "preserve-order"))]
+use std::collections::{btree_map as inner, BTreeMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::ops::Index;
+
+#[cfg(feature = "preserve-order")]
+type Inner<K, V> = IndexMap<K, V>;
+#[cfg(not(feature = "preserve-order"))]
+type Inner<K, V> = BTreeMap<K, V>;
+
+/// A map from keys to values, sorted by key unless the `preserve-order`
+/// feature is enabled
+#[derive(Clone)]
+pub struct Map<K, V>(Inner<K, V>);
+
+impl<K: Hash + Ord, V> Map<K, V> {
+    pub fn new() -> Self {
+        Map(Inner::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.0.get(key)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.0.get_mut(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.0.contains_key(key)
+    }
+
+    /// Inserts the entry, returning the old value of `key` if there was one.
+    /// A replaced entry keeps its position.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.0.insert(key, value)
+    }
+
+    /// Removes the entry with the key `key`, keeping the order of the others
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        #[cfg(feature = "preserve-order")]
+        return self.0.shift_remove(key);
+        #[cfg(not(feature = "preserve-order"))]
+        return self.0.remove(key);
+    }
+
+    /// Keeps only the entries for which `f` returns `true`
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        self.0.retain(|key, value| f(key, value))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.0.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut(self.0.iter_mut())
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.0.keys())
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.0.values())
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut(self.0.values_mut())
+    }
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Map(Inner::default())
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Map<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+impl<K: Hash + Ord, V: PartialEq> PartialEq for Map<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Hash + Ord, V: Eq> Eq for Map<K, V> {}
+
+impl<K: Hash + Ord, V: Hash> Hash for Map<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        state.write_usize(entries.len());
+        for entry in entries {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V, Q> Index<&Q> for Map<K, V>
+where
+    K: Hash + Ord + Borrow<Q>,
+    Q: Hash + Ord + ?Sized,
+{
+    type Output = V;
+
+    /// The value of `key`, panicking if there is none
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Hash + Ord, V> FromIterator<(K, V)> for Map<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Map(Inner::from_iter(iter))
+    }
+}
+
+impl<K: Hash + Ord, V> Extend<(K, V)> for Map<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<K: Hash + Ord, V> From<SortedMap<K, V>> for Map<K, V> {
+    fn from(map: SortedMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Hash + Ord, V> From<Map<K, V>> for SortedMap<K, V> {
+    fn from(map: Map<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+/// Wraps an iterator of the inner map, so that its type doesn't depend on
+/// the features
+macro_rules! iterator {
+    ($(#[$attr:meta])* $name:ident<$($lt:lifetime,)* K, V>($inner:ty) => $item:ty) => {
+        $(#[$attr])*
+        pub struct $name<$($lt,)* K, V>($inner);
+
+        impl<$($lt,)* K, V> Iterator for $name<$($lt,)* K, V> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<$($lt,)* K, V> DoubleEndedIterator for $name<$($lt,)* K, V> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.0.next_back()
+            }
+        }
+
+        impl<$($lt,)* K, V> ExactSizeIterator for $name<$($lt,)* K, V> {}
+    };
+}
+
+iterator!(
+    /// The entries of a `Map`, see `Map::iter`
+    Iter<'a, K, V>(inner::Iter<'a, K, V>) => (&'a K, &'a V)
+);
+iterator!(
+    /// The entries of a `Map` with mutable values, see `Map::iter_mut`
+    IterMut<'a, K, V>(inner::IterMut<'a, K, V>) => (&'a K, &'a mut V)
+);
+iterator!(
+    /// The owned entries of a `Map`
+    IntoIter<K, V>(inner::IntoIter<K, V>) => (K, V)
+);
+iterator!(
+    /// The keys of a `Map`, see `Map::keys`
+    Keys<'a, K, V>(inner::Keys<'a, K, V>) => &'a K
+);
+iterator!(
+    /// The values of a `Map`, see `Map::values`
+    Values<'a, K, V>(inner::Values<'a, K, V>) => &'a V
+);
+iterator!(
+    /// The mutable values of a `Map`, see `Map::values_mut`
+    ValuesMut<'a, K, V>(inner::ValuesMut<'a, K, V>) => &'a mut V
+);
+
+impl<K, V> IntoIterator for Map<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Map<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        Iter(self.0.iter())
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut Map<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        IterMut(self.0.iter_mut())
+    }
+}
+
+impl<K: Serialize, V: Serialize> Serialize for Map<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter())
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for Map<K, V>
+where
+    K: Deserialize<'de> + Hash + Ord,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Inner::deserialize(deserializer).map(Map)
+    }
+}
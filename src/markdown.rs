@@ -0,0 +1,362 @@
+//! A best-effort markdown writer for looking at documents while debugging.
+//!
+//! The output is meant for humans. It is close to pandoc's markdown, but
+//! lines are not wrapped, table cells are flattened into a single line and
+//! metadata is left out. Reading it back with pandoc does not necessarily
+//! result in the same document.
+
+use super::*;
+use std::fmt;
+
+/// characters that would start markup if they appeared in text
+const SPECIAL: &[char] = &['\\', '*', '_', '`', '[', ']', '<', '>', '$', '~', '^'];
+
+fn escape(text: &str, out: &mut String) {
+    for c in text.chars() {
+        if SPECIAL.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// `{#id .class key="value"}`, or nothing for empty attributes
+fn attr(attr: &Attr) -> String {
+    let (ref id, ref classes, ref attributes) = *attr;
+    let mut parts = Vec::new();
+    if !id.is_empty() {
+        parts.push(format!("#{}", id));
+    }
+    parts.extend(classes.iter().map(|class| format!(".{}", class)));
+    parts.extend(
+        attributes
+            .iter()
+            .map(|(key, value)| format!("{}={:?}", key, value)),
+    );
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(" "))
+    }
+}
+
+/// a run of backticks longer than any in `text`
+fn fence(text: &str, min: usize) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(min - 1) + 1)
+}
+
+/// Prefixes the first line of `text` with `first` and the others with
+/// `rest`, leaving empty lines empty
+fn indent(text: &str, first: &str, rest: &str) -> String {
+    let mut out = String::new();
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let prefix = if i == 0 { first } else { rest };
+        if line.is_empty() {
+            out.push_str(prefix.trim_end());
+        } else {
+            out.push_str(prefix);
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+fn inlines(inlines: &[Inline]) -> String {
+    let mut out = String::new();
+    for i in inlines {
+        inline(i, &mut out);
+    }
+    out
+}
+
+fn citation(citation: &Citation) -> String {
+    let mut out = inlines(&citation.citationPrefix);
+    if !out.is_empty() {
+        out.push(' ');
+    }
+    if citation.citationMode == CitationMode::SuppressAuthor {
+        out.push('-');
+    }
+    out.push('@');
+    out.push_str(&citation.citationId);
+    let suffix = inlines(&citation.citationSuffix);
+    if !suffix.is_empty() {
+        if !suffix.starts_with([',', ' ']) {
+            out.push(' ');
+        }
+        out.push_str(&suffix);
+    }
+    out
+}
+
+fn inline(inline: &Inline, out: &mut String) {
+    match *inline {
+        Inline::Str(ref s) => escape(s, out),
+        Inline::Emph(ref c) => *out += &format!("*{}*", inlines(c)),
+        Inline::Underline(ref c) => *out += &format!("[{}]{{.underline}}", inlines(c)),
+        Inline::Strong(ref c) => *out += &format!("**{}**", inlines(c)),
+        Inline::Strikeout(ref c) => *out += &format!("~~{}~~", inlines(c)),
+        Inline::Superscript(ref c) => *out += &format!("^{}^", inlines(c)),
+        Inline::Subscript(ref c) => *out += &format!("~{}~", inlines(c)),
+        Inline::SmallCaps(ref c) => *out += &format!("[{}]{{.smallcaps}}", inlines(c)),
+        Inline::Quoted(QuoteType::SingleQuote, ref c) => *out += &format!("'{}'", inlines(c)),
+        Inline::Quoted(QuoteType::DoubleQuote, ref c) => *out += &format!("\"{}\"", inlines(c)),
+        Inline::Cite(ref citations, _) => match citations[..] {
+            [ref single] if single.citationMode == CitationMode::AuthorInText => {
+                out.push_str(&citation(single))
+            }
+            _ => {
+                let citations: Vec<String> = citations.iter().map(citation).collect();
+                *out += &format!("[{}]", citations.join("; "));
+            }
+        },
+        Inline::Code(ref a, ref code) => {
+            let fence = fence(code, 1);
+            let pad = if code.starts_with('`') || code.ends_with('`') {
+                " "
+            } else {
+                ""
+            };
+            *out += &format!("{0}{1}{2}{1}{0}{3}", fence, pad, code, attr(a));
+        }
+        Inline::Space => out.push(' '),
+        Inline::SoftBreak => out.push('\n'),
+        Inline::LineBreak => out.push_str("\\\n"),
+        Inline::Math(MathType::InlineMath, ref tex) => *out += &format!("${}$", tex),
+        Inline::Math(MathType::DisplayMath, ref tex) => *out += &format!("$${}$$", tex),
+        Inline::RawInline(ref format, ref raw) => {
+            if format.matches("markdown") || format.matches("html") {
+                out.push_str(raw)
+            } else {
+                let fence = fence(raw, 1);
+                *out += &format!("{0}{1}{0}{{={2}}}", fence, raw, format.0);
+            }
+        }
+        Inline::Link(ref a, ref c, (ref url, ref title))
+        | Inline::Image(ref a, ref c, (ref url, ref title)) => {
+            if let Inline::Image(..) = *inline {
+                out.push('!');
+            }
+            *out += &format!("[{}]({}", inlines(c), url);
+            if !title.is_empty() {
+                *out += &format!(" {:?}", title);
+            }
+            out.push(')');
+            out.push_str(&attr(a));
+        }
+        Inline::Note(ref content) => {
+            let text = blocks(content).replace("\n\n", " ").replace('\n', " ");
+            *out += &format!("^[{}]", text);
+        }
+        Inline::Span(ref a, ref c) => *out += &format!("[{}]{}", inlines(c), attr(a)),
+        Inline::Unknown(ref name, _) => *out += &format!("<!-- {} -->", name),
+    }
+}
+
+fn blocks(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .map(block)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// list items are separated by blank lines unless they are all short
+fn list(items: Vec<String>, all_plain: bool) -> String {
+    items.join(if all_plain { "\n" } else { "\n\n" })
+}
+
+fn is_tight(items: &[Vec<Block>]) -> bool {
+    items
+        .iter()
+        .all(|item| matches!(item[..], [] | [Block::Plain(_)]))
+}
+
+/// a cell's content on a single line, with pipes escaped
+fn cell(content: &[Block]) -> String {
+    blocks(content)
+        .replace("\n\n", " ")
+        .replace('\n', " ")
+        .replace('|', "\\|")
+}
+
+fn table_row(cells: &[String]) -> String {
+    format!("| {} |", cells.join(" | "))
+}
+
+fn block(block: &Block) -> String {
+    match *block {
+        Block::Plain(ref c) | Block::Para(ref c) => inlines(c),
+        Block::LineBlock(ref lines) => lines
+            .iter()
+            .map(|line| format!("| {}", inlines(line)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::CodeBlock(ref a, ref code) => {
+            let fence = fence(code, 3);
+            let info = match (&a.0[..], &a.1[..], &a.2[..]) {
+                ("", [], []) => String::new(),
+                ("", [ref class], []) => format!(" {}", class),
+                _ => format!(" {}", attr(a)),
+            };
+            format!("{0}{1}\n{2}\n{0}", fence, info, code.trim_end_matches('\n'))
+        }
+        Block::RawBlock(ref format, ref raw) => {
+            if format.matches("markdown") || format.matches("html") {
+                raw.clone()
+            } else {
+                let fence = fence(raw, 3);
+                format!("{0} {{={1}}}\n{2}\n{0}", fence, format.0, raw)
+            }
+        }
+        Block::BlockQuote(ref content) => indent(&blocks(content), "> ", "> "),
+        Block::OrderedList(ListAttributes { start, delim, .. }, ref items) => {
+            let rendered = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let number = start + i as Int;
+                    let marker = match delim {
+                        ListNumberDelim::OneParen => format!("{})", number),
+                        ListNumberDelim::TwoParens => format!("({})", number),
+                        ListNumberDelim::Period | ListNumberDelim::DefaultDelim => {
+                            format!("{}.", number)
+                        }
+                    };
+                    let marker = format!("{:<4}", marker);
+                    indent(&blocks(item), &marker, "    ")
+                })
+                .collect();
+            list(rendered, is_tight(items))
+        }
+        Block::BulletList(ref items) => {
+            let rendered = items
+                .iter()
+                .map(|item| indent(&blocks(item), "-   ", "    "))
+                .collect();
+            list(rendered, is_tight(items))
+        }
+        Block::DefinitionList(ref items) => items
+            .iter()
+            .map(|(term, definitions)| {
+                let mut out = inlines(term);
+                for definition in definitions {
+                    out.push('\n');
+                    out.push_str(&indent(&blocks(definition), ":   ", "    "));
+                }
+                out
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        Block::Header(level, ref a, ref c) => {
+            let attr = attr(a);
+            let hashes = "#".repeat(level.max(1) as usize);
+            if attr.is_empty() {
+                format!("{} {}", hashes, inlines(c))
+            } else {
+                format!("{} {} {}", hashes, inlines(c), attr)
+            }
+        }
+        Block::HorizontalRule => "---".to_owned(),
+        Block::Table(_, ref caption, ref specs, ref head, ref bodies, ref foot) => {
+            let rows: Vec<&Row> = head
+                .1
+                .iter()
+                .chain(bodies.iter().flat_map(|body| body.2.iter().chain(&body.3)))
+                .chain(&foot.1)
+                .collect();
+            let cells = |row: &Row| -> Vec<String> { row.1.iter().map(|c| cell(&c.4)).collect() };
+            let mut lines = Vec::new();
+            let header = match head.1.first() {
+                Some(row) => cells(row),
+                None => vec![String::new(); specs.len()],
+            };
+            lines.push(table_row(&header));
+            let rules: Vec<String> = specs
+                .iter()
+                .map(|spec| match spec.0 {
+                    Alignment::AlignLeft => ":---",
+                    Alignment::AlignRight => "---:",
+                    Alignment::AlignCenter => ":---:",
+                    Alignment::AlignDefault => "---",
+                })
+                .map(str::to_owned)
+                .collect();
+            lines.push(table_row(&rules));
+            let skip = usize::from(!head.1.is_empty());
+            lines.extend(
+                rows.into_iter()
+                    .skip(skip)
+                    .map(|row| table_row(&cells(row))),
+            );
+            let caption = blocks(&caption.1);
+            if !caption.is_empty() {
+                lines.push(String::new());
+                lines.push(format!("Table: {}", caption.replace('\n', " ")));
+            }
+            lines.join("\n")
+        }
+        Block::Figure(_, ref caption, ref content) => {
+            let mut parts = vec![blocks(content)];
+            parts.push(blocks(&caption.1));
+            parts.retain(|part| !part.is_empty());
+            parts.join("\n\n")
+        }
+        Block::Div(ref a, ref content) => {
+            let attr = attr(a);
+            let attr = if attr.is_empty() {
+                "{}".to_owned()
+            } else {
+                attr
+            };
+            format!(":::: {}\n{}\n::::", attr, blocks(content))
+        }
+        Block::Null => String::new(),
+        Block::Unknown(ref name, _) => format!("<!-- {} -->", name),
+        Block::Unparsed(ref unparsed) => match unparsed.parse() {
+            Ok(parsed) => self::block(&parsed),
+            Err(_) => "<!-- invalid block -->".to_owned(),
+        },
+    }
+}
+
+impl Pandoc {
+    /// Renders the blocks as markdown, see the caveats of `Display for Block`.
+    /// The metadata is left out.
+    pub fn to_markdown_lossy(&self) -> String {
+        let mut out = blocks(&self.blocks);
+        out.push('\n');
+        out
+    }
+}
+
+/// Same as `to_markdown_lossy`
+impl fmt::Display for Pandoc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_markdown_lossy())
+    }
+}
+
+/// Renders the block as pandoc-style markdown for debugging. The output is
+/// not canonical: lines are not wrapped, table cells are flattened into a
+/// single line and footnotes become inline notes. Pandoc does not
+/// necessarily read it back into the same block.
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&block(self))
+    }
+}
+
+/// Renders the inline as markdown, see `Display for Block`
+impl fmt::Display for Inline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        inline(self, &mut out);
+        f.write_str(&out)
+    }
+}
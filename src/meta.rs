@@ -0,0 +1,619 @@
+use super::*;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, Deserialize, Deserializer, IntoDeserializer, Visitor};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// The metadata of a document
+pub type Meta = Map<String, MetaValue>;
+
+/// How `MetaExt::merge_with` resolves keys present on both sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MergeStrategy {
+    /// keep the existing value
+    LeftBias,
+    /// replace the existing value
+    RightBias,
+    /// merge `MetaMap`s present on both sides key by key, replacing
+    /// everything else like `RightBias`
+    Deep,
+}
+
+impl MetaValue {
+    /// the entry of a `MetaMap` or the element of a `MetaList` with the given index
+    pub fn child(&self, key: &str) -> Option<&MetaValue> {
+        match *self {
+            MetaValue::MetaMap(ref map) => map.get(key).map(|value| &**value),
+            MetaValue::MetaList(ref list) => key.parse().ok().and_then(|i: usize| list.get(i)),
+            _ => None,
+        }
+    }
+
+    pub fn child_mut(&mut self, key: &str) -> Option<&mut MetaValue> {
+        match *self {
+            MetaValue::MetaMap(ref mut map) => map.get_mut(key).map(|value| &mut **value),
+            MetaValue::MetaList(ref mut list) => {
+                key.parse().ok().and_then(move |i: usize| list.get_mut(i))
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts this value into any deserializable type, see `MetaExt::deserialize_into`
+    pub fn deserialize_into<'de, T: Deserialize<'de>>(&'de self) -> Result<T, Error> {
+        T::deserialize(MetaDeserializer(self))
+    }
+
+    /// Converts a json value into metadata. Strings become `MetaString`s,
+    /// numbers `MetaNumber`s, booleans `MetaBool`s, arrays `MetaList`s and
+    /// objects `MetaMap`s. Object entries that are `null` are left out, any
+    /// other `null` becomes an empty `MetaString`.
+    pub fn from_json_value(value: serde_json::Value) -> MetaValue {
+        use serde_json::Value;
+        match value {
+            Value::Null => MetaValue::MetaString(String::new()),
+            Value::Bool(b) => MetaValue::MetaBool(b),
+            Value::Number(n) => MetaValue::MetaNumber(n),
+            Value::String(s) => MetaValue::MetaString(s),
+            Value::Array(list) => {
+                MetaValue::MetaList(list.into_iter().map(MetaValue::from_json_value).collect())
+            }
+            Value::Object(map) => MetaValue::MetaMap(
+                map.into_iter()
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(key, value)| (key, Box::new(MetaValue::from_json_value(value))))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Converts metadata into a json value, the inverse of `from_json_value`.
+    /// Inlines and blocks can't be represented and become their `stringify`d
+    /// text. Everything else round-trips, except for empty `MetaString`s
+    /// that were `null`.
+    pub fn to_json_value(&self) -> serde_json::Value {
+        use serde_json::Value;
+        match *self {
+            MetaValue::MetaMap(ref map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.to_json_value()))
+                    .collect(),
+            ),
+            MetaValue::MetaList(ref list) => {
+                Value::Array(list.iter().map(MetaValue::to_json_value).collect())
+            }
+            MetaValue::MetaBool(b) => Value::Bool(b),
+            MetaValue::MetaString(ref s) => Value::String(s.clone()),
+            MetaValue::MetaNumber(ref n) => Value::Number(n.clone()),
+            MetaValue::MetaInlines(ref inlines) => Value::String(stringify(inlines)),
+            MetaValue::MetaBlocks(ref blocks) => Value::String(stringify(blocks)),
+        }
+    }
+
+    /// The text of strings, inlines and blocks (via `stringify`), booleans
+    /// (`true` or `false`) and numbers. Maps and lists have no text.
+    pub fn text(&self) -> Option<String> {
+        match *self {
+            MetaValue::MetaString(ref s) => Some(s.clone()),
+            MetaValue::MetaNumber(ref n) => Some(n.to_string()),
+            MetaValue::MetaInlines(ref inlines) => Some(stringify(inlines)),
+            MetaValue::MetaBlocks(ref blocks) => Some(stringify(blocks)),
+            MetaValue::MetaBool(b) => Some(b.to_string()),
+            MetaValue::MetaMap(_) | MetaValue::MetaList(_) => None,
+        }
+    }
+
+    /// The value as inlines. Strings, booleans and numbers are split into words.
+    /// Blocks are joined by spaces, with `Para`s and `Plain`s contributing
+    /// their inlines and other blocks their text. Maps and lists have no
+    /// inlines.
+    pub fn to_inlines(&self) -> Option<Vec<Inline>> {
+        match *self {
+            MetaValue::MetaInlines(ref inlines) => Some(inlines.clone()),
+            MetaValue::MetaBlocks(ref blocks) => {
+                let mut inlines = Vec::new();
+                for block in blocks {
+                    if !inlines.is_empty() {
+                        inlines.push(Inline::Space);
+                    }
+                    match *block {
+                        Block::Para(ref content) | Block::Plain(ref content) => {
+                            inlines.extend(content.iter().cloned())
+                        }
+                        ref block => inlines.extend(inlines_from_str(&stringify(block))),
+                    }
+                }
+                Some(inlines)
+            }
+            MetaValue::MetaString(ref s) => Some(inlines_from_str(s)),
+            MetaValue::MetaBool(b) => Some(vec![Inline::Str(b.to_string())]),
+            MetaValue::MetaNumber(ref n) => Some(vec![Inline::Str(n.to_string())]),
+            MetaValue::MetaMap(_) | MetaValue::MetaList(_) => None,
+        }
+    }
+
+    /// The value as blocks. Strings, booleans, numbers and inlines become a
+    /// `Para`, unless they are empty. Maps and lists have no blocks.
+    pub fn to_blocks(&self) -> Option<Vec<Block>> {
+        match *self {
+            MetaValue::MetaBlocks(ref blocks) => Some(blocks.clone()),
+            MetaValue::MetaMap(_) | MetaValue::MetaList(_) => None,
+            _ => {
+                let inlines = self.to_inlines()?;
+                if inlines.is_empty() {
+                    Some(Vec::new())
+                } else {
+                    Some(vec![Block::Para(inlines)])
+                }
+            }
+        }
+    }
+
+    pub fn from_inlines(inlines: Vec<Inline>) -> MetaValue {
+        MetaValue::MetaInlines(inlines)
+    }
+
+    /// `MetaInlines` for a single `Para` or `Plain`, like pandoc's YAML
+    /// reader produces for one-paragraph values, `MetaBlocks` otherwise
+    pub fn from_blocks(mut blocks: Vec<Block>) -> MetaValue {
+        if blocks.len() == 1 {
+            match blocks.pop() {
+                Some(Block::Para(inlines)) | Some(Block::Plain(inlines)) => {
+                    return MetaValue::MetaInlines(inlines)
+                }
+                Some(block) => blocks.push(block),
+                None => {}
+            }
+        }
+        MetaValue::MetaBlocks(blocks)
+    }
+}
+
+impl<'a> From<&'a str> for MetaValue {
+    fn from(s: &'a str) -> Self {
+        MetaValue::MetaString(s.to_owned())
+    }
+}
+
+impl From<String> for MetaValue {
+    fn from(s: String) -> Self {
+        MetaValue::MetaString(s)
+    }
+}
+
+impl From<bool> for MetaValue {
+    fn from(b: bool) -> Self {
+        MetaValue::MetaBool(b)
+    }
+}
+
+impl From<serde_json::Number> for MetaValue {
+    fn from(n: serde_json::Number) -> Self {
+        MetaValue::MetaNumber(n)
+    }
+}
+
+impl From<Vec<MetaValue>> for MetaValue {
+    fn from(list: Vec<MetaValue>) -> Self {
+        MetaValue::MetaList(list)
+    }
+}
+
+impl From<Map<String, MetaValue>> for MetaValue {
+    fn from(map: Map<String, MetaValue>) -> Self {
+        MetaValue::MetaMap(
+            map.into_iter()
+                .map(|(key, value)| (key, Box::new(value)))
+                .collect(),
+        )
+    }
+}
+
+/// `MetaValue` as it is read, with a `MetaString` holding either a string
+/// or a number
+#[derive(Deserialize)]
+#[serde(tag = "t", content = "c")]
+#[allow(clippy::enum_variant_names)]
+pub(crate) enum MetaValueDef {
+    MetaMap(Map<String, Box<MetaValue>>),
+    MetaList(Vec<MetaValue>),
+    MetaBool(bool),
+    MetaString(StringOrNumber),
+    MetaInlines(Vec<Inline>),
+    MetaBlocks(Vec<Block>),
+}
+
+impl From<MetaValueDef> for MetaValue {
+    fn from(value: MetaValueDef) -> Self {
+        match value {
+            MetaValueDef::MetaMap(map) => MetaValue::MetaMap(map),
+            MetaValueDef::MetaList(list) => MetaValue::MetaList(list),
+            MetaValueDef::MetaBool(b) => MetaValue::MetaBool(b),
+            MetaValueDef::MetaString(StringOrNumber(s)) => s,
+            MetaValueDef::MetaInlines(inlines) => MetaValue::MetaInlines(inlines),
+            MetaValueDef::MetaBlocks(blocks) => MetaValue::MetaBlocks(blocks),
+        }
+    }
+}
+
+/// the content of a `MetaString`, as a `MetaString` or a `MetaNumber`
+pub(crate) struct StringOrNumber(MetaValue);
+
+impl<'de> Deserialize<'de> for StringOrNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ContentVisitor;
+
+        impl<'de> Visitor<'de> for ContentVisitor {
+            type Value = MetaValue;
+
+            fn expecting(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str("a string or a number")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<MetaValue, E> {
+                Ok(MetaValue::MetaString(s.to_owned()))
+            }
+
+            fn visit_string<E: de::Error>(self, s: String) -> Result<MetaValue, E> {
+                Ok(MetaValue::MetaString(s))
+            }
+
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<MetaValue, E> {
+                Ok(MetaValue::MetaNumber(n.into()))
+            }
+
+            fn visit_i64<E: de::Error>(self, n: i64) -> Result<MetaValue, E> {
+                Ok(MetaValue::MetaNumber(n.into()))
+            }
+
+            fn visit_f64<E: de::Error>(self, n: f64) -> Result<MetaValue, E> {
+                serde_json::Number::from_f64(n)
+                    .map(MetaValue::MetaNumber)
+                    .ok_or_else(|| E::custom(format!("{} is not a finite number", n)))
+            }
+        }
+
+        deserializer.deserialize_any(ContentVisitor).map(StringOrNumber)
+    }
+}
+
+/// the nested value `segments` describes, e.g. `a.b` becomes `{a: {b: value}}`
+fn nest(segments: &[&str], value: MetaValue) -> MetaValue {
+    segments.iter().rev().fold(value, |value, segment| {
+        let mut map = Map::new();
+        map.insert(segment.to_string(), Box::new(value));
+        MetaValue::MetaMap(map)
+    })
+}
+
+fn set_in(
+    container: &mut MetaValue,
+    segments: &[&str],
+    value: MetaValue,
+) -> Result<Option<MetaValue>, MetaValue> {
+    let (first, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return Err(value),
+    };
+    match *container {
+        MetaValue::MetaMap(ref mut map) => {
+            if rest.is_empty() {
+                return Ok(map
+                    .insert(first.to_string(), Box::new(value))
+                    .map(|old| *old));
+            }
+            match map.get_mut(*first) {
+                Some(child) => set_in(child, rest, value),
+                None => {
+                    map.insert(first.to_string(), Box::new(nest(rest, value)));
+                    Ok(None)
+                }
+            }
+        }
+        MetaValue::MetaList(ref mut list) => {
+            let index = match first.parse::<usize>() {
+                Ok(index) => index,
+                Err(_) => return Err(value),
+            };
+            if index < list.len() {
+                if rest.is_empty() {
+                    Ok(Some(::std::mem::replace(&mut list[index], value)))
+                } else {
+                    set_in(&mut list[index], rest, value)
+                }
+            } else if index == list.len() && rest.is_empty() {
+                list.push(value);
+                Ok(None)
+            } else {
+                Err(value)
+            }
+        }
+        _ => Err(value),
+    }
+}
+
+/// Access to nested metadata by dotted paths like `author.0.name`, where
+/// each segment is either a map key or a list index. Keys containing dots
+/// cannot be reached this way.
+pub trait MetaExt {
+    fn get_path(&self, path: &str) -> Option<&MetaValue>;
+    fn get_path_mut(&mut self, path: &str) -> Option<&mut MetaValue>;
+
+    /// The text of the value at `path`, see `MetaValue::text`
+    fn get_string(&self, path: &str) -> Option<String> {
+        self.get_path(path).and_then(MetaValue::text)
+    }
+
+    /// The value at `path` if it is a `MetaBool`
+    fn get_bool(&self, path: &str) -> Option<bool> {
+        match self.get_path(path) {
+            Some(&MetaValue::MetaBool(b)) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Stores `value` at `path` and returns the previous value. Missing map
+    /// entries are created, lists can be extended by using their length as
+    /// index. If the path leads through anything else, `value` is handed back
+    /// as the error.
+    fn set_path(&mut self, path: &str, value: MetaValue) -> Result<Option<MetaValue>, MetaValue>;
+
+    /// Removes the value at `path` from its map or list
+    fn remove_path(&mut self, path: &str) -> Option<MetaValue>;
+
+    /// Converts the metadata into any deserializable type. Inlines and blocks
+    /// are turned into text via `stringify`, lists into sequences and maps
+    /// into maps or structs. Numbers and booleans are parsed from text where
+    /// the target type expects them, as pandoc reads YAML scalars as text.
+    fn deserialize_into<'de, T: Deserialize<'de>>(&'de self) -> Result<T, Error>;
+
+    /// Converts any value that serializes to a map into metadata. Strings,
+    /// numbers and `null` become `MetaString`s, booleans `MetaBool`s.
+    fn from_serialize<T: Serialize>(value: &T) -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    /// Adds the entries of `other`, resolving conflicts with `strategy`
+    fn merge_with(&mut self, other: Meta, strategy: MergeStrategy);
+
+    /// Parses a json object into metadata, see `MetaValue::from_json_value`
+    fn from_json_str(json: &str) -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    /// Parses a YAML mapping into metadata. Unlike pandoc, string values are
+    /// not parsed as markdown but become `MetaString`s.
+    #[cfg(feature = "yaml")]
+    fn from_yaml_str(yaml: &str) -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    /// Reads a metadata or defaults file. With the `yaml` feature the file is
+    /// parsed as YAML (which includes json), otherwise as json.
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error>
+    where
+        Self: Sized;
+}
+
+fn merge_deep(left: &mut MetaValue, right: MetaValue) {
+    match (left, right) {
+        (&mut MetaValue::MetaMap(ref mut left), MetaValue::MetaMap(right)) => {
+            for (key, value) in right {
+                match left.get_mut(&key) {
+                    Some(existing) => merge_deep(existing, *value),
+                    None => {
+                        left.insert(key, value);
+                    }
+                }
+            }
+        }
+        (left, right) => *left = right,
+    }
+}
+
+impl MetaExt for Meta {
+    fn get_path(&self, path: &str) -> Option<&MetaValue> {
+        let mut segments = path.split('.');
+        let first = self.get(segments.next()?)?;
+        segments.try_fold(first, |value, segment| value.child(segment))
+    }
+
+    fn get_path_mut(&mut self, path: &str) -> Option<&mut MetaValue> {
+        let mut segments = path.split('.');
+        let first = self.get_mut(segments.next()?)?;
+        segments.try_fold(first, |value, segment| value.child_mut(segment))
+    }
+
+    fn set_path(&mut self, path: &str, value: MetaValue) -> Result<Option<MetaValue>, MetaValue> {
+        let segments: Vec<&str> = path.split('.').collect();
+        let (first, rest) = segments
+            .split_first()
+            .expect("split yields at least one segment");
+        if rest.is_empty() {
+            return Ok(self.insert(first.to_string(), value));
+        }
+        match self.get_mut(*first) {
+            Some(child) => set_in(child, rest, value),
+            None => {
+                self.insert(first.to_string(), nest(rest, value));
+                Ok(None)
+            }
+        }
+    }
+
+    fn deserialize_into<'de, T: Deserialize<'de>>(&'de self) -> Result<T, Error> {
+        T::deserialize(MapDeserializer::new(
+            self.iter()
+                .map(|(key, value)| (&key[..], MetaDeserializer(value))),
+        ))
+    }
+
+    fn from_serialize<T: Serialize>(value: &T) -> Result<Self, Error> {
+        match serde_json::to_value(value)? {
+            serde_json::Value::Object(map) => Ok(map
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, MetaValue::from_json_value(value)))
+                .collect()),
+            _ => Err(Error::Metadata(
+                "expected a value that serializes to a map".to_owned(),
+            )),
+        }
+    }
+
+    fn merge_with(&mut self, other: Meta, strategy: MergeStrategy) {
+        for (key, value) in other {
+            match (self.get_mut(&key), strategy) {
+                (Some(_), MergeStrategy::LeftBias) => {}
+                (Some(existing), MergeStrategy::Deep) => merge_deep(existing, value),
+                (Some(_), MergeStrategy::RightBias) | (None, _) => {
+                    self.insert(key, value);
+                }
+            }
+        }
+    }
+
+    fn from_json_str(json: &str) -> Result<Self, Error> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        Self::from_serialize(&value)
+    }
+
+    #[cfg(feature = "yaml")]
+    fn from_yaml_str(yaml: &str) -> Result<Self, Error> {
+        let value: serde_json::Value =
+            serde_yaml::from_str(yaml).map_err(|err| Error::Metadata(err.to_string()))?;
+        Self::from_serialize(&value)
+    }
+
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let text = fs::read_to_string(path)?;
+        #[cfg(feature = "yaml")]
+        return Self::from_yaml_str(&text);
+        #[cfg(not(feature = "yaml"))]
+        return Self::from_json_str(&text);
+    }
+
+    fn remove_path(&mut self, path: &str) -> Option<MetaValue> {
+        let (parent, last) = match path.rfind('.') {
+            Some(pos) => (&path[..pos], &path[pos + 1..]),
+            None => return self.remove(path),
+        };
+        match *self.get_path_mut(parent)? {
+            MetaValue::MetaMap(ref mut map) => map.remove(last).map(|old| *old),
+            MetaValue::MetaList(ref mut list) => {
+                let index: usize = last.parse().ok()?;
+                if index < list.len() {
+                    Some(list.remove(index))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+struct MetaDeserializer<'de>(&'de MetaValue);
+
+impl<'de> IntoDeserializer<'de, Error> for MetaDeserializer<'de> {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: ::std::fmt::Display>(msg: T) -> Self {
+        Error::Metadata(msg.to_string())
+    }
+}
+
+macro_rules! deserialize_from_text {
+    ($($method:ident => $visit:ident($ty:ty),)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            let parsed = match *self.0 {
+                MetaValue::MetaMap(_) | MetaValue::MetaList(_) | MetaValue::MetaBool(_) => None,
+                ref value => value.text().and_then(|text| text.trim().parse::<$ty>().ok()),
+            };
+            match parsed {
+                Some(parsed) => visitor.$visit(parsed),
+                None => self.deserialize_any(visitor),
+            }
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for MetaDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match *self.0 {
+            MetaValue::MetaMap(ref map) => visitor.visit_map(MapDeserializer::new(
+                map.iter()
+                    .map(|(key, value)| (&key[..], MetaDeserializer(value))),
+            )),
+            MetaValue::MetaList(ref list) => {
+                visitor.visit_seq(SeqDeserializer::new(list.iter().map(MetaDeserializer)))
+            }
+            MetaValue::MetaBool(b) => visitor.visit_bool(b),
+            MetaValue::MetaString(ref s) => visitor.visit_borrowed_str(s),
+            MetaValue::MetaNumber(ref n) => match (n.as_u64(), n.as_i64(), n.as_f64()) {
+                (Some(u), _, _) => visitor.visit_u64(u),
+                (_, Some(i), _) => visitor.visit_i64(i),
+                (_, _, f) => visitor.visit_f64(f.unwrap_or_default()),
+            },
+            MetaValue::MetaInlines(ref inlines) => visitor.visit_string(stringify(inlines)),
+            MetaValue::MetaBlocks(ref blocks) => visitor.visit_string(stringify(blocks)),
+        }
+    }
+
+    deserialize_from_text! {
+        deserialize_bool => visit_bool(bool),
+        deserialize_i8 => visit_i8(i8),
+        deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16),
+        deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64),
+        deserialize_f32 => visit_f32(f32),
+        deserialize_f64 => visit_f64(f64),
+        deserialize_char => visit_char(char),
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match *self.0 {
+            MetaValue::MetaMap(_) | MetaValue::MetaList(_) | MetaValue::MetaBool(_) => {
+                self.deserialize_any(visitor)
+            }
+            ref value => {
+                let text = value.text().unwrap_or_default();
+                visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(text))
+            }
+        }
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
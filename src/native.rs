@@ -0,0 +1,1057 @@
+//! Pandoc's `native` format, the Haskell representation of documents as
+//! printed by `pandoc -t native`.
+//!
+//! Both the pretty-printed output of current pandoc versions and the single
+//! line `show` output of older ones can be read. Documents are written with one
+//! top-level block per line, which pandoc reads back with `-f native`, although
+//! long blocks are not wrapped like pandoc does.
+//!
+//! The native form of `Block::Unknown` and `Inline::Unknown` is not known,
+//! writing documents containing them fails with `Error::Native`.
+
+use super::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// A Haskell value
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    /// a constructor or function applied to arguments
+    Con(String, Vec<Value>),
+    Record(String, Vec<(String, Value)>),
+    List(Vec<Value>),
+    Tuple(Vec<Value>),
+    Str(String),
+    Num(String),
+}
+
+fn error<T, S: Into<String>>(msg: S) -> Result<T, Error> {
+    Err(Error::Native(msg.into()))
+}
+
+impl Value {
+    fn con<S: Into<String>>(name: S, args: Vec<Value>) -> Value {
+        Value::Con(name.into(), args)
+    }
+
+    fn describe(&self) -> String {
+        match *self {
+            Value::Con(ref name, _) | Value::Record(ref name, _) => format!("`{}`", name),
+            Value::List(_) => "a list".to_owned(),
+            Value::Tuple(_) => "a tuple".to_owned(),
+            Value::Str(_) => "a string".to_owned(),
+            Value::Num(_) => "a number".to_owned(),
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, Error> {
+        error(format!("expected {}, found {}", expected, self.describe()))
+    }
+
+    /// the arguments of the constructor `name`
+    fn args(&self, name: &str) -> Result<&[Value], Error> {
+        match *self {
+            Value::Con(ref con, ref args) if con == name => Ok(args),
+            _ => self.unexpected(&format!("`{}`", name)),
+        }
+    }
+
+    /// the only argument of the constructor `name`
+    fn arg(&self, name: &str) -> Result<&Value, Error> {
+        match *self.args(name)? {
+            [ref arg] => Ok(arg),
+            _ => error(format!("`{}` takes a single argument", name)),
+        }
+    }
+
+    fn list(&self) -> Result<&[Value], Error> {
+        match *self {
+            Value::List(ref values) => Ok(values),
+            _ => self.unexpected("a list"),
+        }
+    }
+
+    fn map_list<T, F: Fn(&Value) -> Result<T, Error>>(&self, f: F) -> Result<Vec<T>, Error> {
+        self.list()?.iter().map(f).collect()
+    }
+
+    fn tuple(&self, len: usize) -> Result<&[Value], Error> {
+        match *self {
+            Value::Tuple(ref values) if values.len() == len => Ok(values),
+            _ => self.unexpected(&format!("a {}-tuple", len)),
+        }
+    }
+
+    fn string(&self) -> Result<String, Error> {
+        match *self {
+            Value::Str(ref s) => Ok(s.clone()),
+            _ => self.unexpected("a string"),
+        }
+    }
+
+    fn int(&self) -> Result<Int, Error> {
+        match *self {
+            Value::Num(ref n) => n
+                .parse()
+                .or_else(|_| error(format!("`{}` is not an integer", n))),
+            _ => self.unexpected("an integer"),
+        }
+    }
+
+    fn double(&self) -> Result<Double, Error> {
+        match *self {
+            Value::Num(ref n) => n
+                .parse()
+                .or_else(|_| error(format!("`{}` is not a number", n))),
+            _ => self.unexpected("a number"),
+        }
+    }
+
+    fn field(&self, record: &str, field: &str) -> Result<&Value, Error> {
+        match *self {
+            Value::Record(ref name, ref fields) if name == record => fields
+                .iter()
+                .find(|&(name, _)| name == field)
+                .map(|(_, value)| value)
+                .ok_or_else(|| Error::Native(format!("`{}` lacks `{}`", record, field))),
+            _ => self.unexpected(&format!("`{}`", record)),
+        }
+    }
+
+    /// a constructor without arguments, for the enums whose serde
+    /// representation is `{"t": name}`
+    fn unit<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        match *self {
+            Value::Con(ref name, ref args) if args.is_empty() => {
+                serde_json::from_value(serde_json::json!({ "t": name }))
+                    .or_else(|_| error(format!("unknown constructor `{}`", name)))
+            }
+            _ => self.unexpected("a constructor"),
+        }
+    }
+}
+
+fn unit<T: Serialize>(value: &T) -> Value {
+    let json = serde_json::to_value(value).expect("serialization failed");
+    Value::con(json["t"].as_str().expect("not a unit constructor"), vec![])
+}
+
+/// Haskell's `show` for doubles
+fn show_double(d: Double) -> String {
+    let abs = d.abs();
+    if abs == 0.0 || (0.1..1e7).contains(&abs) {
+        let s = d.to_string();
+        if s.contains('.') {
+            s
+        } else {
+            s + ".0"
+        }
+    } else {
+        let s = format!("{:e}", d);
+        let (mantissa, exponent) = s.split_at(s.find('e').expect("no exponent"));
+        if mantissa.contains('.') {
+            s
+        } else {
+            format!("{}.0{}", mantissa, exponent)
+        }
+    }
+}
+
+fn str_value(s: &str) -> Value {
+    Value::Str(s.to_owned())
+}
+
+fn int_value(i: Int) -> Value {
+    Value::Num(i.to_string())
+}
+
+fn attr_value(attr: &Attr) -> Value {
+    let (ref id, ref classes, ref attributes) = *attr;
+    Value::Tuple(vec![
+        str_value(id),
+        Value::List(classes.iter().map(|class| str_value(class)).collect()),
+        Value::List(
+            attributes
+                .iter()
+                .map(|(key, value)| Value::Tuple(vec![str_value(key), str_value(value)]))
+                .collect(),
+        ),
+    ])
+}
+
+fn inlines_value(inlines: &[Inline]) -> Result<Value, Error> {
+    Ok(Value::List(
+        inlines.iter().map(inline_value).collect::<Result<_, _>>()?,
+    ))
+}
+
+fn blocks_value(blocks: &[Block]) -> Result<Value, Error> {
+    Ok(Value::List(
+        blocks.iter().map(block_value).collect::<Result<_, _>>()?,
+    ))
+}
+
+fn items_value(items: &[Vec<Block>]) -> Result<Value, Error> {
+    Ok(Value::List(
+        items
+            .iter()
+            .map(|item| blocks_value(item))
+            .collect::<Result<_, _>>()?,
+    ))
+}
+
+fn caption_value(caption: &Caption) -> Result<Value, Error> {
+    let short = match caption.0 {
+        Some(ref short) => Value::con("Just", vec![inlines_value(short)?]),
+        None => Value::con("Nothing", vec![]),
+    };
+    Ok(Value::con(
+        "Caption",
+        vec![short, blocks_value(&caption.1)?],
+    ))
+}
+
+fn rows_value(rows: &[Row]) -> Result<Value, Error> {
+    let rows = rows
+        .iter()
+        .map(|row| {
+            let cells = row
+                .1
+                .iter()
+                .map(|cell| {
+                    Ok(Value::con(
+                        "Cell",
+                        vec![
+                            attr_value(&cell.0),
+                            unit(&cell.1),
+                            Value::con("RowSpan", vec![int_value(cell.2)]),
+                            Value::con("ColSpan", vec![int_value(cell.3)]),
+                            blocks_value(&cell.4)?,
+                        ],
+                    ))
+                })
+                .collect::<Result<_, Error>>()?;
+            Ok(Value::con(
+                "Row",
+                vec![attr_value(&row.0), Value::List(cells)],
+            ))
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(Value::List(rows))
+}
+
+fn meta_value_value(value: &MetaValue) -> Result<Value, Error> {
+    Ok(match *value {
+        MetaValue::MetaMap(ref map) => Value::con(
+            "MetaMap",
+            vec![Value::con(
+                "fromList",
+                vec![Value::List(
+                    map.iter()
+                        .map(|(key, value)| {
+                            Ok(Value::Tuple(vec![str_value(key), meta_value_value(value)?]))
+                        })
+                        .collect::<Result<_, Error>>()?,
+                )],
+            )],
+        ),
+        MetaValue::MetaList(ref list) => Value::con(
+            "MetaList",
+            vec![Value::List(
+                list.iter()
+                    .map(meta_value_value)
+                    .collect::<Result<_, _>>()?,
+            )],
+        ),
+        MetaValue::MetaBool(b) => Value::con(
+            "MetaBool",
+            vec![Value::con(if b { "True" } else { "False" }, vec![])],
+        ),
+        MetaValue::MetaString(ref s) => Value::con("MetaString", vec![str_value(s)]),
+        MetaValue::MetaNumber(ref n) => Value::con("MetaString", vec![str_value(&n.to_string())]),
+        MetaValue::MetaInlines(ref inlines) => {
+            Value::con("MetaInlines", vec![inlines_value(inlines)?])
+        }
+        MetaValue::MetaBlocks(ref blocks) => Value::con("MetaBlocks", vec![blocks_value(blocks)?]),
+    })
+}
+
+fn unknown<T>(name: &str) -> Result<T, Error> {
+    error(format!(
+        "the unknown constructor `{}` can't be written as native",
+        name
+    ))
+}
+
+fn block_value(block: &Block) -> Result<Value, Error> {
+    let name = block.name();
+    let args = match *block {
+        Block::Plain(ref content) | Block::Para(ref content) => vec![inlines_value(content)?],
+        Block::LineBlock(ref lines) => vec![Value::List(
+            lines
+                .iter()
+                .map(|line| inlines_value(line))
+                .collect::<Result<_, _>>()?,
+        )],
+        Block::CodeBlock(ref attr, ref code) => vec![attr_value(attr), str_value(code)],
+        Block::RawBlock(ref format, ref content) => vec![
+            Value::con("Format", vec![str_value(&format.0)]),
+            str_value(content),
+        ],
+        Block::BlockQuote(ref content) => vec![blocks_value(content)?],
+        Block::OrderedList(
+            ListAttributes {
+                start,
+                ref style,
+                ref delim,
+            },
+            ref items,
+        ) => vec![
+            Value::Tuple(vec![int_value(start), unit(style), unit(delim)]),
+            items_value(items)?,
+        ],
+        Block::BulletList(ref items) => vec![items_value(items)?],
+        Block::DefinitionList(ref items) => vec![Value::List(
+            items
+                .iter()
+                .map(|(term, definitions)| {
+                    Ok(Value::Tuple(vec![
+                        inlines_value(term)?,
+                        items_value(definitions)?,
+                    ]))
+                })
+                .collect::<Result<_, Error>>()?,
+        )],
+        Block::Header(level, ref attr, ref content) => {
+            vec![int_value(level), attr_value(attr), inlines_value(content)?]
+        }
+        Block::Table(ref attr, ref caption, ref specs, ref head, ref bodies, ref foot) => vec![
+            attr_value(attr),
+            caption_value(caption)?,
+            Value::List(
+                specs
+                    .iter()
+                    .map(|(alignment, width)| {
+                        let width = match *width {
+                            ColWidth::ColWidth(w) => {
+                                Value::con("ColWidth", vec![Value::Num(show_double(w))])
+                            }
+                            ColWidth::ColWidthDefault => Value::con("ColWidthDefault", vec![]),
+                        };
+                        Value::Tuple(vec![unit(alignment), width])
+                    })
+                    .collect(),
+            ),
+            Value::con("TableHead", vec![attr_value(&head.0), rows_value(&head.1)?]),
+            Value::List(
+                bodies
+                    .iter()
+                    .map(|body| {
+                        Ok(Value::con(
+                            "TableBody",
+                            vec![
+                                attr_value(&body.0),
+                                Value::con("RowHeadColumns", vec![int_value(body.1)]),
+                                rows_value(&body.2)?,
+                                rows_value(&body.3)?,
+                            ],
+                        ))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Value::con("TableFoot", vec![attr_value(&foot.0), rows_value(&foot.1)?]),
+        ],
+        Block::Figure(ref attr, ref caption, ref content) => vec![
+            attr_value(attr),
+            caption_value(caption)?,
+            blocks_value(content)?,
+        ],
+        Block::Div(ref attr, ref content) => vec![attr_value(attr), blocks_value(content)?],
+        Block::HorizontalRule | Block::Null => vec![],
+        Block::Unparsed(ref unparsed) => return block_value(&unparsed.parse()?),
+        Block::Unknown(ref name, _) => return unknown(name),
+    };
+    Ok(Value::con(name, args))
+}
+
+fn inline_value(inline: &Inline) -> Result<Value, Error> {
+    let name = inline.name();
+    let args = match *inline {
+        Inline::Str(ref s) => vec![str_value(s)],
+        Inline::Emph(ref content)
+        | Inline::Underline(ref content)
+        | Inline::Strong(ref content)
+        | Inline::Strikeout(ref content)
+        | Inline::Superscript(ref content)
+        | Inline::Subscript(ref content)
+        | Inline::SmallCaps(ref content) => vec![inlines_value(content)?],
+        Inline::Quoted(ref quote_type, ref content) => {
+            vec![unit(quote_type), inlines_value(content)?]
+        }
+        Inline::Cite(ref citations, ref content) => vec![
+            Value::List(
+                citations
+                    .iter()
+                    .map(|citation| {
+                        Ok(Value::Record(
+                            "Citation".to_owned(),
+                            vec![
+                                ("citationId".to_owned(), str_value(&citation.citationId)),
+                                (
+                                    "citationPrefix".to_owned(),
+                                    inlines_value(&citation.citationPrefix)?,
+                                ),
+                                (
+                                    "citationSuffix".to_owned(),
+                                    inlines_value(&citation.citationSuffix)?,
+                                ),
+                                ("citationMode".to_owned(), unit(&citation.citationMode)),
+                                (
+                                    "citationNoteNum".to_owned(),
+                                    int_value(citation.citationNoteNum),
+                                ),
+                                ("citationHash".to_owned(), int_value(citation.citationHash)),
+                            ],
+                        ))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            inlines_value(content)?,
+        ],
+        Inline::Code(ref attr, ref code) => vec![attr_value(attr), str_value(code)],
+        Inline::Space | Inline::SoftBreak | Inline::LineBreak => vec![],
+        Inline::Math(ref math_type, ref tex) => vec![unit(math_type), str_value(tex)],
+        Inline::RawInline(ref format, ref content) => vec![
+            Value::con("Format", vec![str_value(&format.0)]),
+            str_value(content),
+        ],
+        Inline::Link(ref attr, ref content, (ref url, ref title))
+        | Inline::Image(ref attr, ref content, (ref url, ref title)) => vec![
+            attr_value(attr),
+            inlines_value(content)?,
+            Value::Tuple(vec![str_value(url), str_value(title)]),
+        ],
+        Inline::Note(ref content) => vec![blocks_value(content)?],
+        Inline::Span(ref attr, ref content) => vec![attr_value(attr), inlines_value(content)?],
+        Inline::Unknown(ref name, _) => return unknown(name),
+    };
+    Ok(Value::con(name, args))
+}
+
+fn to_attr(value: &Value) -> Result<Attr, Error> {
+    match *value.tuple(3)? {
+        [ref id, ref classes, ref attributes] => Ok((
+            id.string()?,
+            classes.map_list(Value::string)?,
+            attributes.map_list(|pair| match *pair.tuple(2)? {
+                [ref key, ref value] => Ok((key.string()?, value.string()?)),
+                _ => unreachable!(),
+            })?,
+        )),
+        _ => unreachable!(),
+    }
+}
+
+fn to_inlines(value: &Value) -> Result<Vec<Inline>, Error> {
+    value.map_list(to_inline)
+}
+
+fn to_blocks(value: &Value) -> Result<Vec<Block>, Error> {
+    value.map_list(to_block)
+}
+
+fn to_items(value: &Value) -> Result<Vec<Vec<Block>>, Error> {
+    value.map_list(to_blocks)
+}
+
+fn to_format(value: &Value) -> Result<Format, Error> {
+    Ok(Format(value.arg("Format")?.string()?))
+}
+
+fn to_target(value: &Value) -> Result<Target, Error> {
+    match *value.tuple(2)? {
+        [ref url, ref title] => Ok((url.string()?, title.string()?)),
+        _ => unreachable!(),
+    }
+}
+
+fn to_caption(value: &Value) -> Result<Caption, Error> {
+    match *value.args("Caption")? {
+        [ref short, ref blocks] => {
+            let short = match *short {
+                Value::Con(ref name, _) if name == "Nothing" => None,
+                _ => Some(to_inlines(short.arg("Just")?)?),
+            };
+            Ok((short, to_blocks(blocks)?))
+        }
+        _ => error("`Caption` takes two arguments"),
+    }
+}
+
+fn to_rows(value: &Value) -> Result<Vec<Row>, Error> {
+    value.map_list(|row| match *row.args("Row")? {
+        [ref attr, ref cells] => Ok((
+            to_attr(attr)?,
+            cells.map_list(|cell| match *cell.args("Cell")? {
+                [ref attr, ref alignment, ref row_span, ref col_span, ref content] => Ok((
+                    to_attr(attr)?,
+                    alignment.unit()?,
+                    row_span.arg("RowSpan")?.int()?,
+                    col_span.arg("ColSpan")?.int()?,
+                    to_blocks(content)?,
+                )),
+                _ => error("`Cell` takes five arguments"),
+            })?,
+        )),
+        _ => error("`Row` takes two arguments"),
+    })
+}
+
+fn to_head_or_foot(value: &Value, name: &str) -> Result<TableHead, Error> {
+    match *value.args(name)? {
+        [ref attr, ref rows] => Ok((to_attr(attr)?, to_rows(rows)?)),
+        _ => error(format!("`{}` takes two arguments", name)),
+    }
+}
+
+fn to_meta_value(value: &Value) -> Result<MetaValue, Error> {
+    let (name, args) = match *value {
+        Value::Con(ref name, ref args) => (name, args),
+        _ => return value.unexpected("a meta value"),
+    };
+    Ok(match (&name[..], &args[..]) {
+        ("MetaMap", [ref map]) => MetaValue::MetaMap(
+            to_meta(map)?
+                .into_iter()
+                .map(|(key, value)| (key, Box::new(value)))
+                .collect(),
+        ),
+        ("MetaList", [ref list]) => MetaValue::MetaList(list.map_list(to_meta_value)?),
+        ("MetaBool", [Value::Con(ref b, ref args)]) if args.is_empty() => match &b[..] {
+            "True" => MetaValue::MetaBool(true),
+            "False" => MetaValue::MetaBool(false),
+            _ => return error(format!("expected a boolean, found `{}`", b)),
+        },
+        ("MetaString", [ref s]) => MetaValue::MetaString(s.string()?),
+        ("MetaInlines", [ref inlines]) => MetaValue::MetaInlines(to_inlines(inlines)?),
+        ("MetaBlocks", [ref blocks]) => MetaValue::MetaBlocks(to_blocks(blocks)?),
+        _ => return value.unexpected("a meta value"),
+    })
+}
+
+/// the `fromList [...]` of a `Meta` or `MetaMap`
+fn to_meta(value: &Value) -> Result<Meta, Error> {
+    value
+        .arg("fromList")?
+        .map_list(|pair| match *pair.tuple(2)? {
+            [ref key, ref value] => Ok((key.string()?, to_meta_value(value)?)),
+            _ => unreachable!(),
+        })
+        .map(|pairs| pairs.into_iter().collect())
+}
+
+fn to_block(value: &Value) -> Result<Block, Error> {
+    let (name, args) = match *value {
+        Value::Con(ref name, ref args) => (name, args),
+        _ => return value.unexpected("a block"),
+    };
+    Ok(match (&name[..], &args[..]) {
+        ("Plain", [ref content]) => Block::Plain(to_inlines(content)?),
+        ("Para", [ref content]) => Block::Para(to_inlines(content)?),
+        ("LineBlock", [ref lines]) => Block::LineBlock(lines.map_list(to_inlines)?),
+        ("CodeBlock", [ref attr, ref code]) => Block::CodeBlock(to_attr(attr)?, code.string()?),
+        ("RawBlock", [ref format, ref content]) => {
+            Block::RawBlock(to_format(format)?, content.string()?)
+        }
+        ("BlockQuote", [ref content]) => Block::BlockQuote(to_blocks(content)?),
+        ("OrderedList", [ref list_attributes, ref items]) => {
+            let list_attributes = match *list_attributes.tuple(3)? {
+                [ref start, ref style, ref delim] => ListAttributes {
+                    start: start.int()?,
+                    style: style.unit()?,
+                    delim: delim.unit()?,
+                },
+                _ => unreachable!(),
+            };
+            Block::OrderedList(list_attributes, to_items(items)?)
+        }
+        ("BulletList", [ref items]) => Block::BulletList(to_items(items)?),
+        ("DefinitionList", [ref items]) => {
+            Block::DefinitionList(items.map_list(|item| match *item.tuple(2)? {
+                [ref term, ref definitions] => Ok((to_inlines(term)?, to_items(definitions)?)),
+                _ => unreachable!(),
+            })?)
+        }
+        ("Header", [ref level, ref attr, ref content]) => {
+            Block::Header(level.int()?, to_attr(attr)?, to_inlines(content)?)
+        }
+        ("HorizontalRule", []) => Block::HorizontalRule,
+        ("Table", [ref attr, ref caption, ref specs, ref head, ref bodies, ref foot]) => {
+            Block::Table(
+                to_attr(attr)?,
+                to_caption(caption)?,
+                specs.map_list(|spec| match *spec.tuple(2)? {
+                    [ref alignment, ref width] => {
+                        let width = match *width {
+                            Value::Con(ref name, ref args)
+                                if name == "ColWidth" && args.len() == 1 =>
+                            {
+                                ColWidth::ColWidth(args[0].double()?)
+                            }
+                            Value::Con(ref name, ref args)
+                                if name == "ColWidthDefault" && args.is_empty() =>
+                            {
+                                ColWidth::ColWidthDefault
+                            }
+                            _ => return width.unexpected("a column width"),
+                        };
+                        Ok((alignment.unit()?, width))
+                    }
+                    _ => unreachable!(),
+                })?,
+                to_head_or_foot(head, "TableHead")?,
+                bodies.map_list(|body| match *body.args("TableBody")? {
+                    [ref attr, ref head_columns, ref head, ref rows] => Ok((
+                        to_attr(attr)?,
+                        head_columns.arg("RowHeadColumns")?.int()?,
+                        to_rows(head)?,
+                        to_rows(rows)?,
+                    )),
+                    _ => error("`TableBody` takes four arguments"),
+                })?,
+                to_head_or_foot(foot, "TableFoot")?,
+            )
+        }
+        ("Figure", [ref attr, ref caption, ref content]) => {
+            Block::Figure(to_attr(attr)?, to_caption(caption)?, to_blocks(content)?)
+        }
+        ("Div", [ref attr, ref content]) => Block::Div(to_attr(attr)?, to_blocks(content)?),
+        ("Null", []) => Block::Null,
+        _ => return value.unexpected("a block"),
+    })
+}
+
+fn to_inline(value: &Value) -> Result<Inline, Error> {
+    let (name, args) = match *value {
+        Value::Con(ref name, ref args) => (name, args),
+        _ => return value.unexpected("an inline"),
+    };
+    Ok(match (&name[..], &args[..]) {
+        ("Str", [ref s]) => Inline::Str(s.string()?),
+        ("Emph", [ref content]) => Inline::Emph(to_inlines(content)?),
+        ("Underline", [ref content]) => Inline::Underline(to_inlines(content)?),
+        ("Strong", [ref content]) => Inline::Strong(to_inlines(content)?),
+        ("Strikeout", [ref content]) => Inline::Strikeout(to_inlines(content)?),
+        ("Superscript", [ref content]) => Inline::Superscript(to_inlines(content)?),
+        ("Subscript", [ref content]) => Inline::Subscript(to_inlines(content)?),
+        ("SmallCaps", [ref content]) => Inline::SmallCaps(to_inlines(content)?),
+        ("Quoted", [ref quote_type, ref content]) => {
+            Inline::Quoted(quote_type.unit()?, to_inlines(content)?)
+        }
+        ("Cite", [ref citations, ref content]) => Inline::Cite(
+            citations.map_list(|citation| {
+                let field = |name| citation.field("Citation", name);
+                Ok(Citation {
+                    citationId: field("citationId")?.string()?,
+                    citationPrefix: to_inlines(field("citationPrefix")?)?,
+                    citationSuffix: to_inlines(field("citationSuffix")?)?,
+                    citationMode: field("citationMode")?.unit()?,
+                    citationNoteNum: field("citationNoteNum")?.int()?,
+                    citationHash: field("citationHash")?.int()?,
+                })
+            })?,
+            to_inlines(content)?,
+        ),
+        ("Code", [ref attr, ref code]) => Inline::Code(to_attr(attr)?, code.string()?),
+        ("Space", []) => Inline::Space,
+        ("SoftBreak", []) => Inline::SoftBreak,
+        ("LineBreak", []) => Inline::LineBreak,
+        ("Math", [ref math_type, ref tex]) => Inline::Math(math_type.unit()?, tex.string()?),
+        ("RawInline", [ref format, ref content]) => {
+            Inline::RawInline(to_format(format)?, content.string()?)
+        }
+        ("Link", [ref attr, ref content, ref target]) => {
+            Inline::Link(to_attr(attr)?, to_inlines(content)?, to_target(target)?)
+        }
+        ("Image", [ref attr, ref content, ref target]) => {
+            Inline::Image(to_attr(attr)?, to_inlines(content)?, to_target(target)?)
+        }
+        ("Note", [ref content]) => Inline::Note(to_blocks(content)?),
+        ("Span", [ref attr, ref content]) => Inline::Span(to_attr(attr)?, to_inlines(content)?),
+        _ => return value.unexpected("an inline"),
+    })
+}
+
+/// names of the ascii control characters in Haskell string literals
+const CONTROL_NAMES: [&str; 32] = [
+    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "a", "b", "t", "n", "v", "f", "r", "SO", "SI",
+    "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC", "FS", "GS",
+    "RS", "US",
+];
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            ' '..='~' => out.push(c),
+            _ => {
+                out.push('\\');
+                let next = chars.peek().cloned();
+                let needs_gap = if (c as u32) < 32 {
+                    let name = CONTROL_NAMES[c as usize];
+                    out.push_str(name);
+                    name == "SO" && next == Some('H')
+                } else if c == '\x7f' {
+                    out.push_str("DEL");
+                    false
+                } else {
+                    write!(out, "{}", c as u32).expect("writing to a string failed");
+                    next.is_some_and(|next| next.is_ascii_digit())
+                };
+                if needs_gap {
+                    out.push_str("\\&");
+                }
+            }
+        }
+    }
+    out.push('"');
+}
+
+/// writes `value` as an argument of a constructor if `arg` is set
+fn write_value(out: &mut String, value: &Value, arg: bool) {
+    match *value {
+        Value::Con(ref name, ref args) => {
+            if arg && !args.is_empty() {
+                out.push('(');
+            }
+            out.push_str(name);
+            for a in args {
+                out.push(' ');
+                write_value(out, a, true);
+            }
+            if arg && !args.is_empty() {
+                out.push(')');
+            }
+        }
+        Value::Record(ref name, ref fields) => {
+            out.push_str(name);
+            out.push_str(" {");
+            for (i, (field, value)) in fields.iter().enumerate() {
+                out.push_str(if i == 0 { " " } else { " , " });
+                out.push_str(field);
+                out.push_str(" = ");
+                write_value(out, value, false);
+            }
+            out.push_str(" }");
+        }
+        Value::List(ref values) => write_seq(out, '[', values, ']'),
+        Value::Tuple(ref values) => write_seq(out, '(', values, ')'),
+        Value::Str(ref s) => write_string(out, s),
+        Value::Num(ref n) if arg && n.starts_with('-') => {
+            out.push('(');
+            out.push_str(n);
+            out.push(')');
+        }
+        Value::Num(ref n) => out.push_str(n),
+    }
+}
+
+fn write_seq(out: &mut String, open: char, values: &[Value], close: char) {
+    out.push(open);
+    if values.is_empty() {
+        out.push(close);
+        return;
+    }
+    for (i, value) in values.iter().enumerate() {
+        out.push_str(if i == 0 { " " } else { " , " });
+        write_value(out, value, false);
+    }
+    out.push(' ');
+    out.push(close);
+}
+
+/// writes a block list with one block per line, each line prefixed by `indent`
+fn write_blocks(out: &mut String, blocks: &[Block], indent: &str) -> Result<(), Error> {
+    if blocks.is_empty() {
+        out.push_str(indent);
+        out.push_str("[]\n");
+        return Ok(());
+    }
+    for (i, block) in blocks.iter().enumerate() {
+        out.push_str(indent);
+        out.push_str(if i == 0 { "[ " } else { ", " });
+        write_value(out, &block_value(block)?, false);
+        out.push('\n');
+    }
+    out.push_str(indent);
+    out.push_str("]\n");
+    Ok(())
+}
+
+/// Writes blocks like `pandoc -t native` does
+pub fn blocks_to_string(blocks: &[Block]) -> Result<String, Error> {
+    let mut out = String::new();
+    write_blocks(&mut out, blocks, "")?;
+    Ok(out)
+}
+
+/// Writes a document like `pandoc -s -t native` does
+pub fn to_string(doc: &Pandoc) -> Result<String, Error> {
+    let mut out = String::from("Pandoc\n  ");
+    let meta = Value::con(
+        "fromList",
+        vec![Value::List(
+            doc.meta
+                .iter()
+                .map(|(key, value)| {
+                    Ok(Value::Tuple(vec![str_value(key), meta_value_value(value)?]))
+                })
+                .collect::<Result<_, Error>>()?,
+        )],
+    );
+    write_value(
+        &mut out,
+        &Value::Record("Meta".to_owned(), vec![("unMeta".to_owned(), meta)]),
+        false,
+    );
+    out.push('\n');
+    write_blocks(&mut out, &doc.blocks, "  ")?;
+    Ok(out)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, msg: &str) -> Result<T, Error> {
+        error(format!("{} at offset {}", msg, self.pos))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            self.error(&format!("expected `{}`", c))
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn ident(&mut self) -> &'a str {
+        self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '\'')
+    }
+
+    /// a value, with constructors applied to their arguments
+    fn value(&mut self) -> Result<Value, Error> {
+        match self.atom()? {
+            Value::Con(name, _) => {
+                let mut args = Vec::new();
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_alphanumeric() || "([\"-".contains(c))
+                {
+                    args.push(self.atom()?);
+                }
+                Ok(Value::Con(name, args))
+            }
+            other => Ok(other),
+        }
+    }
+
+    /// a value that doesn't need parentheses to be an argument
+    fn atom(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                Ok(Value::List(self.seq(']')?))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let mut values = self.seq(')')?;
+                if values.len() == 1 {
+                    Ok(values.remove(0))
+                } else {
+                    Ok(Value::Tuple(values))
+                }
+            }
+            Some('"') => {
+                self.pos += 1;
+                self.string().map(Value::Str)
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                self.pos += 1;
+                self.take_while(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+                Ok(Value::Num(self.input[start..self.pos].to_owned()))
+            }
+            Some(c) if c.is_alphabetic() => {
+                let name = self.ident().to_owned();
+                if !self.eat('{') {
+                    return Ok(Value::Con(name, Vec::new()));
+                }
+                let mut fields = Vec::new();
+                while !self.eat('}') {
+                    if !fields.is_empty() {
+                        self.expect(',')?;
+                    }
+                    self.skip_whitespace();
+                    let field = self.ident().to_owned();
+                    if field.is_empty() {
+                        return self.error("expected a field name");
+                    }
+                    self.expect('=')?;
+                    fields.push((field, self.value()?));
+                }
+                Ok(Value::Record(name, fields))
+            }
+            Some(c) => self.error(&format!("unexpected `{}`", c)),
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    /// comma separated values up to `close`
+    fn seq(&mut self, close: char) -> Result<Vec<Value>, Error> {
+        let mut values = Vec::new();
+        while !self.eat(close) {
+            if !values.is_empty() {
+                self.expect(',')?;
+            }
+            values.push(self.value()?);
+        }
+        Ok(values)
+    }
+
+    /// the rest of a string literal after the opening quote
+    fn string(&mut self) -> Result<String, Error> {
+        let mut s = String::new();
+        loop {
+            let c = match self.rest().chars().next() {
+                Some(c) => c,
+                None => return self.error("unterminated string"),
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(s),
+                '\\' => self.escape(&mut s)?,
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self, s: &mut String) -> Result<(), Error> {
+        let rest = self.rest();
+        let (code, len) = if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            let digits = self.take_while(|c| c.is_ascii_digit());
+            (digits.parse().ok(), 0)
+        } else if rest.starts_with('x') || rest.starts_with('o') {
+            self.pos += 1;
+            let radix = if rest.starts_with('x') { 16 } else { 8 };
+            let digits = self.take_while(|c| c.is_digit(radix));
+            (u32::from_str_radix(digits, radix).ok(), 0)
+        } else if rest.starts_with('&') {
+            // the empty escape separating a numeric escape from a digit
+            self.pos += 1;
+            return Ok(());
+        } else if rest.starts_with(|c: char| c.is_whitespace()) {
+            // a string gap, `\` whitespace `\`
+            self.skip_whitespace();
+            return self.expect('\\');
+        } else if let Some(c) = ['"', '\\', '\''].iter().find(|&&c| rest.starts_with(c)) {
+            (Some(*c as u32), 1)
+        } else if rest.starts_with("DEL") {
+            (Some(0x7f), 3)
+        } else {
+            // the longest matching name, so that `\SOH` isn't read as `\SO` `H`
+            match CONTROL_NAMES
+                .iter()
+                .enumerate()
+                .filter(|&(_, name)| rest.starts_with(name))
+                .max_by_key(|&(_, name)| name.len())
+            {
+                Some((code, name)) => (Some(code as u32), name.len()),
+                None => return self.error("invalid escape sequence"),
+            }
+        };
+        self.pos += len;
+        match code.and_then(std::char::from_u32) {
+            Some(c) => {
+                s.push(c);
+                Ok(())
+            }
+            None => self.error("invalid character code"),
+        }
+    }
+}
+
+fn parse(input: &str) -> Result<Value, Error> {
+    let mut parser = Parser { input, pos: 0 };
+    let value = parser.value()?;
+    if parser.peek().is_some() {
+        return parser.error("unexpected trailing input");
+    }
+    Ok(value)
+}
+
+/// Reads a document from the output of `pandoc -t native`, with or without
+/// `-s`. Without it the metadata is empty.
+pub fn from_str(native: &str) -> Result<Pandoc, Error> {
+    let value = parse(native)?;
+    let (meta, blocks) = match value {
+        Value::Con(ref name, ref args) if name == "Pandoc" => match args[..] {
+            [ref meta, ref blocks] => (to_meta(meta.field("Meta", "unMeta")?)?, to_blocks(blocks)?),
+            _ => return error("`Pandoc` takes two arguments"),
+        },
+        ref blocks => (Meta::new(), to_blocks(blocks)?),
+    };
+    Ok(Pandoc {
+        meta,
+        blocks,
+        pandoc_api_version: API_VERSION.to_vec(),
+    })
+}
+
+impl Pandoc {
+    /// see `native::from_str`
+    pub fn from_native(native: &str) -> Result<Self, Error> {
+        from_str(native)
+    }
+
+    /// see `native::to_string`
+    pub fn to_native(&self) -> Result<String, Error> {
+        to_string(self)
+    }
+}
@@ -0,0 +1,475 @@
+use super::*;
+
+/// A reference to either a block or an inline of a document
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Node<'a> {
+    Block(&'a Block),
+    Inline(&'a Inline),
+}
+
+/// A mutable reference to either a block or an inline of a document
+#[derive(Debug, PartialEq)]
+pub enum NodeMut<'a> {
+    Block(&'a mut Block),
+    Inline(&'a mut Inline),
+}
+
+/// An owned block or inline
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::large_enum_variant)]
+pub enum OwnedNode {
+    Block(Block),
+    Inline(Inline),
+}
+
+impl OwnedNode {
+    pub fn as_node(&self) -> Node<'_> {
+        match *self {
+            OwnedNode::Block(ref block) => Node::Block(block),
+            OwnedNode::Inline(ref inline) => Node::Inline(inline),
+        }
+    }
+
+    pub fn as_node_mut(&mut self) -> NodeMut<'_> {
+        match *self {
+            OwnedNode::Block(ref mut block) => NodeMut::Block(block),
+            OwnedNode::Inline(ref mut inline) => NodeMut::Inline(inline),
+        }
+    }
+}
+
+impl<'a> Node<'a> {
+    pub fn to_owned_node(&self) -> OwnedNode {
+        match *self {
+            Node::Block(block) => OwnedNode::Block(block.clone()),
+            Node::Inline(inline) => OwnedNode::Inline(inline.clone()),
+        }
+    }
+}
+
+impl Block {
+    /// the name of the constructor, as used in pandoc's json
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Block::Plain(..) => "Plain",
+            Block::Para(..) => "Para",
+            Block::LineBlock(..) => "LineBlock",
+            Block::CodeBlock(..) => "CodeBlock",
+            Block::RawBlock(..) => "RawBlock",
+            Block::BlockQuote(..) => "BlockQuote",
+            Block::OrderedList(..) => "OrderedList",
+            Block::BulletList(..) => "BulletList",
+            Block::DefinitionList(..) => "DefinitionList",
+            Block::Header(..) => "Header",
+            Block::HorizontalRule => "HorizontalRule",
+            Block::Table(..) => "Table",
+            Block::Figure(..) => "Figure",
+            Block::Div(..) => "Div",
+            Block::Null => "Null",
+            Block::Unknown(..) => "Unknown",
+            Block::Unparsed(..) => "Unparsed",
+        }
+    }
+
+    /// the attributes of blocks that carry them
+    pub fn attr(&self) -> Option<&Attr> {
+        match *self {
+            Block::CodeBlock(ref attr, _)
+            | Block::Header(_, ref attr, _)
+            | Block::Table(ref attr, ..)
+            | Block::Figure(ref attr, ..)
+            | Block::Div(ref attr, _) => Some(attr),
+            _ => None,
+        }
+    }
+}
+
+impl Inline {
+    /// the name of the constructor, as used in pandoc's json
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Inline::Str(..) => "Str",
+            Inline::Emph(..) => "Emph",
+            Inline::Underline(..) => "Underline",
+            Inline::Strong(..) => "Strong",
+            Inline::Strikeout(..) => "Strikeout",
+            Inline::Superscript(..) => "Superscript",
+            Inline::Subscript(..) => "Subscript",
+            Inline::SmallCaps(..) => "SmallCaps",
+            Inline::Quoted(..) => "Quoted",
+            Inline::Cite(..) => "Cite",
+            Inline::Code(..) => "Code",
+            Inline::Space => "Space",
+            Inline::SoftBreak => "SoftBreak",
+            Inline::LineBreak => "LineBreak",
+            Inline::Math(..) => "Math",
+            Inline::RawInline(..) => "RawInline",
+            Inline::Link(..) => "Link",
+            Inline::Image(..) => "Image",
+            Inline::Note(..) => "Note",
+            Inline::Span(..) => "Span",
+            Inline::Unknown(..) => "Unknown",
+        }
+    }
+
+    /// the attributes of inlines that carry them
+    pub fn attr(&self) -> Option<&Attr> {
+        match *self {
+            Inline::Code(ref attr, _)
+            | Inline::Link(ref attr, ..)
+            | Inline::Image(ref attr, ..)
+            | Inline::Span(ref attr, _) => Some(attr),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct Folder<A, F> {
+    pub(crate) acc: Option<A>,
+    pub(crate) f: F,
+}
+
+impl<A, F> Folder<A, F>
+where
+    F: for<'a> FnMut(A, Node<'a>) -> A,
+{
+    fn apply(&mut self, node: Node) {
+        let acc = self.acc.take().expect("accumulator is always present");
+        self.acc = Some((self.f)(acc, node));
+    }
+}
+
+impl<A, F> Visitor for Folder<A, F>
+where
+    F: for<'a> FnMut(A, Node<'a>) -> A,
+{
+    fn visit_block(&mut self, block: &Block) {
+        self.apply(Node::Block(block));
+        self.walk_block(block)
+    }
+    fn visit_inline(&mut self, inline: &Inline) {
+        self.apply(Node::Inline(inline));
+        self.walk_inline(inline)
+    }
+}
+
+/// A list of sibling nodes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ChildList<'a> {
+    Blocks(&'a [Block]),
+    Inlines(&'a [Inline]),
+}
+
+impl<'a> ChildList<'a> {
+    pub(crate) fn len(&self) -> usize {
+        match *self {
+            ChildList::Blocks(blocks) => blocks.len(),
+            ChildList::Inlines(inlines) => inlines.len(),
+        }
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<Node<'a>> {
+        match *self {
+            ChildList::Blocks(blocks) => blocks.get(index).map(Node::Block),
+            ChildList::Inlines(inlines) => inlines.get(index).map(Node::Inline),
+        }
+    }
+}
+
+fn rows<'a>(lists: &mut Vec<ChildList<'a>>, rows: &'a [Row]) {
+    for (_, cells) in rows {
+        for (_, _, _, _, content) in cells {
+            lists.push(ChildList::Blocks(content));
+        }
+    }
+}
+
+/// A mutable list of sibling nodes, in the same order as `Node::child_lists`
+pub(crate) enum ChildListMut<'a> {
+    Blocks(&'a mut Vec<Block>),
+    Inlines(&'a mut Vec<Inline>),
+}
+
+impl<'a> ChildListMut<'a> {
+    pub(crate) fn len(&self) -> usize {
+        match *self {
+            ChildListMut::Blocks(ref blocks) => blocks.len(),
+            ChildListMut::Inlines(ref inlines) => inlines.len(),
+        }
+    }
+
+    pub(crate) fn into_node(self, index: usize) -> Option<NodeMut<'a>> {
+        match self {
+            ChildListMut::Blocks(blocks) => blocks.get_mut(index).map(NodeMut::Block),
+            ChildListMut::Inlines(inlines) => inlines.get_mut(index).map(NodeMut::Inline),
+        }
+    }
+}
+
+fn rows_mut<'a>(lists: &mut Vec<ChildListMut<'a>>, rows: &'a mut [Row]) {
+    for (_, cells) in rows {
+        for (_, _, _, _, content) in cells {
+            lists.push(ChildListMut::Blocks(content));
+        }
+    }
+}
+
+impl<'a> Node<'a> {
+    /// the name of the constructor, as used in pandoc's json
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Node::Block(block) => block.name(),
+            Node::Inline(inline) => inline.name(),
+        }
+    }
+
+    pub fn attr(&self) -> Option<&'a Attr> {
+        match *self {
+            Node::Block(block) => block.attr(),
+            Node::Inline(inline) => inline.attr(),
+        }
+    }
+
+    /// the lists of nodes directly below this node, in document order
+    pub(crate) fn child_lists(&self) -> Vec<ChildList<'a>> {
+        use self::ChildList::{Blocks, Inlines};
+        let mut lists = Vec::new();
+        match *self {
+            Node::Block(block) => match *block {
+                Block::Plain(ref c) | Block::Para(ref c) | Block::Header(_, _, ref c) => {
+                    lists.push(Inlines(c))
+                }
+                Block::LineBlock(ref lines) => lists.extend(lines.iter().map(|c| Inlines(c))),
+                Block::BlockQuote(ref c) | Block::Div(_, ref c) => lists.push(Blocks(c)),
+                Block::OrderedList(_, ref items) | Block::BulletList(ref items) => {
+                    lists.extend(items.iter().map(|c| Blocks(c)))
+                }
+                Block::DefinitionList(ref items) => {
+                    for (term, definitions) in items {
+                        lists.push(Inlines(term));
+                        lists.extend(definitions.iter().map(|c| Blocks(c)));
+                    }
+                }
+                Block::Table(_, ref caption, _, ref head, ref bodies, ref foot) => {
+                    if let Some(ref short) = caption.0 {
+                        lists.push(Inlines(short));
+                    }
+                    lists.push(Blocks(&caption.1));
+                    rows(&mut lists, &head.1);
+                    for body in bodies {
+                        rows(&mut lists, &body.2);
+                        rows(&mut lists, &body.3);
+                    }
+                    rows(&mut lists, &foot.1);
+                }
+                Block::Figure(_, ref caption, ref c) => {
+                    if let Some(ref short) = caption.0 {
+                        lists.push(Inlines(short));
+                    }
+                    lists.push(Blocks(&caption.1));
+                    lists.push(Blocks(c));
+                }
+                Block::CodeBlock(..)
+                | Block::RawBlock(..)
+                | Block::HorizontalRule
+                | Block::Null
+                | Block::Unknown(..)
+                | Block::Unparsed(..) => {}
+            },
+            Node::Inline(inline) => match *inline {
+                Inline::Emph(ref c)
+                | Inline::Underline(ref c)
+                | Inline::Strong(ref c)
+                | Inline::Strikeout(ref c)
+                | Inline::Superscript(ref c)
+                | Inline::Subscript(ref c)
+                | Inline::SmallCaps(ref c)
+                | Inline::Quoted(_, ref c)
+                | Inline::Link(_, ref c, _)
+                | Inline::Image(_, ref c, _)
+                | Inline::Span(_, ref c) => lists.push(Inlines(c)),
+                Inline::Cite(ref citations, ref c) => {
+                    for citation in citations {
+                        lists.push(Inlines(&citation.citationPrefix));
+                        lists.push(Inlines(&citation.citationSuffix));
+                    }
+                    lists.push(Inlines(c));
+                }
+                Inline::Note(ref c) => lists.push(Blocks(c)),
+                Inline::Str(_)
+                | Inline::Code(..)
+                | Inline::Space
+                | Inline::SoftBreak
+                | Inline::LineBreak
+                | Inline::Math(..)
+                | Inline::RawInline(..)
+                | Inline::Unknown(..) => {}
+            },
+        }
+        lists
+    }
+
+    /// the blocks and inlines directly below this node, in document order
+    pub fn children(&self) -> Vec<Node<'a>> {
+        let mut children = Vec::new();
+        for list in self.child_lists() {
+            match list {
+                ChildList::Blocks(blocks) => children.extend(blocks.iter().map(Node::Block)),
+                ChildList::Inlines(inlines) => children.extend(inlines.iter().map(Node::Inline)),
+            }
+        }
+        children
+    }
+}
+
+impl<'a> NodeMut<'a> {
+    /// the lists of nodes directly below this node, see `Node::child_lists`
+    pub(crate) fn child_lists_mut(self) -> Vec<ChildListMut<'a>> {
+        use self::ChildListMut::{Blocks, Inlines};
+        let mut lists = Vec::new();
+        match self {
+            NodeMut::Block(block) => match *block {
+                Block::Plain(ref mut c) | Block::Para(ref mut c) | Block::Header(_, _, ref mut c) => {
+                    lists.push(Inlines(c))
+                }
+                Block::LineBlock(ref mut lines) => lists.extend(lines.iter_mut().map(Inlines)),
+                Block::BlockQuote(ref mut c) | Block::Div(_, ref mut c) => lists.push(Blocks(c)),
+                Block::OrderedList(_, ref mut items) | Block::BulletList(ref mut items) => {
+                    lists.extend(items.iter_mut().map(Blocks))
+                }
+                Block::DefinitionList(ref mut items) => {
+                    for (term, definitions) in items {
+                        lists.push(Inlines(term));
+                        lists.extend(definitions.iter_mut().map(Blocks));
+                    }
+                }
+                Block::Table(_, ref mut caption, _, ref mut head, ref mut bodies, ref mut foot) => {
+                    if let Some(ref mut short) = caption.0 {
+                        lists.push(Inlines(short));
+                    }
+                    lists.push(Blocks(&mut caption.1));
+                    rows_mut(&mut lists, &mut head.1);
+                    for body in bodies {
+                        rows_mut(&mut lists, &mut body.2);
+                        rows_mut(&mut lists, &mut body.3);
+                    }
+                    rows_mut(&mut lists, &mut foot.1);
+                }
+                Block::Figure(_, ref mut caption, ref mut c) => {
+                    if let Some(ref mut short) = caption.0 {
+                        lists.push(Inlines(short));
+                    }
+                    lists.push(Blocks(&mut caption.1));
+                    lists.push(Blocks(c));
+                }
+                Block::CodeBlock(..)
+                | Block::RawBlock(..)
+                | Block::HorizontalRule
+                | Block::Null
+                | Block::Unknown(..)
+                | Block::Unparsed(..) => {}
+            },
+            NodeMut::Inline(inline) => match *inline {
+                Inline::Emph(ref mut c)
+                | Inline::Underline(ref mut c)
+                | Inline::Strong(ref mut c)
+                | Inline::Strikeout(ref mut c)
+                | Inline::Superscript(ref mut c)
+                | Inline::Subscript(ref mut c)
+                | Inline::SmallCaps(ref mut c)
+                | Inline::Quoted(_, ref mut c)
+                | Inline::Link(_, ref mut c, _)
+                | Inline::Image(_, ref mut c, _)
+                | Inline::Span(_, ref mut c) => lists.push(Inlines(c)),
+                Inline::Cite(ref mut citations, ref mut c) => {
+                    for citation in citations {
+                        lists.push(Inlines(&mut citation.citationPrefix));
+                        lists.push(Inlines(&mut citation.citationSuffix));
+                    }
+                    lists.push(Inlines(c));
+                }
+                Inline::Note(ref mut c) => lists.push(Blocks(c)),
+                Inline::Str(_)
+                | Inline::Code(..)
+                | Inline::Space
+                | Inline::SoftBreak
+                | Inline::LineBreak
+                | Inline::Math(..)
+                | Inline::RawInline(..)
+                | Inline::Unknown(..) => {}
+            },
+        }
+        lists
+    }
+}
+
+/// Lazy depth-first iterator over all blocks and inlines below a node,
+/// parents before their children
+#[derive(Debug, Clone)]
+pub struct Descendants<'a> {
+    stack: Vec<Node<'a>>,
+}
+
+impl<'a> Descendants<'a> {
+    pub(crate) fn new(mut roots: Vec<Node<'a>>) -> Self {
+        roots.reverse();
+        Descendants { stack: roots }
+    }
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = Node<'a>;
+    fn next(&mut self) -> Option<Node<'a>> {
+        let node = self.stack.pop()?;
+        let mut children = node.children();
+        children.reverse();
+        self.stack.extend(children);
+        Some(node)
+    }
+}
+
+/// Lazy depth-first iterator over all blocks below a node
+#[derive(Debug, Clone)]
+pub struct Blocks<'a>(pub(crate) Descendants<'a>);
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = &'a Block;
+    fn next(&mut self) -> Option<&'a Block> {
+        self.0.find_map(|node| match node {
+            Node::Block(block) => Some(block),
+            Node::Inline(_) => None,
+        })
+    }
+}
+
+/// Lazy depth-first iterator over all inlines below a node
+#[derive(Debug, Clone)]
+pub struct Inlines<'a>(pub(crate) Descendants<'a>);
+
+impl<'a> Iterator for Inlines<'a> {
+    type Item = &'a Inline;
+    fn next(&mut self) -> Option<&'a Inline> {
+        self.0.find_map(|node| match node {
+            Node::Inline(inline) => Some(inline),
+            Node::Block(_) => None,
+        })
+    }
+}
+
+pub(crate) struct ForEachBlock<F>(pub(crate) F);
+
+impl<F: FnMut(&mut Block)> MutVisitor for ForEachBlock<F> {
+    fn visit_block(&mut self, block: &mut Block) {
+        (self.0)(block);
+        self.walk_block(block)
+    }
+}
+
+pub(crate) struct ForEachInline<F>(pub(crate) F);
+
+impl<F: FnMut(&mut Inline)> MutVisitor for ForEachInline<F> {
+    fn visit_inline(&mut self, inline: &mut Inline) {
+        (self.0)(inline);
+        self.walk_inline(inline)
+    }
+}
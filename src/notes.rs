@@ -0,0 +1,179 @@
+//! Footnote management.
+//!
+//! Pandoc keeps footnotes as `Note` inlines where they are referenced.
+//! `to_endnotes` turns them into a section at the end of the document the
+//! way pandoc's html writer does, `from_endnotes` turns such a section back
+//! into `Note`s.
+
+use super::*;
+use std::collections::HashMap;
+use std::mem;
+use transform::{Action, TraversalOrder};
+
+/// A `Note` inline and where it occurs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Footnote<'a> {
+    /// number of the note as writers assign it, starting at 1
+    pub number: usize,
+    /// index of the top-level block containing the note
+    pub block: usize,
+    pub content: &'a [Block],
+}
+
+/// All `Note`s of the document, in document order
+pub fn collect(doc: &Pandoc) -> Vec<Footnote<'_>> {
+    let mut result = Vec::new();
+    for (index, block) in doc.blocks.iter().enumerate() {
+        for inline in block.inlines() {
+            if let Inline::Note(ref content) = *inline {
+                result.push(Footnote {
+                    number: result.len() + 1,
+                    block: index,
+                    content,
+                });
+            }
+        }
+    }
+    result
+}
+
+fn has_class(attr: &Attr, class: &str) -> bool {
+    attr.1.iter().any(|c| c == class)
+}
+
+/// Replaces every `Note` with a link to an entry in a `Div` with the class
+/// `footnotes` appended to the document. The link has the identifier
+/// `fnref<n>` and class `footnote-ref`, the entry is a `Div` with the
+/// identifier `fn<n>` in an `OrderedList`, ending with a link back with the
+/// class `footnote-back`.
+pub fn to_endnotes(doc: &mut Pandoc) {
+    let mut notes = Vec::new();
+    transform::inlines(
+        &mut doc.blocks,
+        TraversalOrder::TopDown,
+        |inline| match *inline {
+            Inline::Note(ref mut content) => {
+                notes.push(mem::take(content));
+                let number = notes.len();
+                Action::Replace(Inline::Link(
+                    (
+                        format!("fnref{}", number),
+                        vec!["footnote-ref".to_owned()],
+                        Vec::new(),
+                    ),
+                    vec![Inline::Superscript(vec![Inline::Str(number.to_string())])],
+                    (format!("#fn{}", number), String::new()),
+                ))
+            }
+            _ => Action::Keep,
+        },
+    );
+    if notes.is_empty() {
+        return;
+    }
+    let items = notes
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut content)| {
+            let number = i + 1;
+            let backlink = Inline::Link(
+                (String::new(), vec!["footnote-back".to_owned()], Vec::new()),
+                vec![Inline::str("↩︎")],
+                (format!("#fnref{}", number), String::new()),
+            );
+            match content.last_mut() {
+                Some(&mut Block::Para(ref mut inlines))
+                | Some(&mut Block::Plain(ref mut inlines)) => inlines.push(backlink),
+                _ => content.push(Block::Plain(vec![backlink])),
+            }
+            vec![Block::Div(
+                (format!("fn{}", number), Vec::new(), Vec::new()),
+                content,
+            )]
+        })
+        .collect();
+    doc.blocks.push(Block::Div(
+        (
+            "footnotes".to_owned(),
+            vec!["footnotes".to_owned()],
+            Vec::new(),
+        ),
+        vec![
+            Block::HorizontalRule,
+            Block::OrderedList(
+                ListAttributes {
+                    start: 1,
+                    style: ListNumberStyle::Decimal,
+                    delim: ListNumberDelim::Period,
+                },
+                items,
+            ),
+        ],
+    ));
+}
+
+fn remove_backlinks(content: &mut Vec<Block>) {
+    let empty = match content.last_mut() {
+        Some(&mut Block::Para(ref mut inlines)) | Some(&mut Block::Plain(ref mut inlines)) => {
+            inlines.retain(|inline| match *inline {
+                Inline::Link(ref attr, ..) => !has_class(attr, "footnote-back"),
+                _ => true,
+            });
+            while let Some(&Inline::Space) = inlines.last() {
+                inlines.pop();
+            }
+            inlines.is_empty()
+        }
+        _ => false,
+    };
+    if empty {
+        content.pop();
+    }
+}
+
+/// Turns the top-level `Div` with the class `footnotes` back into `Note`s
+/// at the `footnote-ref` links pointing to its entries, the inverse of
+/// `to_endnotes`. Entries that aren't referenced are dropped.
+pub fn from_endnotes(doc: &mut Pandoc) {
+    let position = doc.blocks.iter().position(|block| match *block {
+        Block::Div(ref attr, _) => has_class(attr, "footnotes"),
+        _ => false,
+    });
+    let section = match position {
+        Some(position) => doc.blocks.remove(position),
+        None => return,
+    };
+    let mut notes = HashMap::new();
+    if let Block::Div(_, content) = section {
+        for block in content {
+            if let Block::OrderedList(_, items) = block {
+                for mut item in items {
+                    if let [Block::Div(ref mut attr, ref mut note)] = item[..] {
+                        remove_backlinks(note);
+                        notes.insert(format!("#{}", attr.0), mem::take(note));
+                    }
+                }
+            }
+        }
+    }
+    transform::inlines(
+        &mut doc.blocks,
+        TraversalOrder::TopDown,
+        |inline| match *inline {
+            Inline::Link(ref attr, _, ref target) if has_class(attr, "footnote-ref") => {
+                match notes.remove(&target.0) {
+                    Some(note) => Action::Replace(Inline::Note(note)),
+                    None => Action::Keep,
+                }
+            }
+            _ => Action::Keep,
+        },
+    );
+}
+
+/// Numbers all notes consecutively in the order they are referenced and
+/// drops unreferenced endnotes, leaving them in the `to_endnotes` form
+pub fn renumber(doc: &mut Pandoc) {
+    from_endnotes(doc);
+    to_endnotes(doc);
+}
@@ -0,0 +1,19 @@
+use super::*;
+use rayon::prelude::*;
+use std::mem;
+
+impl Pandoc {
+    /// Applies `f` to every top-level block on rayon's thread pool and puts
+    /// the results back in document order. `f` may return a single block or
+    /// a `Vec<Block>` to replace the block with several or none.
+    pub fn par_map_blocks<F, R>(&mut self, f: F)
+    where
+        F: Fn(Block) -> R + Sync + Send,
+        R: IntoBlocks + Send,
+    {
+        let results: Vec<R> = mem::take(&mut self.blocks).into_par_iter().map(f).collect();
+        for result in results {
+            result.extend_blocks(&mut self.blocks);
+        }
+    }
+}
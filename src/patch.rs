@@ -0,0 +1,98 @@
+//! Replaying edit scripts produced by `diff::diff`.
+
+use super::*;
+use diff::{Edit, EditScript};
+use node::ChildListMut;
+use path::list_mut;
+
+fn conflict<T>(edit: usize, message: String) -> Result<T, Error> {
+    Err(Error::Patch { edit, message })
+}
+
+/// the node at `index` of `list`, if it equals `expected`
+fn check(list: &ChildListMut, index: usize, expected: &OwnedNode) -> bool {
+    match (list, expected) {
+        (ChildListMut::Blocks(blocks), OwnedNode::Block(block)) => blocks.get(index) == Some(block),
+        (ChildListMut::Inlines(inlines), OwnedNode::Inline(inline)) => {
+            inlines.get(index) == Some(inline)
+        }
+        _ => false,
+    }
+}
+
+fn apply_edit(doc: &mut Pandoc, i: usize, edit: &Edit) -> Result<(), Error> {
+    let (path, expected, new) = match *edit {
+        Edit::Meta {
+            ref key,
+            ref old,
+            ref new,
+        } => {
+            if doc.meta.get(key) != old.as_ref() {
+                return conflict(i, format!("metadata field `{}` has changed", key));
+            }
+            match *new {
+                Some(ref value) => doc.meta.insert(key.clone(), value.clone()),
+                None => doc.meta.remove(key),
+            };
+            return Ok(());
+        }
+        Edit::Insert { ref path, ref node } => (path, None, Some(node)),
+        Edit::Remove { ref path, ref node } => (path, Some(node), None),
+        Edit::Replace {
+            ref path,
+            ref old,
+            ref new,
+        } => (path, Some(old), Some(new)),
+    };
+    let (mut list, index) = match list_mut(doc, path) {
+        Some(found) => found,
+        None => return conflict(i, format!("there is no node containing `{}`", path)),
+    };
+    if let Some(expected) = expected {
+        if !check(&list, index, expected) {
+            return conflict(i, format!("the node at `{}` has changed", path));
+        }
+    } else if index > list.len() {
+        return conflict(i, format!("`{}` is out of bounds", path));
+    }
+    match (&mut list, new) {
+        (ChildListMut::Blocks(blocks), Some(OwnedNode::Block(block))) if expected.is_some() => {
+            blocks[index] = block.clone()
+        }
+        (ChildListMut::Blocks(blocks), Some(OwnedNode::Block(block))) => {
+            blocks.insert(index, block.clone())
+        }
+        (ChildListMut::Inlines(inlines), Some(OwnedNode::Inline(inline))) if expected.is_some() => {
+            inlines[index] = inline.clone()
+        }
+        (ChildListMut::Inlines(inlines), Some(OwnedNode::Inline(inline))) => {
+            inlines.insert(index, inline.clone())
+        }
+        (ChildListMut::Blocks(blocks), None) => {
+            blocks.remove(index);
+        }
+        (ChildListMut::Inlines(inlines), None) => {
+            inlines.remove(index);
+        }
+        (ChildListMut::Blocks(_), Some(_)) => {
+            return conflict(i, format!("`{}` holds blocks, not inlines", path))
+        }
+        (ChildListMut::Inlines(_), Some(_)) => {
+            return conflict(i, format!("`{}` holds inlines, not blocks", path))
+        }
+    }
+    Ok(())
+}
+
+/// Applies the edits of `script` in order. Every removed or replaced node
+/// and metadata field must still be in the document as recorded in the script,
+/// otherwise an `Error::Patch` naming the offending edit is returned and the
+/// document is left unchanged.
+pub fn apply(doc: &mut Pandoc, script: &EditScript) -> Result<(), Error> {
+    let mut patched = doc.clone();
+    for (i, edit) in script.edits.iter().enumerate() {
+        apply_edit(&mut patched, i, edit)?;
+    }
+    *doc = patched;
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use super::*;
+use node::{ChildList, ChildListMut};
+use std::fmt;
+use std::mem;
+
+/// One step from a node down to one of its children
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Step {
+    /// which of the node's child lists the child is in. Nodes with several
+    /// lists of children number them in document order, e.g. every item of a
+    /// `BulletList` and every cell of a `Table` is a list of its own.
+    pub list: usize,
+    /// the position of the child within the list
+    pub index: usize,
+}
+
+/// The position of a block or inline within a document, as the steps leading
+/// to it from the document's blocks, which form the only list of the root.
+///
+/// Paths are displayed as the steps separated by `/`, with each step written
+/// as `list:index`, or just `index` for the first list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AstPath {
+    pub steps: Vec<Step>,
+}
+
+impl AstPath {
+    /// the path of the `index`th top-level block
+    pub fn block(index: usize) -> Self {
+        AstPath {
+            steps: vec![Step { list: 0, index }],
+        }
+    }
+
+    /// the path of a child of the node at this path
+    pub fn child(&self, list: usize, index: usize) -> Self {
+        let mut steps = self.steps.clone();
+        steps.push(Step { list, index });
+        AstPath { steps }
+    }
+
+    /// the path of the node containing this one, `None` for top-level blocks
+    pub fn parent(&self) -> Option<Self> {
+        match self.steps.len() {
+            0 | 1 => None,
+            len => Some(AstPath {
+                steps: self.steps[..len - 1].to_vec(),
+            }),
+        }
+    }
+
+    /// the position of the node within its parent
+    pub fn last(&self) -> Option<Step> {
+        self.steps.last().cloned()
+    }
+}
+
+impl fmt::Display for AstPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            if step.list == 0 {
+                write!(f, "{}", step.index)?;
+            } else {
+                write!(f, "{}:{}", step.list, step.index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Pandoc {
+    /// Every block and inline of the document with its path, in document
+    /// order. The metadata is skipped. The paths stay valid as long as no
+    /// nodes are inserted or removed before or above them.
+    pub fn nodes_with_paths(&self) -> Vec<(AstPath, Node<'_>)> {
+        let mut nodes = Vec::new();
+        walk_with_paths(self, |path, node| nodes.push((path.clone(), node)));
+        nodes
+    }
+
+    /// the block or inline at `path`
+    pub fn get(&self, path: &AstPath) -> Option<Node<'_>> {
+        node(self, path)
+    }
+
+    /// the block or inline at `path`
+    pub fn get_mut(&mut self, path: &AstPath) -> Option<NodeMut<'_>> {
+        node_mut(self, path)
+    }
+
+    /// Replaces the node at `path` by `node` and returns the old one. Returns
+    /// `Err(node)` if there is no node at `path` or it is an inline where
+    /// `node` is a block or vice versa.
+    #[allow(clippy::result_large_err)]
+    pub fn replace(&mut self, path: &AstPath, node: OwnedNode) -> Result<OwnedNode, OwnedNode> {
+        let (list, index) = match list_mut(self, path) {
+            Some((list, index)) if index < list.len() => (list, index),
+            _ => return Err(node),
+        };
+        match (list, node) {
+            (ChildListMut::Blocks(blocks), OwnedNode::Block(block)) => {
+                Ok(OwnedNode::Block(mem::replace(&mut blocks[index], block)))
+            }
+            (ChildListMut::Inlines(inlines), OwnedNode::Inline(inline)) => {
+                Ok(OwnedNode::Inline(mem::replace(&mut inlines[index], inline)))
+            }
+            (_, node) => Err(node),
+        }
+    }
+}
+
+/// The list containing the node at `path`, and the node's index in it. The
+/// index may be out of bounds.
+pub(crate) fn list_mut<'a>(
+    doc: &'a mut Pandoc,
+    path: &AstPath,
+) -> Option<(ChildListMut<'a>, usize)> {
+    let (last, parents) = path.steps.split_last()?;
+    let mut lists = vec![ChildListMut::Blocks(&mut doc.blocks)];
+    for step in parents {
+        let node = lists.into_iter().nth(step.list)?.into_node(step.index)?;
+        lists = node.child_lists_mut();
+    }
+    let list = lists.into_iter().nth(last.list)?;
+    Some((list, last.index))
+}
+
+/// The list containing the node at `path`, and the node's index in it. The
+/// index may be out of bounds.
+pub(crate) fn list<'a>(doc: &'a Pandoc, path: &AstPath) -> Option<(ChildList<'a>, usize)> {
+    let (last, parents) = path.steps.split_last()?;
+    let mut lists = vec![ChildList::Blocks(&doc.blocks)];
+    for step in parents {
+        let node = lists.get(step.list)?.get(step.index)?;
+        lists = node.child_lists();
+    }
+    let list = *lists.get(last.list)?;
+    Some((list, last.index))
+}
+
+/// the node at `path`
+pub(crate) fn node<'a>(doc: &'a Pandoc, path: &AstPath) -> Option<Node<'a>> {
+    let (list, index) = list(doc, path)?;
+    list.get(index)
+}
+
+/// the node at `path`
+pub(crate) fn node_mut<'a>(doc: &'a mut Pandoc, path: &AstPath) -> Option<NodeMut<'a>> {
+    let (list, index) = list_mut(doc, path)?;
+    list.into_node(index)
+}
+
+fn walk_list<'a, F: FnMut(&AstPath, Node<'a>)>(
+    list: ChildList<'a>,
+    parent: &AstPath,
+    list_index: usize,
+    f: &mut F,
+) {
+    let nodes: Vec<Node<'a>> = match list {
+        ChildList::Blocks(blocks) => blocks.iter().map(Node::Block).collect(),
+        ChildList::Inlines(inlines) => inlines.iter().map(Node::Inline).collect(),
+    };
+    for (index, node) in nodes.into_iter().enumerate() {
+        let path = parent.child(list_index, index);
+        f(&path, node);
+        for (i, list) in node.child_lists().into_iter().enumerate() {
+            walk_list(list, &path, i, f);
+        }
+    }
+}
+
+/// Calls `f` with every block and inline of the document and its path, in
+/// document order. The metadata is skipped.
+pub(crate) fn walk_with_paths<'a, F: FnMut(&AstPath, Node<'a>)>(doc: &'a Pandoc, mut f: F) {
+    walk_list(
+        ChildList::Blocks(&doc.blocks),
+        &AstPath::default(),
+        0,
+        &mut f,
+    );
+}
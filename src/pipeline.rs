@@ -0,0 +1,94 @@
+//! Running several filters in one process, parsing and serializing the
+//! document only once
+
+use super::*;
+use std::time::{Duration, Instant};
+
+/// How long a stage of the last run of a `FilterPipeline` took
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StageTiming {
+    pub name: String,
+    pub duration: Duration,
+}
+
+type Stage<'a> = Box<dyn FnMut(Pandoc) -> Pandoc + 'a>;
+
+/// A sequence of filter stages applied in order
+///
+/// ```no_run
+/// # use pandoc_ast::pipeline::FilterPipeline;
+/// FilterPipeline::new()
+///     .stage("headers", |mut doc| {
+///         pandoc_ast::transform::shift_headers(&mut doc, 1);
+///         doc
+///     })
+///     .stage("notes", |mut doc| {
+///         pandoc_ast::notes::to_endnotes(&mut doc);
+///         doc
+///     })
+///     .run_filter();
+/// ```
+#[derive(Default)]
+pub struct FilterPipeline<'a> {
+    stages: Vec<(String, Stage<'a>)>,
+    timings: Vec<StageTiming>,
+}
+
+impl<'a> FilterPipeline<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a stage transforming the whole document
+    pub fn stage<F: FnMut(Pandoc) -> Pandoc + 'a>(mut self, name: &str, f: F) -> Self {
+        self.stages.push((name.to_owned(), Box::new(f)));
+        self
+    }
+
+    /// Appends a stage walking the document with `visitor`
+    pub fn visitor<V: MutVisitor + 'a>(self, name: &str, mut visitor: V) -> Self {
+        self.stage(name, move |mut doc| {
+            visitor.walk_pandoc(&mut doc);
+            doc
+        })
+    }
+
+    /// Names of the stages, in order
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|(name, _)| &name[..]).collect()
+    }
+
+    /// Passes `doc` through all stages
+    pub fn run(&mut self, mut doc: Pandoc) -> Pandoc {
+        self.timings.clear();
+        for (name, stage) in &mut self.stages {
+            let start = Instant::now();
+            doc = stage(doc);
+            self.timings.push(StageTiming {
+                name: name.clone(),
+                duration: start.elapsed(),
+            });
+        }
+        doc
+    }
+
+    /// Parses `json`, passes it through all stages and serializes the result
+    pub fn run_json(&mut self, json: &str) -> Result<String, Error> {
+        let doc = self.run(Pandoc::try_from_json(json)?);
+        Ok(serde_json::to_string(&doc)?)
+    }
+
+    /// Runs the pipeline as a filter binary like `run_filter`
+    pub fn run_filter(mut self) {
+        let result = filter_io(io::stdin(), io::stdout(), |doc| self.run(doc));
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+
+    /// Durations of the stages during the last run
+    pub fn timings(&self) -> &[StageTiming] {
+        &self.timings
+    }
+}
@@ -0,0 +1,102 @@
+//! Read-only extraction of common nodes, in document order
+
+use super::*;
+
+/// text and target of every link
+pub fn links<T: Visitable + ?Sized>(node: &T) -> Vec<(&Vec<Inline>, &Target)> {
+    node.inlines()
+        .filter_map(|inline| match *inline {
+            Inline::Link(_, ref text, ref target) => Some((text, target)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// alt text and target of every image
+pub fn images<T: Visitable + ?Sized>(node: &T) -> Vec<(&Vec<Inline>, &Target)> {
+    node.inlines()
+        .filter_map(|inline| match *inline {
+            Inline::Image(_, ref alt, ref target) => Some((alt, target)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// level, attributes and text of every header
+pub fn headers<T: Visitable + ?Sized>(node: &T) -> Vec<(Int, &Attr, &Vec<Inline>)> {
+    node.blocks()
+        .filter_map(|block| match *block {
+            Block::Header(level, ref attr, ref text) => Some((level, attr, text)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// attributes and contents of every code block
+pub fn code_blocks<T: Visitable + ?Sized>(node: &T) -> Vec<(&Attr, &str)> {
+    node.blocks()
+        .filter_map(|block| match *block {
+            Block::CodeBlock(ref attr, ref code) => Some((attr, &code[..])),
+            _ => None,
+        })
+        .collect()
+}
+
+/// content of every raw block in `format`, see `Format::matches`
+pub fn raw_blocks<'a, T: Visitable + ?Sized>(node: &'a T, format: &str) -> Vec<&'a str> {
+    node.blocks()
+        .filter_map(|block| match *block {
+            Block::RawBlock(ref f, ref content) if f.matches(format) => Some(&content[..]),
+            _ => None,
+        })
+        .collect()
+}
+
+/// content of every raw inline in `format`, see `Format::matches`
+pub fn raw_inlines<'a, T: Visitable + ?Sized>(node: &'a T, format: &str) -> Vec<&'a str> {
+    node.inlines()
+        .filter_map(|inline| match *inline {
+            Inline::RawInline(ref f, ref content) if f.matches(format) => Some(&content[..]),
+            _ => None,
+        })
+        .collect()
+}
+
+/// attributes and code of every code block with the class `language`
+pub fn code_blocks_by_language<'a, T: Visitable + ?Sized>(
+    node: &'a T,
+    language: &str,
+) -> Vec<(&'a Attr, &'a str)> {
+    code_blocks(node)
+        .into_iter()
+        .filter(|(attr, _)| attr.1.iter().any(|class| class == language))
+        .collect()
+}
+
+/// every citation with its location: the index of the `Cite` inline it is
+/// part of (counting all `Cite`s in document order) and its index in that `Cite`
+pub fn citations<T: Visitable + ?Sized>(node: &T) -> Vec<(usize, usize, &Citation)> {
+    node.inlines()
+        .filter_map(|inline| match *inline {
+            Inline::Cite(ref citations, _) => Some(citations),
+            _ => None,
+        })
+        .enumerate()
+        .flat_map(|(cite, citations)| {
+            citations
+                .iter()
+                .enumerate()
+                .map(move |(position, citation)| (cite, position, citation))
+        })
+        .collect()
+}
+
+/// type and TeX source of every math inline
+pub fn math<T: Visitable + ?Sized>(node: &T) -> Vec<(MathType, &str)> {
+    node.inlines()
+        .filter_map(|inline| match *inline {
+            Inline::Math(math_type, ref tex) => Some((math_type, &tex[..])),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,221 @@
+//! Traversals that report every problem instead of stopping at the first
+//!
+//! The methods of a `TryVisitor` or `TryMutVisitor` return a `Result`.
+//! `try_walk` and `try_walk_mut` call them for every block and inline in
+//! document order and, depending on the `ErrorMode`, either stop at the first
+//! error or go on and collect all of them, each with the path of its node:
+//!
+//! ```
+//! # use pandoc_ast::*;
+//! # use pandoc_ast::recover::{self, ErrorMode, TryVisitor};
+//! struct NoEmptyLinks;
+//!
+//! impl TryVisitor for NoEmptyLinks {
+//!     type Error = &'static str;
+//!
+//!     fn visit_inline(&mut self, _: &AstPath, inline: &Inline) -> Result<(), Self::Error> {
+//!         match *inline {
+//!             Inline::Link(_, _, ref target) if target.0.is_empty() => Err("link without target"),
+//!             _ => Ok(()),
+//!         }
+//!     }
+//! }
+//!
+//! let link = |url: &str| Inline::Link(Attr::default(), inlines!["a"], (url.into(), "".into()));
+//! let mut doc = Pandoc::new();
+//! doc.blocks = vec![para![link("")], para!["b", link("#b"), link("")]];
+//! let errors = recover::try_walk(&doc, ErrorMode::CollectAll, &mut NoEmptyLinks).unwrap_err();
+//! assert_eq!(errors.len(), 2);
+//! assert_eq!(errors[1].to_string(), "1/2: link without target");
+//! ```
+//!
+//! The errors are turned into `Diagnostic`s for a `Reporter` with
+//! `Diagnostic::from`.
+
+use super::*;
+use node::{ChildList, ChildListMut};
+use std::error;
+use std::fmt;
+
+/// What a traversal does when a visitor method fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorMode {
+    /// stop at the first error
+    FailFast,
+    /// visit all nodes and return every error
+    CollectAll,
+}
+
+/// An error of a visitor method, with the path of the node it was called with
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeError<E> {
+    pub path: AstPath,
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for NodeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.error)
+    }
+}
+
+impl<E: error::Error> error::Error for NodeError<E> {}
+
+/// A `Visitor` whose methods may fail, see `try_walk`. The children of a
+/// node are visited after the node, whether it failed or not.
+pub trait TryVisitor {
+    type Error;
+
+    fn visit_block(&mut self, _path: &AstPath, _block: &Block) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_inline(&mut self, _path: &AstPath, _inline: &Inline) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A `MutVisitor` whose methods may fail, see `try_walk_mut`. The children of
+/// a node are visited after the node, so they include the changes made to it.
+pub trait TryMutVisitor {
+    type Error;
+
+    fn visit_block(&mut self, _path: &AstPath, _block: &mut Block) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_inline(&mut self, _path: &AstPath, _inline: &mut Inline) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// The errors of a traversal so far
+struct Errors<E> {
+    mode: ErrorMode,
+    errors: Vec<NodeError<E>>,
+}
+
+impl<E> Errors<E> {
+    fn new(mode: ErrorMode) -> Self {
+        Errors {
+            mode,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Records the error, if any. Returns whether the traversal should stop.
+    fn push(&mut self, path: &AstPath, result: Result<(), E>) -> bool {
+        if let Err(error) = result {
+            self.errors.push(NodeError {
+                path: path.clone(),
+                error,
+            });
+        }
+        self.mode == ErrorMode::FailFast && !self.errors.is_empty()
+    }
+
+    fn into_result(self) -> Result<(), Vec<NodeError<E>>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Calls the methods of `visitor` with every block and inline of `doc` and
+/// their paths, in document order. The metadata is skipped. With
+/// `ErrorMode::FailFast` the error list has at most one entry.
+pub fn try_walk<V: TryVisitor>(
+    doc: &Pandoc,
+    mode: ErrorMode,
+    visitor: &mut V,
+) -> Result<(), Vec<NodeError<V::Error>>> {
+    let mut errors = Errors::new(mode);
+    walk_list(
+        ChildList::Blocks(&doc.blocks),
+        &AstPath::default(),
+        0,
+        visitor,
+        &mut errors,
+    );
+    errors.into_result()
+}
+
+/// Like `try_walk`, but the visitor may change the nodes. The changes made
+/// before an error are kept.
+pub fn try_walk_mut<V: TryMutVisitor>(
+    doc: &mut Pandoc,
+    mode: ErrorMode,
+    visitor: &mut V,
+) -> Result<(), Vec<NodeError<V::Error>>> {
+    let mut errors = Errors::new(mode);
+    walk_list_mut(
+        ChildListMut::Blocks(&mut doc.blocks),
+        &AstPath::default(),
+        0,
+        visitor,
+        &mut errors,
+    );
+    errors.into_result()
+}
+
+/// Returns whether the traversal should stop
+fn walk_list<V: TryVisitor>(
+    list: ChildList,
+    parent: &AstPath,
+    list_index: usize,
+    visitor: &mut V,
+    errors: &mut Errors<V::Error>,
+) -> bool {
+    let nodes: Vec<Node> = match list {
+        ChildList::Blocks(blocks) => blocks.iter().map(Node::Block).collect(),
+        ChildList::Inlines(inlines) => inlines.iter().map(Node::Inline).collect(),
+    };
+    for (index, node) in nodes.into_iter().enumerate() {
+        let path = parent.child(list_index, index);
+        let result = match node {
+            Node::Block(block) => visitor.visit_block(&path, block),
+            Node::Inline(inline) => visitor.visit_inline(&path, inline),
+        };
+        if errors.push(&path, result) {
+            return true;
+        }
+        for (i, list) in node.child_lists().into_iter().enumerate() {
+            if walk_list(list, &path, i, visitor, errors) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Returns whether the traversal should stop
+fn walk_list_mut<V: TryMutVisitor>(
+    list: ChildListMut,
+    parent: &AstPath,
+    list_index: usize,
+    visitor: &mut V,
+    errors: &mut Errors<V::Error>,
+) -> bool {
+    let nodes: Vec<NodeMut> = match list {
+        ChildListMut::Blocks(blocks) => blocks.iter_mut().map(NodeMut::Block).collect(),
+        ChildListMut::Inlines(inlines) => inlines.iter_mut().map(NodeMut::Inline).collect(),
+    };
+    for (index, mut node) in nodes.into_iter().enumerate() {
+        let path = parent.child(list_index, index);
+        let result = match node {
+            NodeMut::Block(ref mut block) => visitor.visit_block(&path, block),
+            NodeMut::Inline(ref mut inline) => visitor.visit_inline(&path, inline),
+        };
+        if errors.push(&path, result) {
+            return true;
+        }
+        for (i, list) in node.child_lists_mut().into_iter().enumerate() {
+            if walk_list_mut(list, &path, i, visitor, errors) {
+                return true;
+            }
+        }
+    }
+    false
+}
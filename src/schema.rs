@@ -0,0 +1,126 @@
+use super::*;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+fn fails<T: DeserializeOwned>(value: &Value) -> Option<serde_json::Error> {
+    T::deserialize(value).err()
+}
+
+/// where `from_json_validated` found the problem
+struct Location {
+    path: String,
+    constructor: Option<String>,
+    message: String,
+}
+
+/// Whether a `{"t": ...}` object is neither a node nor one of the small
+/// enums like `Alignment`, and what is wrong with it
+fn invalid(value: &Value) -> Option<String> {
+    let block = fails::<Block>(value)?;
+    let inline = fails::<Inline>(value)?;
+    let meta = fails::<MetaValue>(value)?;
+    fails::<Alignment>(value)?;
+    fails::<ListNumberStyle>(value)?;
+    fails::<ListNumberDelim>(value)?;
+    fails::<QuoteType>(value)?;
+    fails::<ColWidth>(value)?;
+    fails::<MathType>(value)?;
+    fails::<CitationMode>(value)?;
+    let messages = [block.to_string(), inline.to_string(), meta.to_string()];
+    Some(
+        messages
+            .iter()
+            .find(|message| !message.starts_with("unknown variant"))
+            .cloned()
+            .unwrap_or_else(|| format!("unknown constructor {}", value["t"])),
+    )
+}
+
+/// the `{"t": ...}` objects below `value` that are not inside other such objects
+fn tagged_children<'a>(value: &'a Value, path: String, children: &mut Vec<(String, &'a Value)>) {
+    match *value {
+        Value::Array(ref values) => {
+            for (i, value) in values.iter().enumerate() {
+                tagged_children(value, format!("{}[{}]", path, i), children);
+            }
+        }
+        Value::Object(ref map) if map.contains_key("t") => children.push((path, value)),
+        Value::Object(ref map) => {
+            for (key, value) in map {
+                tagged_children(value, format!("{}.{}", path, key), children);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// descends to the innermost invalid node below the invalid node `value`
+fn locate(value: &Value, path: String, message: String) -> Location {
+    let mut children = Vec::new();
+    if let Some(content) = value.get("c") {
+        tagged_children(content, format!("{}.c", path), &mut children);
+    }
+    for (path, child) in children {
+        if let Some(message) = invalid(child) {
+            return locate(child, path, message);
+        }
+    }
+    let path = if message.starts_with("unknown") {
+        format!("{}.t", path)
+    } else {
+        path
+    };
+    Location {
+        path,
+        constructor: value.get("t").and_then(Value::as_str).map(str::to_owned),
+        message,
+    }
+}
+
+fn locate_in_document(value: &Value, err: serde_json::Error) -> Location {
+    if let Some(meta) = value.get("meta").and_then(Value::as_object) {
+        for (key, value) in meta {
+            if let Some(err) = fails::<MetaValue>(value) {
+                return locate(value, format!("meta.{}", key), err.to_string());
+            }
+        }
+    }
+    if let Some(blocks) = value.get("blocks").and_then(Value::as_array) {
+        for (i, value) in blocks.iter().enumerate() {
+            if let Some(err) = fails::<Block>(value) {
+                return locate(value, format!("blocks[{}]", i), err.to_string());
+            }
+        }
+    }
+    Location {
+        path: ".".to_owned(),
+        constructor: None,
+        message: err.to_string(),
+    }
+}
+
+impl Pandoc {
+    /// Like `try_from_json`, but errors in the document structure are reported
+    /// as `Error::Schema`, which tells where in the json the problem is, which
+    /// constructor it concerns and whether the document was written for a
+    /// different version of the pandoc types than this crate implements.
+    /// Slower than `try_from_json` for invalid documents, as every node on
+    /// the way to the problem is parsed again.
+    pub fn from_json_validated(json: &str) -> Result<Self, Error> {
+        let value: Value = serde_json::from_str(json)?;
+        let version: Vec<u32> = value
+            .get("pandoc-api-version")
+            .and_then(|version| serde_json::from_value(version.clone()).ok())
+            .unwrap_or_default();
+        check_api_version(&version)?;
+        serde_json::from_value::<Pandoc>(value.clone()).map_err(|err| {
+            let location = locate_in_document(&value, err);
+            Error::Schema {
+                path: location.path,
+                constructor: location.constructor,
+                message: location.message,
+                version,
+            }
+        })
+    }
+}
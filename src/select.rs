@@ -0,0 +1,320 @@
+//! CSS-like selection of nodes.
+//!
+//! A selector consists of compound selectors joined by combinators:
+//!
+//! * `para`, `codeblock`, `*`: the (case-insensitive) constructor name
+//! * `#intro`: the identifier
+//! * `.note`: a class
+//! * `[key]`, `[key=value]`, `[key="value"]`: a key-value attribute
+//! * `a b`: `b` somewhere below `a`
+//! * `a > b`: `b` directly below `a`
+//! * `a, b`: either `a` or `b`
+//!
+//! The parent of a node is the closest block or inline containing it, so the
+//! paragraphs of a bullet list item are children of the `BulletList`.
+
+use super::*;
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A parsed selector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    alternatives: Vec<Vec<(Combinator, Compound)>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Compound {
+    name: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, Option<String>)>,
+}
+
+/// the parts of a node that selectors look at
+struct Info<'a> {
+    name: &'static str,
+    attr: Option<Cow<'a, Attr>>,
+}
+
+impl<'a> Info<'a> {
+    fn new(name: &'static str, attr: Option<&'a Attr>) -> Self {
+        Info {
+            name,
+            attr: attr.map(Cow::Borrowed),
+        }
+    }
+
+    /// A copy that doesn't borrow the node, for the ancestors in `select_mut`
+    fn into_owned(self) -> Info<'static> {
+        Info {
+            name: self.name,
+            attr: self.attr.map(|attr| Cow::Owned(attr.into_owned())),
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || c == ':'
+}
+
+fn ident(chars: &mut Peekable<Chars>) -> Result<String, Error> {
+    let mut ident = String::new();
+    while let Some(&c) = chars.peek() {
+        if !is_ident_char(c) {
+            break;
+        }
+        ident.push(c);
+        chars.next();
+    }
+    if ident.is_empty() {
+        return Err(Error::InvalidSelector("expected a name".to_owned()));
+    }
+    Ok(ident)
+}
+
+fn attribute(chars: &mut Peekable<Chars>) -> Result<(String, Option<String>), Error> {
+    let mut content = String::new();
+    // the quote a value is in, a `]` in there doesn't close the attribute
+    let mut quote = None;
+    loop {
+        match chars.next() {
+            Some(']') if quote.is_none() => break,
+            Some(c) => {
+                match quote {
+                    Some(q) if q == c => quote = None,
+                    None if c == '"' || c == '\'' => quote = Some(c),
+                    _ => {}
+                }
+                content.push(c)
+            }
+            None => return Err(Error::InvalidSelector("unclosed `[`".to_owned())),
+        }
+    }
+    let mut parts = content.splitn(2, '=');
+    let key = parts.next().unwrap_or("").trim().to_owned();
+    if key.is_empty() {
+        return Err(Error::InvalidSelector("empty attribute name".to_owned()));
+    }
+    let value = parts.next().map(|value| {
+        let value = value.trim();
+        let quoted = (value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\''));
+        let unquoted = if value.len() >= 2 && quoted {
+            &value[1..value.len() - 1]
+        } else {
+            value
+        };
+        unquoted.to_owned()
+    });
+    Ok((key, value))
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, Error> {
+        let mut alternatives = Vec::new();
+        let mut parts = Vec::new();
+        let mut current: Option<Compound> = None;
+        let mut combinator = Combinator::Descendant;
+        let mut chars = selector.trim().chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                c if c.is_whitespace() || c == '>' || c == ',' => {
+                    chars.next();
+                    if let Some(compound) = current.take() {
+                        parts.push((combinator, compound));
+                        combinator = Combinator::Descendant;
+                    }
+                    if c == '>' {
+                        if parts.is_empty() || combinator == Combinator::Child {
+                            return Err(Error::InvalidSelector("misplaced `>`".to_owned()));
+                        }
+                        combinator = Combinator::Child;
+                    } else if c == ',' {
+                        if parts.is_empty() || combinator == Combinator::Child {
+                            return Err(Error::InvalidSelector("misplaced `,`".to_owned()));
+                        }
+                        alternatives.push(parts.split_off(0));
+                    }
+                }
+                '#' | '.' | '[' => {
+                    chars.next();
+                    let compound = current.get_or_insert_with(Compound::default);
+                    match c {
+                        '#' => compound.id = Some(ident(&mut chars)?),
+                        '.' => compound.classes.push(ident(&mut chars)?),
+                        _ => compound.attributes.push(attribute(&mut chars)?),
+                    }
+                }
+                '*' if current.is_none() => {
+                    chars.next();
+                    current = Some(Compound::default());
+                }
+                c if is_ident_char(c) && current.is_none() => {
+                    let name = ident(&mut chars)?.to_lowercase();
+                    current = Some(Compound {
+                        name: Some(name),
+                        ..Compound::default()
+                    });
+                }
+                c => return Err(Error::InvalidSelector(format!("unexpected `{}`", c))),
+            }
+        }
+        if let Some(compound) = current {
+            parts.push((combinator, compound));
+        } else if !parts.is_empty() || !alternatives.is_empty() {
+            return Err(Error::InvalidSelector(
+                "selector ends with a combinator".to_owned(),
+            ));
+        }
+        if parts.is_empty() {
+            return Err(Error::InvalidSelector("empty selector".to_owned()));
+        }
+        alternatives.push(parts);
+        Ok(Selector { alternatives })
+    }
+
+    fn matches(&self, node: &Info<'_>, ancestors: &[Info<'_>]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|parts| matches_parts(parts, node, ancestors))
+    }
+}
+
+fn matches_compound(compound: &Compound, node: &Info<'_>) -> bool {
+    if let Some(ref name) = compound.name {
+        if !node.name.eq_ignore_ascii_case(name) {
+            return false;
+        }
+    }
+    if compound.id.is_none() && compound.classes.is_empty() && compound.attributes.is_empty() {
+        return true;
+    }
+    let (ref id, ref classes, ref attributes) = match node.attr {
+        Some(ref attr) => &**attr,
+        None => return false,
+    };
+    compound.id.as_ref().map_or(true, |wanted| wanted == id)
+        && compound.classes.iter().all(|class| classes.contains(class))
+        && compound.attributes.iter().all(|(key, value)| {
+            attributes
+                .iter()
+                .any(|(k, v)| k == key && value.as_ref().map_or(true, |value| value == v))
+        })
+}
+
+fn matches_parts(
+    parts: &[(Combinator, Compound)],
+    node: &Info<'_>,
+    ancestors: &[Info<'_>],
+) -> bool {
+    let (&(combinator, ref compound), rest) = match parts.split_last() {
+        Some(last) => last,
+        None => return true,
+    };
+    if !matches_compound(compound, node) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    match combinator {
+        Combinator::Child => match ancestors.split_last() {
+            Some((parent, above)) => matches_parts(rest, parent, above),
+            None => false,
+        },
+        Combinator::Descendant => (0..ancestors.len())
+            .rev()
+            .any(|i| matches_parts(rest, &ancestors[i], &ancestors[..i])),
+    }
+}
+
+fn select_in<'a>(
+    selector: &Selector,
+    node: Node<'a>,
+    ancestors: &mut Vec<Info<'a>>,
+    found: &mut Vec<Node<'a>>,
+) {
+    let info = Info::new(node.name(), node.attr());
+    if selector.matches(&info, ancestors) {
+        found.push(node);
+    }
+    ancestors.push(info);
+    for child in node.children() {
+        select_in(selector, child, ancestors, found);
+    }
+    ancestors.pop();
+}
+
+/// All nodes below `node` (including its roots) matching `selector`, in document order
+pub fn select<'a, T: Visitable + ?Sized>(node: &'a T, selector: &Selector) -> Vec<Node<'a>> {
+    let mut found = Vec::new();
+    for root in node.roots() {
+        select_in(selector, root, &mut Vec::new(), &mut found);
+    }
+    found
+}
+
+/// Calls `f` on all nodes below `node` (including its roots) matching `selector`,
+/// in document order. Parents are passed before their children, whose
+/// matching sees the parent as modified by `f`.
+pub fn select_mut<T, F>(node: &mut T, selector: &Selector, f: F)
+where
+    T: MutVisitable,
+    F: FnMut(NodeMut),
+{
+    node.accept_mut(&mut SelectMut {
+        selector,
+        f,
+        nested: selector.alternatives.iter().any(|parts| parts.len() > 1),
+        ancestors: Vec::new(),
+    })
+}
+
+struct SelectMut<'s, F> {
+    selector: &'s Selector,
+    f: F,
+    /// whether the selector looks at ancestors, which are only copied then
+    nested: bool,
+    ancestors: Vec<Info<'static>>,
+}
+
+impl<'s, F: FnMut(NodeMut)> MutVisitor for SelectMut<'s, F> {
+    fn visit_block(&mut self, block: &mut Block) {
+        if self
+            .selector
+            .matches(&Info::new(block.name(), block.attr()), &self.ancestors)
+        {
+            (self.f)(NodeMut::Block(block));
+        }
+        if !self.nested {
+            return self.walk_block(block);
+        }
+        let info = Info::new(block.name(), block.attr()).into_owned();
+        self.ancestors.push(info);
+        self.walk_block(block);
+        self.ancestors.pop();
+    }
+    fn visit_inline(&mut self, inline: &mut Inline) {
+        if self
+            .selector
+            .matches(&Info::new(inline.name(), inline.attr()), &self.ancestors)
+        {
+            (self.f)(NodeMut::Inline(inline));
+        }
+        if !self.nested {
+            return self.walk_inline(inline);
+        }
+        let info = Info::new(inline.name(), inline.attr()).into_owned();
+        self.ancestors.push(info);
+        self.walk_inline(inline);
+        self.ancestors.pop();
+    }
+}
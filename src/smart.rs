@@ -0,0 +1,240 @@
+//! Typographic punctuation like pandoc's `smart` extension
+//!
+//! `smartify` turns `---` into an em dash, `--` into an en dash, `...` into
+//! an ellipsis and straight quotes into `Quoted` nodes or curly quotes,
+//! telling opening from closing quotes and apostrophes by the characters
+//! around them. `unsmartify` goes the other way, like pandoc's writers do
+//! without the extension. Only `Str`s are changed, so code, math and raw
+//! content keep their quotes and dashes.
+
+use super::*;
+use std::mem;
+
+/// What `smartify` turns pairs of straight quotes into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quotes {
+    /// `Quoted` nodes, as pandoc's readers produce them
+    Nodes,
+    /// curly quote characters
+    Characters,
+}
+
+/// Replaces straight quotes, dashes and dots in all `Str`s below `node`
+pub fn smartify<T: MutVisitable>(node: &mut T, quotes: Quotes) {
+    node.accept_mut(&mut Smartifier(quotes));
+}
+
+/// Replaces `Quoted` nodes, curly quotes, dashes and ellipses below `node`
+/// by their ascii spelling
+pub fn unsmartify<T: MutVisitable>(node: &mut T) {
+    node.accept_mut(&mut Unsmartifier);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Open,
+    Close,
+}
+
+enum Token {
+    Inline(Inline),
+    Quote(QuoteType, Side),
+}
+
+fn quote_type(c: char) -> QuoteType {
+    if c == '"' {
+        QuoteType::DoubleQuote
+    } else {
+        QuoteType::SingleQuote
+    }
+}
+
+fn curly(kind: QuoteType, side: Side) -> char {
+    match (kind, side) {
+        (QuoteType::DoubleQuote, Side::Open) => '\u{201c}',
+        (QuoteType::DoubleQuote, Side::Close) => '\u{201d}',
+        (QuoteType::SingleQuote, Side::Open) => '\u{2018}',
+        (QuoteType::SingleQuote, Side::Close) => '\u{2019}',
+    }
+}
+
+/// The character next to a quote on one side, `None` for spaces and the
+/// ends of the list. Inlines other than `Str`s count as letters.
+fn edge(inline: Option<&Inline>, first: bool) -> Option<char> {
+    match inline {
+        None | Some(&Inline::Space) | Some(&Inline::SoftBreak) | Some(&Inline::LineBreak) => None,
+        Some(Inline::Str(s)) if first => s.chars().next(),
+        Some(Inline::Str(s)) => s.chars().last(),
+        Some(_) => Some('a'),
+    }
+}
+
+/// Whether a quote is opening, closing or an apostrophe (`None`), or stays
+/// straight because it stands alone
+fn classify(quote: char, before: Option<char>, after: Option<char>) -> Option<Option<Side>> {
+    let opens = before.map_or(true, |c| {
+        c.is_whitespace() || "([{-\u{2013}\u{2014}\"'\u{201c}\u{2018}".contains(c)
+    });
+    let word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    if quote == '\''
+        && (word(before) && word(after) || opens && after.is_some_and(|c| c.is_ascii_digit()))
+    {
+        Some(None)
+    } else if opens && after.is_some_and(|c| !c.is_whitespace()) {
+        Some(Some(Side::Open))
+    } else if !opens {
+        Some(Some(Side::Close))
+    } else {
+        None
+    }
+}
+
+fn dashes(text: &str) -> String {
+    text.replace("---", "\u{2014}")
+        .replace("--", "\u{2013}")
+        .replace("...", "\u{2026}")
+}
+
+fn tokenize(inlines: Vec<Inline>) -> Vec<Token> {
+    let edges: Vec<(Option<char>, Option<char>)> = (0..inlines.len())
+        .map(|i| {
+            let before = if i == 0 { None } else { inlines.get(i - 1) };
+            (edge(before, false), edge(inlines.get(i + 1), true))
+        })
+        .collect();
+    let mut tokens = Vec::with_capacity(inlines.len());
+    for (inline, (before, after)) in inlines.into_iter().zip(edges) {
+        let text = match inline {
+            Inline::Str(text) => dashes(&text),
+            inline => {
+                tokens.push(Token::Inline(inline));
+                continue;
+            }
+        };
+        let chars: Vec<char> = text.chars().collect();
+        let mut word = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            if c != '"' && c != '\'' {
+                word.push(c);
+                continue;
+            }
+            let prev = if i == 0 { before } else { Some(chars[i - 1]) };
+            let next =
+                chars
+                    .get(i + 1)
+                    .cloned()
+                    .or(if i + 1 == chars.len() { after } else { None });
+            match classify(c, prev, next) {
+                Some(Some(side)) => {
+                    if !word.is_empty() {
+                        tokens.push(Token::Inline(Inline::Str(mem::take(&mut word))));
+                    }
+                    tokens.push(Token::Quote(quote_type(c), side));
+                }
+                Some(None) => word.push('\u{2019}'),
+                None => word.push(c),
+            }
+        }
+        if !word.is_empty() {
+            tokens.push(Token::Inline(Inline::Str(word)));
+        }
+    }
+    tokens
+}
+
+/// Pairs up opening and closing quotes of the same kind. Unmatched quotes
+/// become curly characters.
+fn pair(tokens: Vec<Token>) -> Vec<Inline> {
+    let mut frames: Vec<(Option<QuoteType>, Vec<Inline>)> = vec![(None, Vec::new())];
+    for token in tokens {
+        match token {
+            Token::Inline(inline) => frames.last_mut().unwrap().1.push(inline),
+            Token::Quote(kind, Side::Open) => frames.push((Some(kind), Vec::new())),
+            Token::Quote(kind, Side::Close) if frames.last().unwrap().0 == Some(kind) => {
+                let (_, content) = frames.pop().unwrap();
+                frames
+                    .last_mut()
+                    .unwrap()
+                    .1
+                    .push(Inline::Quoted(kind, content));
+            }
+            Token::Quote(kind, Side::Close) => {
+                let close = curly(kind, Side::Close).to_string();
+                frames.last_mut().unwrap().1.push(Inline::Str(close));
+            }
+        }
+    }
+    while frames.len() > 1 {
+        let (kind, content) = frames.pop().unwrap();
+        let parent = &mut frames.last_mut().unwrap().1;
+        if let Some(kind) = kind {
+            parent.push(Inline::Str(curly(kind, Side::Open).to_string()));
+        }
+        parent.extend(content);
+    }
+    frames.pop().unwrap().1
+}
+
+fn merge_strs(inlines: &mut Vec<Inline>) {
+    let mut merged: Vec<Inline> = Vec::with_capacity(inlines.len());
+    for inline in inlines.drain(..) {
+        match (merged.last_mut(), inline) {
+            (Some(&mut Inline::Str(ref mut last)), Inline::Str(s)) => last.push_str(&s),
+            (_, inline) => merged.push(inline),
+        }
+    }
+    *inlines = merged;
+}
+
+struct Smartifier(Quotes);
+
+impl MutVisitor for Smartifier {
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        let tokens = tokenize(mem::take(vec_inline));
+        *vec_inline = match self.0 {
+            Quotes::Nodes => pair(tokens),
+            Quotes::Characters => tokens
+                .into_iter()
+                .map(|token| match token {
+                    Token::Inline(inline) => inline,
+                    Token::Quote(kind, side) => Inline::Str(curly(kind, side).to_string()),
+                })
+                .collect(),
+        };
+        merge_strs(vec_inline);
+        self.walk_vec_inline(vec_inline);
+    }
+}
+
+struct Unsmartifier;
+
+impl MutVisitor for Unsmartifier {
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        self.walk_vec_inline(vec_inline);
+        let mut result = Vec::with_capacity(vec_inline.len());
+        for inline in vec_inline.drain(..) {
+            match inline {
+                Inline::Str(text) => result.push(Inline::Str(
+                    text.replace(['\u{201c}', '\u{201d}'], "\"")
+                        .replace(['\u{2018}', '\u{2019}'], "'")
+                        .replace('\u{2014}', "---")
+                        .replace('\u{2013}', "--")
+                        .replace('\u{2026}', "..."),
+                )),
+                Inline::Quoted(kind, content) => {
+                    let quote = if kind == QuoteType::DoubleQuote {
+                        "\""
+                    } else {
+                        "'"
+                    };
+                    result.push(Inline::Str(quote.to_owned()));
+                    result.extend(content);
+                    result.push(Inline::Str(quote.to_owned()));
+                }
+                inline => result.push(inline),
+            }
+        }
+        merge_strs(&mut result);
+        *vec_inline = result;
+    }
+}
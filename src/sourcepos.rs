@@ -0,0 +1,220 @@
+//! Source positions from pandoc's `sourcepos` extension
+//!
+//! With `pandoc -f commonmark+sourcepos`, every element carries its range in
+//! the input as a `data-pos` attribute like `notes.md@3:1-3:22`. Elements
+//! without attributes of their own are wrapped in a `Div` or `Span` holding
+//! nothing but that attribute. `lift` removes these wrappers and attributes
+//! and returns the positions by the paths of the nodes in the cleaned up
+//! document.
+
+use super::*;
+use node::ChildListMut;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The attribute pandoc stores positions in
+pub const ATTRIBUTE: &str = "data-pos";
+
+/// Where a node starts in the input of pandoc
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SourcePos {
+    /// the name of the input file, `None` for standard input
+    pub file: Option<String>,
+    /// starting at 1
+    pub line: usize,
+    /// starting at 1
+    pub column: usize,
+}
+
+impl SourcePos {
+    /// Parses the value of a `data-pos` attribute, `[file@]line:column-line:column`.
+    /// Only the start of the range is kept.
+    pub fn parse(value: &str) -> Option<SourcePos> {
+        let (file, range) = match value.rfind('@') {
+            Some(at) => (Some(value[..at].to_owned()), &value[at + 1..]),
+            None => (None, value),
+        };
+        let start = range.split('-').next()?;
+        let mut numbers = start.splitn(2, ':');
+        let line = numbers.next()?.parse().ok()?;
+        let column = numbers.next()?.parse().ok()?;
+        Some(SourcePos { file, line, column })
+    }
+}
+
+impl fmt::Display for SourcePos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.file {
+            Some(ref file) => write!(f, "{}:{}:{}", file, self.line, self.column),
+            None => write!(f, "{}:{}", self.line, self.column),
+        }
+    }
+}
+
+/// The positions of the nodes of a document, see `lift`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    positions: BTreeMap<AstPath, SourcePos>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the position of the node at `path`, if it has one
+    pub fn get(&self, path: &AstPath) -> Option<&SourcePos> {
+        self.positions.get(path)
+    }
+
+    /// the position of the node at `path` or, failing that, of the closest
+    /// node containing it
+    pub fn find(&self, path: &AstPath) -> Option<&SourcePos> {
+        let mut path = path.clone();
+        loop {
+            if let Some(pos) = self.positions.get(&path) {
+                return Some(pos);
+            }
+            path = path.parent()?;
+        }
+    }
+
+    pub fn insert(&mut self, path: AstPath, pos: SourcePos) {
+        self.positions.insert(path, pos);
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// all positions, in document order
+    pub fn iter(&self) -> impl Iterator<Item = (&AstPath, &SourcePos)> {
+        self.positions.iter()
+    }
+}
+
+/// Removes the wrappers and `data-pos` attributes added by the `sourcepos`
+/// extension from the blocks of `doc` and returns the positions they held.
+/// The contents of a wrapper take over its position, the contents of
+/// wrappers that aren't valid positions are kept without one.
+pub fn lift(doc: &mut Pandoc) -> SourceMap {
+    let mut map = SourceMap::new();
+    lift_list(
+        ChildListMut::Blocks(&mut doc.blocks),
+        &AstPath::default(),
+        0,
+        &mut map,
+    );
+    map
+}
+
+fn lift_list(list: ChildListMut, parent: &AstPath, list_index: usize, map: &mut SourceMap) {
+    match list {
+        ChildListMut::Blocks(blocks) => lift_vec(blocks, parent, list_index, map),
+        ChildListMut::Inlines(inlines) => lift_vec(inlines, parent, list_index, map),
+    }
+}
+
+fn lift_vec<T: Positioned>(vec: &mut Vec<T>, parent: &AstPath, list: usize, map: &mut SourceMap) {
+    let mut nodes = Vec::with_capacity(vec.len());
+    for node in vec.drain(..) {
+        unwrap(node, None, &mut nodes);
+    }
+    for (index, (mut node, pos)) in nodes.into_iter().enumerate() {
+        let path = parent.child(list, index);
+        if let Some(pos) = take_pos(&mut node).or(pos) {
+            map.insert(path.clone(), pos);
+        }
+        vec.push(node);
+        let lists = vec.last_mut().unwrap().node_mut().child_lists_mut();
+        for (i, list) in lists.into_iter().enumerate() {
+            lift_list(list, &path, i, map);
+        }
+    }
+}
+
+/// Pushes `node`, or the contents of nested wrappers with the innermost
+/// position, to `nodes`
+fn unwrap<T: Positioned>(node: T, pos: Option<SourcePos>, nodes: &mut Vec<(T, Option<SourcePos>)>) {
+    match node.into_wrapped() {
+        Ok((content, value)) => {
+            let pos = SourcePos::parse(&value).or(pos);
+            for node in content {
+                unwrap(node, pos.clone(), nodes);
+            }
+        }
+        Err(node) => nodes.push((node, pos)),
+    }
+}
+
+/// Removes the `data-pos` attribute of the node, returning the position
+fn take_pos<T: Positioned>(node: &mut T) -> Option<SourcePos> {
+    let attributes = &mut node.attr_mut()?.2;
+    let index = attributes.iter().position(|(key, _)| key == ATTRIBUTE)?;
+    SourcePos::parse(&attributes.remove(index).1)
+}
+
+fn is_wrapper(attr: &Attr) -> bool {
+    attr.0.is_empty() && attr.1.is_empty() && attr.2.len() == 1 && attr.2[0].0 == ATTRIBUTE
+}
+
+trait Positioned: Sized {
+    /// the contents and the `data-pos` value of a wrapper, `Err(self)` for
+    /// any other node
+    fn into_wrapped(self) -> Result<(Vec<Self>, String), Self>;
+    fn attr_mut(&mut self) -> Option<&mut Attr>;
+    fn node_mut(&mut self) -> NodeMut<'_>;
+}
+
+impl Positioned for Block {
+    fn into_wrapped(self) -> Result<(Vec<Self>, String), Self> {
+        match self {
+            Block::Div(mut attr, content) if is_wrapper(&attr) => Ok((content, attr.2.remove(0).1)),
+            block => Err(block),
+        }
+    }
+
+    fn attr_mut(&mut self) -> Option<&mut Attr> {
+        match *self {
+            Block::CodeBlock(ref mut attr, _)
+            | Block::Header(_, ref mut attr, _)
+            | Block::Table(ref mut attr, ..)
+            | Block::Figure(ref mut attr, ..)
+            | Block::Div(ref mut attr, _) => Some(attr),
+            _ => None,
+        }
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Block(self)
+    }
+}
+
+impl Positioned for Inline {
+    fn into_wrapped(self) -> Result<(Vec<Self>, String), Self> {
+        match self {
+            Inline::Span(mut attr, content) if is_wrapper(&attr) => {
+                Ok((content, attr.2.remove(0).1))
+            }
+            inline => Err(inline),
+        }
+    }
+
+    fn attr_mut(&mut self) -> Option<&mut Attr> {
+        match *self {
+            Inline::Code(ref mut attr, _)
+            | Inline::Link(ref mut attr, ..)
+            | Inline::Image(ref mut attr, ..)
+            | Inline::Span(ref mut attr, _) => Some(attr),
+            _ => None,
+        }
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Inline(self)
+    }
+}
@@ -0,0 +1,74 @@
+//! Document statistics like word counts, following pandoc's `wordcount.lua`
+
+use super::*;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Statistics about the body of a document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocStats {
+    /// `Str`s containing anything but punctuation, plus the whitespace
+    /// separated words of inline code and code blocks
+    pub words: usize,
+    /// characters of `Str`s and code, not counting whitespace
+    pub characters: usize,
+    /// `characters` plus `Space`s and the whitespace inside code
+    pub characters_with_spaces: usize,
+    /// number of blocks per constructor name, including nested ones
+    pub blocks: BTreeMap<&'static str, usize>,
+    /// level and text of every header
+    pub outline: Vec<(Int, String)>,
+    pub images: usize,
+    pub links: usize,
+    pub notes: usize,
+    pub citations: usize,
+}
+
+impl DocStats {
+    /// The time it takes to read `words` at the given speed, 200 to 250 words
+    /// per minute being typical for prose
+    pub fn reading_time(&self, words_per_minute: u32) -> Duration {
+        if words_per_minute == 0 {
+            return Duration::default();
+        }
+        Duration::from_millis(self.words as u64 * 60_000 / u64::from(words_per_minute))
+    }
+
+    fn count_code(&mut self, code: &str) {
+        self.words += code.split_whitespace().count();
+        self.characters += code.chars().filter(|c| !c.is_whitespace()).count();
+        self.characters_with_spaces += code.chars().count();
+    }
+}
+
+/// Collects statistics about the blocks of `doc`, ignoring its metadata
+pub fn analyze(doc: &Pandoc) -> DocStats {
+    let mut stats = DocStats::default();
+    for block in doc.blocks.blocks() {
+        *stats.blocks.entry(block.name()).or_insert(0) += 1;
+        match *block {
+            Block::CodeBlock(_, ref code) => stats.count_code(code),
+            Block::Header(level, _, ref text) => stats.outline.push((level, stringify(text))),
+            _ => {}
+        }
+    }
+    for inline in doc.blocks.inlines() {
+        match *inline {
+            Inline::Str(ref s) => {
+                if s.chars().any(|c| !c.is_ascii_punctuation()) {
+                    stats.words += 1;
+                }
+                stats.characters += s.chars().count();
+                stats.characters_with_spaces += s.chars().count();
+            }
+            Inline::Space => stats.characters_with_spaces += 1,
+            Inline::Code(_, ref code) => stats.count_code(code),
+            Inline::Image(..) => stats.images += 1,
+            Inline::Link(..) => stats.links += 1,
+            Inline::Note(..) => stats.notes += 1,
+            Inline::Cite(ref citations, _) => stats.citations += citations.len(),
+            _ => {}
+        }
+    }
+    stats
+}
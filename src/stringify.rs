@@ -0,0 +1,53 @@
+use super::*;
+
+/// Extracts the plain text of a node, like pandoc's `Text.Pandoc.Shared.stringify`.
+///
+/// Spaces and line breaks become a single space, formatting is dropped,
+/// quotes are rendered as curly quotes, while footnotes, citations and
+/// raw content are skipped. Calling this on a `Pandoc` includes the text
+/// of the metadata, just like pandoc does.
+pub fn stringify<T: Visitable + ?Sized>(node: &T) -> String {
+    let mut stringifier = Stringifier(String::new());
+    node.accept(&mut stringifier);
+    stringifier.0
+}
+
+/// method form of `stringify`
+pub trait Stringify {
+    fn stringify(&self) -> String;
+}
+
+impl<T: Visitable + ?Sized> Stringify for T {
+    fn stringify(&self) -> String {
+        stringify(self)
+    }
+}
+
+struct Stringifier(String);
+
+impl Visitor for Stringifier {
+    fn visit_inline(&mut self, inline: &Inline) {
+        use Inline::*;
+        match *inline {
+            Str(ref s) | Code(_, ref s) | Math(_, ref s) => self.0.push_str(s),
+            Space | SoftBreak | LineBreak => self.0.push(' '),
+            // see pandoc issue #2105
+            RawInline(ref format, ref s) if *format == "html" && s.starts_with("<br") => {
+                self.0.push(' ')
+            }
+            Quoted(QuoteType::SingleQuote, ref c) => {
+                self.0.push('\u{2018}');
+                self.visit_vec_inline(c);
+                self.0.push('\u{2019}');
+            }
+            Quoted(QuoteType::DoubleQuote, ref c) => {
+                self.0.push('\u{201C}');
+                self.visit_vec_inline(c);
+                self.0.push('\u{201D}');
+            }
+            Cite(_, ref c) => self.visit_vec_inline(c),
+            Note(_) => {}
+            _ => self.walk_inline(inline),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Hierarchical view of the headers of a document.
+//!
+//! Only top-level headers start sections, headers nested inside of
+//! other blocks (e.g. a `Div`) are treated like any other block.
+
+use super::*;
+
+/// A header together with everything up to the next header of the same or a higher level
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Section {
+    pub level: Int,
+    pub attr: Attr,
+    pub title: Vec<Inline>,
+    /// the blocks between the header and the first subsection
+    pub content: Vec<Block>,
+    pub children: Vec<Section>,
+}
+
+impl Section {
+    /// the header followed by the content and all subsections
+    pub fn into_blocks(self) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        self.append_to(&mut blocks);
+        blocks
+    }
+
+    fn append_to(self, blocks: &mut Vec<Block>) {
+        blocks.push(Block::Header(self.level, self.attr, self.title));
+        blocks.extend(self.content);
+        for child in self.children {
+            child.append_to(blocks);
+        }
+    }
+}
+
+/// A list of blocks split into sections
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Sections {
+    /// the blocks before the first header
+    pub preamble: Vec<Block>,
+    pub sections: Vec<Section>,
+}
+
+impl Sections {
+    /// Builds the section tree. Sections whose level jumps more than one
+    /// deeper (e.g. a level 3 header directly below a level 1 header) still
+    /// become children of the closest preceding section with a lower level.
+    pub fn from_blocks(blocks: Vec<Block>) -> Self {
+        let mut result = Sections::default();
+        let mut open: Vec<Section> = Vec::new();
+        for block in blocks {
+            match block {
+                Block::Header(level, attr, title) => {
+                    while open.last().is_some_and(|section| section.level >= level) {
+                        result.close(&mut open);
+                    }
+                    open.push(Section {
+                        level,
+                        attr,
+                        title,
+                        content: Vec::new(),
+                        children: Vec::new(),
+                    });
+                }
+                block => match open.last_mut() {
+                    Some(section) => section.content.push(block),
+                    None => result.preamble.push(block),
+                },
+            }
+        }
+        while !open.is_empty() {
+            result.close(&mut open);
+        }
+        result
+    }
+
+    fn close(&mut self, open: &mut Vec<Section>) {
+        let section = open.pop().expect("only called with open sections");
+        match open.last_mut() {
+            Some(parent) => parent.children.push(section),
+            None => self.sections.push(section),
+        }
+    }
+
+    /// Flattens the tree back into the exact list of blocks it was built from
+    pub fn into_blocks(self) -> Vec<Block> {
+        let mut blocks = self.preamble;
+        for section in self.sections {
+            section.append_to(&mut blocks);
+        }
+        blocks
+    }
+}
+
+/// the top-level blocks making up the section with the given identifier
+fn section_range(blocks: &[Block], id: &str) -> Option<std::ops::Range<usize>> {
+    let id = id.strip_prefix('#').unwrap_or(id);
+    let (start, level) = blocks
+        .iter()
+        .enumerate()
+        .find_map(|(i, block)| match *block {
+            Block::Header(level, (ref header_id, ..), _) if header_id == id => Some((i, level)),
+            _ => None,
+        })?;
+    let end = blocks[start + 1..]
+        .iter()
+        .position(|block| matches!(*block, Block::Header(l, ..) if l <= level))
+        .map_or(blocks.len(), |i| start + 1 + i);
+    Some(start..end)
+}
+
+impl Pandoc {
+    /// The header with the identifier `id` (a leading `#` is ignored) together
+    /// with everything up to the next header of the same or a higher level
+    pub fn extract_section(&self, id: &str) -> Option<Vec<Block>> {
+        section_range(&self.blocks, id).map(|range| self.blocks[range].to_vec())
+    }
+
+    /// Like `extract_section`, but removes the section from the document
+    pub fn remove_section(&mut self, id: &str) -> Option<Vec<Block>> {
+        section_range(&self.blocks, id).map(|range| self.blocks.drain(range).collect())
+    }
+}
@@ -0,0 +1,425 @@
+//! Construction and manipulation of tables without spelling out pandoc's
+//! nested tuples
+
+use super::*;
+use std::io::Write;
+#[cfg(feature = "csv")]
+use std::io::{self, Read};
+
+fn cell(content: Vec<Inline>) -> Cell {
+    let blocks = if content.is_empty() {
+        Vec::new()
+    } else {
+        vec![Block::Plain(content)]
+    };
+    (Attr::default(), Alignment::AlignDefault, 1, 1, blocks)
+}
+
+fn row<I, C>(cells: I) -> Vec<Vec<Inline>>
+where
+    I: IntoIterator<Item = C>,
+    C: IntoInlines,
+{
+    cells
+        .into_iter()
+        .map(|cell| {
+            let mut inlines = Vec::new();
+            cell.extend_inlines(&mut inlines);
+            inlines
+        })
+        .collect()
+}
+
+/// Builds a `Block::Table` with a single header row and a single body.
+/// Cells are anything `inlines!` accepts, rows shorter than the widest row
+/// are padded with empty cells.
+///
+/// ```
+/// # use pandoc_ast::table::TableBuilder;
+/// # use pandoc_ast::Alignment;
+/// let table = TableBuilder::new()
+///     .caption("Planets")
+///     .header(vec!["Name", "Moons"])
+///     .row(vec!["Earth", "1"])
+///     .row(vec!["Mars", "2"])
+///     .alignments(vec![Alignment::AlignLeft, Alignment::AlignRight])
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableBuilder {
+    attr: Attr,
+    caption: Vec<Inline>,
+    header: Option<Vec<Vec<Inline>>>,
+    rows: Vec<Vec<Vec<Inline>>>,
+    alignments: Vec<Alignment>,
+    widths: Vec<Double>,
+}
+
+impl TableBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn attr(mut self, attr: Attr) -> Self {
+        self.attr = attr;
+        self
+    }
+
+    pub fn caption<C: IntoInlines>(mut self, caption: C) -> Self {
+        self.caption.clear();
+        caption.extend_inlines(&mut self.caption);
+        self
+    }
+
+    pub fn header<I: IntoIterator<Item = C>, C: IntoInlines>(mut self, cells: I) -> Self {
+        self.header = Some(row(cells));
+        self
+    }
+
+    pub fn row<I: IntoIterator<Item = C>, C: IntoInlines>(mut self, cells: I) -> Self {
+        self.rows.push(row(cells));
+        self
+    }
+
+    pub fn rows<R, I, C>(self, rows: R) -> Self
+    where
+        R: IntoIterator<Item = I>,
+        I: IntoIterator<Item = C>,
+        C: IntoInlines,
+    {
+        rows.into_iter().fold(self, TableBuilder::row)
+    }
+
+    /// Alignments of the columns, missing ones are `AlignDefault`
+    pub fn alignments(mut self, alignments: Vec<Alignment>) -> Self {
+        self.alignments = alignments;
+        self
+    }
+
+    /// Widths of the columns as fractions of the text width, missing ones
+    /// are `ColWidthDefault`
+    pub fn widths(mut self, widths: Vec<Double>) -> Self {
+        self.widths = widths;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        let columns = self
+            .header
+            .iter()
+            .chain(&self.rows)
+            .map(Vec::len)
+            .chain(Some(self.alignments.len()))
+            .chain(Some(self.widths.len()))
+            .max()
+            .unwrap_or(0);
+        let specs = (0..columns)
+            .map(|i| {
+                let alignment = self
+                    .alignments
+                    .get(i)
+                    .cloned()
+                    .unwrap_or(Alignment::AlignDefault);
+                let width = self
+                    .widths
+                    .get(i)
+                    .map_or(ColWidth::ColWidthDefault, |&w| ColWidth::ColWidth(w));
+                (alignment, width)
+            })
+            .collect();
+        let to_row = |cells: Vec<Vec<Inline>>| -> Row {
+            let mut cells: Vec<Cell> = cells.into_iter().map(cell).collect();
+            cells.resize_with(columns, || cell(Vec::new()));
+            (Attr::default(), cells)
+        };
+        let caption = if self.caption.is_empty() {
+            Vec::new()
+        } else {
+            vec![Block::Plain(self.caption)]
+        };
+        Block::Table(
+            self.attr,
+            (None, caption),
+            specs,
+            (
+                Attr::default(),
+                self.header.into_iter().map(to_row).collect(),
+            ),
+            vec![(
+                Attr::default(),
+                0,
+                Vec::new(),
+                self.rows.into_iter().map(to_row).collect(),
+            )],
+            (Attr::default(), Vec::new()),
+        )
+    }
+}
+
+/// The parts of a `Block::Table`, for manipulating it as a whole.
+///
+/// Column operations index cells within each row and assume that no cell
+/// spans several columns.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Table {
+    pub attr: Attr,
+    pub caption: Caption,
+    pub col_specs: Vec<ColSpec>,
+    pub head: TableHead,
+    pub bodies: Vec<TableBody>,
+    pub foot: TableFoot,
+}
+
+impl Table {
+    /// Takes the table apart, handing other blocks back as the error
+    #[allow(clippy::result_large_err)]
+    pub fn from_block(block: Block) -> Result<Table, Block> {
+        match block {
+            Block::Table(attr, caption, col_specs, head, bodies, foot) => Ok(Table {
+                attr,
+                caption,
+                col_specs,
+                head,
+                bodies,
+                foot,
+            }),
+            other => Err(other),
+        }
+    }
+
+    pub fn into_block(self) -> Block {
+        Block::Table(
+            self.attr,
+            self.caption,
+            self.col_specs,
+            self.head,
+            self.bodies,
+            self.foot,
+        )
+    }
+
+    /// All rows: head, the intermediate heads and rows of every body, foot
+    pub fn rows(&self) -> impl Iterator<Item = &Row> {
+        self.head
+            .1
+            .iter()
+            .chain(
+                self.bodies
+                    .iter()
+                    .flat_map(|body| body.2.iter().chain(body.3.iter())),
+            )
+            .chain(self.foot.1.iter())
+    }
+
+    /// All rows like `rows`, mutably
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut Row> {
+        self.head
+            .1
+            .iter_mut()
+            .chain(
+                self.bodies
+                    .iter_mut()
+                    .flat_map(|body| body.2.iter_mut().chain(body.3.iter_mut())),
+            )
+            .chain(self.foot.1.iter_mut())
+    }
+
+    /// Swaps rows and columns. All bodies and the foot are merged into a
+    /// single body. Header rows become row head columns and vice versa,
+    /// alignments and widths are reset.
+    pub fn transpose(&mut self) {
+        let head_rows = self.head.1.len();
+        let head_columns = self.bodies.first().map_or(0, |body| body.1);
+        let mut grid: Vec<Vec<Cell>> = Vec::new();
+        grid.extend(self.head.1.drain(..).map(|row| row.1));
+        for body in self.bodies.drain(..) {
+            grid.extend(body.2.into_iter().chain(body.3).map(|row| row.1));
+        }
+        grid.extend(self.foot.1.drain(..).map(|row| row.1));
+        let columns = grid.iter().map(Vec::len).max().unwrap_or(0);
+        let mut transposed: Vec<Vec<Cell>> = (0..columns).map(|_| Vec::new()).collect();
+        for row in grid {
+            let len = row.len();
+            for (i, cell) in row.into_iter().enumerate() {
+                transposed[i].push(cell);
+            }
+            for column in &mut transposed[len..] {
+                column.push(cell(Vec::new()));
+            }
+        }
+        self.col_specs = vec![
+            (Alignment::AlignDefault, ColWidth::ColWidthDefault);
+            transposed.first().map_or(0, Vec::len)
+        ];
+        let mut rows = transposed.into_iter().map(|cells| (Attr::default(), cells));
+        let head_count = (head_columns.max(0) as usize).min(columns);
+        self.head.1 = rows.by_ref().take(head_count).collect();
+        self.bodies = vec![(
+            Attr::default(),
+            head_rows as Int,
+            Vec::new(),
+            rows.collect(),
+        )];
+    }
+
+    /// Inserts a column at `index` with `header` in the first header row and
+    /// `cells` in the body rows. Other rows and missing cells are left empty.
+    /// Panics if `index` is larger than the number of columns.
+    pub fn insert_column<H, I, C>(
+        &mut self,
+        index: usize,
+        header: H,
+        cells: I,
+        alignment: Alignment,
+    ) where
+        H: IntoInlines,
+        I: IntoIterator<Item = C>,
+        C: IntoInlines,
+    {
+        self.col_specs
+            .insert(index, (alignment, ColWidth::ColWidthDefault));
+        let mut header = Some(row(Some(header)).remove(0));
+        for head_row in &mut self.head.1 {
+            let content = header.take().unwrap_or_default();
+            let position = index.min(head_row.1.len());
+            head_row.1.insert(position, cell(content));
+        }
+        let mut cells = row(cells).into_iter();
+        for body in &mut self.bodies {
+            for body_row in body.2.iter_mut() {
+                let position = index.min(body_row.1.len());
+                body_row.1.insert(position, cell(Vec::new()));
+            }
+            for body_row in body.3.iter_mut() {
+                let position = index.min(body_row.1.len());
+                body_row
+                    .1
+                    .insert(position, cell(cells.next().unwrap_or_default()));
+            }
+        }
+        for foot_row in &mut self.foot.1 {
+            let position = index.min(foot_row.1.len());
+            foot_row.1.insert(position, cell(Vec::new()));
+        }
+    }
+
+    /// Removes the column at `index` from the column specs and every row
+    /// that has a cell there
+    pub fn remove_column(&mut self, index: usize) {
+        if index < self.col_specs.len() {
+            self.col_specs.remove(index);
+        }
+        for row in self.rows_mut() {
+            if index < row.1.len() {
+                row.1.remove(index);
+            }
+        }
+    }
+
+    /// Sets the alignment of the column at `index`, if there is one
+    pub fn set_alignment(&mut self, index: usize, alignment: Alignment) {
+        if let Some(spec) = self.col_specs.get_mut(index) {
+            spec.0 = alignment;
+        }
+    }
+
+    /// Scales the explicit column widths so they add up to 1, leaving
+    /// `ColWidthDefault` alone
+    pub fn normalize_widths(&mut self) {
+        let total: Double = self
+            .col_specs
+            .iter()
+            .filter_map(|spec| match spec.1 {
+                ColWidth::ColWidth(width) => Some(width),
+                ColWidth::ColWidthDefault => None,
+            })
+            .sum();
+        if total <= 0.0 {
+            return;
+        }
+        for spec in &mut self.col_specs {
+            if let ColWidth::ColWidth(ref mut width) = spec.1 {
+                *width /= total;
+            }
+        }
+    }
+}
+
+/// Options for `Table::to_csv`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CsvOptions {
+    /// field separator, `,` by default, `\t` for TSV
+    pub delimiter: char,
+    /// whether to write the header rows, `true` by default
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            header: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Tab separated values with a header
+    pub fn tsv() -> Self {
+        CsvOptions {
+            delimiter: '\t',
+            ..CsvOptions::default()
+        }
+    }
+}
+
+fn csv_field(text: &str, delimiter: char) -> String {
+    if text.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+impl Table {
+    /// Writes the `stringify`d cells as delimited text, one line per row.
+    /// Fields containing the delimiter, quotes or line breaks are quoted.
+    pub fn to_csv<W: Write>(&self, mut writer: W, options: &CsvOptions) -> Result<(), Error> {
+        let skip = if options.header { 0 } else { self.head.1.len() };
+        let mut delimiter = [0; 4];
+        let delimiter = options.delimiter.encode_utf8(&mut delimiter);
+        for row in self.rows().skip(skip) {
+            let fields: Vec<String> = row
+                .1
+                .iter()
+                .map(|cell| csv_field(&stringify(&cell.4), options.delimiter))
+                .collect();
+            writeln!(writer, "{}", fields.join(delimiter))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Block {
+    /// A table from CSV data, whose first record is the header row
+    #[cfg(feature = "csv")]
+    pub fn table_from_csv<R: Read>(reader: R) -> Result<Block, Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .flexible(true)
+            .from_reader(reader);
+        let header: Vec<String> = reader
+            .headers()
+            .map_err(io::Error::from)?
+            .iter()
+            .map(str::to_owned)
+            .collect();
+        let mut builder = TableBuilder::new().header(header);
+        for record in reader.records() {
+            let record = record.map_err(io::Error::from)?;
+            builder = builder.row(record.iter());
+        }
+        Ok(builder.build())
+    }
+}
@@ -0,0 +1,87 @@
+//! Assertions for testing filters
+//!
+//! Failures panic with the differences between the documents as rendered by
+//! `diff::diff`, one edit per line.
+//!
+//! Golden files hold the expected document as canonical json, see
+//! `Pandoc::to_json_canonical`. Missing golden files are created, and
+//! running the tests with the environment variable `UPDATE_GOLDEN` set
+//! overwrites all of them with the current results.
+
+use super::*;
+use std::fs;
+use std::path::Path;
+
+/// the environment variable that makes `assert_golden` update the files
+pub const UPDATE_GOLDEN: &str = "UPDATE_GOLDEN";
+
+fn differences(expected: &Pandoc, actual: &Pandoc) -> String {
+    let edits = diff::diff(expected, actual);
+    if edits.is_empty() {
+        format!(
+            "pandoc-api-version {:?} -> {:?}\n",
+            expected.pandoc_api_version, actual.pandoc_api_version
+        )
+    } else {
+        edits.to_string()
+    }
+}
+
+/// Parses the json, serializes the document and parses the result again,
+/// asserting that both parsed documents are equal. Returns the document.
+#[track_caller]
+pub fn assert_roundtrip(json: &str) -> Pandoc {
+    let doc = Pandoc::try_from_json(json).unwrap_or_else(|err| panic!("parsing failed: {}", err));
+    let reparsed = Pandoc::try_from_json(&doc.to_json())
+        .unwrap_or_else(|err| panic!("parsing the serialized document failed: {}", err));
+    if reparsed != doc {
+        panic!(
+            "the document changed in a round trip:\n{}",
+            differences(&doc, &reparsed)
+        );
+    }
+    doc
+}
+
+/// Asserts that applying `f` to its own result doesn't change the document
+/// any further. Returns the result of the first application.
+#[track_caller]
+pub fn assert_filter_idempotent<F: Fn(Pandoc) -> Pandoc>(f: F, doc: &Pandoc) -> Pandoc {
+    let once = f(doc.clone());
+    let twice = f(once.clone());
+    if twice != once {
+        panic!(
+            "applying the filter a second time changed the document:\n{}",
+            differences(&once, &twice)
+        );
+    }
+    once
+}
+
+/// Compares the document with the one stored in the golden file at `path`.
+/// The file is written instead if it doesn't exist or `UPDATE_GOLDEN` is set.
+#[track_caller]
+pub fn assert_golden<P: AsRef<Path>>(path: P, doc: &Pandoc) {
+    let path = path.as_ref();
+    let update = std::env::var_os(UPDATE_GOLDEN).is_some();
+    if update || !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .unwrap_or_else(|err| panic!("{}: {}", parent.display(), err));
+        }
+        fs::write(path, doc.to_json_canonical())
+            .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+        return;
+    }
+    let json = fs::read_to_string(path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+    let expected =
+        Pandoc::try_from_json(&json).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+    if expected != *doc {
+        panic!(
+            "the document differs from {}:\n{}rerun with {}=1 to accept the changes",
+            path.display(),
+            differences(&expected, doc),
+            UPDATE_GOLDEN
+        );
+    }
+}
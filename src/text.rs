@@ -0,0 +1,78 @@
+//! Regex search and replace on the text of a document.
+//!
+//! Patterns are matched against runs of adjacent `Str`, `Space` and
+//! `SoftBreak` inlines, so `RFC \d+` finds `Str("RFC"), Space, Str("2119")`.
+//! Spaces are matched as `" "` and soft breaks as `"\n"`. Runs containing a
+//! match are rebuilt with `inlines_from_str`, other runs are left untouched.
+
+use super::*;
+pub use regex::{Captures, Regex};
+
+/// Replaces every match of `pattern` with `replacement`, which may refer to
+/// capture groups like `Regex::replace` (`$1`, `${name}`)
+pub fn replace<T: MutVisitable>(node: &mut T, pattern: &Regex, replacement: &str) {
+    replace_with(node, pattern, |captures| {
+        let mut text = String::new();
+        captures.expand(replacement, &mut text);
+        inlines_from_str(&text)
+    })
+}
+
+/// Replaces every match of `pattern` with the inlines `f` builds from it,
+/// e.g. a `Link` wrapping the matched text
+pub fn replace_with<T, F>(node: &mut T, pattern: &Regex, f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&Captures) -> Vec<Inline>,
+{
+    node.accept_mut(&mut Replacer { pattern, f })
+}
+
+struct Replacer<'r, F> {
+    pattern: &'r Regex,
+    f: F,
+}
+
+fn is_text(inline: &Inline) -> bool {
+    matches!(*inline, Inline::Str(_) | Inline::Space | Inline::SoftBreak)
+}
+
+impl<'r, F: FnMut(&Captures) -> Vec<Inline>> Replacer<'r, F> {
+    fn flush(&mut self, run: &mut Vec<Inline>, result: &mut Vec<Inline>) {
+        if run.is_empty() {
+            return;
+        }
+        let text = inlines_to_string(run);
+        if !self.pattern.is_match(&text) {
+            result.append(run);
+            return;
+        }
+        run.clear();
+        let mut last = 0;
+        for captures in self.pattern.captures_iter(&text) {
+            let found = captures.get(0).expect("group 0 always matches");
+            result.extend(inlines_from_str(&text[last..found.start()]));
+            result.extend((self.f)(&captures));
+            last = found.end();
+        }
+        result.extend(inlines_from_str(&text[last..]));
+    }
+}
+
+impl<'r, F: FnMut(&Captures) -> Vec<Inline>> MutVisitor for Replacer<'r, F> {
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        self.walk_vec_inline(vec_inline);
+        let mut result = Vec::with_capacity(vec_inline.len());
+        let mut run = Vec::new();
+        for inline in vec_inline.drain(..) {
+            if is_text(&inline) {
+                run.push(inline);
+            } else {
+                self.flush(&mut run, &mut result);
+                result.push(inline);
+            }
+        }
+        self.flush(&mut run, &mut result);
+        *vec_inline = result;
+    }
+}
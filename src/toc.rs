@@ -0,0 +1,84 @@
+//! Table of contents generation, mimicking pandoc's `--toc`
+
+use super::*;
+use transform::{Action, TraversalOrder};
+
+struct Entry {
+    level: Int,
+    ident: String,
+    text: Vec<Inline>,
+}
+
+/// Gives all top-level headers without an identifier one generated by
+/// pandoc's algorithm (lowercased text with hyphens instead of spaces).
+pub(crate) fn identify_headers(doc: &mut Pandoc) {
+    let mut used = ident::used_identifiers(&doc.blocks);
+    for block in &mut doc.blocks {
+        if let Block::Header(_, ref mut attr, ref text) = *block {
+            if attr.0.is_empty() {
+                attr.0 = ident::unique_identifier(ident::slugify(text), &mut used);
+            }
+        }
+    }
+}
+
+/// Nested `BulletList` of links to all top-level headers of at most level
+/// `depth`. Headers that lack an identifier are assigned one first, headers
+/// with the class `unlisted` are left out.
+pub fn bullet_list(doc: &mut Pandoc, depth: Int) -> Block {
+    identify_headers(doc);
+    let entries: Vec<Entry> = doc
+        .blocks
+        .iter()
+        .filter_map(|block| match *block {
+            Block::Header(level, (ref ident, ref classes, _), ref text)
+                if level <= depth && !classes.iter().any(|class| class == "unlisted") =>
+            {
+                let mut text = text.clone();
+                transform::inlines(
+                    &mut text,
+                    TraversalOrder::BottomUp,
+                    |inline| match *inline {
+                        Inline::Note(_) => Action::Delete,
+                        Inline::Link(_, ref mut content, _) => {
+                            Action::ReplaceMany(content.split_off(0))
+                        }
+                        _ => Action::Keep,
+                    },
+                );
+                Some(Entry {
+                    level,
+                    ident: ident.clone(),
+                    text,
+                })
+            }
+            _ => None,
+        })
+        .collect();
+    Block::BulletList(items(&entries))
+}
+
+/// The `bullet_list` wrapped in a `Div` with the class `toc`
+pub fn div(doc: &mut Pandoc, depth: Int) -> Block {
+    Block::div("toc", vec![bullet_list(doc, depth)])
+}
+
+fn items(entries: &[Entry]) -> Vec<Vec<Block>> {
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < entries.len() {
+        let entry = &entries[i];
+        let end = entries[i + 1..]
+            .iter()
+            .position(|next| next.level <= entry.level)
+            .map_or(entries.len(), |pos| i + 1 + pos);
+        let link = Inline::link(entry.text.clone(), format!("#{}", entry.ident));
+        let mut item = vec![Block::Plain(vec![link])];
+        if end > i + 1 {
+            item.push(Block::BulletList(items(&entries[i + 1..end])));
+        }
+        result.push(item);
+        i = end;
+    }
+    result
+}
@@ -0,0 +1,818 @@
+//! Common transformations of documents
+
+use super::*;
+use context::Context;
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
+#[cfg(feature = "html")]
+use std::sync::OnceLock;
+use std::{fs, io};
+
+/// What to do with a node after a transformation callback has seen it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action<T> {
+    /// keep the (possibly modified in place) node
+    Keep,
+    Replace(T),
+    /// splice several siblings into the parent list in place of the node
+    ReplaceMany(Vec<T>),
+    /// remove the node from the parent list
+    Delete,
+}
+
+/// Order in which transformation callbacks see the nodes of a tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TraversalOrder {
+    /// Parents are passed to the callback before their children. The
+    /// children of a node are visited *after* the callback returned, so
+    /// the children of nodes inserted via `Replace`/`ReplaceMany` are
+    /// visited, while the inserted nodes themselves are not.
+    TopDown,
+    /// Children are passed to the callback before their parents, like
+    /// pandoc's `walk`. Nodes inserted via `Replace`/`ReplaceMany` are
+    /// never visited.
+    BottomUp,
+}
+
+/// Applies `f` to every inline below `node` and splices the results into
+/// the parent lists
+pub fn inlines<T, F>(node: &mut T, order: TraversalOrder, f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&mut Inline) -> Action<Inline>,
+{
+    node.accept_mut(&mut InlineSplicer {
+        f,
+        order,
+        context: None,
+    });
+}
+
+/// `inlines`, polling `context` while walking the tree. Returns
+/// `Error::Cancelled` if the context was cancelled, in which case only
+/// part of the tree has been transformed.
+pub fn inlines_with_context<T, F>(
+    node: &mut T,
+    order: TraversalOrder,
+    context: &mut Context,
+    f: F,
+) -> Result<(), Error>
+where
+    T: MutVisitable,
+    F: FnMut(&mut Inline) -> Action<Inline>,
+{
+    node.accept_mut(&mut InlineSplicer {
+        f,
+        order,
+        context: Some(&mut *context),
+    });
+    context.check_cancelled()
+}
+
+/// Applies `f` to every block below `node` and splices the results into
+/// the parent lists
+pub fn blocks<T, F>(node: &mut T, order: TraversalOrder, f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&mut Block) -> Action<Block>,
+{
+    node.accept_mut(&mut BlockSplicer {
+        f,
+        order,
+        context: None,
+    });
+}
+
+/// `blocks`, polling `context` while walking the tree, see
+/// `inlines_with_context`
+pub fn blocks_with_context<T, F>(
+    node: &mut T,
+    order: TraversalOrder,
+    context: &mut Context,
+    f: F,
+) -> Result<(), Error>
+where
+    T: MutVisitable,
+    F: FnMut(&mut Block) -> Action<Block>,
+{
+    node.accept_mut(&mut BlockSplicer {
+        f,
+        order,
+        context: Some(&mut *context),
+    });
+    context.check_cancelled()
+}
+
+/// Replaces every item of `vec` by the result of `f`. Once `context` has
+/// been cancelled the remaining items are kept without calling `f`.
+fn splice<T, F: FnMut(&mut T) -> Action<T>>(
+    vec: &mut Vec<T>,
+    context: Option<&Context>,
+    f: &mut F,
+) {
+    let old = mem::replace(vec, Vec::with_capacity(vec.len()));
+    for mut item in old {
+        if context.is_some_and(|context| context.check_cancelled().is_err()) {
+            vec.push(item);
+            continue;
+        }
+        match f(&mut item) {
+            Action::Keep => vec.push(item),
+            Action::Replace(new) => vec.push(new),
+            Action::ReplaceMany(new) => vec.extend(new),
+            Action::Delete => {}
+        }
+    }
+}
+
+struct InlineSplicer<'c, F> {
+    f: F,
+    order: TraversalOrder,
+    context: Option<&'c mut Context>,
+}
+
+impl<'c, F: FnMut(&mut Inline) -> Action<Inline>> MutVisitor for InlineSplicer<'c, F> {
+    fn context(&mut self) -> Option<&mut Context> {
+        self.context.as_deref_mut()
+    }
+
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        match self.order {
+            TraversalOrder::TopDown => {
+                splice(vec_inline, self.context.as_deref(), &mut self.f);
+                self.walk_vec_inline(vec_inline);
+            }
+            TraversalOrder::BottomUp => {
+                self.walk_vec_inline(vec_inline);
+                splice(vec_inline, self.context.as_deref(), &mut self.f);
+            }
+        }
+    }
+}
+
+struct BlockSplicer<'c, F> {
+    f: F,
+    order: TraversalOrder,
+    context: Option<&'c mut Context>,
+}
+
+impl<'c, F: FnMut(&mut Block) -> Action<Block>> MutVisitor for BlockSplicer<'c, F> {
+    fn context(&mut self) -> Option<&mut Context> {
+        self.context.as_deref_mut()
+    }
+
+    fn visit_vec_block(&mut self, vec_block: &mut Vec<Block>) {
+        match self.order {
+            TraversalOrder::TopDown => {
+                splice(vec_block, self.context.as_deref(), &mut self.f);
+                self.walk_vec_block(vec_block);
+            }
+            TraversalOrder::BottomUp => {
+                self.walk_vec_block(vec_block);
+                splice(vec_block, self.context.as_deref(), &mut self.f);
+            }
+        }
+    }
+}
+
+/// Calls `f` with the attributes and content of every `Div` with the class
+/// `class`, innermost first, and splices the result into the parent list
+pub fn on_div_class<T, F>(node: &mut T, class: &str, mut f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&mut Attr, &mut Vec<Block>) -> Action<Block>,
+{
+    blocks(node, TraversalOrder::BottomUp, |block| match *block {
+        Block::Div(ref mut attr, ref mut content) if attr.1.iter().any(|c| c == class) => {
+            f(attr, content)
+        }
+        _ => Action::Keep,
+    })
+}
+
+/// Calls `f` with the attributes and content of every `Span` with the class
+/// `class`, innermost first, and splices the result into the parent list
+pub fn on_span_class<T, F>(node: &mut T, class: &str, mut f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&mut Attr, &mut Vec<Inline>) -> Action<Inline>,
+{
+    inlines(node, TraversalOrder::BottomUp, |inline| match *inline {
+        Inline::Span(ref mut attr, ref mut content) if attr.1.iter().any(|c| c == class) => {
+            f(attr, content)
+        }
+        _ => Action::Keep,
+    })
+}
+
+/// Replaces every `Div` with the class `class` by its content
+pub fn unwrap_div<T: MutVisitable>(node: &mut T, class: &str) {
+    on_div_class(node, class, |_, content| {
+        Action::ReplaceMany(mem::take(content))
+    })
+}
+
+/// Moves the blocks in `range` into a new `Div` with the attributes `attr`
+/// at their position. Panics if `range` is out of bounds.
+pub fn wrap_blocks_in_div(blocks: &mut Vec<Block>, range: Range<usize>, attr: Attr) {
+    let start = range.start;
+    let content = blocks.drain(range).collect();
+    blocks.insert(start, Block::Div(attr, content));
+}
+
+/// Removes every `RawBlock` and `RawInline` whose format matches none of
+/// `keep`, see `Format::matches`
+pub fn strip_raw_except<T: MutVisitable>(node: &mut T, keep: &[&str]) {
+    let kept = |format: &Format| keep.iter().any(|name| format.matches(name));
+    blocks(node, TraversalOrder::TopDown, |block| match *block {
+        Block::RawBlock(ref format, _) if !kept(format) => Action::Delete,
+        _ => Action::Keep,
+    });
+    inlines(node, TraversalOrder::TopDown, |inline| match *inline {
+        Inline::RawInline(ref format, _) if !kept(format) => Action::Delete,
+        _ => Action::Keep,
+    });
+}
+
+/// Removes the conditions from `attr` and returns whether `f` holds for all
+/// of them, `None` if there are none
+fn take_conditions<F: FnMut(&str) -> bool>(attr: &mut Attr, f: &mut F) -> Option<bool> {
+    let mut conditions = Vec::new();
+    attr.1.retain(|class| match class.strip_prefix("if-") {
+        Some(condition) => {
+            conditions.push(condition.to_owned());
+            false
+        }
+        None => true,
+    });
+    attr.2.retain(|(key, value)| {
+        if key == "when" {
+            conditions.push(value.clone());
+        }
+        key != "when"
+    });
+    if conditions.is_empty() {
+        None
+    } else {
+        Some(conditions.iter().all(|condition| f(condition)))
+    }
+}
+
+/// What to do with a `Div` or `Span` depending on its conditions, which are
+/// removed from `attr`
+fn conditional_action<T, F>(attr: &mut Attr, content: &mut Vec<T>, f: &mut F) -> Action<T>
+where
+    F: FnMut(&str) -> bool,
+{
+    match take_conditions(attr, f) {
+        None => Action::Keep,
+        Some(false) => Action::Delete,
+        Some(true) if *attr == Attr::default() => Action::ReplaceMany(mem::take(content)),
+        Some(true) => Action::Keep,
+    }
+}
+
+/// Keeps or removes the `Div`s and `Span`s with conditions, which are
+/// classes like `if-html` (condition `html`) and `when` attributes like
+/// `when="draft"` (condition `draft`). Nodes are kept if `f` holds for all
+/// their conditions. Kept nodes lose their conditions and are replaced by
+/// their content if nothing else is left of their attributes.
+pub fn filter_conditional<T, F>(node: &mut T, mut f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&str) -> bool,
+{
+    blocks(node, TraversalOrder::BottomUp, |block| match *block {
+        Block::Div(ref mut attr, ref mut content) => conditional_action(attr, content, &mut f),
+        _ => Action::Keep,
+    });
+    inlines(node, TraversalOrder::BottomUp, |inline| match *inline {
+        Inline::Span(ref mut attr, ref mut content) => conditional_action(attr, content, &mut f),
+        _ => Action::Keep,
+    });
+}
+
+/// What `map_math` replaces a `Math` inline with
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MathReplacement {
+    /// leave the inline unchanged
+    Keep,
+    /// keep the math type, but with different TeX
+    Tex(String),
+    /// raw content like MathML or rendered html
+    Raw(Format, String),
+    /// an image at the given url, e.g. a rendered svg, with the TeX as alt
+    /// text and the classes `math` and `display` or `inline`
+    Image(String),
+}
+
+/// Calls `f` with the type and TeX of every `Math` inline and replaces the
+/// inline according to the result
+pub fn map_math<T, F>(node: &mut T, mut f: F)
+where
+    T: MutVisitable,
+    F: FnMut(MathType, &str) -> MathReplacement,
+{
+    node.for_each_inline_mut(|inline| {
+        let replacement = match *inline {
+            Inline::Math(math_type, ref mut tex) => match f(math_type, tex) {
+                MathReplacement::Keep => return,
+                MathReplacement::Tex(new) => {
+                    *tex = new;
+                    return;
+                }
+                MathReplacement::Raw(format, content) => Inline::RawInline(format, content),
+                MathReplacement::Image(url) => {
+                    let class = match math_type {
+                        MathType::DisplayMath => "display",
+                        MathType::InlineMath => "inline",
+                    };
+                    Inline::Image(
+                        (
+                            String::new(),
+                            vec!["math".to_owned(), class.to_owned()],
+                            Vec::new(),
+                        ),
+                        vec![Inline::Str(mem::take(tex))],
+                        (url, String::new()),
+                    )
+                }
+            },
+            _ => return,
+        };
+        *inline = replacement;
+    });
+}
+
+/// Replaces the text of every `Str` below `node` with the result of `f`.
+/// Code, math and raw content are not `Str`s and stay as they are.
+pub fn map_text<T, F>(node: &mut T, mut f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&str) -> String,
+{
+    node.for_each_inline_mut(|inline| {
+        if let Inline::Str(ref mut text) = *inline {
+            *text = f(text);
+        }
+    });
+}
+
+/// Converts the text below `node` to upper case, see `map_text`
+pub fn uppercase<T: MutVisitable>(node: &mut T) {
+    map_text(node, str::to_uppercase);
+}
+
+/// words `title_case` leaves in lower case unless they start a list of inlines
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or", "the", "to",
+    "up", "via",
+];
+
+/// Capitalizes the first letter of every word below `node`, except for
+/// articles, conjunctions and short prepositions that don't start a list of
+/// inlines, e.g. a header or an emphasized phrase
+pub fn title_case<T: MutVisitable>(node: &mut T) {
+    node.accept_mut(&mut TitleCaser);
+}
+
+struct TitleCaser;
+
+impl MutVisitor for TitleCaser {
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        for (i, inline) in vec_inline.iter_mut().enumerate() {
+            if let Inline::Str(ref mut text) = *inline {
+                if i > 0 && MINOR_WORDS.contains(&text.to_lowercase().as_str()) {
+                    continue;
+                }
+                let mut chars = text.chars();
+                if let Some(first) = chars.next() {
+                    *text = first.to_uppercase().chain(chars).collect();
+                }
+            }
+        }
+        self.walk_vec_inline(vec_inline);
+    }
+}
+
+/// Wraps every word below `node` written in capitals only, like `NASA`, in
+/// `SmallCaps` and converts it to lower case, so acronyms don't stand out
+/// from the text. Words need at least two letters, punctuation around them
+/// stays outside.
+pub fn small_caps<T: MutVisitable>(node: &mut T) {
+    node.accept_mut(&mut SmallCapser);
+}
+
+fn small_caps_word(inline: &mut Inline) -> Action<Inline> {
+    let text = match *inline {
+        Inline::Str(ref text) => text,
+        _ => return Action::Keep,
+    };
+    let word = text.trim_matches(|c: char| !c.is_alphanumeric());
+    let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+    if letters < 2 || word.chars().any(char::is_lowercase) {
+        return Action::Keep;
+    }
+    let (before, rest) = text.split_at(text.find(word).unwrap_or(0));
+    let after = &rest[word.len()..];
+    let mut result = Vec::new();
+    if !before.is_empty() {
+        result.push(Inline::Str(before.to_owned()));
+    }
+    result.push(Inline::SmallCaps(vec![Inline::Str(word.to_lowercase())]));
+    if !after.is_empty() {
+        result.push(Inline::Str(after.to_owned()));
+    }
+    Action::ReplaceMany(result)
+}
+
+struct SmallCapser;
+
+impl MutVisitor for SmallCapser {
+    fn visit_inline(&mut self, inline: &mut Inline) {
+        if let Inline::SmallCaps(_) = *inline {
+            return;
+        }
+        self.walk_inline(inline);
+    }
+
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        self.walk_vec_inline(vec_inline);
+        splice(vec_inline, None, &mut small_caps_word);
+    }
+}
+
+/// Adds `delta` to the level of every header, clamping the result to 1..=6
+pub fn shift_headers<T: MutVisitable>(node: &mut T, delta: Int) {
+    node.for_each_block_mut(|block| {
+        if let Block::Header(ref mut level, ..) = *block {
+            *level = (*level + delta).clamp(1, 6);
+        }
+    });
+}
+
+/// Prefixes every header with its hierarchical number ("2.3.1") like pandoc's
+/// `--number-sections`, in a `Span` with the class `header-section-number`.
+/// Headers with the class `unnumbered` are skipped, as are headers in the
+/// metadata of a document. Levels above 6 are numbered like level 6. If
+/// `attribute` is given, the number is also stored in a key-value attribute
+/// of that name.
+pub fn number_headings<T: MutVisitable>(node: &mut T, attribute: Option<&str>) {
+    node.accept_mut(&mut HeadingNumberer {
+        counters: Vec::new(),
+        attribute,
+    })
+}
+
+struct HeadingNumberer<'a> {
+    counters: Vec<Int>,
+    attribute: Option<&'a str>,
+}
+
+impl<'a> MutVisitor for HeadingNumberer<'a> {
+    fn visit_block(&mut self, block: &mut Block) {
+        if let Block::Header(level, ref mut attr, ref mut text) = *block {
+            if level >= 1 && !attr.1.iter().any(|class| class == "unnumbered") {
+                let level = level.min(6) as usize;
+                self.counters.resize(level, 0);
+                self.counters[level - 1] += 1;
+                let number = self
+                    .counters
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                if let Some(key) = self.attribute {
+                    attr.2.retain(|(k, _)| k != key);
+                    attr.2.push((key.to_owned(), number.clone()));
+                }
+                let span = Inline::span("header-section-number", vec![Inline::Str(number)]);
+                text.splice(0..0, vec![span, Inline::Space]);
+            }
+        }
+        self.walk_block(block)
+    }
+
+    fn visit_meta(&mut self, _key: &str, _meta: &mut MetaValue) {}
+}
+
+/// Cleans up inlines the way pandoc's former `normalize` did: adjacent `Str`s
+/// are merged, empty `Str`s and formatting containers (`Emph`, `Strong`, ...)
+/// are dropped, and runs of `Space` and `SoftBreak` collapse into a single
+/// `SoftBreak` if they contain one and a single `Space` otherwise. Spaces
+/// around a `LineBreak` are removed.
+pub fn normalize<T: MutVisitable>(node: &mut T) {
+    node.accept_mut(&mut Normalizer)
+}
+
+struct Normalizer;
+
+fn is_empty_inline(inline: &Inline) -> bool {
+    match *inline {
+        Inline::Str(ref s) => s.is_empty(),
+        Inline::Emph(ref content)
+        | Inline::Underline(ref content)
+        | Inline::Strong(ref content)
+        | Inline::Strikeout(ref content)
+        | Inline::Superscript(ref content)
+        | Inline::Subscript(ref content)
+        | Inline::SmallCaps(ref content) => content.is_empty(),
+        _ => false,
+    }
+}
+
+fn is_space(inline: &Inline) -> bool {
+    matches!(*inline, Inline::Space | Inline::SoftBreak)
+}
+
+impl MutVisitor for Normalizer {
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        self.walk_vec_inline(vec_inline);
+        let mut result: Vec<Inline> = Vec::with_capacity(vec_inline.len());
+        for inline in vec_inline.drain(..) {
+            if is_empty_inline(&inline) {
+                continue;
+            }
+            match (result.last_mut(), inline) {
+                (Some(&mut Inline::Str(ref mut last)), Inline::Str(s)) => last.push_str(&s),
+                (Some(last), Inline::SoftBreak) if is_space(last) => *last = Inline::SoftBreak,
+                (Some(last), Inline::Space) if is_space(last) => {}
+                (Some(last), Inline::LineBreak) if is_space(last) => *last = Inline::LineBreak,
+                (Some(&mut Inline::LineBreak), ref inline) if is_space(inline) => {}
+                (_, inline) => result.push(inline),
+            }
+        }
+        *vec_inline = result;
+    }
+}
+
+/// Rewrites values that this crate reads and writes back unchanged, but that
+/// pandoc rejects or reads differently, into the form pandoc writes itself:
+///
+/// - column widths that aren't positive, including `-0.0` and `NaN`, become
+///   `ColWidthDefault`, which is what pandoc means by a width of 0. `NaN` and
+///   infinite widths would otherwise be written as `null`.
+/// - row and column spans of cells are at least 1, as in pandoc's table
+///   builder
+/// - `MetaNumber`s become the `MetaString` of their number
+///
+/// The text and the attributes of the nodes are left unchanged. See
+/// `Pandoc::to_json` for what a round trip through json preserves.
+pub fn canonicalize<T: MutVisitable>(node: &mut T) {
+    node.accept_mut(&mut Canonicalizer)
+}
+
+struct Canonicalizer;
+
+fn canonicalize_meta(meta: &mut MetaValue) {
+    let text = match *meta {
+        MetaValue::MetaNumber(ref n) => n.to_string(),
+        MetaValue::MetaList(ref mut list) => {
+            list.iter_mut().for_each(canonicalize_meta);
+            return;
+        }
+        _ => return,
+    };
+    *meta = MetaValue::MetaString(text);
+}
+
+impl MutVisitor for Canonicalizer {
+    fn visit_block(&mut self, block: &mut Block) {
+        if let Block::Table(_, _, ref mut specs, _, _, _) = *block {
+            for spec in specs {
+                if let ColWidth::ColWidth(width) = spec.1 {
+                    if !(width > 0.0 && width.is_finite()) {
+                        spec.1 = ColWidth::ColWidthDefault;
+                    }
+                }
+            }
+        }
+        self.walk_block(block)
+    }
+
+    fn visit_rows(&mut self, rows: &mut Vec<Row>) {
+        for (_, cells) in rows.iter_mut() {
+            for (_, _, row_span, col_span, _) in cells {
+                *row_span = (*row_span).max(1);
+                *col_span = (*col_span).max(1);
+            }
+        }
+        self.walk_rows(rows)
+    }
+
+    fn visit_meta(&mut self, _key: &str, meta: &mut MetaValue) {
+        canonicalize_meta(meta);
+        self.walk_meta(meta)
+    }
+}
+
+/// Where a url passed to the callback of `rewrite_urls` comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UrlKind {
+    /// the target of a `Link`
+    Link,
+    /// the target of an `Image`
+    Image,
+    /// an `href` attribute in raw html (requires the `html` feature)
+    RawHref,
+    /// a `src` attribute in raw html (requires the `html` feature)
+    RawSrc,
+}
+
+/// Replaces the url of every link and image target with the result of `f`.
+/// With the `html` feature, `href` and `src` attributes in raw html inlines
+/// and blocks are rewritten as well. Their values are passed as written,
+/// without decoding entities, and must not contain the quote character used.
+pub fn rewrite_urls<T, F>(node: &mut T, mut f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&str, UrlKind) -> String,
+{
+    node.for_each_inline_mut(|inline| match *inline {
+        Inline::Link(_, _, ref mut target) => target.0 = f(&target.0, UrlKind::Link),
+        Inline::Image(_, _, ref mut target) => target.0 = f(&target.0, UrlKind::Image),
+        #[cfg(feature = "html")]
+        Inline::RawInline(ref format, ref mut html) if format.is_html() => {
+            *html = rewrite_html_urls(html, &mut f)
+        }
+        _ => {}
+    });
+    #[cfg(feature = "html")]
+    node.for_each_block_mut(|block| {
+        if let Block::RawBlock(ref format, ref mut html) = *block {
+            if format.is_html() {
+                *html = rewrite_html_urls(html, &mut f);
+            }
+        }
+    });
+}
+
+#[cfg(feature = "html")]
+fn rewrite_html_urls<F: FnMut(&str, UrlKind) -> String>(html: &str, f: &mut F) -> String {
+    static ATTRIBUTE: OnceLock<regex::Regex> = OnceLock::new();
+    let attribute = ATTRIBUTE.get_or_init(|| {
+        regex::Regex::new(r#"(?i)\s(href|src)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).expect("valid regex")
+    });
+    let mut result = String::with_capacity(html.len());
+    let mut last = 0;
+    for captures in attribute.captures_iter(html) {
+        let kind = if captures[1].eq_ignore_ascii_case("href") {
+            UrlKind::RawHref
+        } else {
+            UrlKind::RawSrc
+        };
+        let value = captures
+            .get(2)
+            .or_else(|| captures.get(3))
+            .expect("one of the alternatives matched");
+        result.push_str(&html[last..value.start()]);
+        result.push_str(&f(value.as_str(), kind));
+        last = value.end();
+    }
+    result.push_str(&html[last..]);
+    result
+}
+
+/// Replaces the key of every citation with the result of `f`. The content
+/// of the `Cite`, which holds its original text, is left unchanged.
+pub fn map_citation_keys<T, F>(node: &mut T, mut f: F)
+where
+    T: MutVisitable,
+    F: FnMut(&str) -> String,
+{
+    node.for_each_inline_mut(|inline| {
+        if let Inline::Cite(ref mut citations, _) = *inline {
+            for citation in citations {
+                citation.citationId = f(&citation.citationId);
+            }
+        }
+    });
+}
+
+/// Recomputes `citationNoteNum` and `citationHash` the way pandoc's
+/// markdown reader assigns them: citations in a note get the number of the
+/// note, other citations the number of notes before them. Hashes are
+/// numbered consecutively from 1, making them unique.
+pub fn renumber_citations<T: MutVisitable>(node: &mut T) {
+    node.accept_mut(&mut CitationNumbering {
+        notes: 0,
+        current_note: None,
+        hash: 0,
+    })
+}
+
+struct CitationNumbering {
+    notes: Int,
+    current_note: Option<Int>,
+    hash: Int,
+}
+
+impl MutVisitor for CitationNumbering {
+    fn visit_inline(&mut self, inline: &mut Inline) {
+        match *inline {
+            Inline::Note(_) => {
+                self.notes += 1;
+                let outer = self.current_note.replace(self.notes);
+                self.walk_inline(inline);
+                self.current_note = outer;
+                return;
+            }
+            Inline::Cite(ref mut citations, _) => {
+                for citation in citations {
+                    self.hash += 1;
+                    citation.citationNoteNum = self.current_note.unwrap_or(self.notes);
+                    citation.citationHash = self.hash;
+                }
+            }
+            _ => {}
+        }
+        self.walk_inline(inline);
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match &extension[..] {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "bmp" => "image/bmp",
+        "ico" => "image/vnd.microsoft.icon",
+        "tif" | "tiff" => "image/tiff",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Replaces the targets of images referring to local files with `data:` uris
+/// containing the file, making the document self-contained. Relative paths
+/// are resolved against `base_dir`, urls with a scheme are left alone, as
+/// are files larger than `max_size` bytes. The mime type is guessed from the
+/// file extension. Stops at the first file that can't be read.
+pub fn embed_images<T, P>(node: &mut T, base_dir: P, max_size: Option<u64>) -> Result<(), Error>
+where
+    T: MutVisitable,
+    P: AsRef<Path>,
+{
+    let base_dir = base_dir.as_ref();
+    let mut result = Ok(());
+    node.for_each_inline_mut(|inline| {
+        if let Inline::Image(_, _, ref mut target) = *inline {
+            if result.is_err() || target.0.starts_with("data:") || target.0.contains("://") {
+                return;
+            }
+            let end = target.0.find(['?', '#']).unwrap_or(target.0.len());
+            let path = base_dir.join(&target.0[..end]);
+            match embed_file(&path, max_size) {
+                Ok(Some(uri)) => target.0 = uri,
+                Ok(None) => {}
+                Err(source) => result = Err(Error::File { path, source }),
+            }
+        }
+    });
+    result
+}
+
+fn embed_file(path: &Path, max_size: Option<u64>) -> io::Result<Option<String>> {
+    if let Some(max_size) = max_size {
+        if fs::metadata(path)?.len() > max_size {
+            return Ok(None);
+        }
+    }
+    let data = fs::read(path)?;
+    Ok(Some(format!(
+        "data:{};base64,{}",
+        mime_type(path),
+        base64(&data)
+    )))
+}
@@ -0,0 +1,457 @@
+//! Documents of pandoc-types 1.17 to 1.20 (pandoc 1.18 to 2.9).
+//!
+//! These versions describe tables by a caption, the alignments and widths of
+//! the columns, the header cells and the rows, and lack `Inline::Underline`
+//! and `MetaValue::MetaNumber`, whose numbers become `MetaString`s.
+//! Everything else is shared with the newer versions.
+
+use super::{
+    Alignment, Attr, CitationMode, ColWidth, Double, Error, Format, Int, ListAttributes, Map,
+    MathType, QuoteType, Target,
+};
+use std::convert::TryFrom;
+use std::iter;
+
+/// the root object of a pandoc document
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Pandoc {
+    pub meta: Meta,
+    pub blocks: Vec<Block>,
+    #[serde(rename = "pandoc-api-version")]
+    pub pandoc_api_version: Vec<u32>,
+}
+
+pub type Meta = Map<String, MetaValue>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "t", content = "c")]
+pub enum MetaValue {
+    MetaMap(Map<String, Box<MetaValue>>),
+    MetaList(Vec<MetaValue>),
+    MetaBool(bool),
+    MetaString(String),
+    MetaInlines(Vec<Inline>),
+    MetaBlocks(Vec<Block>),
+}
+
+/// see `super::Block`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "t", content = "c")]
+pub enum Block {
+    Plain(Vec<Inline>),
+    Para(Vec<Inline>),
+    LineBlock(Vec<Vec<Inline>>),
+    CodeBlock(Attr, String),
+    RawBlock(Format, String),
+    BlockQuote(Vec<Block>),
+    OrderedList(ListAttributes, Vec<Vec<Block>>),
+    BulletList(Vec<Vec<Block>>),
+    DefinitionList(Vec<(Vec<Inline>, Vec<Vec<Block>>)>),
+    Header(Int, Attr, Vec<Inline>),
+    #[serde(deserialize_with = "::lenient::nullary")]
+    HorizontalRule,
+    /// Table, with caption, column alignments, relative column widths (0 if
+    /// unspecified), column headers and rows
+    Table(
+        Vec<Inline>,
+        Vec<Alignment>,
+        Vec<Double>,
+        Vec<TableCell>,
+        Vec<Vec<TableCell>>,
+    ),
+    Div(Attr, Vec<Block>),
+    #[serde(deserialize_with = "::lenient::nullary")]
+    Null,
+}
+
+/// Table cells are list of Blocks
+pub type TableCell = Vec<Block>;
+
+/// see `super::Inline`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "t", content = "c")]
+pub enum Inline {
+    Str(String),
+    Emph(Vec<Inline>),
+    Strong(Vec<Inline>),
+    Strikeout(Vec<Inline>),
+    Superscript(Vec<Inline>),
+    Subscript(Vec<Inline>),
+    SmallCaps(Vec<Inline>),
+    Quoted(QuoteType, Vec<Inline>),
+    Cite(Vec<Citation>, Vec<Inline>),
+    Code(Attr, String),
+    #[serde(deserialize_with = "::lenient::nullary")]
+    Space,
+    #[serde(deserialize_with = "::lenient::nullary")]
+    SoftBreak,
+    #[serde(deserialize_with = "::lenient::nullary")]
+    LineBreak,
+    Math(MathType, String),
+    RawInline(Format, String),
+    Link(Attr, Vec<Inline>, Target),
+    Image(Attr, Vec<Inline>, Target),
+    Note(Vec<Block>),
+    Span(Attr, Vec<Inline>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[allow(non_snake_case)]
+pub struct Citation {
+    pub citationId: String,
+    pub citationPrefix: Vec<Inline>,
+    pub citationSuffix: Vec<Inline>,
+    pub citationMode: CitationMode,
+    pub citationNoteNum: Int,
+    pub citationHash: Int,
+}
+
+mod up {
+    use crate as new;
+    use super::*;
+
+    pub fn meta(meta: Meta) -> new::Meta {
+        meta.into_iter().map(|(k, v)| (k, meta_value(v))).collect()
+    }
+
+    fn meta_value(value: MetaValue) -> new::MetaValue {
+        use super::MetaValue as M;
+        match value {
+            M::MetaMap(map) => new::MetaValue::MetaMap(
+                map.into_iter()
+                    .map(|(k, v)| (k, Box::new(meta_value(*v))))
+                    .collect(),
+            ),
+            M::MetaList(values) => {
+                new::MetaValue::MetaList(values.into_iter().map(meta_value).collect())
+            }
+            M::MetaBool(b) => new::MetaValue::MetaBool(b),
+            M::MetaString(s) => new::MetaValue::MetaString(s),
+            M::MetaInlines(content) => new::MetaValue::MetaInlines(inlines(content)),
+            M::MetaBlocks(content) => new::MetaValue::MetaBlocks(blocks(content)),
+        }
+    }
+
+    pub fn blocks(blocks: Vec<Block>) -> Vec<new::Block> {
+        blocks.into_iter().map(block).collect()
+    }
+
+    fn items(items: Vec<Vec<Block>>) -> Vec<Vec<new::Block>> {
+        items.into_iter().map(blocks).collect()
+    }
+
+    fn inlines(inlines: Vec<Inline>) -> Vec<new::Inline> {
+        inlines.into_iter().map(inline).collect()
+    }
+
+    fn row(cells: Vec<TableCell>) -> new::Row {
+        let cells = cells
+            .into_iter()
+            .map(|content| {
+                (
+                    Attr::default(),
+                    Alignment::AlignDefault,
+                    1,
+                    1,
+                    blocks(content),
+                )
+            })
+            .collect();
+        (Attr::default(), cells)
+    }
+
+    fn block(block: Block) -> new::Block {
+        use super::super::Block as B;
+        match block {
+            Block::Plain(content) => B::Plain(inlines(content)),
+            Block::Para(content) => B::Para(inlines(content)),
+            Block::LineBlock(lines) => B::LineBlock(lines.into_iter().map(inlines).collect()),
+            Block::CodeBlock(attr, code) => B::CodeBlock(attr, code),
+            Block::RawBlock(format, content) => B::RawBlock(format, content),
+            Block::BlockQuote(content) => B::BlockQuote(blocks(content)),
+            Block::OrderedList(attrs, list) => B::OrderedList(attrs, items(list)),
+            Block::BulletList(list) => B::BulletList(items(list)),
+            Block::DefinitionList(list) => B::DefinitionList(
+                list.into_iter()
+                    .map(|(term, definitions)| (inlines(term), items(definitions)))
+                    .collect(),
+            ),
+            Block::Header(level, attr, content) => B::Header(level, attr, inlines(content)),
+            Block::HorizontalRule => B::HorizontalRule,
+            Block::Table(caption, alignments, widths, head, rows) => {
+                let caption = if caption.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![B::Plain(inlines(caption))]
+                };
+                let specs = alignments
+                    .into_iter()
+                    .zip(widths.into_iter().chain(iter::repeat(0.0)))
+                    .map(|(alignment, width)| {
+                        let width = if width == 0.0 {
+                            ColWidth::ColWidthDefault
+                        } else {
+                            ColWidth::ColWidth(width)
+                        };
+                        (alignment, width)
+                    })
+                    .collect();
+                let head = if head.iter().all(Vec::is_empty) {
+                    Vec::new()
+                } else {
+                    vec![row(head)]
+                };
+                B::Table(
+                    Attr::default(),
+                    (None, caption),
+                    specs,
+                    (Attr::default(), head),
+                    vec![(
+                        Attr::default(),
+                        0,
+                        Vec::new(),
+                        rows.into_iter().map(row).collect(),
+                    )],
+                    (Attr::default(), Vec::new()),
+                )
+            }
+            Block::Div(attr, content) => B::Div(attr, blocks(content)),
+            Block::Null => B::Null,
+        }
+    }
+
+    fn inline(inline: Inline) -> new::Inline {
+        use super::super::Inline as I;
+        match inline {
+            Inline::Str(s) => I::Str(s),
+            Inline::Emph(content) => I::Emph(inlines(content)),
+            Inline::Strong(content) => I::Strong(inlines(content)),
+            Inline::Strikeout(content) => I::Strikeout(inlines(content)),
+            Inline::Superscript(content) => I::Superscript(inlines(content)),
+            Inline::Subscript(content) => I::Subscript(inlines(content)),
+            Inline::SmallCaps(content) => I::SmallCaps(inlines(content)),
+            Inline::Quoted(quote_type, content) => I::Quoted(quote_type, inlines(content)),
+            Inline::Cite(citations, content) => I::Cite(
+                citations
+                    .into_iter()
+                    .map(|citation| new::Citation {
+                        citationId: citation.citationId,
+                        citationPrefix: inlines(citation.citationPrefix),
+                        citationSuffix: inlines(citation.citationSuffix),
+                        citationMode: citation.citationMode,
+                        citationNoteNum: citation.citationNoteNum,
+                        citationHash: citation.citationHash,
+                    })
+                    .collect(),
+                inlines(content),
+            ),
+            Inline::Code(attr, code) => I::Code(attr, code),
+            Inline::Space => I::Space,
+            Inline::SoftBreak => I::SoftBreak,
+            Inline::LineBreak => I::LineBreak,
+            Inline::Math(math_type, tex) => I::Math(math_type, tex),
+            Inline::RawInline(format, content) => I::RawInline(format, content),
+            Inline::Link(attr, content, target) => I::Link(attr, inlines(content), target),
+            Inline::Image(attr, content, target) => I::Image(attr, inlines(content), target),
+            Inline::Note(content) => I::Note(blocks(content)),
+            Inline::Span(attr, content) => {
+                if is_underline(&attr) {
+                    I::Underline(inlines(content))
+                } else {
+                    I::Span(attr, inlines(content))
+                }
+            }
+        }
+    }
+}
+
+mod down {
+    use crate as new;
+    use super::*;
+
+    pub fn meta(meta: new::Meta) -> Meta {
+        meta.into_iter().map(|(k, v)| (k, meta_value(v))).collect()
+    }
+
+    fn meta_value(value: new::MetaValue) -> MetaValue {
+        use super::super::MetaValue as M;
+        match value {
+            M::MetaMap(map) => MetaValue::MetaMap(
+                map.into_iter()
+                    .map(|(k, v)| (k, Box::new(meta_value(*v))))
+                    .collect(),
+            ),
+            M::MetaList(values) => {
+                MetaValue::MetaList(values.into_iter().map(meta_value).collect())
+            }
+            M::MetaBool(b) => MetaValue::MetaBool(b),
+            M::MetaString(s) => MetaValue::MetaString(s),
+            M::MetaNumber(n) => MetaValue::MetaString(n.to_string()),
+            M::MetaInlines(content) => MetaValue::MetaInlines(inlines(content)),
+            M::MetaBlocks(content) => MetaValue::MetaBlocks(blocks(content)),
+        }
+    }
+
+    pub fn blocks(blocks: Vec<new::Block>) -> Vec<Block> {
+        blocks.into_iter().map(block).collect()
+    }
+
+    fn items(items: Vec<Vec<new::Block>>) -> Vec<Vec<Block>> {
+        items.into_iter().map(blocks).collect()
+    }
+
+    fn inlines(inlines: Vec<new::Inline>) -> Vec<Inline> {
+        inlines.into_iter().map(inline).collect()
+    }
+
+    fn cells(row: new::Row) -> Vec<TableCell> {
+        row.1.into_iter().map(|cell| blocks(cell.4)).collect()
+    }
+
+    /// the inlines of the paragraphs of a caption, separated by spaces
+    fn caption_inlines(caption: Vec<new::Block>) -> Vec<Inline> {
+        let mut result = Vec::new();
+        for block in caption {
+            if let new::Block::Plain(content) | new::Block::Para(content) = block {
+                if !result.is_empty() {
+                    result.push(Inline::Space);
+                }
+                result.extend(inlines(content));
+            }
+        }
+        result
+    }
+
+    fn block(block: new::Block) -> Block {
+        use super::super::Block as B;
+        match block {
+            B::Plain(content) => Block::Plain(inlines(content)),
+            B::Para(content) => Block::Para(inlines(content)),
+            B::LineBlock(lines) => Block::LineBlock(lines.into_iter().map(inlines).collect()),
+            B::CodeBlock(attr, code) => Block::CodeBlock(attr, code),
+            B::RawBlock(format, content) => Block::RawBlock(format, content),
+            B::BlockQuote(content) => Block::BlockQuote(blocks(content)),
+            B::OrderedList(attrs, list) => Block::OrderedList(attrs, items(list)),
+            B::BulletList(list) => Block::BulletList(items(list)),
+            B::DefinitionList(list) => Block::DefinitionList(
+                list.into_iter()
+                    .map(|(term, definitions)| (inlines(term), items(definitions)))
+                    .collect(),
+            ),
+            B::Header(level, attr, content) => Block::Header(level, attr, inlines(content)),
+            B::HorizontalRule => Block::HorizontalRule,
+            B::Table(_, caption, specs, head, bodies, foot) => {
+                let alignments = specs.iter().map(|spec| spec.0).collect();
+                let widths = specs
+                    .iter()
+                    .map(|spec| match spec.1 {
+                        ColWidth::ColWidth(width) => width,
+                        ColWidth::ColWidthDefault => 0.0,
+                    })
+                    .collect();
+                let mut head_rows = head.1.into_iter();
+                let header = head_rows
+                    .next()
+                    .map(cells)
+                    .unwrap_or_else(|| vec![Vec::new(); specs.len()]);
+                let rows = head_rows
+                    .chain(
+                        bodies
+                            .into_iter()
+                            .flat_map(|body| body.2.into_iter().chain(body.3)),
+                    )
+                    .chain(foot.1)
+                    .map(cells)
+                    .collect();
+                Block::Table(caption_inlines(caption.1), alignments, widths, header, rows)
+            }
+            B::Figure(mut attr, caption, mut content) => {
+                attr.1.insert(0, "figure".to_owned());
+                content.extend(caption.1);
+                Block::Div(attr, blocks(content))
+            }
+            B::Div(attr, content) => Block::Div(attr, blocks(content)),
+            // unparsed blocks are parsed by `try_from` beforehand
+            B::Null | B::Unknown(..) | B::Unparsed(_) => Block::Null,
+        }
+    }
+
+    fn inline(inline: new::Inline) -> Inline {
+        use super::super::Inline as I;
+        match inline {
+            I::Str(s) => Inline::Str(s),
+            I::Emph(content) => Inline::Emph(inlines(content)),
+            I::Underline(content) => Inline::Span(
+                (String::new(), vec!["underline".to_owned()], Vec::new()),
+                inlines(content),
+            ),
+            I::Strong(content) => Inline::Strong(inlines(content)),
+            I::Strikeout(content) => Inline::Strikeout(inlines(content)),
+            I::Superscript(content) => Inline::Superscript(inlines(content)),
+            I::Subscript(content) => Inline::Subscript(inlines(content)),
+            I::SmallCaps(content) => Inline::SmallCaps(inlines(content)),
+            I::Quoted(quote_type, content) => Inline::Quoted(quote_type, inlines(content)),
+            I::Cite(citations, content) => Inline::Cite(
+                citations
+                    .into_iter()
+                    .map(|citation| Citation {
+                        citationId: citation.citationId,
+                        citationPrefix: inlines(citation.citationPrefix),
+                        citationSuffix: inlines(citation.citationSuffix),
+                        citationMode: citation.citationMode,
+                        citationNoteNum: citation.citationNoteNum,
+                        citationHash: citation.citationHash,
+                    })
+                    .collect(),
+                inlines(content),
+            ),
+            I::Code(attr, code) => Inline::Code(attr, code),
+            I::Space => Inline::Space,
+            I::SoftBreak => Inline::SoftBreak,
+            I::LineBreak => Inline::LineBreak,
+            I::Math(math_type, tex) => Inline::Math(math_type, tex),
+            I::RawInline(format, content) => Inline::RawInline(format, content),
+            I::Link(attr, content, target) => Inline::Link(attr, inlines(content), target),
+            I::Image(attr, content, target) => Inline::Image(attr, inlines(content), target),
+            I::Note(content) => Inline::Note(blocks(content)),
+            I::Span(attr, content) => Inline::Span(attr, inlines(content)),
+            I::Unknown(..) => Inline::Span(Attr::default(), Vec::new()),
+        }
+    }
+}
+
+/// whether a span is how older pandoc versions read `[text]{.underline}`
+fn is_underline(attr: &Attr) -> bool {
+    attr.0.is_empty() && attr.1 == ["underline"] && attr.2.is_empty()
+}
+
+/// Simple tables become tables with a single body and spans with nothing but
+/// the class `underline` become `Underline`, like newer pandoc versions read
+/// them.
+impl From<Pandoc> for super::v1_22::Pandoc {
+    fn from(doc: Pandoc) -> Self {
+        super::v1_22::Pandoc(super::Pandoc {
+            meta: up::meta(doc.meta),
+            blocks: up::blocks(doc.blocks),
+            pandoc_api_version: vec![1, 22],
+        })
+    }
+}
+
+/// Lossy: the attributes of tables and their parts and the spans of cells are
+/// dropped, all rows except for the first header row become regular rows and
+/// only the paragraphs of captions are kept. `Underline` becomes a span with
+/// the class `underline`. Nodes unknown to this crate are removed. Fails if
+/// a `Block::Unparsed` isn't a valid block.
+impl TryFrom<super::v1_22::Pandoc> for Pandoc {
+    type Error = Error;
+
+    fn try_from(mut doc: super::v1_22::Pandoc) -> Result<Self, Error> {
+        doc.0.ensure_parsed()?;
+        Ok(Pandoc {
+            meta: down::meta(doc.0.meta),
+            blocks: down::blocks(doc.0.blocks),
+            pandoc_api_version: vec![1, 20],
+        })
+    }
+}
@@ -0,0 +1,47 @@
+//! Documents of pandoc-types 1.21 and 1.22 (pandoc 2.10 to 2.19).
+//!
+//! Their model is the one of 1.23 without `Block::Figure`. Figures are
+//! implicit figures instead, see the `figure` module.
+
+use super::*;
+use std::mem;
+
+/// A document without `Figure` blocks
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Pandoc(pub super::Pandoc);
+
+/// Implicit figures become `Figure`s
+impl From<Pandoc> for super::Pandoc {
+    fn from(doc: Pandoc) -> Self {
+        let mut doc = doc.0;
+        figure::to_figures(&mut doc);
+        doc.pandoc_api_version = API_VERSION.to_vec();
+        doc
+    }
+}
+
+/// Lossy: `Figure`s holding a single image become implicit figures, other
+/// figures become a `Div` with the class `figure` containing the content
+/// followed by the caption.
+impl From<super::Pandoc> for Pandoc {
+    fn from(mut doc: super::Pandoc) -> Self {
+        doc.for_each_block_mut(|block| {
+            if !figure::to_implicit_figure(block) {
+                figure_to_div(block);
+            }
+        });
+        doc.pandoc_api_version = vec![1, 22];
+        Pandoc(doc)
+    }
+}
+
+fn figure_to_div(block: &mut Block) {
+    if let Block::Figure(..) = *block {
+        if let Block::Figure(mut attr, caption, mut content) = mem::replace(block, Block::Null) {
+            attr.1.insert(0, "figure".to_owned());
+            content.extend(caption.1);
+            *block = Block::Div(attr, content);
+        }
+    }
+}
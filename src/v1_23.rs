@@ -0,0 +1,5 @@
+//! Documents of pandoc-types 1.23 (pandoc 3), the model of the crate root
+
+pub use super::{
+    Block, Citation, ColSpec, Inline, Meta, MetaValue, Pandoc, Row, TableBody, TableFoot, TableHead,
+};
@@ -0,0 +1,183 @@
+//! Checks for problems pandoc itself doesn't complain about
+
+use super::*;
+use path::walk_with_paths;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A problem with the cross-references of a document
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LinkIssue {
+    /// a link to `#target`, but no element has the identifier `target`
+    BrokenLink { path: AstPath, target: String },
+    /// an element reusing the identifier of an earlier one
+    DuplicateIdentifier { path: AstPath, id: String },
+    /// a link with an empty url
+    EmptyTarget { path: AstPath },
+}
+
+impl LinkIssue {
+    /// the path of the offending node
+    pub fn path(&self) -> &AstPath {
+        match *self {
+            LinkIssue::BrokenLink { ref path, .. }
+            | LinkIssue::DuplicateIdentifier { ref path, .. }
+            | LinkIssue::EmptyTarget { ref path } => path,
+        }
+    }
+
+    /// the description of the problem, without the path
+    pub fn message(&self) -> String {
+        match *self {
+            LinkIssue::BrokenLink { ref target, .. } => {
+                format!("link to missing identifier #{}", target)
+            }
+            LinkIssue::DuplicateIdentifier { ref id, .. } => format!("duplicate identifier #{}", id),
+            LinkIssue::EmptyTarget { .. } => "link without target".to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for LinkIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path(), self.message())
+    }
+}
+
+/// All broken internal links, duplicate identifiers and links without a
+/// target in the blocks of `doc`, in document order. Links to other documents
+/// are not checked.
+pub fn check_links(doc: &Pandoc) -> Vec<LinkIssue> {
+    let mut issues = Vec::new();
+    let mut ids = HashSet::new();
+    let mut links = Vec::new();
+    walk_with_paths(doc, |path, node| {
+        if let Some(attr) = node.attr() {
+            if !attr.0.is_empty() && !ids.insert(attr.0.as_str()) {
+                issues.push(LinkIssue::DuplicateIdentifier {
+                    path: path.clone(),
+                    id: attr.0.clone(),
+                });
+            }
+        }
+        if let Node::Inline(Inline::Link(_, _, (ref url, _))) = node {
+            if url.is_empty() {
+                issues.push(LinkIssue::EmptyTarget { path: path.clone() });
+            } else if let Some(target) = url.strip_prefix('#') {
+                links.push((path.clone(), target));
+            }
+        }
+    });
+    issues.extend(
+        links
+            .into_iter()
+            .filter(|&(_, target)| !ids.contains(target))
+            .map(|(path, target)| LinkIssue::BrokenLink {
+                path,
+                target: target.to_owned(),
+            }),
+    );
+    issues.sort_by(|a, b| a.path().cmp(b.path()));
+    issues
+}
+
+/// An accessibility problem found by `accessibility`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AccessibilityIssue {
+    /// an image with an empty description
+    ImageWithoutAlt { path: AstPath },
+    /// a table whose head has no rows or only empty cells
+    TableWithoutHeader { path: AstPath },
+    /// a header more than one level deeper than the header before it
+    HeadingLevelJump { path: AstPath, from: Int, to: Int },
+    /// a link whose text, like "here", says nothing about where it goes
+    UninformativeLinkText { path: AstPath, text: String },
+}
+
+impl AccessibilityIssue {
+    /// the path of the offending node
+    pub fn path(&self) -> &AstPath {
+        match *self {
+            AccessibilityIssue::ImageWithoutAlt { ref path }
+            | AccessibilityIssue::TableWithoutHeader { ref path }
+            | AccessibilityIssue::HeadingLevelJump { ref path, .. }
+            | AccessibilityIssue::UninformativeLinkText { ref path, .. } => path,
+        }
+    }
+
+    /// the description of the problem, without the path
+    pub fn message(&self) -> String {
+        match *self {
+            AccessibilityIssue::ImageWithoutAlt { .. } => "image without alt text".to_owned(),
+            AccessibilityIssue::TableWithoutHeader { .. } => "table without header row".to_owned(),
+            AccessibilityIssue::HeadingLevelJump { from, to, .. } => {
+                format!("heading level jumps from {} to {}", from, to)
+            }
+            AccessibilityIssue::UninformativeLinkText { ref text, .. } => {
+                format!("uninformative link text {:?}", text)
+            }
+        }
+    }
+}
+
+impl fmt::Display for AccessibilityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path(), self.message())
+    }
+}
+
+/// link texts that only make sense next to the surrounding text, compared case-insensitively
+const UNINFORMATIVE_LINK_TEXTS: &[&str] = &[
+    "here",
+    "click here",
+    "link",
+    "this link",
+    "more",
+    "read more",
+];
+
+fn is_empty_row(row: &Row) -> bool {
+    row.1
+        .iter()
+        .all(|cell| stringify(&cell.4).trim().is_empty())
+}
+
+/// Images without alt text, tables without a header row, headers skipping
+/// levels (e.g. a level 4 header following a level 2 header) and links with
+/// texts like "here", in document order. The first header may have any level.
+pub fn accessibility(doc: &Pandoc) -> Vec<AccessibilityIssue> {
+    let mut issues = Vec::new();
+    let mut last_level = None;
+    walk_with_paths(doc, |path, node| match node {
+        Node::Inline(Inline::Image(_, ref alt, _)) if stringify(alt).trim().is_empty() => {
+            issues.push(AccessibilityIssue::ImageWithoutAlt { path: path.clone() })
+        }
+        Node::Inline(Inline::Link(_, ref text, _)) => {
+            let text = stringify(text);
+            let normalized = text.trim().trim_end_matches(['.', '!']).to_lowercase();
+            if UNINFORMATIVE_LINK_TEXTS.contains(&normalized.as_str()) {
+                issues.push(AccessibilityIssue::UninformativeLinkText {
+                    path: path.clone(),
+                    text,
+                });
+            }
+        }
+        Node::Block(Block::Table(_, _, _, ref head, ..)) if head.1.iter().all(is_empty_row) => {
+            issues.push(AccessibilityIssue::TableWithoutHeader { path: path.clone() })
+        }
+        Node::Block(&Block::Header(level, ..)) => {
+            if let Some(from) = last_level {
+                if level > from + 1 {
+                    issues.push(AccessibilityIssue::HeadingLevelJump {
+                        path: path.clone(),
+                        from,
+                        to: level,
+                    });
+                }
+            }
+            last_level = Some(level);
+        }
+        _ => {}
+    });
+    issues
+}
@@ -0,0 +1,102 @@
+//! Substitution of metadata values into the text of a document
+//!
+//! Variables are written as `%{name}` in the text or as a `Span` with the
+//! class `var`, named by its `name` attribute or else its text. Names are
+//! dotted paths into the metadata, see `MetaExt::get_path`, and values are
+//! inserted as `MetaValue::to_inlines`.
+//!
+//! ```
+//! # use pandoc_ast::*;
+//! let mut doc = Pandoc::new();
+//! doc.meta.insert("version".to_owned(), MetaValue::MetaString("1.2".to_owned()));
+//! doc.blocks.push(para!["Version %{version}, see %{changelog}."]);
+//! assert_eq!(variables::substitute(&mut doc), vec!["changelog"]);
+//! assert_eq!(doc.blocks, vec![para!["Version 1.2, see %{changelog}."]]);
+//! ```
+
+use super::*;
+use transform::{Action, TraversalOrder};
+
+/// Appends `inline` to `inlines`, merging adjacent `Str`s
+fn push(inlines: &mut Vec<Inline>, inline: Inline) {
+    match (inlines.last_mut(), inline) {
+        (Some(&mut Inline::Str(ref mut last)), Inline::Str(s)) => last.push_str(&s),
+        (_, inline) => inlines.push(inline),
+    }
+}
+
+/// Records `name` as unresolved unless it already is
+fn unresolved(name: &str, names: &mut Vec<String>) {
+    if !names.iter().any(|n| n == name) {
+        names.push(name.to_owned());
+    }
+}
+
+/// The inlines of `text` with its `%{name}` tokens substituted, `None` if
+/// none of them could be
+fn substitute_tokens(text: &str, meta: &Meta, names: &mut Vec<String>) -> Option<Vec<Inline>> {
+    let mut result = Vec::new();
+    let mut rest = text;
+    let mut substituted = false;
+    while let Some(start) = rest.find("%{") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = &rest[start + 2..end];
+        push(&mut result, Inline::Str(rest[..start].to_owned()));
+        match meta.get_path(name).and_then(MetaValue::to_inlines) {
+            Some(inlines) => {
+                substituted = true;
+                for inline in inlines {
+                    push(&mut result, inline);
+                }
+            }
+            None => {
+                unresolved(name, names);
+                push(&mut result, Inline::Str(rest[start..=end].to_owned()));
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    if !substituted {
+        return None;
+    }
+    push(&mut result, Inline::Str(rest.to_owned()));
+    result.retain(|inline| *inline != Inline::Str(String::new()));
+    Some(result)
+}
+
+/// Replaces the variables below `node` with the values from `meta` and
+/// returns the names of the variables that are missing or have no inlines,
+/// in document order and without duplicates. These are left as they are.
+pub fn substitute_with<T: MutVisitable>(node: &mut T, meta: &Meta) -> Vec<String> {
+    let mut names = Vec::new();
+    transform::inlines(node, TraversalOrder::BottomUp, |inline| match *inline {
+        Inline::Span(ref attr, ref content) if attr.1.iter().any(|class| class == "var") => {
+            let name = attr
+                .2
+                .iter()
+                .find(|(key, _)| key == "name")
+                .map_or_else(|| stringify(content), |(_, name)| name.clone());
+            match meta.get_path(name.trim()).and_then(MetaValue::to_inlines) {
+                Some(inlines) => Action::ReplaceMany(inlines),
+                None => {
+                    unresolved(name.trim(), &mut names);
+                    Action::Keep
+                }
+            }
+        }
+        Inline::Str(ref text) => match substitute_tokens(text, meta, &mut names) {
+            Some(inlines) => Action::ReplaceMany(inlines),
+            None => Action::Keep,
+        },
+        _ => Action::Keep,
+    });
+    names
+}
+
+/// `substitute_with` on the blocks of `doc` with its own metadata
+pub fn substitute(doc: &mut Pandoc) -> Vec<String> {
+    substitute_with(&mut doc.blocks, &doc.meta)
+}
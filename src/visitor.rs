@@ -1,4 +1,6 @@
 use super::*;
+use context::Context;
+use node::{Blocks, Descendants, Folder, ForEachBlock, ForEachInline, Inlines};
 
 pub trait MutVisitor {
     fn visit_block(&mut self, block: &mut Block) {
@@ -22,6 +24,11 @@ pub trait MutVisitor {
     fn visit_rows(&mut self, rows: &mut Vec<Row>) {
         self.walk_rows(rows)
     }
+    /// The context polled while walking blocks and inlines, see the
+    /// `context` module
+    fn context(&mut self) -> Option<&mut Context> {
+        None
+    }
     fn walk_meta(&mut self, meta: &mut MetaValue) {
         use MetaValue::*;
         match *meta {
@@ -37,6 +44,7 @@ pub trait MutVisitor {
             }
             MetaBool(_) => {}
             MetaString(_) => {}
+            MetaNumber(_) => {}
             MetaInlines(ref mut v_inline) => {
                 self.visit_vec_inline(v_inline);
             }
@@ -53,6 +61,9 @@ pub trait MutVisitor {
     }
     fn walk_block(&mut self, block: &mut Block) {
         use Block::*;
+        if self.context().is_some_and(Context::tick) {
+            return;
+        }
         match *block {
             Plain(ref mut vec_inline) | Para(ref mut vec_inline) => {
                 self.visit_vec_inline(vec_inline);
@@ -114,16 +125,28 @@ pub trait MutVisitor {
                 }
 
             }
+            Figure(ref mut attr, ref mut caption, ref mut vec_block) => {
+                self.visit_attr(attr);
+                let (short, caption) = caption;
+                if let Some(shortcaption) = short {
+                    self.visit_vec_inline(shortcaption);
+                }
+                self.visit_vec_block(caption);
+                self.visit_vec_block(vec_block);
+            }
             Div(ref mut attr, ref mut vec_block) => {
                 self.visit_attr(attr);
                 self.visit_vec_block(vec_block);
             }
-            Null => {}
+            Null | Unknown(..) | Unparsed(_) => {}
         }
     }
     fn walk_attr(&mut self, _attr: &mut Attr) {}
     fn walk_inline(&mut self, inline: &mut Inline) {
         use Inline::*;
+        if self.context().is_some_and(Context::tick) {
+            return;
+        }
         match *inline {
             Str { .. } => {}
             Emph(ref mut c)
@@ -144,9 +167,9 @@ pub trait MutVisitor {
                 self.visit_vec_inline(v_inl);
             }
             Code(ref mut attr, _) => self.visit_attr(attr),
-            Space { .. } => {}
-            SoftBreak { .. } => {}
-            LineBreak { .. } => {}
+            Space => {}
+            SoftBreak => {}
+            LineBreak => {}
             Math { .. } => {}
             RawInline { .. } => {}
             Link(ref mut attr, ref mut v_inline, _)
@@ -158,6 +181,7 @@ pub trait MutVisitor {
             Note(ref mut c) => {
                 self.visit_vec_block(c);
             }
+            Unknown(..) => {}
         }
     }
     fn walk_rows(&mut self, rows: &mut Vec<Row>){
@@ -180,3 +204,397 @@ pub trait MutVisitor {
         }
     }
 }
+
+/// AST nodes that a `MutVisitor` can be started on
+pub trait MutVisitable {
+    fn accept_mut<V: MutVisitor>(&mut self, visitor: &mut V);
+
+    /// Calls `f` on every block below this node in depth-first order. Parents
+    /// are passed before their children, so the children of a block are
+    /// looked up after `f` modified it.
+    fn for_each_block_mut<F: FnMut(&mut Block)>(&mut self, f: F) {
+        self.accept_mut(&mut ForEachBlock(f))
+    }
+
+    /// Calls `f` on every inline below this node in depth-first order. Parents
+    /// are passed before their children, so the children of an inline are
+    /// looked up after `f` modified it.
+    fn for_each_inline_mut<F: FnMut(&mut Inline)>(&mut self, f: F) {
+        self.accept_mut(&mut ForEachInline(f))
+    }
+
+    /// Calls `f` on all nodes matching a selector like `div.note > para`,
+    /// see the `select` module
+    fn select_mut<F: FnMut(NodeMut)>(&mut self, selector: &str, f: F) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        select::select_mut(self, &select::Selector::parse(selector)?, f);
+        Ok(())
+    }
+}
+
+impl MutVisitable for Pandoc {
+    fn accept_mut<V: MutVisitor>(&mut self, visitor: &mut V) {
+        visitor.walk_pandoc(self)
+    }
+}
+
+impl MutVisitable for MetaValue {
+    fn accept_mut<V: MutVisitor>(&mut self, visitor: &mut V) {
+        visitor.walk_meta(self)
+    }
+}
+
+impl MutVisitable for Block {
+    fn accept_mut<V: MutVisitor>(&mut self, visitor: &mut V) {
+        visitor.visit_block(self)
+    }
+}
+
+impl MutVisitable for Inline {
+    fn accept_mut<V: MutVisitor>(&mut self, visitor: &mut V) {
+        visitor.visit_inline(self)
+    }
+}
+
+impl MutVisitable for Vec<Block> {
+    fn accept_mut<V: MutVisitor>(&mut self, visitor: &mut V) {
+        visitor.visit_vec_block(self)
+    }
+}
+
+impl MutVisitable for Vec<Inline> {
+    fn accept_mut<V: MutVisitor>(&mut self, visitor: &mut V) {
+        visitor.visit_vec_inline(self)
+    }
+}
+
+pub trait Visitor {
+    fn visit_block(&mut self, block: &Block) {
+        self.walk_block(block)
+    }
+    fn visit_attr(&mut self, attr: &Attr) {
+        self.walk_attr(attr)
+    }
+    fn visit_inline(&mut self, inline: &Inline) {
+        self.walk_inline(inline)
+    }
+    fn visit_meta(&mut self, _key: &str, meta: &MetaValue) {
+        self.walk_meta(meta)
+    }
+    fn visit_vec_block(&mut self, vec_block: &[Block]) {
+        self.walk_vec_block(vec_block)
+    }
+    fn visit_vec_inline(&mut self, vec_inline: &[Inline]) {
+        self.walk_vec_inline(vec_inline)
+    }
+    fn visit_rows(&mut self, rows: &[Row]) {
+        self.walk_rows(rows)
+    }
+    /// The context polled while walking blocks and inlines, see the
+    /// `context` module
+    fn context(&mut self) -> Option<&mut Context> {
+        None
+    }
+    fn walk_meta(&mut self, meta: &MetaValue) {
+        use MetaValue::*;
+        match *meta {
+            MetaMap(ref c) => {
+                for (key, meta) in c {
+                    self.visit_meta(key, meta);
+                }
+            }
+            MetaList(ref c) => {
+                for meta in c {
+                    self.walk_meta(meta);
+                }
+            }
+            MetaBool(_) => {}
+            MetaString(_) => {}
+            MetaNumber(_) => {}
+            MetaInlines(ref v_inline) => {
+                self.visit_vec_inline(v_inline);
+            }
+            MetaBlocks(ref v_block) => {
+                self.visit_vec_block(v_block);
+            }
+        }
+    }
+    fn walk_pandoc(&mut self, pandoc: &Pandoc) {
+        for (key, meta) in &pandoc.meta {
+            self.visit_meta(key, meta);
+        }
+        self.visit_vec_block(&pandoc.blocks);
+    }
+    fn walk_block(&mut self, block: &Block) {
+        use Block::*;
+        if self.context().is_some_and(Context::tick) {
+            return;
+        }
+        match *block {
+            Plain(ref vec_inline) | Para(ref vec_inline) => {
+                self.visit_vec_inline(vec_inline);
+            }
+            LineBlock(ref vec_vec_inline) => {
+                for vec_inline in vec_vec_inline {
+                    self.visit_vec_inline(vec_inline);
+                }
+            }
+            CodeBlock(ref attr, _) => self.visit_attr(attr),
+            RawBlock { .. } => {}
+            BlockQuote(ref vec_block) => {
+                self.visit_vec_block(vec_block);
+            }
+            OrderedList(_, ref vec_vec_block) | BulletList(ref vec_vec_block) => {
+                for vec_block in vec_vec_block {
+                    self.visit_vec_block(vec_block);
+                }
+            }
+            DefinitionList(ref c) => {
+                for def in c {
+                    self.visit_vec_inline(&def.0);
+                    for vec_block in &def.1 {
+                        self.visit_vec_block(vec_block);
+                    }
+                }
+            }
+            Header(_, ref attr, ref vec_inline) => {
+                self.visit_attr(attr);
+                self.visit_vec_inline(vec_inline);
+            }
+            HorizontalRule => {}
+            Table(ref attr, ref caption, _, ref head, ref bodies, ref foot) => {
+                self.visit_attr(attr);
+                {
+                    let (short, caption) = caption;
+                    if let Some(shortcaption) = short {
+                        self.visit_vec_inline(shortcaption);
+                    }
+
+                    self.visit_vec_block(caption);
+                }
+                {
+                    let (attr, rows) = head;
+                    self.visit_attr(attr);
+                    self.visit_rows(rows);
+                }
+                for body in bodies {
+                    let (attr, _, rows_h, rows) = body;
+                    self.visit_attr(attr);
+                    self.visit_rows(rows_h);
+                    self.visit_rows(rows);
+                }
+                {
+                    let (attr, rows) = foot;
+                    self.visit_attr(attr);
+                    self.visit_rows(rows);
+                }
+            }
+            Figure(ref attr, ref caption, ref vec_block) => {
+                self.visit_attr(attr);
+                let (short, caption) = caption;
+                if let Some(shortcaption) = short {
+                    self.visit_vec_inline(shortcaption);
+                }
+                self.visit_vec_block(caption);
+                self.visit_vec_block(vec_block);
+            }
+            Div(ref attr, ref vec_block) => {
+                self.visit_attr(attr);
+                self.visit_vec_block(vec_block);
+            }
+            Null | Unknown(..) | Unparsed(_) => {}
+        }
+    }
+    fn walk_attr(&mut self, _attr: &Attr) {}
+    fn walk_inline(&mut self, inline: &Inline) {
+        use Inline::*;
+        if self.context().is_some_and(Context::tick) {
+            return;
+        }
+        match *inline {
+            Str { .. } => {}
+            Emph(ref c)
+            | Strong(ref c)
+            | Underline(ref c)
+            | Strikeout(ref c)
+            | Superscript(ref c)
+            | Subscript(ref c)
+            | SmallCaps(ref c)
+            | Quoted(_, ref c) => {
+                self.visit_vec_inline(c);
+            }
+            Cite(ref v_cite, ref v_inl) => {
+                for cite in v_cite {
+                    self.visit_vec_inline(&cite.citationPrefix);
+                    self.visit_vec_inline(&cite.citationSuffix);
+                }
+                self.visit_vec_inline(v_inl);
+            }
+            Code(ref attr, _) => self.visit_attr(attr),
+            Space => {}
+            SoftBreak => {}
+            LineBreak => {}
+            Math { .. } => {}
+            RawInline { .. } => {}
+            Link(ref attr, ref v_inline, _)
+            | Image(ref attr, ref v_inline, _)
+            | Span(ref attr, ref v_inline) => {
+                self.visit_attr(attr);
+                self.visit_vec_inline(v_inline);
+            }
+            Note(ref c) => {
+                self.visit_vec_block(c);
+            }
+            Unknown(..) => {}
+        }
+    }
+    fn walk_rows(&mut self, rows: &[Row]) {
+        for (attr, cells) in rows {
+            self.visit_attr(attr);
+            for (cell_attr, _, _, _, content) in cells {
+                self.visit_attr(cell_attr);
+                self.visit_vec_block(content);
+            }
+        }
+    }
+    fn walk_vec_block(&mut self, vec_block: &[Block]) {
+        for block in vec_block {
+            self.visit_block(block);
+        }
+    }
+    fn walk_vec_inline(&mut self, vec_inline: &[Inline]) {
+        for inline in vec_inline {
+            self.visit_inline(inline);
+        }
+    }
+}
+
+/// AST nodes that a `Visitor` can be started on
+pub trait Visitable {
+    fn accept<V: Visitor>(&self, visitor: &mut V);
+
+    /// the outermost blocks and inlines of this node, which is the node itself
+    /// for a single `Block` or `Inline`
+    fn roots(&self) -> Vec<Node<'_>>;
+
+    /// All blocks and inlines below this node (including the roots) in
+    /// depth-first order, parents before their children
+    fn descendants(&self) -> Descendants<'_> {
+        Descendants::new(self.roots())
+    }
+
+    /// All blocks below this node in depth-first order
+    fn blocks(&self) -> Blocks<'_> {
+        Blocks(self.descendants())
+    }
+
+    /// All inlines below this node in depth-first order
+    fn inlines(&self) -> Inlines<'_> {
+        Inlines(self.descendants())
+    }
+
+    /// All nodes matching a selector like `div.note > para`, see the `select` module
+    fn select(&self, selector: &str) -> Result<Vec<Node<'_>>, Error> {
+        Ok(select::select(self, &select::Selector::parse(selector)?))
+    }
+
+    /// Threads `init` through all blocks and inlines below this node in
+    /// depth-first order, parents before their children. This includes
+    /// footnotes, table cells, captions, citations and (for `Pandoc`) the
+    /// metadata.
+    fn fold<A, F>(&self, init: A, f: F) -> A
+    where
+        F: for<'a> FnMut(A, Node<'a>) -> A,
+    {
+        let mut folder = Folder { acc: Some(init), f };
+        self.accept(&mut folder);
+        folder.acc.expect("accumulator is always present")
+    }
+}
+
+impl Visitable for Pandoc {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.walk_pandoc(self)
+    }
+    fn roots(&self) -> Vec<Node<'_>> {
+        let mut roots = Vec::new();
+        for meta in self.meta.values() {
+            roots.extend(meta.roots());
+        }
+        roots.extend(self.blocks.iter().map(Node::Block));
+        roots
+    }
+}
+
+impl Visitable for MetaValue {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.walk_meta(self)
+    }
+    fn roots(&self) -> Vec<Node<'_>> {
+        match *self {
+            MetaValue::MetaMap(ref c) => c.values().flat_map(|meta| meta.roots()).collect(),
+            MetaValue::MetaList(ref c) => c.iter().flat_map(|meta| meta.roots()).collect(),
+            MetaValue::MetaBool(_) | MetaValue::MetaString(_) | MetaValue::MetaNumber(_) => {
+                Vec::new()
+            }
+            MetaValue::MetaInlines(ref c) => c.roots(),
+            MetaValue::MetaBlocks(ref c) => c.roots(),
+        }
+    }
+}
+
+impl Visitable for Block {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_block(self)
+    }
+    fn roots(&self) -> Vec<Node<'_>> {
+        vec![Node::Block(self)]
+    }
+}
+
+impl Visitable for Inline {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_inline(self)
+    }
+    fn roots(&self) -> Vec<Node<'_>> {
+        vec![Node::Inline(self)]
+    }
+}
+
+impl Visitable for [Block] {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_vec_block(self)
+    }
+    fn roots(&self) -> Vec<Node<'_>> {
+        self.iter().map(Node::Block).collect()
+    }
+}
+
+impl Visitable for Vec<Block> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_vec_block(self)
+    }
+    fn roots(&self) -> Vec<Node<'_>> {
+        self.iter().map(Node::Block).collect()
+    }
+}
+
+impl Visitable for [Inline] {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_vec_inline(self)
+    }
+    fn roots(&self) -> Vec<Node<'_>> {
+        self.iter().map(Node::Inline).collect()
+    }
+}
+
+impl Visitable for Vec<Inline> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.visit_vec_inline(self)
+    }
+    fn roots(&self) -> Vec<Node<'_>> {
+        self.iter().map(Node::Inline).collect()
+    }
+}
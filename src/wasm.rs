@@ -0,0 +1,69 @@
+//! Bindings for running transformations in the browser
+//!
+//! The crate compiles to `wasm32-unknown-unknown`, only `run_filter` and the
+//! `exec` module need a process and fail there. Filters for the browser are
+//! exported from the crate built with `wasm-bindgen`, with `filter_json`
+//! doing the work:
+//!
+//! ```ignore
+//! #[wasm_bindgen]
+//! pub fn demote_headers(json: &str) -> Result<String, JsValue> {
+//!     Ok(pandoc_ast::filter_json(json, |mut doc| {
+//!         pandoc_ast::transform::shift_headers(&mut doc, 1);
+//!         doc
+//!     })?)
+//! }
+//! ```
+//!
+//! The functions of this module are exported to javascript as they are.
+//! They take a document as pandoc json and report errors as exceptions with
+//! the error message.
+
+use super::*;
+use wasm_bindgen::prelude::*;
+
+impl From<Error> for JsValue {
+    fn from(err: Error) -> JsValue {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/// Converts a document of an older pandoc version to the current one
+#[wasm_bindgen]
+pub fn upgrade(json: &str) -> Result<String, JsValue> {
+    Ok(from_any_version(json.as_bytes())?.0.to_json())
+}
+
+/// Applies `transform::normalize`, keeping the version of the document
+#[wasm_bindgen]
+pub fn normalize(json: &str) -> Result<String, JsValue> {
+    Ok(filter_json(json, |mut doc| {
+        transform::normalize(&mut doc);
+        doc
+    })?)
+}
+
+/// The text of the document without any formatting
+#[wasm_bindgen(js_name = toPlainText)]
+pub fn to_plain_text(json: &str) -> Result<String, JsValue> {
+    Ok(stringify(&Pandoc::try_from_json(json)?))
+}
+
+/// See `Pandoc::to_markdown_lossy`
+#[wasm_bindgen(js_name = toMarkdown)]
+pub fn to_markdown(json: &str) -> Result<String, JsValue> {
+    Ok(Pandoc::try_from_json(json)?.to_markdown_lossy())
+}
+
+/// See `Pandoc::to_html_fragment`
+#[cfg(feature = "html")]
+#[wasm_bindgen(js_name = toHtmlFragment)]
+pub fn to_html_fragment(json: &str) -> Result<String, JsValue> {
+    Ok(Pandoc::try_from_json(json)?.to_html_fragment())
+}
+
+/// See `Pandoc::dump_tree`
+#[wasm_bindgen(js_name = dumpTree)]
+pub fn dump_tree(json: &str) -> Result<String, JsValue> {
+    Ok(Pandoc::try_from_json(json)?.dump_tree())
+}
@@ -0,0 +1,94 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::acronym::{Acronyms, Scope, Style};
+use pandoc_ast::*;
+
+fn acronyms() -> Acronyms {
+    let mut definitions = Map::new();
+    definitions.insert("HTML".to_owned(), "Hyper Text Markup Language".to_owned());
+    definitions.insert("CSS".to_owned(), "Cascading Style Sheets".to_owned());
+    definitions.insert("U.S. Army".to_owned(), "United States Army".to_owned());
+    definitions.insert("U.S.".to_owned(), "United States".to_owned());
+    Acronyms::new(definitions)
+}
+
+fn abbr(text: &str, title: &str) -> Inline {
+    Inline::Span(
+        (
+            String::new(),
+            vec!["abbr".to_owned()],
+            vec![("title".to_owned(), title.to_owned())],
+        ),
+        inlines![text],
+    )
+}
+
+#[test]
+fn first_occurrences() {
+    let mut doc = Pandoc::new();
+    doc.meta
+        .insert("title".to_owned(), MetaValue::MetaString("HTML".to_owned()));
+    doc.blocks = vec![
+        Block::Header(1, Attr::default(), inlines!["HTML"]),
+        Block::Para(vec![
+            Inline::Str("XHTML,".to_owned()),
+            Inline::Space,
+            Inline::Str("HT".to_owned()),
+            Inline::Str("ML,".to_owned()),
+            Inline::Space,
+            emph!["HTML and CSS"],
+            Inline::SoftBreak,
+            Inline::Str("CSS".to_owned()),
+        ]),
+        para!["the U.S. Army and the U.S."],
+    ];
+    acronyms().expand(&mut doc);
+    assert_eq!(doc.meta["title"], MetaValue::MetaString("HTML".to_owned()));
+    assert_eq!(
+        doc.blocks,
+        vec![
+            Block::Header(1, Attr::default(), inlines!["HTML"]),
+            Block::Para(vec![
+                Inline::Str("XHTML,".to_owned()),
+                Inline::Space,
+                abbr("HTML", "Hyper Text Markup Language"),
+                Inline::Str(",".to_owned()),
+                Inline::Space,
+                emph!["HTML and ", abbr("CSS", "Cascading Style Sheets")],
+                Inline::SoftBreak,
+                Inline::Str("CSS".to_owned()),
+            ]),
+            para![
+                "the ",
+                abbr("U.S. Army", "United States Army"),
+                " and the ",
+                abbr("U.S.", "United States")
+            ],
+        ]
+    );
+}
+
+#[test]
+fn scopes_and_styles() {
+    let mut blocks = vec![
+        para!["CSS"],
+        Block::Header(2, Attr::default(), inlines!["Styles"]),
+        para!["CSS"],
+        Block::Header(1, Attr::default(), inlines!["Other"]),
+        para!["CSS and CSS"],
+    ];
+    let expanded = "Cascading Style Sheets (CSS)";
+    let mut once = blocks.clone();
+    acronyms().style(Style::Inline).expand(&mut once);
+    assert_eq!(once[0], para![expanded]);
+    assert_eq!(once[2], para!["CSS"]);
+    assert_eq!(once[4], para!["CSS and CSS"]);
+
+    acronyms()
+        .style(Style::Inline)
+        .scope(Scope::Section(1))
+        .expand(&mut blocks);
+    assert_eq!(blocks[0], para![expanded]);
+    assert_eq!(blocks[2], para!["CSS"]);
+    assert_eq!(blocks[4], para![format!("{} and CSS", expanded)]);
+}
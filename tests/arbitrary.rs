@@ -0,0 +1,42 @@
+#![cfg(feature = "quickcheck")]
+extern crate pandoc_ast;
+extern crate quickcheck;
+
+use pandoc_ast::*;
+use quickcheck::{Arbitrary, QuickCheck};
+
+fn is_normalized(doc: &Pandoc) -> bool {
+    let mut normalized = doc.clone();
+    transform::normalize(&mut normalized);
+    normalized == *doc
+}
+
+#[test]
+fn documents_round_trip() {
+    fn round_trip(doc: Pandoc) -> bool {
+        Pandoc::from_json(&doc.to_json()) == doc
+    }
+    QuickCheck::new()
+        .tests(200)
+        .quickcheck(round_trip as fn(Pandoc) -> bool);
+}
+
+#[test]
+fn fast_serializer_matches_serde() {
+    fn same_json(doc: Pandoc) -> bool {
+        doc.to_json_fast() == doc.to_json()
+    }
+    QuickCheck::new()
+        .tests(200)
+        .quickcheck(same_json as fn(Pandoc) -> bool);
+}
+
+#[test]
+fn shrinking_keeps_documents_valid() {
+    fn valid_shrinks(doc: Pandoc) -> bool {
+        is_normalized(&doc) && doc.shrink().take(50).all(|smaller| is_normalized(&smaller))
+    }
+    QuickCheck::new()
+        .tests(50)
+        .quickcheck(valid_shrinks as fn(Pandoc) -> bool);
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "arena")]
+extern crate pandoc_ast;
+
+use pandoc_ast::arena::{self, Bump};
+use pandoc_ast::{Block, Inline, Meta, Pandoc};
+
+#[test]
+fn build_in_arena() {
+    let bump = Bump::new();
+    let words = bump
+        .alloc_slice_fill_iter((0..3).map(|i| arena::Inline::Str(bump.alloc_str(&i.to_string()))));
+    let blocks = bump.alloc_slice_fill_iter(vec![
+        arena::Block::Para(words),
+        arena::Block::Div(("d", &[], &[]), &[]),
+    ]);
+    let doc = arena::Pandoc {
+        meta: Meta::new(),
+        blocks,
+        pandoc_api_version: vec![1, 22],
+    };
+    let owned = doc.to_owned_ast();
+    assert_eq!(
+        owned.blocks[0],
+        Block::Para(vec![Inline::str("0"), Inline::str("1"), Inline::str("2")])
+    );
+    assert_eq!(doc.to_json(), owned.to_json());
+}
+
+#[test]
+fn roundtrip_through_arena() {
+    let json = r#"{"pandoc-api-version":[1,22],"meta":{},"blocks":[{"t":"Header","c":[1,["h",["c"],[["k","v"]]],[{"t":"Emph","c":[{"t":"Str","c":"a"}]}]]},{"t":"BulletList","c":[[{"t":"Plain","c":[{"t":"Link","c":[["",[],[]],[],["u","t"]]}]}]]}]}"#;
+    let owned = Pandoc::from_json(json);
+    let bump = Bump::new();
+    let doc = arena::Pandoc::from_json(&bump, json).unwrap();
+    assert_eq!(doc, arena::Pandoc::from_owned(&bump, &owned));
+    assert_eq!(doc.to_owned_ast(), owned);
+    assert_eq!(doc.to_json(), owned.to_json());
+}
+
+#[test]
+fn interned_strings() {
+    let json = r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[{"t":"Para","c":[{"t":"Code","c":[["",["rust"],[["lang","en"]]],"a"]},{"t":"Code","c":[["",["rust"],[["lang","en"]]],"b"]},{"t":"RawInline","c":["tex","\\x"]}]},{"t":"RawBlock","c":["tex","\\y"]}]}"#;
+    let bump = Bump::new();
+    let interner = arena::Interner::new(&bump);
+    let doc = arena::Pandoc::from_json_interned(&interner, json).unwrap();
+    assert_eq!(doc.to_owned_ast(), Pandoc::from_json(json));
+    assert_eq!(interner.len(), 3);
+
+    let (first, second, format) = match doc.blocks[0] {
+        arena::Block::Para(
+            &[arena::Inline::Code(first, _), arena::Inline::Code(second, _), arena::Inline::RawInline(format, _)],
+        ) => (first, second, format),
+        ref other => panic!("unexpected {:?}", other),
+    };
+    assert!(std::ptr::eq(first.1[0], second.1[0]));
+    assert!(std::ptr::eq(first.2[0].0, second.2[0].0));
+    assert!(!std::ptr::eq(first.2[0].1, second.2[0].1));
+    match doc.blocks[1] {
+        arena::Block::RawBlock(raw, _) => assert!(std::ptr::eq(raw.0, format.0)),
+        ref other => panic!("unexpected {:?}", other),
+    }
+    assert!(std::ptr::eq(interner.intern("rust"), first.1[0]));
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "tokio")]
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// in-memory readers and writers are always ready, so polling in a loop
+/// is enough without a runtime
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// a future that is pending once before it completes
+struct YieldOnce<T>(Option<T>, bool);
+
+impl<T: Unpin> Future for YieldOnce<T> {
+    type Output = T;
+    fn poll(mut self: std::pin::Pin<&mut Self>, _: &mut Context) -> Poll<T> {
+        if self.1 {
+            Poll::Ready(self.0.take().unwrap())
+        } else {
+            self.1 = true;
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn filter_async() {
+    let input = Pandoc::builder().block(para!["hello"]).build().to_json();
+    let mut output = Vec::new();
+    block_on(run_filter_async(
+        input.as_bytes(),
+        &mut output,
+        |mut doc| {
+            doc.blocks.push(Block::HorizontalRule);
+            YieldOnce(Some(doc), false)
+        },
+    ))
+    .unwrap();
+    let doc = Pandoc::from_json(&String::from_utf8(output).unwrap());
+    assert_eq!(doc.blocks, vec![para!["hello"], Block::HorizontalRule]);
+
+    let err = block_on(run_filter_async(&b"{"[..], Vec::new(), |doc| {
+        YieldOnce(Some(doc), false)
+    }));
+    assert!(matches!(err, Err(Error::Json(_))));
+}
@@ -0,0 +1,23 @@
+#![cfg(any(feature = "msgpack", feature = "cbor"))]
+extern crate pandoc_ast;
+
+use pandoc_ast::Pandoc;
+
+const JSON: &str = r#"{"pandoc-api-version":[1,22],"meta":{"title":{"t":"MetaInlines","c":[{"t":"Str","c":"T"}]}},"blocks":[{"t":"Table","c":[["",[],[]],[null,[]],[[{"t":"AlignLeft"},{"t":"ColWidth","c":0.5}]],[["",[],[]],[]],[],[["",[],[]],[]]]},{"t":"Para","c":[{"t":"Quoted","c":[{"t":"DoubleQuote"},[{"t":"Math","c":[{"t":"InlineMath"},"x"]}]]},{"t":"Space"}]}]}"#;
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_roundtrip() {
+    let doc = Pandoc::from_json(JSON);
+    let bytes = doc.to_msgpack().unwrap();
+    assert_eq!(Pandoc::from_msgpack(&bytes).unwrap(), doc);
+    assert!(Pandoc::from_msgpack(&bytes[..bytes.len() / 2]).is_err());
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_roundtrip() {
+    let doc = Pandoc::from_json(JSON);
+    let bytes = doc.to_cbor().unwrap();
+    assert_eq!(Pandoc::from_cbor(&bytes).unwrap(), doc);
+}
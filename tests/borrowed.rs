@@ -0,0 +1,49 @@
+#![cfg(feature = "borrowed")]
+extern crate pandoc_ast;
+
+use pandoc_ast::borrowed::{Block, Inline, Pandoc};
+
+const JSON: &str = r#"{"pandoc-api-version":[1,23,1],"meta":{"title":{"t":"MetaInlines","c":[{"t":"Str","c":"Title"}]}},"blocks":[{"t":"Para","c":[{"t":"Str","c":"plain"},{"t":"Space"},{"t":"Str","c":"esc\"aped"}]},{"t":"CodeBlock","c":[["",["rust"],[]],"fn main() {}"]}]}"#;
+
+#[test]
+fn borrows_from_input() {
+    let doc = Pandoc::from_json(JSON).unwrap();
+    match doc.blocks[0] {
+        Block::Para(ref inlines) => {
+            match inlines[0] {
+                Inline::Str(ref s) => assert!(s.is_borrowed() && &**s == "plain"),
+                ref other => panic!("unexpected {:?}", other),
+            }
+            match inlines[2] {
+                Inline::Str(ref s) => assert!(!s.is_borrowed() && &**s == "esc\"aped"),
+                ref other => panic!("unexpected {:?}", other),
+            }
+        }
+        ref other => panic!("unexpected {:?}", other),
+    }
+    match doc.blocks[1] {
+        Block::CodeBlock((_, ref classes, _), _) => assert!(classes[0].is_borrowed()),
+        ref other => panic!("unexpected {:?}", other),
+    }
+    assert!(doc.meta.keys().all(|key| key.is_borrowed()));
+}
+
+#[test]
+fn into_owned_matches_owned_parse() {
+    let doc = Pandoc::from_json(JSON).unwrap();
+    let json = doc.to_json();
+    assert_eq!(doc.into_owned(), pandoc_ast::Pandoc::from_json(JSON));
+    assert_eq!(json, pandoc_ast::Pandoc::from_json(JSON).to_json());
+    assert!(Pandoc::from_json(r#"{"pandoc-api-version":[1,17],"meta":{},"blocks":[]}"#).is_err());
+}
+
+#[test]
+fn nullary_constructors_with_content() {
+    let json = r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[{"t":"HorizontalRule","c":[]},{"t":"Para","c":[{"t":"Space","c":[]},{"t":"LineBreak"}]}]}"#;
+    let doc = Pandoc::from_json(json).unwrap();
+    assert_eq!(doc.blocks[0], Block::HorizontalRule);
+    assert_eq!(
+        doc.blocks[1],
+        Block::Para(vec![Inline::Space, Inline::LineBreak])
+    );
+}
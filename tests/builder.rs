@@ -0,0 +1,118 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn header() {
+    let header = Block::header(2, "Hello  brave world");
+    let expected = Block::Header(
+        2,
+        Attr::default(),
+        vec![
+            Inline::Str("Hello".to_owned()),
+            Inline::Space,
+            Inline::Str("brave".to_owned()),
+            Inline::Space,
+            Inline::Str("world".to_owned()),
+        ],
+    );
+    assert_eq!(header, expected);
+}
+
+#[test]
+fn code() {
+    assert_eq!(
+        Inline::code("rust", "fn main(){}"),
+        Inline::Code(
+            (String::new(), vec!["rust".to_owned()], vec![]),
+            "fn main(){}".to_owned()
+        )
+    );
+    assert_eq!(
+        Block::code_block_with_class("", "x"),
+        Block::CodeBlock(Attr::default(), "x".to_owned())
+    );
+}
+
+#[test]
+fn hash_and_eq() {
+    use std::collections::HashSet;
+
+    let mut set = HashSet::new();
+    set.insert(Block::para(vec![Inline::str("a")]));
+    set.insert(Block::para(vec![Inline::str("a")]));
+    set.insert(Block::para(vec![Inline::str("b")]));
+    assert_eq!(set.len(), 2);
+
+    assert_eq!(ColWidth::ColWidth(0.5), ColWidth::ColWidth(0.5));
+    assert_eq!(ColWidth::ColWidth(f64::NAN), ColWidth::ColWidth(f64::NAN));
+    assert_ne!(ColWidth::ColWidth(0.5), ColWidth::ColWidthDefault);
+}
+
+#[test]
+fn str_roundtrip() {
+    let inlines = inlines_from_str(" hello brave\n  world");
+    assert_eq!(
+        inlines,
+        vec![
+            Inline::Space,
+            Inline::str("hello"),
+            Inline::Space,
+            Inline::str("brave"),
+            Inline::SoftBreak,
+            Inline::str("world"),
+        ]
+    );
+    assert_eq!(inlines_to_string(&inlines), " hello brave\nworld");
+    assert_eq!(inlines_from_str(&inlines_to_string(&inlines)), inlines);
+}
+
+#[test]
+fn document_builder() {
+    let doc = Pandoc::builder()
+        .title(inlines!["The ", emph!["Report"]])
+        .author("Ada")
+        .author("Grace")
+        .meta("lang", "en")
+        .block(Block::header(1, "Summary"))
+        .block(vec![para!["one"], para!["two"]])
+        .build();
+    assert_eq!(doc.meta.get_string("title"), Some("The Report".to_owned()));
+    assert_eq!(doc.meta.get_string("author.1"), Some("Grace".to_owned()));
+    assert_eq!(doc.meta.get_string("lang"), Some("en".to_owned()));
+    assert_eq!(doc.blocks.len(), 3);
+    assert_eq!(Pandoc::from_json(&doc.to_json()), doc);
+
+    let empty = Pandoc::default();
+    assert_eq!(empty, Pandoc::new());
+    assert!(Pandoc::try_from_json(&empty.to_json()).is_ok());
+}
+
+#[test]
+fn citations() {
+    let citation = Citation::new("doe99");
+    assert_eq!(citation.citationMode, CitationMode::NormalCitation);
+    assert_eq!((citation.citationNoteNum, citation.citationHash), (0, 0));
+    assert!(citation.citationPrefix.is_empty() && citation.citationSuffix.is_empty());
+
+    let cite = Inline::cite(vec![
+        Citation::new("doe99").prefix("see").suffix(", p. 33"),
+        Citation::new("smith04").suppress_author(),
+    ]);
+    match cite {
+        Inline::Cite(ref citations, ref content) => {
+            assert_eq!(citations[0].citationPrefix, inlines!["see"]);
+            assert_eq!(citations[0].citationSuffix, inlines![", p. 33"]);
+            assert_eq!(citations[1].citationMode, CitationMode::SuppressAuthor);
+            assert_eq!(inlines_to_string(content), "[see @doe99, p. 33; -@smith04]");
+        }
+        ref other => panic!("unexpected {:?}", other),
+    }
+
+    let in_text = Inline::cite(vec![Citation::new("doe99")
+        .author_in_text()
+        .suffix(", p. 33")]);
+    assert_eq!(stringify(&in_text), "@doe99 [p. 33]");
+    let bare = Inline::cite(vec![Citation::new("doe99").author_in_text().note_num(2)]);
+    assert_eq!(stringify(&bare), "@doe99");
+}
@@ -0,0 +1,105 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::changes::{self, Change, ChangeKind};
+use pandoc_ast::*;
+
+const DATE: &str = "2024-01-31T12:00:00Z";
+
+fn mark(class: &str) -> Inline {
+    Inline::Span(
+        (
+            String::new(),
+            vec![class.to_owned()],
+            vec![("author".to_owned(), "bob".to_owned())],
+        ),
+        vec![],
+    )
+}
+
+fn document() -> Pandoc {
+    let mut first = inlines!["The ", changes::deletion("alice", DATE, inlines!["old"])];
+    first.push(changes::insertion("alice", DATE, inlines!["new"]));
+    first.extend(changes::comment(
+        "0",
+        "bob",
+        DATE,
+        inlines!["why?"],
+        inlines![" text"],
+    ));
+    first.push(mark("paragraph-deletion"));
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![
+        Block::Para(first),
+        Block::Para(vec![
+            Inline::Str("joined".to_owned()),
+            mark("paragraph-insertion"),
+        ]),
+        para![strong![changes::insertion("bob", DATE, inlines!["Last"])]],
+    ];
+    doc
+}
+
+#[test]
+fn report() {
+    let changes = changes::report(&document());
+    let kinds: Vec<ChangeKind> = changes.iter().map(|change| change.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ChangeKind::Deletion,
+            ChangeKind::Insertion,
+            ChangeKind::Comment,
+            ChangeKind::ParagraphDeletion,
+            ChangeKind::ParagraphInsertion,
+            ChangeKind::Insertion,
+        ]
+    );
+    assert_eq!(
+        changes[2],
+        Change {
+            kind: ChangeKind::Comment,
+            id: "0".to_owned(),
+            author: Some("bob".to_owned()),
+            date: Some(DATE.to_owned()),
+            text: "why?".to_owned(),
+            path: AstPath::block(0).child(0, 4),
+        }
+    );
+    assert_eq!(changes[3].date, None);
+    assert_eq!(changes[5].text, "Last");
+    assert_eq!(changes[5].path, AstPath::block(2).child(0, 0).child(0, 0));
+}
+
+#[test]
+fn accepting_and_rejecting() {
+    let mut doc = document();
+    changes::accept_all(&mut doc);
+    changes::strip_comments(&mut doc);
+    assert_eq!(
+        doc.blocks,
+        vec![
+            Block::Para(vec![
+                Inline::Str("The".to_owned()),
+                Inline::Space,
+                Inline::Str("new".to_owned()),
+                Inline::Space,
+                Inline::Str("text".to_owned()),
+                Inline::Str("joined".to_owned()),
+            ]),
+            para![strong!["Last"]],
+        ]
+    );
+
+    let mut doc = document();
+    changes::reject_all(&mut doc);
+    assert_eq!(doc.blocks.len(), 2);
+    assert_eq!(stringify(&doc.blocks[0]), "The oldwhy? text");
+    assert_eq!(
+        doc.blocks[1],
+        Block::Para(vec![
+            Inline::Str("joined".to_owned()),
+            Inline::Strong(vec![]),
+        ])
+    );
+    assert_eq!(changes::report(&doc)[0].kind, ChangeKind::Comment);
+}
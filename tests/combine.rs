@@ -0,0 +1,92 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+fn chapter(title: &str, id: &str, body: Vec<Block>) -> Pandoc {
+    let mut blocks = vec![Block::Header(
+        1,
+        (id.to_owned(), vec![], vec![]),
+        inlines_from_str(title),
+    )];
+    blocks.extend(body);
+    let mut meta = Meta::new();
+    meta.insert("title".to_owned(), MetaValue::from(title));
+    Pandoc {
+        meta,
+        blocks,
+        pandoc_api_version: vec![1, 22],
+    }
+}
+
+#[test]
+fn concat_chapters() {
+    let one = chapter(
+        "One",
+        "intro",
+        vec![Block::Para(vec![Inline::Note(vec![para!["n1"]])])],
+    );
+    let mut two = chapter(
+        "Two",
+        "intro",
+        vec![Block::Para(vec![
+            Inline::link(inlines_from_str("back"), "#intro"),
+            Inline::Note(vec![para!["n2"]]),
+        ])],
+    );
+    notes::to_endnotes(&mut two);
+    let book = Pandoc::concat(
+        vec![one, two],
+        MergeOptions {
+            header_shifts: vec![0, 1],
+            ..MergeOptions::default()
+        },
+    );
+    assert_eq!(book.meta.get_string("title"), Some("One".to_owned()));
+    assert_eq!(book.pandoc_api_version, vec![1, 22]);
+    match book.blocks[2] {
+        Block::Header(2, (ref id, _, _), _) => assert_eq!(id, "intro-1"),
+        ref other => panic!("unexpected {:?}", other),
+    }
+    match book.blocks[3] {
+        Block::Para(ref content) => match content[0] {
+            Inline::Link(_, _, (ref url, _)) => assert_eq!(url, "#intro-1"),
+            ref other => panic!("unexpected {:?}", other),
+        },
+        ref other => panic!("unexpected {:?}", other),
+    }
+    // both notes end up in one list of endnotes
+    assert_eq!(book.blocks.len(), 5);
+    assert_eq!(notes::collect(&book).len(), 0);
+    let mut book = book;
+    notes::from_endnotes(&mut book);
+    assert_eq!(notes::collect(&book).len(), 2);
+}
+
+#[test]
+fn split_chapters() {
+    let mut doc = chapter("Book", "book", vec![para!["preface"]]);
+    doc.blocks.remove(0);
+    doc.blocks.extend(vec![
+        Block::header(1, "One"),
+        Block::Para(vec![Inline::Note(vec![para!["n1"]])]),
+        Block::header(2, "One.One"),
+        Block::header(1, "Two"),
+        Block::Para(vec![Inline::Note(vec![para!["n2"]])]),
+    ]);
+    notes::to_endnotes(&mut doc);
+    let parts = doc.clone().split_at_level(1);
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[0].meta.get_string("title"), Some("Book".to_owned()));
+    assert_eq!(parts[0].blocks, vec![para!["preface"]]);
+    assert_eq!(parts[1].meta.get_string("title"), Some("One".to_owned()));
+    // header, paragraph, subheader, endnotes
+    assert_eq!(parts[1].blocks.len(), 4);
+    assert_eq!(parts[2].meta.get_string("title"), Some("Two".to_owned()));
+    let mut part = parts[2].clone();
+    notes::from_endnotes(&mut part);
+    assert_eq!(notes::collect(&part)[0].content, &[para!["n2"]][..]);
+
+    let mut joined = Pandoc::concat(parts, MergeOptions::default());
+    joined.meta = doc.meta.clone();
+    assert_eq!(joined, doc);
+}
@@ -0,0 +1,71 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::comment::{self, Comment};
+use pandoc_ast::*;
+
+fn html() -> Format {
+    Format("html".to_owned())
+}
+
+#[test]
+fn collecting_and_stripping() {
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![
+        Block::RawBlock(html(), "<!-- TODO(alice): check the numbers -->".to_owned()),
+        Block::Para(vec![
+            Inline::Str("Sales".to_owned()),
+            Inline::RawInline(html(), "<!--FIXME: wrong year-->".to_owned()),
+            Inline::Space,
+            Inline::RawInline(
+                html(),
+                "<b><!-- just a note --></b><!-- NOTE(): Done -->".to_owned(),
+            ),
+        ]),
+        Block::RawBlock(html(), "<!-- NOTE() : unclosed ( <!-- TODO".to_owned()),
+        Block::RawBlock(Format("tex".to_owned()), "<!-- TODO: tex -->".to_owned()),
+    ];
+    let comments = comment::collect(&doc);
+    assert_eq!(
+        comments,
+        vec![
+            Comment {
+                author: Some("alice".to_owned()),
+                tag: "TODO".to_owned(),
+                text: "check the numbers".to_owned(),
+                path: AstPath::block(0),
+            },
+            Comment {
+                author: None,
+                tag: "FIXME".to_owned(),
+                text: "wrong year".to_owned(),
+                path: AstPath::block(1).child(0, 1),
+            },
+            Comment {
+                author: None,
+                tag: String::new(),
+                text: "just a note".to_owned(),
+                path: AstPath::block(1).child(0, 3),
+            },
+            Comment {
+                author: None,
+                tag: "NOTE".to_owned(),
+                text: "Done".to_owned(),
+                path: AstPath::block(1).child(0, 3),
+            },
+        ]
+    );
+
+    comment::strip(&mut doc);
+    assert_eq!(
+        doc.blocks,
+        vec![
+            Block::Para(vec![
+                Inline::Str("Sales".to_owned()),
+                Inline::Space,
+                Inline::RawInline(html(), "<b></b>".to_owned()),
+            ]),
+            Block::RawBlock(html(), "<!-- NOTE() : unclosed ( <!-- TODO".to_owned()),
+            Block::RawBlock(Format("tex".to_owned()), "<!-- TODO: tex -->".to_owned()),
+        ]
+    );
+}
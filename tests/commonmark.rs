@@ -0,0 +1,61 @@
+#![cfg(feature = "commonmark")]
+extern crate pandoc_ast;
+
+use pandoc_ast::commonmark;
+use pandoc_ast::*;
+
+#[test]
+fn markdown_to_blocks() {
+    let markdown = "\
+# Title {#top}
+
+Some *emph* and `code`.[^n]
+
+- one
+- two [link](https://example.com)
+
+1. first
+
+2. second
+
+```rust
+fn main() {}
+```
+
+| a | b |
+|:--|--:|
+| 1 | 2 |
+
+[^n]: The note.
+";
+    let table = table::TableBuilder::new()
+        .header(vec!["a", "b"])
+        .row(vec!["1", "2"])
+        .alignments(vec![Alignment::AlignLeft, Alignment::AlignRight])
+        .build();
+    let expected = vec![
+        Block::Header(1, ("top".to_owned(), vec![], vec![]), inlines!["Title"]),
+        para![
+            "Some ",
+            emph!["emph"],
+            " and ",
+            Inline::Code(Attr::default(), "code".to_owned()),
+            ".",
+            Inline::Note(vec![para!["The note."]]),
+        ],
+        Block::bullet_list(vec![
+            vec![plain!["one"]],
+            vec![plain![
+                "two ",
+                Inline::link(inlines!["link"], "https://example.com")
+            ]],
+        ]),
+        Block::OrderedList(
+            (1, ListNumberStyle::Decimal, ListNumberDelim::Period).into(),
+            vec![vec![para!["first"]], vec![para!["second"]]],
+        ),
+        Block::code_block_with_class("rust", "fn main() {}"),
+        table,
+    ];
+    assert_eq!(commonmark::from_markdown(markdown), expected);
+}
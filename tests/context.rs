@@ -0,0 +1,102 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::context::{CancellationToken, Context};
+use pandoc_ast::transform::{Action, TraversalOrder};
+use pandoc_ast::*;
+use std::sync::{Arc, Mutex};
+
+fn doc() -> Pandoc {
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![para!["a", emph!["b"]]; 10];
+    doc
+}
+
+struct Counter {
+    strs: usize,
+    context: Context,
+}
+
+impl Visitor for Counter {
+    fn visit_inline(&mut self, inline: &Inline) {
+        if let Inline::Str(_) = *inline {
+            self.strs += 1;
+        }
+        self.walk_inline(inline)
+    }
+
+    fn context(&mut self) -> Option<&mut Context> {
+        Some(&mut self.context)
+    }
+}
+
+#[test]
+fn progress_and_cancellation() {
+    let doc = doc();
+    let total = doc.descendants().count();
+    assert_eq!(total, 40);
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = reports.clone();
+    let mut counter = Counter {
+        strs: 0,
+        context: Context::new()
+            .interval(16)
+            .total_estimate(total)
+            .on_progress(move |done, total| sink.lock().unwrap().push((done, total))),
+    };
+    doc.accept(&mut counter);
+    assert_eq!(counter.strs, 20);
+    assert_eq!(counter.context.nodes_done(), 40);
+    assert_eq!(*reports.lock().unwrap(), vec![(16, 40), (32, 40)]);
+    assert!(counter.context.check_cancelled().is_ok());
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let mut counter = Counter {
+        strs: 0,
+        context: Context::new().interval(4).cancelled_by(token),
+    };
+    doc.accept(&mut counter);
+    // the token is polled at the first node, so nothing below it is visited
+    assert_eq!(counter.strs, 0);
+    assert_eq!(counter.context.nodes_done(), 1);
+    match counter.context.check_cancelled() {
+        Err(Error::Cancelled) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn cancelled_transforms() {
+    let mut doc = doc();
+    let mut context = Context::new().interval(1);
+    let token = context.token();
+    let mut seen = 0;
+    let result =
+        transform::blocks_with_context(&mut doc, TraversalOrder::TopDown, &mut context, |_| {
+            seen += 1;
+            token.cancel();
+            Action::Keep
+        });
+    assert!(result.is_err());
+    // the splicer stops calling `f` as soon as the token is cancelled
+    assert_eq!(seen, 1);
+    let mut untouched = doc.clone();
+    transform::inlines_with_context(
+        &mut untouched,
+        TraversalOrder::BottomUp,
+        &mut context,
+        |_| Action::Delete,
+    )
+    .unwrap_err();
+    assert_eq!(untouched, doc);
+
+    let mut context = Context::new();
+    transform::inlines_with_context(&mut doc, TraversalOrder::BottomUp, &mut context, |inline| {
+        match *inline {
+            Inline::Emph(_) => Action::Delete,
+            _ => Action::Keep,
+        }
+    })
+    .unwrap();
+    assert_eq!(doc.blocks[9], para!["a"]);
+}
@@ -0,0 +1,53 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn navigation() {
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![
+        Block::BlockQuote(vec![para!["quoted"], para!["more"]]),
+        para!["after"],
+    ];
+    let mut cursor = Cursor::new(&mut doc).unwrap();
+    assert_eq!(cursor.node().name(), "BlockQuote");
+    assert!(cursor.parent_node().is_none());
+    assert!(!cursor.parent());
+    assert!(!cursor.prev_sibling());
+
+    assert!(cursor.first_child());
+    assert_eq!(cursor.path().to_string(), "0/0");
+    assert_eq!(
+        cursor.parent_node().map(|node| node.name()),
+        Some("BlockQuote")
+    );
+    assert!(cursor.next_sibling());
+    assert!(!cursor.next_sibling());
+    assert_eq!(cursor.node(), Node::Block(&para!["more"]));
+    assert!(cursor.first_child());
+    assert!(!cursor.first_child());
+    assert_eq!(cursor.node(), Node::Inline(&Inline::Str("more".to_owned())));
+
+    assert_eq!(
+        cursor.replace(OwnedNode::Block(Block::HorizontalRule)),
+        Err(OwnedNode::Block(Block::HorizontalRule))
+    );
+    assert_eq!(
+        cursor.replace(OwnedNode::Inline(emph!["most"])),
+        Ok(OwnedNode::Inline(Inline::Str("more".to_owned())))
+    );
+    assert!(cursor.parent());
+    assert!(cursor.prev_sibling());
+    if let NodeMut::Block(block) = cursor.node_mut() {
+        *block = para!["changed"];
+    }
+    assert!(cursor.parent());
+    assert!(cursor.next_sibling());
+    assert_eq!(cursor.path(), &AstPath::block(1));
+    assert!(Cursor::at(&mut doc, AstPath::block(2)).is_none());
+
+    assert_eq!(
+        doc.blocks[0],
+        Block::BlockQuote(vec![para!["changed"], para![emph!["most"]]])
+    );
+}
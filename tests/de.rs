@@ -10,6 +10,22 @@ fn format() {
     assert_eq!(format.0, "hello");
 }
 
+#[test]
+fn format_aliases() {
+    let format = |name: &str| Format(name.to_owned());
+    assert!(format("LaTeX").matches("latex"));
+    assert!(format("tex").matches("latex") && format("latex").matches("tex"));
+    assert!(format("tex").matches("ConTeXt"));
+    assert!(!format("latex").matches("context"));
+    assert!(format("html").matches("html5") && format("HTML4").matches("html"));
+    assert!(!format("html4").matches("html5"));
+    assert!(format("context").is_tex() && format("Beamer").is_tex());
+    assert!(format("tex").matches("beamer") && format("beamer").matches("latex"));
+    assert!(!format("beamer").matches("context") && !format("markdown").is_tex());
+    assert!(format("Html5").is_html() && !format("xhtml").is_html());
+    assert!(format("HTML") == "html" && format("tex") != "latex");
+}
+
 /*
 #[test]
 fn citation() {
@@ -50,4 +66,427 @@ fn table_with_col_width() {
     let value: serde_json::Value = serde_json::from_str(s).unwrap();
     println!("{:?}", value);
     let _: Pandoc = serde_json::from_value(value).unwrap();
-}
\ No newline at end of file
+}
+
+#[test]
+fn version_errors() {
+    match Pandoc::try_from_json(r#"{"pandoc-api-version":[1,17],"meta":{},"blocks":[]}"#) {
+        Err(Error::VersionMismatch {
+            major: 1,
+            minor: 17,
+        }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    match Pandoc::try_from_json(r#"{"meta":{},"blocks":[]}"#) {
+        Err(Error::MissingVersion) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(
+        Pandoc::try_from_json(r#"{"pandoc-api-version":[1,22],"meta":{},"blocks":[]}"#).is_ok()
+    );
+}
+
+#[test]
+fn reader_and_writer() {
+    let input = r#"{"pandoc-api-version":[1,22],"meta":{},"blocks":[{"t":"Para","c":[{"t":"Str","c":"a"}]}]}"#;
+    let doc = Pandoc::from_reader(input.as_bytes()).unwrap();
+    let mut output = Vec::new();
+    doc.to_writer(&mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), doc.to_json());
+
+    let mut filtered = Vec::new();
+    filter_io(input.as_bytes(), &mut filtered, |mut doc| {
+        doc.blocks.clear();
+        doc
+    })
+    .unwrap();
+    let filtered = Pandoc::from_json(&String::from_utf8(filtered).unwrap());
+    assert!(filtered.blocks.is_empty());
+    match Pandoc::from_reader(&br#"{"pandoc-api-version":[1,17],"meta":{},"blocks":[]}"#[..]) {
+        Err(Error::VersionMismatch {
+            major: 1,
+            minor: 17,
+        }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn lazy_blocks() {
+    let input = r#"{"pandoc-api-version":[1,22],"meta":{},"blocks":[{"c":[{"t":"Str","c":"a"}],"t":"Para"},{"t":"CodeBlock","c":[["",["rust"],[]],"x"]}]}"#;
+    let mut doc = Pandoc::from_json_lazy(input).unwrap();
+    for block in &mut doc.blocks {
+        let is_code = match *block {
+            Block::Unparsed(ref unparsed) => unparsed.name().unwrap() == "CodeBlock",
+            _ => false,
+        };
+        if is_code {
+            if let Block::CodeBlock(_, ref mut code) = *block.ensure_parsed().unwrap() {
+                code.push('y');
+            }
+        }
+    }
+    // the untouched paragraph is written back as it was read
+    assert_eq!(
+        doc.to_json(),
+        r#"{"meta":{},"blocks":[{"c":[{"t":"Str","c":"a"}],"t":"Para"},{"t":"CodeBlock","c":[["",["rust"],[]],"xy"]}],"pandoc-api-version":[1,22]}"#
+    );
+    doc.ensure_parsed().unwrap();
+    let mut expected = Pandoc::from_json(input);
+    expected.blocks[1] = Block::code_block_with_class("rust", "xy");
+    assert_eq!(doc, expected);
+}
+
+#[test]
+fn filter_blocks_keeps_meta() {
+    let input = r#"{"pandoc-api-version":[1,22],"meta":{"title":{"c":[{"t":"Str","c":"T"}],"t":"MetaInlines"}},"blocks":[{"t":"Para","c":[{"t":"Str","c":"a"}]}]}"#;
+    let output = filter_blocks(input.to_owned(), |mut blocks| {
+        blocks.push(Block::HorizontalRule);
+        blocks
+    });
+    assert_eq!(
+        output,
+        r#"{"meta":{"title":{"c":[{"t":"Str","c":"T"}],"t":"MetaInlines"}},"blocks":[{"t":"Para","c":[{"t":"Str","c":"a"}]},{"t":"HorizontalRule"}],"pandoc-api-version":[1,22]}"#
+    );
+}
+
+#[test]
+fn pretty_and_canonical() {
+    let input = r#"{"pandoc-api-version":[1,22],"meta":{},"blocks":[{"t":"Para","c":[{"t":"Str","c":"a"}]}]}"#;
+    let doc = Pandoc::from_json(input);
+    assert_eq!(Pandoc::from_json(&doc.to_json_pretty()), doc);
+    assert_eq!(
+        doc.to_json_canonical(),
+        r#"{
+  "blocks": [
+    {
+      "c": [
+        {
+          "c": "a",
+          "t": "Str"
+        }
+      ],
+      "t": "Para"
+    }
+  ],
+  "meta": {},
+  "pandoc-api-version": [
+    1,
+    22
+  ]
+}
+"#
+    );
+}
+
+#[test]
+fn validated_errors() {
+    let json = r#"{"pandoc-api-version":[1,24],"meta":{},"blocks":[
+        {"t":"Para","c":[{"t":"Str","c":"a"}]},
+        {"t":"BulletList","c":[[{"t":"Plain","c":[{"t":"Space"},{"t":"Fancy","c":[]}]}]]}
+    ]}"#;
+    match Pandoc::from_json_validated(json) {
+        Err(Error::Schema {
+            path, constructor, ..
+        }) => {
+            assert_eq!(path, "blocks[1].c[0][0].c[1].t");
+            assert_eq!(constructor.as_deref(), Some("Fancy"));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    let message = Pandoc::from_json_validated(json).unwrap_err().to_string();
+    assert_eq!(
+        message,
+        "blocks[1].c[0][0].c[1].t (in Fancy): unknown constructor \"Fancy\"; the document uses \
+         pandoc-api-version 1.24, which is newer than the 1.23 supported here"
+    );
+    let json = json.replace("Fancy", "Emph").replace("1,24", "1,23");
+    let header = json.replace(
+        r#"{"t":"Str","c":"a"}"#,
+        r#"{"t":"Header","c":["1",["",[],[]],[]]}"#,
+    );
+    match Pandoc::from_json_validated(&header) {
+        Err(Error::Schema {
+            path, constructor, ..
+        }) => {
+            assert_eq!(path, "blocks[0].c[0]");
+            assert_eq!(constructor.as_deref(), Some("Header"));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(Pandoc::from_json_validated(&json).is_ok());
+}
+
+#[test]
+fn lenient_unknown_nodes() {
+    let json = r#"{"meta":{},"blocks":[{"t":"Aside","c":[["",[],[]],[]]},{"t":"Para","c":[{"t":"Emph","c":[{"t":"Str","c":"a"},{"t":"Tab"}]}]}],"pandoc-api-version":[1,24]}"#;
+    assert!(Pandoc::try_from_json(json).is_err());
+    let doc = Pandoc::from_json_lenient(json).unwrap();
+    assert_eq!(
+        doc.blocks[0],
+        Block::Unknown("Aside".into(), serde_json::json!([["", [], []], []]))
+    );
+    assert_eq!(
+        doc.blocks[1],
+        Block::Para(vec![Inline::Emph(vec![
+            Inline::Str("a".into()),
+            Inline::Unknown("Tab".into(), serde_json::Value::Null),
+        ])])
+    );
+    assert_eq!(doc.to_json(), json);
+    // strict parsing is back afterwards
+    assert!(Pandoc::try_from_json(json).is_err());
+}
+
+#[test]
+fn lenient_errors_in_known_nodes() {
+    let doc = |block: &str| {
+        format!(
+            r#"{{"pandoc-api-version":[1,23],"meta":{{}},"blocks":[{}]}}"#,
+            block
+        )
+    };
+    let table = r#"{"t":"Table","c":[["",[],[]],[null,[]],[[{"t":"AlignBogus"},{"t":"ColWidthDefault"}]],[["",[],[]],[]],[],[["",[],[]],[]]]}"#;
+    assert!(Pandoc::from_json_lenient(&doc(table)).is_err());
+    let quoted = r#"{"t":"Para","c":[{"t":"Quoted","c":[{"t":"TripleQuote"},[]]}]}"#;
+    assert!(Pandoc::from_json_lenient(&doc(quoted)).is_err());
+
+    // the content may come before the tag
+    let reordered = r#"{"c":[{"c":"a","t":"Str"},{"c":[],"t":"Tab"}],"t":"Para"}"#;
+    let parsed = Pandoc::from_json_lenient(&doc(reordered)).unwrap();
+    assert_eq!(
+        parsed.blocks,
+        [Block::Para(vec![
+            Inline::Str("a".into()),
+            Inline::Unknown("Tab".into(), serde_json::json!([])),
+        ])]
+    );
+    let old = r#"{"pandoc-api-version":[1,17],"meta":{},"blocks":[]}"#;
+    match Pandoc::from_json_lenient(old) {
+        Err(Error::VersionMismatch { .. }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn lenient_wrapper() {
+    let json =
+        r#"[{"t":"Div","c":[["",[],[]],[{"t":"Figure2","c":1}]]},{"t":"Plain","c":[{"t":"Tab"}]}]"#;
+    let expected = vec![
+        Block::Div(
+            Attr::default(),
+            vec![Block::Unknown("Figure2".into(), serde_json::json!(1))],
+        ),
+        Block::Plain(vec![Inline::Unknown("Tab".into(), serde_json::Value::Null)]),
+    ];
+    let Lenient(blocks): Lenient<Vec<Block>> = serde_json::from_str(json).unwrap();
+    assert_eq!(blocks, expected);
+    // from other deserializers, and nested in other types
+    let value =
+        serde_json::json!({ "blocks": serde_json::from_str::<serde_json::Value>(json).unwrap() });
+    let Lenient(map): Lenient<std::collections::HashMap<String, Option<Vec<Block>>>> =
+        serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(map["blocks"], Some(expected));
+    // without the wrapper, parsing stays strict
+    assert!(serde_json::from_str::<Vec<Block>>(json).is_err());
+    assert!(
+        serde_json::from_value::<std::collections::HashMap<String, Vec<Block>>>(value).is_err()
+    );
+}
+
+#[test]
+fn document_streams() {
+    let doc = |text: &str| Pandoc::builder().block(para![text]).build().to_json();
+    // json lines, followed by documents without separator
+    let input = format!("{}\n{}{}", doc("a"), doc("b"), doc("c"));
+    let mut output = Vec::new();
+    let mut count = 0;
+    filter_stream(input.as_bytes(), &mut output, |mut doc| {
+        count += 1;
+        doc.blocks.push(Block::HorizontalRule);
+        doc
+    })
+    .unwrap();
+    assert_eq!(count, 3);
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    let last = Pandoc::from_json(lines[2]);
+    assert_eq!(last.blocks, vec![para!["c"], Block::HorizontalRule]);
+}
+
+#[test]
+fn untrusted_input() {
+    let json = Pandoc::builder()
+        .title("Title")
+        .block(para!["Some ", emph!["text"]])
+        .block(Block::bullet_list(vec![vec![plain!["item"]]]))
+        .build()
+        .to_json();
+    assert!(parse_untrusted(json.as_bytes()).is_ok());
+    // every single byte replaced by characters that matter in json
+    for i in 0..json.len() {
+        for &byte in b"\"{}[]0:,t\\x" {
+            let mut mutated = json.clone().into_bytes();
+            mutated[i] = byte;
+            let _ = parse_untrusted(&mutated);
+        }
+    }
+    let nested = format!(
+        r#"{{"pandoc-api-version":[1,23],"meta":{{}},"blocks":[{}{}]}}"#,
+        r#"{"t":"BlockQuote","c":["#.repeat(10_000),
+        "]}".repeat(10_000)
+    );
+    assert!(parse_untrusted(nested.as_bytes()).is_err());
+    let numeric_tag = r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[{"t":5}]}"#;
+    assert!(parse_untrusted(numeric_tag.as_bytes()).is_err());
+}
+
+#[test]
+fn metadata_key_order() {
+    let input = r#"{"meta":{"title":{"t":"MetaString","c":"T"},"author":{"t":"MetaMap","c":{"name":{"t":"MetaString","c":"A"},"email":{"t":"MetaString","c":"a@b"}}},"date":{"t":"MetaBool","c":true}},"blocks":[],"pandoc-api-version":[1,23]}"#;
+    let mut doc = Pandoc::from_json(input);
+    let keys: Vec<&str> = doc.meta.keys().map(String::as_str).collect();
+    if cfg!(feature = "preserve-order") {
+        assert_eq!(keys, vec!["title", "author", "date"]);
+        assert_eq!(doc.to_json(), input);
+    } else {
+        assert_eq!(keys, vec!["author", "date", "title"]);
+    }
+    doc.meta.remove("author");
+    let keys: Vec<&str> = doc.meta.keys().map(String::as_str).collect();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(Pandoc::from_json(&doc.to_json()), doc);
+
+    // the same code compiles with and without the feature, and the order
+    // doesn't matter for equality
+    let mut sorted = std::collections::BTreeMap::new();
+    sorted.insert("title".to_owned(), MetaValue::MetaBool(true));
+    sorted.insert("date".to_owned(), MetaValue::MetaBool(true));
+    let meta: Map<String, MetaValue> = sorted.into();
+    let reversed: Map<String, MetaValue> = meta.clone().into_iter().rev().collect();
+    assert_eq!(meta, reversed);
+    assert_eq!(meta["title"], MetaValue::MetaBool(true));
+}
+
+#[test]
+fn fast_serializer() {
+    let mut doc = Pandoc::new();
+    doc.meta = Meta::from_json_str(r#"{"title": "T\u0001\"é\"", "draft": true, "n": 3}"#).unwrap();
+    doc.meta
+        .insert("pages".to_owned(), MetaValue::MetaNumber((-12).into()));
+    doc.meta.insert(
+        "abstract".to_owned(),
+        MetaValue::MetaBlocks(vec![para!["a\tb\\c\n"]]),
+    );
+    let citation = Citation {
+        citationId: "knuth".to_owned(),
+        citationPrefix: inlines!["see"],
+        citationSuffix: vec![],
+        citationMode: CitationMode::AuthorInText,
+        citationNoteNum: 1,
+        citationHash: -3,
+    };
+    doc.blocks = vec![
+        Block::Header(
+            2,
+            (
+                "id".into(),
+                vec!["a".into()],
+                vec![("k".into(), "v".into())],
+            ),
+            inlines!["H"],
+        ),
+        para![
+            emph!["x"],
+            Inline::Quoted(QuoteType::DoubleQuote, inlines!["q"]),
+            Inline::Cite(vec![citation], inlines!["@knuth"]),
+            Inline::Math(MathType::InlineMath, "x^2".into()),
+            Inline::RawInline(Format("html".into()), "<br>".into()),
+            Inline::link(inlines!["l"], "https://example.com"),
+            Inline::Note(vec![Block::Null]),
+            Inline::LineBreak,
+            Inline::SoftBreak,
+            Inline::Code(Default::default(), "c".into())
+        ],
+        Block::OrderedList(ListAttributes::default(), vec![vec![plain!["1"]], vec![]]),
+        Block::DefinitionList(vec![(inlines!["term"], vec![vec![para!["def"]]])]),
+        Block::LineBlock(vec![inlines!["a"], inlines![]]),
+        Block::HorizontalRule,
+        Block::Figure(Default::default(), (Some(inlines!["s"]), vec![]), vec![]),
+        Block::RawBlock(Format("tex".into()), "\\x\u{7f}\u{1f}".into()),
+    ];
+    assert_eq!(doc.to_json_fast(), doc.to_json());
+
+    let tables = r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[{"t":"Table","c":[["t",[],[]],[null,[]],[[{"t":"AlignCenter"},{"t":"ColWidth","c":0.25}],[{"t":"AlignRight"},{"t":"ColWidthDefault"}]],[["",[],[]],[[["",[],[]],[[["",[],[]],{"t":"AlignDefault"},1,2,[{"t":"Plain","c":[{"t":"Str","c":"Name"}]}]]]]]],[[["",[],[]],1,[],[[["",[],[]],[[["",[],[]],{"t":"AlignLeft"},2,1,[]]]]]]],[["",[],[]],[]]]},{"t":"Aside","c":[1]},{"t":"Div","c":[["",[],[]],[{"t":"Para","c":[{"t":"Span","c":[["",[],[]],[{"t":"Tab"}]]}]}]]}]}"#;
+    let doc = Pandoc::from_json_lenient(tables).unwrap();
+    assert_eq!(doc.to_json_fast(), doc.to_json());
+    let doc = Pandoc::from_json_lazy(tables).unwrap();
+    assert_eq!(doc.to_json_fast(), doc.to_json());
+
+    let mut written = Vec::new();
+    doc.to_writer_fast(&mut written).unwrap();
+    assert_eq!(String::from_utf8(written).unwrap(), doc.to_json());
+}
+
+#[test]
+fn round_trip_edge_cases() {
+    let mut table = table::TableBuilder::new()
+        .header(vec!["a", "b", "c"])
+        .row(vec!["", "", ""])
+        .widths(vec![0.0, -0.0, 1.0 / 3.0])
+        .build();
+    if let Block::Table(_, _, _, _, ref mut bodies, _) = table {
+        bodies[0].3[0].1[0].2 = 0;
+    }
+    let mut doc = Pandoc::new();
+    doc.meta.insert(
+        "ratio".to_owned(),
+        MetaValue::MetaNumber(serde_json::Number::from_f64(0.1).unwrap()),
+    );
+    doc.meta
+        .insert("empty".to_owned(), MetaValue::MetaString(String::new()));
+    doc.blocks = vec![
+        Block::HorizontalRule,
+        Block::Null,
+        Block::Div(Attr::default(), vec![]),
+        Block::LineBlock(vec![vec![], inlines![""]]),
+        para![
+            Inline::Span(
+                ("".into(), vec!["".into()], vec![("".into(), "".into())]),
+                vec![]
+            ),
+            Inline::Space,
+            Inline::LineBreak,
+            Inline::Str(String::new()),
+        ],
+        table,
+    ];
+    let json = doc.to_json();
+    assert!(json.contains(r#"{"t":"HorizontalRule"}"#));
+    assert!(json.contains(r#"{"t":"ColWidth","c":-0.0}"#));
+    assert_eq!(Pandoc::from_json(&json), doc);
+}
+
+#[test]
+fn nullary_constructors() {
+    let with_content = r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[{"t":"HorizontalRule","c":[]},{"t":"Null","c":[]},{"t":"Para","c":[{"t":"Str","c":"a"},{"t":"Space","c":[]},{"t":"SoftBreak","c":[]},{"t":"LineBreak","c":[]}]},{"t":"Table","c":[["",[],[]],[null,[]],[[{"t":"AlignDefault","c":[]},{"t":"ColWidthDefault","c":[]}]],[["",[],[]],[]],[],[["",[],[]],[]]]}]}"#;
+    let doc = Pandoc::from_json(with_content);
+    let json = doc.to_json();
+    for node in &[
+        "HorizontalRule",
+        "Null",
+        "Space",
+        "SoftBreak",
+        "LineBreak",
+        "AlignDefault",
+        "ColWidthDefault",
+    ] {
+        assert!(json.contains(&format!(r#"{{"t":"{}"}}"#, node)), "{}", node);
+    }
+    assert_eq!(Pandoc::from_json(&json), doc);
+
+    let content =
+        r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[{"t":"HorizontalRule","c":[1]}]}"#;
+    assert!(Pandoc::try_from_json(content).is_err());
+}
@@ -0,0 +1,73 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::definition::{DefinitionList, Entry};
+use pandoc_ast::table::{Table, TableBuilder};
+use pandoc_ast::*;
+
+#[test]
+fn building_and_sorting() {
+    let mut list = DefinitionList::from_pairs(vec![("pear", "a fruit"), ("Apple", "red")])
+        .entry("banana", vec![emph!["yellow"]]);
+    list.sort_by_term();
+    assert_eq!(
+        list.into_block(),
+        Block::DefinitionList(vec![
+            (inlines!["Apple"], vec![vec![plain!["red"]]]),
+            (inlines!["banana"], vec![vec![plain![emph!["yellow"]]]]),
+            (inlines!["pear"], vec![vec![plain!["a fruit"]]]),
+        ])
+    );
+    let block = Block::DefinitionList(vec![(inlines!["term"], vec![])]);
+    let list = DefinitionList::from_block(block.clone()).unwrap();
+    assert_eq!(
+        list.entries,
+        vec![Entry {
+            term: inlines!["term"],
+            definitions: vec![]
+        }]
+    );
+    assert_eq!(list.into_block(), block);
+    assert_eq!(
+        DefinitionList::from_block(Block::HorizontalRule),
+        Err(Block::HorizontalRule)
+    );
+}
+
+#[test]
+fn tables() {
+    let table = TableBuilder::new()
+        .header(vec!["Term", "Definition"])
+        .row(vec![inlines!["one ", strong!["1"]], inlines!["the first"]])
+        .row(vec!["two"])
+        .build();
+    let list = DefinitionList::from_table(Table::from_block(table).unwrap()).unwrap();
+    assert_eq!(
+        list,
+        DefinitionList {
+            entries: vec![
+                Entry::new(inlines!["one ", strong!["1"]], "the first"),
+                Entry {
+                    term: inlines!["two"],
+                    definitions: vec![],
+                },
+            ],
+        }
+    );
+
+    let mut list = DefinitionList::from_pairs(vec![("a", "first")]);
+    list.entries[0].definitions.push(vec![para!["second"]]);
+    let table = list.clone().into_table();
+    assert_eq!(table.col_specs.len(), 2);
+    let row = &table.bodies[0].3[0].1;
+    assert_eq!(row[0].4, vec![plain!["a"]]);
+    assert_eq!(row[1].4, vec![plain!["first"], para!["second"]]);
+    let back = DefinitionList::from_table(table).unwrap();
+    assert_eq!(back.entries[0].term, inlines!["a"]);
+    assert_eq!(
+        back.entries[0].definitions,
+        vec![vec![plain!["first"], para!["second"]]]
+    );
+
+    let three = TableBuilder::new().row(vec!["a", "b", "c"]).build();
+    assert!(DefinitionList::from_table(Table::from_block(three).unwrap()).is_err());
+}
@@ -0,0 +1,73 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::diagnostics::{Diagnostic, Reporter, Severity};
+use pandoc_ast::sourcepos::{SourceMap, SourcePos};
+use pandoc_ast::*;
+
+#[test]
+fn reporting() {
+    let source = "# Cats\n\nSome ![](cat.png) text\n";
+    let mut map = SourceMap::new();
+    let pos = |line, column| SourcePos {
+        file: Some("notes.md".to_owned()),
+        line,
+        column,
+    };
+    map.insert(AstPath::block(0), pos(1, 1));
+    map.insert(AstPath::block(1), pos(3, 1));
+    map.insert(AstPath::block(1).child(0, 2), pos(3, 6));
+
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![
+        Block::Header(1, (String::new(), vec![], vec![]), inlines!["Cats"]),
+        para![
+            "Some ",
+            Inline::Image(
+                (String::new(), vec![], vec![]),
+                vec![],
+                ("cat.png".to_owned(), String::new())
+            ),
+            " text"
+        ],
+    ];
+    let mut reporter = Reporter::new().source_map(map).source(source);
+    for issue in validate::accessibility(&doc) {
+        reporter.report(issue);
+    }
+    reporter.note(&AstPath::block(0).child(0, 0), "consider a longer title");
+    reporter.report(Diagnostic::error(AstPath::block(7), "something is off"));
+    assert!(reporter.has_errors());
+
+    assert_eq!(
+        reporter.render(),
+        "warning: image without alt text
+ --> notes.md:3:6
+  |
+3 | Some ![](cat.png) text
+  |      ^
+  = at 1/2
+
+note: consider a longer title
+ --> notes.md:1:1
+  |
+1 | # Cats
+  | ^
+  = at 0/0
+
+error: something is off
+  = at 7
+"
+    );
+    assert_eq!(
+        reporter.diagnostics()[0].to_string(),
+        "notes.md:3:6: warning: image without alt text"
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&reporter.to_json()).unwrap();
+    assert_eq!(json[0]["severity"], "warning");
+    assert_eq!(json[0]["path"], "1/2");
+    assert_eq!(json[0]["source_pos"]["line"], 3);
+    assert_eq!(json[0]["source_pos"]["column"], 6);
+    assert_eq!(json[2]["source_pos"], serde_json::Value::Null);
+    assert_eq!(reporter.diagnostics()[2].severity, Severity::Error);
+}
@@ -0,0 +1,48 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::diff::{diff, Edit};
+use pandoc_ast::*;
+
+fn doc(blocks: Vec<Block>) -> Pandoc {
+    Pandoc {
+        meta: Meta::new(),
+        blocks,
+        pandoc_api_version: vec![1, 22],
+    }
+}
+
+#[test]
+fn diff_documents() {
+    let old = doc(vec![
+        Block::header(1, "Title"),
+        para!["one two three"],
+        Block::HorizontalRule,
+        para!["gone"],
+    ]);
+    let mut new = doc(vec![
+        Block::header(1, "Title"),
+        para!["one", strong!["two"], "three"],
+        Block::HorizontalRule,
+        Block::code_block("x"),
+    ]);
+    new.meta.insert("draft".to_owned(), MetaValue::MetaBool(true));
+    let script = diff(&old, &new);
+    assert_eq!(
+        script.edits[0],
+        Edit::Meta {
+            key: "draft".to_owned(),
+            old: None,
+            new: Some(MetaValue::MetaBool(true)),
+        }
+    );
+    assert_eq!(
+        script.to_string(),
+        r#"~ meta draft: nothing -> "true"
+~ 1/1 Space -> Strong "two"
+- 1/2 Str "two"
+- 1/2 Space
+~ 3 Para "gone" -> CodeBlock
+"#
+    );
+    assert!(diff(&new, &new).is_empty());
+}
@@ -0,0 +1,86 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::directive::{Args, Directives};
+use pandoc_ast::transform::Action;
+use pandoc_ast::*;
+
+fn directive(value: &str) -> Attr {
+    (
+        String::new(),
+        vec!["keep".to_owned()],
+        vec![("data-macro".to_owned(), value.to_owned())],
+    )
+}
+
+#[test]
+fn parsing_arguments() {
+    let args = Args::parse(r#"figure cat.png width=50% caption="A \"big\" cat" """#).unwrap();
+    assert_eq!(args.name, "figure");
+    assert_eq!(args.positional, vec!["cat.png".to_owned(), String::new()]);
+    assert_eq!(args.get("width"), Some("50%"));
+    assert_eq!(args.get("caption"), Some(r#"A "big" cat"#));
+    assert_eq!(Args::parse("  "), None);
+    assert_eq!(Args::parse(r#"figure "open"#), None);
+}
+
+#[test]
+fn dispatching() {
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![
+        Block::Div(
+            directive("box title=Outer"),
+            vec![Block::Para(vec![
+                Inline::Span(directive("kbd Ctrl C"), vec![]),
+                Inline::Span(directive("unknown"), vec![]),
+            ])],
+        ),
+        Block::CodeBlock(directive("drop"), "code".to_owned()),
+        Block::Div(directive("box \"unterminated"), vec![]),
+    ];
+    let mut seen = Vec::new();
+    let result = Directives::new("data-macro")
+        .inline("kbd", |args, _| {
+            Action::Replace(Inline::Code(
+                (String::new(), vec!["kbd".to_owned()], vec![]),
+                args.positional.join("+"),
+            ))
+        })
+        .block("box", |args, block| {
+            seen.push(args.get("title").map(str::to_owned));
+            if let Block::Div(ref mut attr, _) = *block {
+                attr.1.push("box".to_owned());
+            }
+            Action::Keep
+        })
+        .block("drop", |_, _| Action::Delete)
+        .apply(&mut doc);
+
+    match result {
+        Err(Error::InvalidAttribute { key, value }) => {
+            assert_eq!(key, "data-macro");
+            assert_eq!(value, "box \"unterminated");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert_eq!(seen, vec![Some("Outer".to_owned())]);
+    assert_eq!(
+        doc.blocks,
+        vec![
+            Block::Div(
+                (
+                    String::new(),
+                    vec!["keep".to_owned(), "box".to_owned()],
+                    vec![]
+                ),
+                vec![Block::Para(vec![
+                    Inline::Code(
+                        (String::new(), vec!["kbd".to_owned()], vec![]),
+                        "Ctrl+C".to_owned()
+                    ),
+                    Inline::Span(directive("unknown"), vec![]),
+                ])],
+            ),
+            Block::Div(directive("box \"unterminated"), vec![]),
+        ]
+    );
+}
@@ -0,0 +1,34 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn dump_tree() {
+    let json = r#"{"pandoc-api-version":[1,22],"meta":{"draft":{"t":"MetaBool","c":true},"title":{"t":"MetaInlines","c":[{"t":"Str","c":"T"}]}},"blocks":[{"t":"Header","c":[1,["intro",["c"],[["k","v"]]],[{"t":"Str","c":"Intro"}]]},{"t":"BulletList","c":[[{"t":"Plain","c":[{"t":"Str","c":"a"}]}],[{"t":"Plain","c":[{"t":"Link","c":[["",[],[]],[{"t":"Str","c":"b"}],["https://example.com",""]]}]}]]},{"t":"CodeBlock","c":[["",[],[]],"0123456789012345678901234567890123456789xyz"]}]}"#;
+    assert_eq!(
+        Pandoc::from_json(json).dump_tree(),
+        r#"Pandoc
+  draft MetaBool true
+  title MetaInlines
+    Str "T"
+  Header 1 #intro .c k="v"
+    Str "Intro"
+  BulletList
+    -
+      Plain
+        Str "a"
+    -
+      Plain
+        Link "https://example.com"
+          Str "b"
+  CodeBlock "0123456789012345678901234567890123456789"…
+"#
+    );
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn dump_tree_colored() {
+    let doc = Pandoc::from_json(r#"{"pandoc-api-version":[1,22],"meta":{},"blocks":[]}"#);
+    assert_eq!(doc.dump_tree_colored(), "\x1b[1;34mPandoc\x1b[0m\n");
+}
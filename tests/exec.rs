@@ -0,0 +1,58 @@
+#![cfg(all(unix, feature = "exec"))]
+extern crate pandoc_ast;
+extern crate serde_json;
+
+use pandoc_ast::exec::Executable;
+use pandoc_ast::*;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+/// A stand-in for pandoc 2.x that reads everything as a single figure-less
+/// paragraph and echoes the json it is asked to write
+fn fake_pandoc(name: &str, script: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pandoc-ast-{}-{}", name, std::process::id()));
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn read_and_write() {
+    let path = fake_pandoc(
+        "echo",
+        r#"#!/bin/sh
+if [ "$2" = json ]; then cat; exit 0; fi
+cat > /dev/null
+echo '{"pandoc-api-version":[1,22],"meta":{},"blocks":[{"t":"Para","c":[{"t":"Image","c":[["",[],[]],[],["a.png","fig:"]]}]}]}'
+"#,
+    );
+    let pandoc = Executable::with_program(&path);
+    let doc = pandoc
+        .read("![](a.png)", "markdown", &[] as &[&str])
+        .unwrap();
+    assert_eq!(doc.pandoc_api_version, vec![1, 23]);
+    assert!(matches!(doc.blocks[..], [Block::Figure(..)]));
+    assert_eq!(pandoc.api_version().unwrap(), [1, 22]);
+    let json = pandoc.write(&doc, "json", &[] as &[&str]).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["pandoc-api-version"], serde_json::json!([1, 22]));
+    assert_eq!(json["blocks"][0]["c"][0]["c"][2][1], "fig:");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn errors_include_stderr() {
+    let path = fake_pandoc(
+        "fail",
+        "#!/bin/sh\necho 'Unknown input format nope' >&2\nexit 21\n",
+    );
+    let err = Executable::with_program(&path)
+        .read("text", "nope", &[] as &[&str])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "pandoc exited with status 21: Unknown input format nope"
+    );
+    fs::remove_file(path).unwrap();
+}
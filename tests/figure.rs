@@ -0,0 +1,76 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+fn implicit() -> Block {
+    para![Inline::Image(
+        (
+            "fig-1".to_owned(),
+            vec![],
+            vec![("width".to_owned(), "50%".to_owned())]
+        ),
+        inlines!["A caption"],
+        ("plot.png".to_owned(), "fig:".to_owned()),
+    )]
+}
+
+#[test]
+fn conversion() {
+    let mut block = implicit();
+    assert!(figure::is_implicit_figure(&block));
+    assert!(!figure::is_implicit_figure(&para![Inline::image(
+        vec![],
+        "x.png"
+    )]));
+    assert_eq!(figure::caption(&block), Some(inlines!["A caption"]));
+
+    assert!(figure::to_figure(&mut block));
+    assert_eq!(
+        block,
+        Block::Figure(
+            ("fig-1".to_owned(), vec![], vec![]),
+            (None, vec![plain!["A caption"]]),
+            vec![plain![Inline::Image(
+                (
+                    String::new(),
+                    vec![],
+                    vec![("width".to_owned(), "50%".to_owned())]
+                ),
+                inlines!["A caption"],
+                ("plot.png".to_owned(), String::new()),
+            )]],
+        )
+    );
+    assert!(figure::set_caption(&mut block, inlines!["New"]));
+    assert_eq!(figure::caption(&block), Some(inlines!["New"]));
+    assert_eq!(
+        select::select(&block, &select::Selector::parse("figure image").unwrap()).len(),
+        1
+    );
+
+    let mut doc = vec![block];
+    figure::to_implicit_figures(&mut doc);
+    let mut expected = implicit();
+    figure::set_caption(&mut expected, inlines!["New"]);
+    assert_eq!(doc, vec![expected]);
+    figure::to_figures(&mut doc);
+    assert!(matches!(doc[0], Block::Figure(..)));
+}
+
+#[test]
+fn json() {
+    let json = r#"{"t":"Figure","c":[["",[],[]],[null,[]],[{"t":"Plain","c":[]}]]}"#;
+    let block: Block = parse_block(json);
+    assert_eq!(
+        block,
+        Block::Figure(Attr::default(), (None, vec![]), vec![plain![]])
+    );
+}
+
+fn parse_block(json: &str) -> Block {
+    let doc = format!(
+        r#"{{"pandoc-api-version":[1,23],"meta":{{}},"blocks":[{}]}}"#,
+        json
+    );
+    Pandoc::from_json(&doc).blocks.remove(0)
+}
@@ -0,0 +1,32 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn headings_and_images() {
+    let doc = Pandoc {
+        meta: Map::new(),
+        blocks: vec![
+            Block::header(1, "Intro"),
+            Block::para(vec![
+                Inline::image(vec![], "a.png"),
+                Inline::Note(vec![Block::para(vec![Inline::image(vec![], "b.png")])]),
+            ]),
+            Block::header(2, "Details"),
+        ],
+        pandoc_api_version: vec![1, 22],
+    };
+    let (headers, images) = doc.fold((Vec::new(), 0), |(mut headers, images), node| match node {
+        Node::Block(&Block::Header(level, _, ref title)) => {
+            headers.push((level, stringify(title)));
+            (headers, images)
+        }
+        Node::Inline(&Inline::Image(..)) => (headers, images + 1),
+        _ => (headers, images),
+    });
+    assert_eq!(
+        headers,
+        vec![(1, "Intro".to_owned()), (2, "Details".to_owned())]
+    );
+    assert_eq!(images, 2);
+}
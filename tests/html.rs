@@ -0,0 +1,47 @@
+#![cfg(feature = "html")]
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn html_fragment() {
+    let doc = Pandoc::builder()
+        .title("Ignored")
+        .block(Block::header(2, "A & B"))
+        .block(para![
+            "Some ",
+            emph!["stressed"],
+            " ",
+            Inline::link(inlines!["link"], "https://example.com/?a=1&b=2"),
+            Inline::Note(vec![para!["A note."]]),
+        ])
+        .block(Block::bullet_list(vec![
+            vec![plain!["one"]],
+            vec![plain!["<two>"]],
+        ]))
+        .block(Block::code_block_with_class("rust", "a < b"))
+        .block(Block::RawBlock(
+            Format("latex".to_owned()),
+            "\\newpage".to_owned(),
+        ))
+        .build();
+    let expected = "\
+<h2>A &amp; B</h2>
+<p>Some <em>stressed</em> <a href=\"https://example.com/?a=1&amp;b=2\">link</a>\
+<a href=\"#fn1\" class=\"footnote-ref\" id=\"fnref1\"><sup>1</sup></a></p>
+<ul>
+<li>one</li>
+<li>&lt;two&gt;</li>
+</ul>
+<pre class=\"rust\"><code>a &lt; b</code></pre>
+<section class=\"footnotes\">
+<hr />
+<ol>
+<li id=\"fn1\">
+<p>A note.</p>
+<a href=\"#fnref1\" class=\"footnote-back\">↩︎</a></li>
+</ol>
+</section>
+";
+    assert_eq!(doc.to_html_fragment(), expected);
+}
@@ -0,0 +1,62 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::hyphenation::{self, Break, Hyphenator};
+use pandoc_ast::*;
+
+/// the patterns from the example in Liang's thesis
+const PATTERNS: &str = "hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n";
+
+#[test]
+fn breaks() {
+    let hyphenator = Hyphenator::new(PATTERNS);
+    assert_eq!(hyphenator.breaks("hyphenation"), vec![2, 6]);
+    assert_eq!(hyphenator.breaks("Hyphenation"), vec![2, 6]);
+    assert_eq!(hyphenator.breaks("hyphen"), vec![2]);
+    assert_eq!(hyphenator.breaks("hyph"), Vec::<usize>::new());
+    let strict = Hyphenator::new(PATTERNS).margins(3, 6);
+    assert_eq!(strict.breaks("hyphenation"), Vec::<usize>::new());
+    let short = Hyphenator::new(PATTERNS).min_length(12);
+    assert_eq!(short.breaks("hyphenation"), Vec::<usize>::new());
+}
+
+#[test]
+fn inserting_breaks() {
+    let hyphenator = Hyphenator::new(PATTERNS);
+    let code = Inline::Code(Attr::default(), "hyphenation".to_owned());
+    let mut doc = vec![para![
+        "(hyphenation), http://hyphenation.org ",
+        code.clone(),
+        " ",
+        Inline::Math(MathType::InlineMath, "hyphenation".to_owned())
+    ]];
+    let original = doc.clone();
+    hyphenation::hyphenate(&mut doc, &hyphenator, Break::SoftHyphen);
+    assert_eq!(
+        doc,
+        vec![para![
+            "(hy\u{ad}phen\u{ad}ation), http://hyphenation.org ",
+            code.clone(),
+            " ",
+            Inline::Math(MathType::InlineMath, "hyphenation".to_owned())
+        ]]
+    );
+
+    let mut doc = original;
+    hyphenation::hyphenate(&mut doc, &hyphenator, Break::SoftBreak);
+    assert_eq!(
+        doc[0],
+        Block::Para(vec![
+            Inline::Str("(hy".to_owned()),
+            Inline::SoftBreak,
+            Inline::Str("phen".to_owned()),
+            Inline::SoftBreak,
+            Inline::Str("ation),".to_owned()),
+            Inline::Space,
+            Inline::Str("http://hyphenation.org".to_owned()),
+            Inline::Space,
+            code,
+            Inline::Space,
+            Inline::Math(MathType::InlineMath, "hyphenation".to_owned()),
+        ])
+    );
+}
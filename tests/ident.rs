@@ -0,0 +1,67 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::ident::*;
+use pandoc_ast::*;
+use std::collections::HashSet;
+
+#[test]
+fn slugs() {
+    assert_eq!(
+        slugify(&inlines_from_str("Header identifiers in HTML")),
+        "header-identifiers-in-html"
+    );
+    assert_eq!(
+        slugify(&[
+            Inline::emph(inlines_from_str("Dogs?--in")),
+            Inline::str(" my house?")
+        ]),
+        "dogs--in-my-house"
+    );
+    assert_eq!(
+        slugify(&inlines_from_str("3. Applications")),
+        "applications"
+    );
+    assert_eq!(slugify(&inlines_from_str("33")), "");
+    assert_eq!(slugify(&inlines_from_str("Über größe")), "über-größe");
+    assert_eq!(slugify_ascii(&inlines_from_str("Über größe")), "uber-groe");
+}
+
+#[test]
+fn uniqueness() {
+    let mut used = HashSet::new();
+    assert_eq!(unique_identifier(String::new(), &mut used), "section");
+    assert_eq!(
+        unique_identifier("section".to_owned(), &mut used),
+        "section-1"
+    );
+    assert_eq!(
+        unique_identifier("section".to_owned(), &mut used),
+        "section-2"
+    );
+}
+
+#[test]
+fn assign() {
+    let mut doc = Pandoc {
+        meta: Map::new(),
+        blocks: vec![
+            Block::header(1, "Intro"),
+            Block::div(
+                "",
+                vec![
+                    Block::header(2, "Intro"),
+                    Block::para(inlines_from_str("text")),
+                ],
+            ),
+            Block::Header(1, ("keep".to_owned(), vec![], vec![]), vec![]),
+        ],
+        pandoc_api_version: vec![1, 22],
+    };
+    assign_identifiers(&mut doc);
+    let idents: Vec<_> = doc
+        .descendants()
+        .filter_map(|node| node.attr())
+        .map(|attr| &attr.0[..])
+        .collect();
+    assert_eq!(idents, ["intro", "intro-1", "intro-2", "keep"]);
+}
@@ -0,0 +1,78 @@
+extern crate pandoc_ast;
+extern crate serde_json;
+
+use pandoc_ast::incremental::{content_hash, Cache};
+use pandoc_ast::*;
+
+fn shout(block: Block, calls: &mut Vec<String>) -> Vec<Block> {
+    calls.push(stringify(&block));
+    let mut block = block;
+    transform::inlines(
+        &mut block,
+        transform::TraversalOrder::TopDown,
+        |inline| match *inline {
+            Inline::Str(ref s) => transform::Action::Replace(Inline::Str(s.to_uppercase())),
+            _ => transform::Action::Keep,
+        },
+    );
+    vec![block, Block::HorizontalRule]
+}
+
+#[test]
+fn reuses_unchanged_blocks() {
+    assert_eq!(content_hash(&para!["a"]), content_hash(&para!["a"]));
+    assert_ne!(content_hash(&para!["a"]), content_hash(&plain!["a"]));
+    assert_eq!(content_hash(&Block::Null), 0x1bdc_7827_d64e_e72e);
+
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![para!["one"], para!["two"]];
+    let mut calls = Vec::new();
+    let mut cache = Cache::new("shout 1");
+    cache.map_blocks(&mut doc, |block| shout(block, &mut calls));
+    assert_eq!(calls, vec!["one", "two"]);
+    assert_eq!(doc.blocks.len(), 4);
+    let json = cache.to_json();
+
+    let mut cache = Cache::from_json(&json, "shout 1").unwrap();
+    let mut edited = Pandoc::new();
+    edited.blocks = vec![para!["one"], para!["three"]];
+    calls.clear();
+    cache.map_blocks(&mut edited, |block| shout(block, &mut calls));
+    assert_eq!(calls, vec!["three"]);
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    assert_eq!(
+        edited.blocks,
+        vec![
+            para!["ONE"],
+            Block::HorizontalRule,
+            para!["THREE"],
+            Block::HorizontalRule
+        ]
+    );
+    assert_eq!(cache.len(), 3);
+    cache.prune();
+    assert_eq!(cache.len(), 2);
+
+    let cache = Cache::from_json(&json, "shout 2").unwrap();
+    assert!(cache.is_empty());
+    assert_eq!(cache.key(), "shout 2");
+}
+
+#[test]
+fn hash_collisions() {
+    // a cache entry for another block under the hash of `para!["a"]`
+    let json = format!(
+        r#"{{"key":"k","entries":{{"{}":[{{"input":{},"output":[]}}]}}}}"#,
+        content_hash(&para!["a"]),
+        serde_json::to_string(&para!["b"]).unwrap()
+    );
+    let mut cache = Cache::from_json(&json, "k").unwrap();
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![para!["a"], para!["b"]];
+    let mut calls = Vec::new();
+    cache.map_blocks(&mut doc, |block| shout(block, &mut calls));
+    assert_eq!(calls, vec!["a", "b"]);
+    assert_eq!((cache.hits(), cache.misses()), (0, 2));
+    assert_eq!(doc.blocks.len(), 4);
+    assert_eq!(cache.len(), 3);
+}
@@ -0,0 +1,136 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::index::{Index, Term};
+use pandoc_ast::*;
+
+fn entry(attrs: &[(&str, &str)], text: &str) -> Inline {
+    let attrs = attrs
+        .iter()
+        .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+        .collect();
+    Inline::Span(
+        (String::new(), vec!["index".to_owned()], attrs),
+        inlines![text],
+    )
+}
+
+fn link(number: &str, target: &str) -> Inline {
+    Inline::link(inlines![number], format!("#{}", target))
+}
+
+#[test]
+fn collecting_terms() {
+    let mut doc = Pandoc::new();
+    doc.blocks.push(Block::Para(vec![
+        entry(&[], "zebra"),
+        Inline::Space,
+        entry(&[("sub", "moves")], "Ownership"),
+        Inline::Span(("index-1".to_owned(), vec![], vec![]), inlines!["taken"]),
+    ]));
+    doc.blocks.push(Block::Para(vec![
+        entry(&[("term", "ownership")], "owned"),
+        entry(&[], " "),
+        Inline::span("other", inlines!["zebra"]),
+        entry(&[("sub", "moves")], "Ownership"),
+    ]));
+    let terms = Index::new().collect(&mut doc);
+    assert_eq!(
+        terms,
+        vec![
+            Term {
+                name: "Ownership".to_owned(),
+                targets: vec![],
+                sub_terms: vec![Term {
+                    name: "moves".to_owned(),
+                    targets: vec!["index-2".to_owned(), "index-4".to_owned()],
+                    sub_terms: vec![],
+                }],
+            },
+            Term {
+                name: "ownership".to_owned(),
+                targets: vec!["index-3".to_owned()],
+                sub_terms: vec![],
+            },
+            Term {
+                name: "zebra".to_owned(),
+                targets: vec!["index-1-1".to_owned()],
+                sub_terms: vec![],
+            },
+        ]
+    );
+    match doc.blocks[1] {
+        Block::Para(ref inlines) => match inlines[1] {
+            Inline::Span(ref attr, _) => assert_eq!(attr.0, ""),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn building_the_index() {
+    let mut doc = Pandoc::new();
+    doc.blocks.push(Block::Para(vec![
+        entry(&[], "borrowing"),
+        entry(&[], "arena"),
+        entry(&[("sub", "mutable")], "borrowing"),
+        Inline::Span(
+            ("own".to_owned(), vec!["idx".to_owned()], vec![]),
+            inlines!["2D graphics"],
+        ),
+    ]));
+    let index = Index::new().build(&mut doc);
+    let letter = |letter: &str, list| {
+        Block::div(
+            "letter",
+            vec![
+                Block::Header(2, Attr::default(), inlines![letter]),
+                Block::DefinitionList(list),
+            ],
+        )
+    };
+    assert_eq!(
+        index,
+        Block::div(
+            "index",
+            vec![
+                letter(
+                    "A",
+                    vec![(inlines!["arena"], vec![vec![plain![link("1", "index-2")]]])]
+                ),
+                letter(
+                    "B",
+                    vec![(
+                        inlines!["borrowing"],
+                        vec![vec![
+                            plain![link("1", "index-1")],
+                            Block::DefinitionList(vec![(
+                                inlines!["mutable"],
+                                vec![vec![plain![link("1", "index-3")]]]
+                            )]),
+                        ]]
+                    )]
+                ),
+            ]
+        )
+    );
+
+    let index = Index::new().class("idx").prefix("ix").build(&mut doc);
+    let list = vec![(
+        inlines!["2D graphics"],
+        vec![vec![plain![link("1", "own")]]],
+    )];
+    assert_eq!(index, Block::div("index", vec![letter("#", list)]));
+
+    let mut doc = Pandoc::new();
+    doc.blocks.push(Block::Para(vec![
+        entry(&[("sub", "a"), ("level", "b")], "x"),
+        entry(&[("sub", "a"), ("level", "b")], "x"),
+    ]));
+    let terms = Index::new()
+        .sub_term_key("level")
+        .prefix("ix")
+        .collect(&mut doc);
+    assert_eq!(terms[0].sub_terms[0].name, "b");
+    assert_eq!(terms[0].sub_terms[0].targets, vec!["ix-1", "ix-2"]);
+}
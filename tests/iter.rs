@@ -0,0 +1,50 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+fn doc() -> Pandoc {
+    Pandoc {
+        meta: Map::new(),
+        blocks: vec![
+            Block::header(1, "Title"),
+            Block::BlockQuote(vec![Block::para(vec![
+                Inline::emph(vec![Inline::str("deep")]),
+                Inline::Note(vec![Block::plain(vec![Inline::str("note")])]),
+            ])]),
+        ],
+        pandoc_api_version: vec![1, 22],
+    }
+}
+
+#[test]
+fn depth_first_order() {
+    let doc = doc();
+    let strs: Vec<_> = doc
+        .inlines()
+        .filter_map(|inline| match *inline {
+            Inline::Str(ref s) => Some(&s[..]),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(strs, ["Title", "deep", "note"]);
+    assert_eq!(doc.blocks().count(), 4);
+    assert_eq!(doc.blocks[1].blocks().count(), 3);
+    assert_eq!(doc.descendants().count(), 9);
+}
+
+#[test]
+fn mutable_iteration() {
+    let mut doc = doc();
+    doc.for_each_inline_mut(|inline| {
+        if let Inline::Str(ref mut s) = *inline {
+            *s = s.to_uppercase();
+        }
+    });
+    doc.blocks.for_each_block_mut(|block| {
+        if let Block::Header(ref mut level, ..) = *block {
+            *level += 1;
+        }
+    });
+    assert_eq!(stringify(&doc), "TITLEDEEP");
+    assert_eq!(doc.blocks[0], Block::header(2, "TITLE"));
+}
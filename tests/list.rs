@@ -0,0 +1,119 @@
+extern crate pandoc_ast;
+extern crate serde_json;
+
+use pandoc_ast::*;
+
+fn ordered(start: Int, items: Vec<Vec<Block>>) -> Block {
+    Block::OrderedList(
+        ListAttributes {
+            start,
+            ..ListAttributes::default()
+        },
+        items,
+    )
+}
+
+#[test]
+fn list_attributes_json() {
+    let attributes = ListAttributes {
+        start: 3,
+        style: ListNumberStyle::LowerRoman,
+        delim: ListNumberDelim::OneParen,
+    };
+    let json = serde_json::to_string(&attributes).unwrap();
+    assert_eq!(json, r#"[3,{"t":"LowerRoman"},{"t":"OneParen"}]"#);
+    assert_eq!(
+        serde_json::from_str::<ListAttributes>(&json).unwrap(),
+        attributes
+    );
+    assert_eq!(
+        ListAttributes::from((
+            1,
+            ListNumberStyle::DefaultStyle,
+            ListNumberDelim::DefaultDelim
+        )),
+        ListAttributes::default()
+    );
+}
+
+#[test]
+fn numbering() {
+    let mut blocks = vec![
+        ordered(4, vec![vec![plain!["a"]], vec![plain!["b"]]]),
+        para!["between"],
+        ordered(
+            1,
+            vec![vec![plain!["c"], ordered(1, vec![vec![plain!["d"]]])]],
+        ),
+        ordered(1, vec![]),
+    ];
+    list::continue_numbering(&mut blocks);
+    let starts: Vec<Int> = blocks
+        .iter()
+        .filter_map(|block| match *block {
+            Block::OrderedList(ref attributes, _) => Some(attributes.start),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(starts, vec![4, 6, 7]);
+
+    list::restart_numbering(&mut blocks, 1);
+    list::set_style(
+        &mut blocks,
+        ListNumberStyle::UpperAlpha,
+        ListNumberDelim::Period,
+    );
+    let mut count = 0;
+    blocks.for_each_block_mut(|block| {
+        if let Block::OrderedList(ref attributes, _) = *block {
+            assert_eq!(
+                *attributes,
+                ListAttributes {
+                    start: 1,
+                    style: ListNumberStyle::UpperAlpha,
+                    delim: ListNumberDelim::Period,
+                }
+            );
+            count += 1;
+        }
+    });
+    assert_eq!(count, 4);
+}
+
+#[test]
+fn converting_and_flattening() {
+    let mut block = Block::bullet_list(vec![
+        vec![
+            plain!["a"],
+            Block::bullet_list(vec![vec![plain!["b"], ordered(1, vec![vec![plain!["c"]]])]]),
+        ],
+        vec![Block::bullet_list(vec![vec![plain!["d"]]])],
+        vec![Block::BlockQuote(vec![Block::bullet_list(vec![vec![
+            plain!["e"],
+        ]])])],
+    ]);
+    list::flatten(&mut block);
+    let flat = vec![
+        vec![plain!["a"]],
+        vec![plain!["b"]],
+        vec![plain!["c"]],
+        vec![plain!["d"]],
+        vec![Block::BlockQuote(vec![Block::bullet_list(vec![vec![
+            plain!["e"],
+        ]])])],
+    ];
+    assert_eq!(block, Block::bullet_list(flat.clone()));
+
+    list::to_ordered(&mut block, ListAttributes::default());
+    assert_eq!(block, ordered(1, flat.clone()));
+    list::to_ordered(
+        &mut block,
+        ListAttributes {
+            start: 5,
+            ..ListAttributes::default()
+        },
+    );
+    assert_eq!(block, ordered(1, flat.clone()));
+    list::to_bullet(&mut block);
+    assert_eq!(block, Block::bullet_list(flat));
+}
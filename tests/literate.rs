@@ -0,0 +1,92 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+fn code(class: &str, file: Option<&str>, code: &str) -> Block {
+    let attributes = file
+        .map(|file| vec![("file".to_owned(), file.to_owned())])
+        .unwrap_or_default();
+    Block::CodeBlock(
+        (String::new(), vec![class.to_owned()], attributes),
+        code.to_owned(),
+    )
+}
+
+#[test]
+fn tangle() {
+    let doc = blocks![
+        code("rust", Some("src/main.rs"), "fn main() {"),
+        para!["explanation"],
+        code("sh", None, "cargo run"),
+        code("rust", Some("src/main.rs"), "}\n"),
+        code("toml", Some("Cargo.toml"), "[package]"),
+    ];
+    assert_eq!(query::code_blocks_by_language(&doc, "rust").len(), 2);
+    assert_eq!(query::code_blocks_by_language(&doc, "sh")[0].1, "cargo run");
+    let files = literate::tangle(&doc);
+    assert_eq!(files.len(), 2);
+    assert_eq!(files["src/main.rs"], "fn main() {\n}\n");
+
+    let dir = std::env::temp_dir().join(format!("pandoc_ast_tangle_{}", std::process::id()));
+    let written = literate::tangle_to_dir(&doc, &dir).unwrap();
+    assert_eq!(written, vec!["Cargo.toml", "src/main.rs"]);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("Cargo.toml")).unwrap(),
+        "[package]\n"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn include() {
+    let dir = std::env::temp_dir().join(format!("pandoc_ast_include_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("lib.rs"),
+        "mod a {\n    fn f() {}\n    fn g() {}\n}\n",
+    )
+    .unwrap();
+    let attr = |pairs: &[(&str, &str)]| {
+        let pairs = pairs
+            .iter()
+            .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+            .collect();
+        (String::new(), vec!["rust".to_owned()], pairs)
+    };
+    let mut doc = vec![
+        Block::CodeBlock(
+            attr(&[
+                ("include", "lib.rs"),
+                ("startLine", "2"),
+                ("endLine", "3"),
+                ("dedent", "4"),
+            ]),
+            String::new(),
+        ),
+        Block::CodeBlock(attr(&[("include", "lib.rs")]), String::new()),
+    ];
+    literate::include_code(&mut doc, &dir).unwrap();
+    assert_eq!(
+        doc[0],
+        Block::CodeBlock(attr(&[]), "fn f() {}\nfn g() {}".to_owned())
+    );
+    assert_eq!(
+        query::code_blocks(&doc)[1].1,
+        "mod a {\n    fn f() {}\n    fn g() {}\n}"
+    );
+
+    let mut missing = vec![Block::CodeBlock(
+        attr(&[("include", "missing.rs")]),
+        String::new(),
+    )];
+    match literate::include_code(&mut missing, &dir) {
+        Err(Error::File { path, .. }) => assert_eq!(path, dir.join("missing.rs")),
+        other => panic!("unexpected {:?}", other),
+    }
+    let mut invalid = vec![Block::CodeBlock(
+        attr(&[("include", "lib.rs"), ("startLine", "x")]),
+        String::new(),
+    )];
+    assert!(literate::include_code(&mut invalid, &dir).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,74 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::lua_compat::{Filter, Traverse};
+use pandoc_ast::transform::Action;
+use pandoc_ast::*;
+use std::cell::RefCell;
+
+#[test]
+fn typewise_order() {
+    let mut doc = Pandoc::builder()
+        .title("A title")
+        .block(Block::Div(
+            Attr::default(),
+            vec![para!["one ", emph!["two"]]],
+        ))
+        .build();
+    let log = RefCell::new(Vec::new());
+    Filter::new()
+        .on("Block", |b: &mut Block| {
+            log.borrow_mut().push(b.name());
+            Action::Keep
+        })
+        .on("Str", |s: &mut Inline| {
+            log.borrow_mut().push("Str");
+            match *s {
+                Inline::Str(ref text) if text == "two" => Action::Replace(Inline::str("2")),
+                _ => Action::Keep,
+            }
+        })
+        .on("Emph", |_: &mut Inline| {
+            log.borrow_mut().push("Emph");
+            Action::Keep
+        })
+        .on_inlines(|_| log.borrow_mut().push("Inlines"))
+        .on_meta(|_| log.borrow_mut().push("Meta"))
+        .apply(&mut doc);
+    assert_eq!(
+        *log.borrow(),
+        [
+            // the title in the metadata comes first
+            "Str", "Str", "Str", "Str", "Emph", "Inlines", "Inlines", "Inlines", "Para", "Div",
+            "Meta"
+        ]
+    );
+    assert_eq!(
+        doc.blocks,
+        vec![Block::div("", vec![para!["one ", emph!["2"]]])]
+    );
+}
+
+#[test]
+fn topdown_skips_children() {
+    let mut doc = Pandoc::builder()
+        .block(para![emph!["a"], strong!["b"]])
+        .build();
+    let mut seen = Vec::new();
+    Filter::new()
+        .traverse(Traverse::Topdown)
+        .on("Inline", |i: &mut Inline| {
+            seen.push(i.name());
+            match *i {
+                Inline::Emph(_) => (Action::Keep, false),
+                _ => (Action::Keep, true),
+            }
+        })
+        .apply(&mut doc);
+    assert_eq!(seen, ["Emph", "Strong", "Str"]);
+}
+
+#[test]
+#[should_panic(expected = "there is no Inline element called `Para`")]
+fn unknown_names() {
+    Filter::new().on("Para", |_: &mut Inline| Action::Keep);
+}
@@ -0,0 +1,31 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn nested() {
+    let name = "brave".to_owned();
+    let blocks = blocks![
+        para!["Hello ", emph![name, " new"], Inline::LineBreak, "world"],
+        vec![Block::HorizontalRule],
+    ];
+    assert_eq!(
+        blocks,
+        vec![
+            Block::Para(vec![
+                Inline::str("Hello"),
+                Inline::Space,
+                Inline::Emph(vec![
+                    Inline::str("brave"),
+                    Inline::Space,
+                    Inline::str("new"),
+                ]),
+                Inline::LineBreak,
+                Inline::str("world"),
+            ]),
+            Block::HorizontalRule,
+        ]
+    );
+    let empty: Vec<Inline> = inlines![];
+    assert!(empty.is_empty());
+}
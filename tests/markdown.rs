@@ -0,0 +1,59 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn lossy_markdown() {
+    let doc = Pandoc::builder()
+        .title("Ignored")
+        .block(Block::Header(
+            1,
+            ("intro".to_owned(), vec![], vec![]),
+            inlines!["Intro"],
+        ))
+        .block(para![
+            "Some ",
+            emph!["stressed"],
+            " and ",
+            strong!["bold"],
+            " text with a ",
+            Inline::link(inlines!["link"], "https://example.com"),
+            " and a_b."
+        ])
+        .block(Block::bullet_list(vec![
+            vec![plain!["one"]],
+            vec![plain!["two"]],
+        ]))
+        .block(Block::OrderedList(
+            (3, ListNumberStyle::Decimal, ListNumberDelim::OneParen).into(),
+            vec![vec![para!["first"], para!["more"]], vec![para!["second"]]],
+        ))
+        .block(Block::code_block_with_class("rust", "let s = \"```\";\n"))
+        .block(Block::BlockQuote(vec![para!["quoted"], para!["twice"]]))
+        .build();
+    let expected = "\
+# Intro {#intro}
+
+Some *stressed* and **bold** text with a [link](https://example.com) and a\\_b.
+
+-   one
+-   two
+
+3)  first
+
+    more
+
+4)  second
+
+```` rust
+let s = \"```\";
+````
+
+> quoted
+>
+> twice
+";
+    assert_eq!(doc.to_markdown_lossy(), expected);
+    assert_eq!(doc.to_string(), expected);
+    assert_eq!(emph!["a*b"].to_string(), "*a\\*b*");
+}
@@ -0,0 +1,257 @@
+extern crate pandoc_ast;
+#[macro_use]
+extern crate serde_json;
+#[macro_use]
+extern crate serde_derive;
+
+use pandoc_ast::*;
+
+fn meta() -> Meta {
+    let mut author = Map::new();
+    author.insert(
+        "name".to_owned(),
+        Box::new(MetaValue::MetaInlines(inlines_from_str("Jane Doe"))),
+    );
+    let mut meta = Meta::new();
+    meta.insert(
+        "author".to_owned(),
+        MetaValue::MetaList(vec![MetaValue::MetaMap(author)]),
+    );
+    meta.insert("draft".to_owned(), MetaValue::MetaBool(true));
+    meta
+}
+
+#[test]
+fn get() {
+    let meta = meta();
+    assert_eq!(meta.get_string("author.0.name").unwrap(), "Jane Doe");
+    assert_eq!(meta.get_bool("draft"), Some(true));
+    assert_eq!(meta.get_bool("author.0.name"), None);
+    assert!(meta.get_path("author.1.name").is_none());
+    assert!(meta.get_path("author.x").is_none());
+}
+
+#[test]
+fn set_and_remove() {
+    let mut meta = meta();
+    let title = MetaValue::MetaString("T".to_owned());
+    assert_eq!(meta.set_path("book.title", title.clone()), Ok(None));
+    assert_eq!(meta.get_path("book.title"), Some(&title));
+    assert_eq!(meta.set_path("author.1", title.clone()), Ok(None));
+    assert_eq!(meta.get_string("author.1").unwrap(), "T");
+    assert!(meta.set_path("author.5", title.clone()).is_err());
+    assert!(meta.set_path("draft.x", title.clone()).is_err());
+    assert_eq!(
+        meta.remove_path("author.0.name")
+            .and_then(|v| v.text())
+            .unwrap(),
+        "Jane Doe"
+    );
+    assert_eq!(
+        meta.remove_path("author.0"),
+        Some(MetaValue::MetaMap(Map::new()))
+    );
+    assert_eq!(meta.remove_path("draft"), Some(MetaValue::MetaBool(true)));
+    assert_eq!(meta.remove_path("draft"), None);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Author {
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Frontmatter {
+    author: Vec<Author>,
+    draft: bool,
+    year: Option<u32>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn typed() {
+    let mut meta = meta();
+    meta.insert(
+        "year".to_owned(),
+        MetaValue::MetaInlines(inlines_from_str("2021")),
+    );
+    let frontmatter: Frontmatter = meta.deserialize_into().unwrap();
+    assert_eq!(
+        frontmatter,
+        Frontmatter {
+            author: vec![Author {
+                name: "Jane Doe".to_owned()
+            }],
+            draft: true,
+            year: Some(2021),
+            tags: vec![],
+        }
+    );
+    let meta = Meta::from_serialize(&frontmatter).unwrap();
+    assert_eq!(meta.get_string("year").unwrap(), "2021");
+    assert_eq!(
+        meta.get_path("author.0.name"),
+        Some(&MetaValue::MetaString("Jane Doe".to_owned()))
+    );
+    assert_eq!(meta.deserialize_into::<Frontmatter>().unwrap(), frontmatter);
+    assert!(meta.deserialize_into::<Author>().is_err());
+}
+
+#[test]
+fn json_value() {
+    let json = json!({
+        "title": "Sidecar",
+        "draft": false,
+        "version": 1.5,
+        "missing": null,
+        "authors": ["a", "b"],
+    });
+    let value = MetaValue::from_json_value(json.clone());
+    assert_eq!(
+        value.child("version"),
+        Some(&MetaValue::MetaNumber(
+            serde_json::Number::from_f64(1.5).unwrap()
+        ))
+    );
+    assert_eq!(value.child("missing"), None);
+    assert_eq!(
+        value
+            .child("authors")
+            .and_then(|a| a.child("1"))
+            .and_then(MetaValue::text),
+        Some("b".to_owned())
+    );
+    let mut expected = json;
+    expected.as_object_mut().unwrap().remove("missing");
+    assert_eq!(value.to_json_value(), expected);
+    assert_eq!(
+        MetaValue::MetaInlines(inlines_from_str("some  text")).to_json_value(),
+        serde_json::Value::String("some text".to_owned())
+    );
+}
+
+#[test]
+fn numbers() {
+    let json = r#"{"blocks":[],"pandoc-api-version":[1,23],"meta":{"pages":{"t":"MetaString","c":12},"ratio":{"t":"MetaString","c":-0.5},"isbn":{"t":"MetaString","c":"12"}}}"#;
+    let doc = Pandoc::from_json(json);
+    assert_eq!(doc.meta["pages"], MetaValue::MetaNumber(12.into()));
+    assert_eq!(doc.meta["isbn"], MetaValue::MetaString("12".to_owned()));
+    assert_eq!(doc.meta["ratio"].text(), Some("-0.5".to_owned()));
+    assert_eq!(doc.meta["pages"].deserialize_into::<u32>().unwrap(), 12);
+    assert_eq!(doc.meta["ratio"].deserialize_into::<f64>().unwrap(), -0.5);
+    assert_eq!(
+        doc.meta["pages"]
+            .deserialize_into::<serde_json::Value>()
+            .unwrap(),
+        json!(12)
+    );
+    assert_eq!(doc.meta["pages"].to_json_value(), json!(12));
+
+    let written: serde_json::Value = serde_json::from_str(&doc.to_json()).unwrap();
+    assert_eq!(
+        written["meta"]["pages"],
+        json!({"t": "MetaString", "c": 12})
+    );
+    assert_eq!(
+        written["meta"]["ratio"],
+        json!({"t": "MetaString", "c": -0.5})
+    );
+    assert_eq!(Pandoc::from_json(&doc.to_json()), doc);
+
+    let err = serde_json::from_str::<MetaValue>(r#"{"t":"MetaString","c":true}"#).unwrap_err();
+    assert!(err.to_string().contains("a string or a number"));
+
+    // numbers are limited to those of `serde_json::Number`
+    let json = r#"{"blocks":[],"pandoc-api-version":[1,23],"meta":{"big":{"t":"MetaString","c":12345678901234567890123}}}"#;
+    assert_eq!(
+        Pandoc::from_json(json).meta["big"].text(),
+        Some("1.2345678901234568e+22".to_owned())
+    );
+    assert!(Pandoc::try_from_json(&json.replace("12345678901234567890123", "1e400")).is_err());
+}
+
+#[test]
+fn macro_and_from() {
+    let built = meta! {
+        "author" => vec![MetaValue::from(meta! { "name" => "Jane Doe" })],
+        "draft" => true,
+    };
+    let mut expected = meta();
+    expected
+        .set_path("author.0.name", "Jane Doe".into())
+        .unwrap();
+    assert_eq!(built, expected);
+    assert_eq!(
+        MetaValue::from(String::from("x")),
+        MetaValue::MetaString("x".to_owned())
+    );
+}
+
+#[test]
+fn blocks_and_inlines() {
+    let abstract_ = vec![para!["First."], para!["Second."]];
+    let value = MetaValue::from_blocks(abstract_.clone());
+    assert_eq!(value, MetaValue::MetaBlocks(abstract_.clone()));
+    assert_eq!(value.to_blocks(), Some(abstract_));
+    assert_eq!(value.to_inlines(), Some(inlines!["First. Second."]));
+
+    let value = MetaValue::from_blocks(vec![plain![emph!["One"]]]);
+    assert_eq!(value, MetaValue::from_inlines(inlines![emph!["One"]]));
+    assert_eq!(value.to_blocks(), Some(vec![para![emph!["One"]]]));
+    assert_eq!(
+        MetaValue::MetaBlocks(vec![para!["x"]]).to_inlines(),
+        Some(inlines!["x"])
+    );
+
+    let value = MetaValue::from("two words");
+    assert_eq!(value.to_inlines(), Some(inlines!["two words"]));
+    assert_eq!(value.to_blocks(), Some(vec![para!["two words"]]));
+    assert_eq!(MetaValue::from("").to_blocks(), Some(vec![]));
+    assert_eq!(MetaValue::from(true).to_inlines(), Some(inlines!["true"]));
+    assert_eq!(MetaValue::MetaList(vec![]).to_blocks(), None);
+    assert_eq!(MetaValue::MetaMap(Map::new()).to_inlines(), None);
+}
+
+#[test]
+fn merge() {
+    let defaults = meta! {
+        "draft" => false,
+        "author" => meta! { "name" => "Nobody", "email" => "n@example.com" },
+    };
+    let doc = meta! {
+        "title" => "Doc",
+        "author" => meta! { "name" => "Jane" },
+    };
+
+    let mut left = doc.clone();
+    left.merge_with(defaults.clone(), MergeStrategy::LeftBias);
+    assert_eq!(left.get_string("author.name").unwrap(), "Jane");
+    assert_eq!(left.get_path("author.email"), None);
+    assert_eq!(left.get_bool("draft"), Some(false));
+
+    let mut right = doc.clone();
+    right.merge_with(defaults.clone(), MergeStrategy::RightBias);
+    assert_eq!(right.get_string("author.name").unwrap(), "Nobody");
+    assert_eq!(right.get_string("title").unwrap(), "Doc");
+
+    let mut deep = defaults;
+    deep.merge_with(doc, MergeStrategy::Deep);
+    assert_eq!(deep.get_string("author.name").unwrap(), "Jane");
+    assert_eq!(deep.get_string("author.email").unwrap(), "n@example.com");
+
+    let json = Meta::from_json_str(r#"{"draft": true, "n": 3}"#).unwrap();
+    assert_eq!(
+        json,
+        meta! { "draft" => true, "n" => serde_json::Number::from(3) }
+    );
+    assert!(Meta::from_json_str("[1]").is_err());
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml() {
+    let meta = Meta::from_yaml_str("title: Doc\ntags: [a, b]\ndraft: false\n").unwrap();
+    assert_eq!(meta.get_string("tags.1").unwrap(), "b");
+    assert_eq!(meta.get_bool("draft"), Some(false));
+}
@@ -0,0 +1,72 @@
+extern crate pandoc_ast;
+extern crate serde_json;
+
+use pandoc_ast::*;
+
+#[test]
+fn read_pandoc_output() {
+    let native = r#"Pandoc
+  Meta
+    { unMeta =
+        fromList [ ( "title" , MetaInlines [ Str "T" ] ) ]
+    }
+  [ Header 1 ( "intro" , [ "c" ] , [ ( "k" , "v" ) ] ) [ Str "Intro" ]
+  , Para
+      [ Str "caf\233\&1" , Space , Emph [ Str "\"x\"\SOH" ] ]
+  ]
+"#;
+    let doc = Pandoc::from_native(native).unwrap();
+    assert_eq!(
+        doc.meta.get("title"),
+        Some(&MetaValue::MetaInlines(vec![Inline::str("T")]))
+    );
+    assert_eq!(doc.blocks[0].attr().unwrap().2[0].1, "v");
+    assert_eq!(
+        doc.blocks[1],
+        para![
+            Inline::str("café1"),
+            Inline::Space,
+            emph![Inline::str("\"x\"\u{1}")]
+        ]
+    );
+
+    // older versions print haskell's `show` without a document
+    let shown = r#"[Para [Str "a",Space,Math InlineMath "x"],OrderedList (1,Decimal,Period) [[Plain [Str "b"]]]]"#;
+    let doc = Pandoc::from_native(shown).unwrap();
+    assert!(doc.meta.is_empty());
+    assert_eq!(doc.blocks.len(), 2);
+    match Pandoc::from_native("[Para [Strr \"a\"]]") {
+        Err(Error::Native(msg)) => assert!(msg.contains("Strr"), "{}", msg),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn write_and_read_back() {
+    let json = r#"{"pandoc-api-version":[1,23],"meta":{"flag":{"t":"MetaBool","c":true},"m":{"t":"MetaMap","c":{"k":{"t":"MetaString","c":"v"}}}},"blocks":[{"t":"Table","c":[["",[],[]],[null,[]],[[{"t":"AlignLeft"},{"t":"ColWidth","c":0.05}]],[["",[],[]],[]],[[["",[],[]],0,[],[[["",[],[]],[[["",[],[]],{"t":"AlignDefault"},1,1,[{"t":"Plain","c":[{"t":"Str","c":"c"}]}]]]]]]],[["",[],[]],[]]]},{"t":"Para","c":[{"t":"Cite","c":[[{"citationId":"k","citationPrefix":[],"citationSuffix":[],"citationMode":{"t":"NormalCitation"},"citationNoteNum":-1,"citationHash":0}],[{"t":"Str","c":"[@k]\n"}]]},{"t":"Link","c":[["",[],[]],[],["u",""]]}]}]}"#;
+    let doc = Pandoc::from_json(json);
+    let native = doc.to_native().unwrap();
+    assert!(native.contains("ColWidth 5.0e-2"), "{}", native);
+    assert!(native.contains("citationNoteNum = -1"), "{}", native);
+    assert!(native.contains(r#"Str "[@k]\n""#), "{}", native);
+    assert_eq!(Pandoc::from_native(&native).unwrap(), doc);
+    assert_eq!(
+        native::blocks_to_string(&[para!["a b"], Block::HorizontalRule]).unwrap(),
+        "[ Para [ Str \"a\" , Space , Str \"b\" ]\n, HorizontalRule\n]\n"
+    );
+}
+
+#[test]
+fn unknown_nodes_are_errors() {
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![para![Inline::Unknown(
+        "Frob".into(),
+        serde_json::Value::Null
+    )]];
+    match doc.to_native() {
+        Err(Error::Native(msg)) => assert!(msg.contains("Frob"), "{}", msg),
+        other => panic!("unexpected {:?}", other),
+    }
+    doc.blocks = vec![Block::Unknown("Frob".into(), serde_json::Value::Null)];
+    assert!(native::blocks_to_string(&doc.blocks).is_err());
+}
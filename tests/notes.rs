@@ -0,0 +1,49 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+fn doc() -> Pandoc {
+    Pandoc {
+        meta: Meta::new(),
+        blocks: blocks![
+            para!["Text", Inline::Note(vec![para!["First."]])],
+            Block::header(1, "Next"),
+            para!["More", Inline::Note(vec![Block::code_block("second")])],
+        ],
+        pandoc_api_version: vec![1, 22],
+    }
+}
+
+#[test]
+fn endnotes_roundtrip() {
+    let original = doc();
+    let found = notes::collect(&original);
+    assert_eq!(found.len(), 2);
+    assert_eq!((found[1].number, found[1].block), (2, 2));
+
+    let mut doc = original.clone();
+    notes::to_endnotes(&mut doc);
+    assert!(notes::collect(&doc).is_empty());
+    assert_eq!(doc.blocks.len(), 4);
+    assert_eq!(query::links(&doc).len(), 4);
+    assert_eq!((query::links(&doc)[0].1).0, "#fn1");
+    let refs = select::Selector::parse("div#fn2 > codeblock").unwrap();
+    assert_eq!(select::select(&doc, &refs).len(), 1);
+
+    notes::from_endnotes(&mut doc);
+    assert_eq!(doc, original);
+}
+
+#[test]
+fn renumber() {
+    let mut doc = doc();
+    notes::to_endnotes(&mut doc);
+    // drop the first reference, so the second note becomes number one
+    doc.blocks[0] = para!["Text"];
+    notes::renumber(&mut doc);
+    assert_eq!((query::links(&doc)[0].1).0, "#fn1");
+    notes::from_endnotes(&mut doc);
+    let found = notes::collect(&doc);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].content, &[Block::code_block("second")][..]);
+}
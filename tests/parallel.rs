@@ -0,0 +1,22 @@
+#![cfg(feature = "rayon")]
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn par_map_blocks() {
+    let mut doc = Pandoc {
+        meta: Meta::new(),
+        blocks: (0..100).map(|i| para![i.to_string()]).collect(),
+        pandoc_api_version: vec![1, 22],
+    };
+    doc.par_map_blocks(|block| match block {
+        Block::Para(ref content) if stringify(content).parse::<u32>().unwrap() % 2 == 0 => vec![],
+        Block::Para(content) => vec![Block::Plain(content), Block::HorizontalRule],
+        other => vec![other],
+    });
+    assert_eq!(doc.blocks.len(), 100);
+    assert_eq!(doc.blocks[0], plain!["1"]);
+    assert_eq!(doc.blocks[98], plain!["99"]);
+    assert_eq!(doc.blocks[99], Block::HorizontalRule);
+}
@@ -0,0 +1,48 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::diff::diff;
+use pandoc_ast::*;
+
+fn doc(blocks: Vec<Block>) -> Pandoc {
+    Pandoc {
+        meta: Meta::new(),
+        blocks,
+        pandoc_api_version: vec![1, 22],
+    }
+}
+
+#[test]
+fn patch_replays_diff() {
+    let old = doc(vec![
+        para!["a b c"],
+        Block::bullet_list(vec![vec![plain!["x"]], vec![plain!["y z"]]]),
+        Block::HorizontalRule,
+    ]);
+    let mut new = doc(vec![
+        Block::header(2, "New"),
+        para!["a", emph!["b"], "c d"],
+        Block::bullet_list(vec![vec![plain!["y"]], vec![plain!["y z w"]]]),
+    ]);
+    new.meta
+        .insert("k".to_owned(), MetaValue::MetaString("v".to_owned()));
+    let script = diff(&old, &new);
+    let mut patched = old.clone();
+    patch::apply(&mut patched, &script).unwrap();
+    assert_eq!(patched, new);
+
+    // a second application finds the document changed and leaves it alone
+    match patch::apply(&mut patched, &script) {
+        Err(Error::Patch { edit: 0, .. }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(patched, new);
+
+    let mut drifted = old.clone();
+    drifted.blocks[1] = Block::Null;
+    match patch::apply(&mut drifted, &script) {
+        Err(Error::Patch { message, .. }) => {
+            assert!(message.contains("has changed"), "{}", message)
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
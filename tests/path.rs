@@ -0,0 +1,41 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn addressing() {
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![
+        para!["one ", emph!["two"]],
+        Block::BulletList(vec![vec![plain!["a"]], vec![plain!["b"]]]),
+    ];
+    // decide in one pass, edit in another
+    let paths: Vec<AstPath> = doc
+        .nodes_with_paths()
+        .into_iter()
+        .filter(|&(_, node)| node == Node::Inline(&Inline::Str("b".to_owned())))
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(paths, vec![AstPath::block(1).child(1, 0).child(0, 0)]);
+    assert_eq!(
+        doc.replace(&paths[0], OwnedNode::Inline(strong!["B"])),
+        Ok(OwnedNode::Inline(Inline::Str("b".to_owned())))
+    );
+    assert_eq!(
+        doc.get(&AstPath::block(1).child(1, 0)),
+        Some(Node::Block(&plain![strong!["B"]]))
+    );
+
+    let emph = AstPath::block(0).child(0, 2);
+    if let Some(NodeMut::Inline(inline)) = doc.get_mut(&emph) {
+        *inline = Inline::Str("three".to_owned());
+    }
+    assert_eq!(doc.blocks[0], para!["one three"]);
+
+    assert_eq!(doc.get(&AstPath::block(2)), None);
+    assert_eq!(doc.get(&AstPath::block(0).child(1, 0)), None);
+    assert_eq!(doc.get(&AstPath::default()), None);
+    let rule = OwnedNode::Block(Block::HorizontalRule);
+    assert_eq!(doc.replace(&emph, rule.clone()), Err(rule.clone()));
+    assert_eq!(doc.replace(&AstPath::block(2), rule.clone()), Err(rule));
+}
@@ -0,0 +1,42 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::pipeline::FilterPipeline;
+use pandoc_ast::*;
+
+struct Upper;
+
+impl MutVisitor for Upper {
+    fn visit_inline(&mut self, inline: &mut Inline) {
+        if let Inline::Str(ref mut s) = *inline {
+            *s = s.to_uppercase();
+        }
+        self.walk_inline(inline);
+    }
+}
+
+#[test]
+fn stages() {
+    let mut calls = 0;
+    let json = {
+        let mut pipeline = FilterPipeline::new()
+            .stage("shift", |mut doc| {
+                transform::shift_headers(&mut doc, 1);
+                doc
+            })
+            .visitor("upper", Upper)
+            .stage("count", |doc| {
+                calls += 1;
+                doc
+            });
+        assert_eq!(pipeline.stage_names(), vec!["shift", "upper", "count"]);
+        let input = r#"{"pandoc-api-version":[1,22],"meta":{},"blocks":[{"t":"Header","c":[1,["",[],[]],[{"t":"Str","c":"title"}]]}]}"#;
+        let output = pipeline.run_json(input).unwrap();
+        assert_eq!(pipeline.timings().len(), 3);
+        assert_eq!(pipeline.timings()[1].name, "upper");
+        assert!(pipeline.run_json("{}").is_err());
+        output
+    };
+    assert_eq!(calls, 1);
+    let doc = Pandoc::from_json(&json);
+    assert_eq!(doc.blocks, vec![Block::header(2, "TITLE")]);
+}
@@ -0,0 +1,25 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn extractors() {
+    let blocks = vec![
+        Block::header(1, "Intro"),
+        Block::para(vec![
+            Inline::link(vec![Inline::str("a")], "#a"),
+            Inline::Math(MathType::InlineMath, "x^2".to_owned()),
+            Inline::Note(vec![Block::para(vec![Inline::link(vec![], "#b")])]),
+        ]),
+        Block::code_block_with_class("rust", "fn main() {}"),
+    ];
+    let links = query::links(&blocks);
+    assert_eq!(links.len(), 2);
+    assert_eq!((links[0].1).0, "#a");
+    assert_eq!((links[1].1).0, "#b");
+    assert_eq!(query::headers(&blocks)[0].0, 1);
+    assert_eq!(query::code_blocks(&blocks)[0].1, "fn main() {}");
+    assert_eq!(query::math(&blocks), vec![(MathType::InlineMath, "x^2")]);
+    assert!(query::images(&blocks).is_empty());
+    assert!(query::citations(&blocks).is_empty());
+}
@@ -0,0 +1,108 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::diagnostics::{Diagnostic, Severity};
+use pandoc_ast::recover::{self, ErrorMode, NodeError, TryMutVisitor, TryVisitor};
+use pandoc_ast::*;
+
+fn doc() -> Pandoc {
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![
+        para!["a", "TODO"],
+        Block::BlockQuote(vec![para![emph!["TODO"]]]),
+        para!["b"],
+    ];
+    doc
+}
+
+struct NoTodo {
+    visited: usize,
+}
+
+impl TryVisitor for NoTodo {
+    type Error = String;
+
+    fn visit_inline(&mut self, _: &AstPath, inline: &Inline) -> Result<(), String> {
+        self.visited += 1;
+        match *inline {
+            Inline::Str(ref s) if s == "TODO" => Err(format!("unfinished: {}", s)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn collect_all() {
+    let mut visitor = NoTodo { visited: 0 };
+    let errors = recover::try_walk(&doc(), ErrorMode::CollectAll, &mut visitor).unwrap_err();
+    assert_eq!(visitor.visited, 5);
+    let paths: Vec<String> = errors.iter().map(|e| e.path.to_string()).collect();
+    assert_eq!(paths, ["0/1", "1/0/0/0"]);
+    assert_eq!(errors[0].to_string(), "0/1: unfinished: TODO");
+}
+
+#[test]
+fn fail_fast() {
+    let mut visitor = NoTodo { visited: 0 };
+    let errors = recover::try_walk(&doc(), ErrorMode::FailFast, &mut visitor).unwrap_err();
+    assert_eq!(visitor.visited, 2);
+    assert_eq!(
+        errors,
+        [NodeError {
+            path: AstPath::block(0).child(0, 1),
+            error: "unfinished: TODO".to_owned(),
+        }]
+    );
+}
+
+#[test]
+fn no_errors() {
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![para!["a"]];
+    let mut visitor = NoTodo { visited: 0 };
+    assert_eq!(
+        recover::try_walk(&doc, ErrorMode::FailFast, &mut visitor),
+        Ok(())
+    );
+}
+
+/// Upper-cases every `Str`, but refuses to touch code
+struct Shout;
+
+impl TryMutVisitor for Shout {
+    type Error = &'static str;
+
+    fn visit_inline(&mut self, _: &AstPath, inline: &mut Inline) -> Result<(), &'static str> {
+        match *inline {
+            Inline::Str(ref mut s) => *s = s.to_uppercase(),
+            Inline::Code(..) => return Err("code"),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn mutable() {
+    let code = Inline::Code(Attr::default(), "x".into());
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![para!["a", code.clone(), "b"], para![code.clone()]];
+
+    let mut collected = doc.clone();
+    let errors =
+        recover::try_walk_mut(&mut collected, ErrorMode::CollectAll, &mut Shout).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(collected.blocks[0], para!["A", code.clone(), "B"]);
+
+    let errors = recover::try_walk_mut(&mut doc, ErrorMode::FailFast, &mut Shout).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(doc.blocks[0], para!["A", code, "b"]);
+}
+
+#[test]
+fn diagnostics() {
+    let errors =
+        recover::try_walk(&doc(), ErrorMode::CollectAll, &mut NoTodo { visited: 0 }).unwrap_err();
+    let diagnostic = Diagnostic::from(errors[1].clone());
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.to_string(), "1/0/0/0: error: unfinished: TODO");
+}
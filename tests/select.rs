@@ -0,0 +1,116 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+fn blocks() -> Vec<Block> {
+    vec![
+        Block::div(
+            "note",
+            vec![
+                Block::para(vec![Inline::str("direct")]),
+                Block::BlockQuote(vec![Block::para(vec![Inline::str("nested")])]),
+            ],
+        ),
+        Block::para(vec![Inline::span("", vec![Inline::str("outside")])]),
+        Block::code_block_with_class("rust", "fn main() {}"),
+        Block::CodeBlock(
+            (
+                "main".to_owned(),
+                vec![],
+                vec![("file".to_owned(), "a.rs".to_owned())],
+            ),
+            String::new(),
+        ),
+    ]
+}
+
+fn texts(nodes: &[Node]) -> Vec<String> {
+    nodes
+        .iter()
+        .map(|node| match *node {
+            Node::Block(block) => stringify(block),
+            Node::Inline(inline) => stringify(inline),
+        })
+        .collect()
+}
+
+#[test]
+fn combinators() {
+    let blocks = blocks();
+    assert_eq!(
+        texts(&blocks.select("div.note > para").unwrap()),
+        ["direct"]
+    );
+    assert_eq!(
+        texts(&blocks.select("div.note para").unwrap()),
+        ["direct", "nested"]
+    );
+    assert_eq!(
+        texts(&blocks.select("para > span, blockquote para").unwrap()),
+        ["nested", "outside"]
+    );
+    assert_eq!(blocks.select("Para").unwrap().len(), 3);
+}
+
+#[test]
+fn attributes() {
+    let blocks = blocks();
+    assert_eq!(
+        blocks.select("codeblock.rust").unwrap(),
+        vec![Node::Block(&blocks[2])]
+    );
+    assert_eq!(
+        blocks.select("#main").unwrap(),
+        vec![Node::Block(&blocks[3])]
+    );
+    assert_eq!(
+        blocks.select("[file=\"a.rs\"]").unwrap(),
+        vec![Node::Block(&blocks[3])]
+    );
+    assert_eq!(blocks.select("*[file]").unwrap().len(), 1);
+    assert!(blocks.select("div >").is_err());
+    assert!(blocks.select("").is_err());
+    assert!(blocks.select("[title=\"a]").is_err());
+
+    let div = vec![Block::Div(
+        (String::new(), vec![], vec![("title".into(), "a]b".into())]),
+        vec![],
+    )];
+    assert_eq!(div.select("div[title=\"a]b\"]").unwrap().len(), 1);
+    assert_eq!(div.select("div[title='a]b']").unwrap().len(), 1);
+}
+
+#[test]
+fn surrounding_whitespace() {
+    let blocks = blocks();
+    assert_eq!(
+        blocks.select(" para ").unwrap(),
+        blocks.select("para").unwrap()
+    );
+    assert_eq!(
+        blocks.select("div >\n").unwrap_err().to_string(),
+        blocks.select("div >").unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn mutable() {
+    let mut blocks = blocks();
+    blocks
+        .select_mut("div.note str", |node| {
+            if let NodeMut::Inline(&mut Inline::Str(ref mut s)) = node {
+                s.make_ascii_uppercase();
+            }
+        })
+        .unwrap();
+    assert_eq!(stringify(&blocks), "DIRECTNESTEDoutside");
+    // without combinators no ancestors are kept
+    blocks
+        .select_mut("str", |node| {
+            if let NodeMut::Inline(&mut Inline::Str(ref mut s)) = node {
+                s.make_ascii_lowercase();
+            }
+        })
+        .unwrap();
+    assert_eq!(stringify(&blocks), "directnestedoutside");
+}
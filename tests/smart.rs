@@ -0,0 +1,64 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::smart::{self, Quotes};
+use pandoc_ast::*;
+
+#[test]
+fn quotes_dashes_and_ellipses() {
+    let mut inlines = inlines![
+        "\"Don't -- it's the '90s...\" she said --- 'really'. ",
+        Inline::Code(Attr::default(), "\"x\" -- 'y'".to_owned())
+    ];
+    let original = inlines.clone();
+    smart::smartify(&mut inlines, Quotes::Nodes);
+    assert_eq!(
+        inlines,
+        inlines![
+            Inline::Quoted(
+                QuoteType::DoubleQuote,
+                inlines!["Don\u{2019}t \u{2013} it\u{2019}s the \u{2019}90s\u{2026}"]
+            ),
+            " she said \u{2014} ",
+            Inline::Quoted(QuoteType::SingleQuote, inlines!["really"]),
+            ". ",
+            Inline::Code(Attr::default(), "\"x\" -- 'y'".to_owned())
+        ]
+    );
+    smart::unsmartify(&mut inlines);
+    assert_eq!(inlines, original);
+
+    let mut inlines = inlines![
+        "\"quoted",
+        Inline::Emph(inlines!["word"]),
+        "\" and dangling\""
+    ];
+    smart::smartify(&mut inlines, Quotes::Characters);
+    assert_eq!(
+        inlines,
+        inlines![
+            "\u{201c}quoted",
+            Inline::Emph(inlines!["word"]),
+            "\u{201d} and dangling\u{201d}"
+        ]
+    );
+}
+
+#[test]
+fn unmatched_and_nested() {
+    let mut inlines = inlines!["\"an 'inner' quote\" \" \"open"];
+    smart::smartify(&mut inlines, Quotes::Nodes);
+    assert_eq!(
+        inlines,
+        inlines![
+            Inline::Quoted(
+                QuoteType::DoubleQuote,
+                inlines![
+                    "an ",
+                    Inline::Quoted(QuoteType::SingleQuote, inlines!["inner"]),
+                    " quote"
+                ]
+            ),
+            " \" \u{201c}open"
+        ]
+    );
+}
@@ -0,0 +1,93 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::sourcepos::{self, SourcePos};
+use pandoc_ast::*;
+
+fn pos(value: &str) -> Attr {
+    (
+        String::new(),
+        vec![],
+        vec![("data-pos".to_owned(), value.to_owned())],
+    )
+}
+
+fn at(line: usize, column: usize) -> SourcePos {
+    SourcePos {
+        file: Some("notes.md".to_owned()),
+        line,
+        column,
+    }
+}
+
+#[test]
+fn lifting() {
+    // pandoc -f commonmark+sourcepos, for "# Title\n\nSome *text*\n"
+    let mut doc = Pandoc::new();
+    doc.blocks = vec![
+        Block::Header(
+            1,
+            (
+                "title".to_owned(),
+                vec![],
+                vec![("data-pos".to_owned(), "notes.md@1:1-2:1".to_owned())],
+            ),
+            vec![Inline::Span(pos("notes.md@1:3-1:8"), inlines!["Title"])],
+        ),
+        Block::Div(
+            pos("notes.md@3:1-4:1"),
+            vec![Block::Para(vec![
+                Inline::Span(pos("notes.md@3:1-3:5"), inlines!["Some"]),
+                Inline::Span(pos("notes.md@3:5-3:6"), vec![Inline::Space]),
+                Inline::Span(
+                    pos("notes.md@3:6-3:12"),
+                    vec![emph![Inline::Span(
+                        pos("notes.md@3:7-3:11"),
+                        inlines!["text"]
+                    )]],
+                ),
+            ])],
+        ),
+        Block::Div(pos("garbage"), vec![plain!["unknown"]]),
+    ];
+    let map = sourcepos::lift(&mut doc);
+
+    let mut expected = Pandoc::new();
+    expected.blocks = vec![
+        Block::Header(1, ("title".to_owned(), vec![], vec![]), inlines!["Title"]),
+        para!["Some ", emph!["text"]],
+        plain!["unknown"],
+    ];
+    assert_eq!(doc, expected);
+
+    let positions: Vec<(String, SourcePos)> = map
+        .iter()
+        .map(|(path, pos)| (path.to_string(), pos.clone()))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![
+            ("0".to_owned(), at(1, 1)),
+            ("0/0".to_owned(), at(1, 3)),
+            ("1".to_owned(), at(3, 1)),
+            ("1/0".to_owned(), at(3, 1)),
+            ("1/1".to_owned(), at(3, 5)),
+            ("1/2".to_owned(), at(3, 6)),
+            ("1/2/0".to_owned(), at(3, 7)),
+        ]
+    );
+    assert_eq!(map.find(&AstPath::block(2).child(0, 0)), None);
+    assert_eq!(
+        map.find(&AstPath::block(1).child(0, 2).child(0, 0)),
+        Some(&at(3, 7))
+    );
+    assert_eq!(map.find(&AstPath::block(0).child(0, 7)), Some(&at(1, 1)));
+    assert_eq!(at(3, 7).to_string(), "notes.md:3:7");
+    assert_eq!(
+        SourcePos::parse("2:4-2:9"),
+        Some(SourcePos {
+            file: None,
+            line: 2,
+            column: 4
+        })
+    );
+}
@@ -0,0 +1,32 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+use std::time::Duration;
+
+#[test]
+fn analyze() {
+    let doc = Pandoc {
+        meta: meta! { "title" => "not counted" },
+        blocks: blocks![
+            Block::header(1, "Hello world"),
+            para![
+                "One, two — three.",
+                Inline::code("", "let x"),
+                Inline::Note(vec![para!["note"]]),
+            ],
+            Block::code_block("a b\nc"),
+        ],
+        pandoc_api_version: vec![1, 22],
+    };
+    let stats = stats::analyze(&doc);
+    // Hello world One, two three. let x note a b c
+    assert_eq!(stats.words, 12);
+    assert_eq!(stats.characters, 10 + 14 + 4 + 4 + 3);
+    assert_eq!(stats.characters_with_spaces, 11 + 17 + 5 + 4 + 5);
+    assert_eq!(stats.blocks["Para"], 2);
+    assert_eq!(stats.blocks["Header"], 1);
+    assert_eq!(stats.outline, vec![(1, "Hello world".to_owned())]);
+    assert_eq!(stats.notes, 1);
+    assert_eq!(stats.links, 0);
+    assert_eq!(stats.reading_time(200), Duration::from_millis(3600));
+}
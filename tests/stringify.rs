@@ -0,0 +1,39 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn inlines() {
+    let inlines = vec![
+        Inline::str("Hello"),
+        Inline::Space,
+        Inline::Emph(vec![Inline::str("brave")]),
+        Inline::SoftBreak,
+        Inline::Quoted(QuoteType::DoubleQuote, vec![Inline::str("new")]),
+        Inline::LineBreak,
+        Inline::code("", "world"),
+        Inline::Note(vec![Block::para(vec![Inline::str("ignored")])]),
+        Inline::RawInline(Format("latex".to_owned()), "\\relax".to_owned()),
+        Inline::RawInline(Format("html".to_owned()), "<br/>".to_owned()),
+        Inline::Image(
+            Attr::default(),
+            vec![Inline::str("alt")],
+            Default::default(),
+        ),
+    ];
+    assert_eq!(
+        stringify(&inlines),
+        "Hello brave \u{201C}new\u{201D} world alt"
+    );
+    assert_eq!(inlines.stringify(), stringify(&inlines[..]));
+}
+
+#[test]
+fn blocks_skip_code() {
+    let blocks = vec![
+        Block::header(1, "A title"),
+        Block::code_block("skipped"),
+        Block::para(vec![Inline::str("text")]),
+    ];
+    assert_eq!(stringify(&blocks), "A titletext");
+}
@@ -0,0 +1,50 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::structure::Sections;
+use pandoc_ast::*;
+
+#[test]
+fn tree_and_back() {
+    let blocks = vec![
+        Block::para(vec![Inline::str("preamble")]),
+        Block::header(1, "One"),
+        Block::para(vec![Inline::str("one")]),
+        Block::header(3, "One.deep"),
+        Block::header(2, "One.Two"),
+        Block::para(vec![Inline::str("two")]),
+        Block::header(1, "Three"),
+    ];
+    let sections = Sections::from_blocks(blocks.clone());
+    assert_eq!(sections.preamble.len(), 1);
+    assert_eq!(sections.sections.len(), 2);
+    let one = &sections.sections[0];
+    assert_eq!(stringify(&one.title), "One");
+    assert_eq!(one.content.len(), 1);
+    assert_eq!(one.children.len(), 2);
+    assert_eq!(one.children[0].level, 3);
+    assert_eq!(one.children[1].content.len(), 1);
+    assert!(sections.sections[1].children.is_empty());
+    assert_eq!(sections.into_blocks(), blocks);
+}
+
+#[test]
+fn extract_and_remove() {
+    let mut doc = Pandoc::from_json(r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[]}"#);
+    doc.blocks = vec![
+        Block::header(1, "Intro"),
+        Block::Header(
+            2,
+            ("methods".into(), vec![], vec![]),
+            vec![Inline::str("Methods")],
+        ),
+        Block::para(vec![Inline::str("measure")]),
+        Block::header(3, "Details"),
+        Block::header(2, "Results"),
+    ];
+    let section = doc.extract_section("#methods").unwrap();
+    assert_eq!(section, doc.blocks[1..4].to_vec());
+    assert_eq!(doc.extract_section("methods"), Some(section.clone()));
+    assert_eq!(doc.extract_section("#missing"), None);
+    assert_eq!(doc.remove_section("#methods"), Some(section));
+    assert_eq!(doc.blocks.len(), 2);
+}
@@ -0,0 +1,135 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::table::TableBuilder;
+use pandoc_ast::*;
+
+#[test]
+fn builder() {
+    let table = TableBuilder::new()
+        .caption("Planets")
+        .header(vec!["Name", "Moons"])
+        .rows(vec![vec!["Earth", "1"], vec!["Mars"]])
+        .row(vec![inlines![emph!["Jupiter"]], inlines!["95"]])
+        .alignments(vec![Alignment::AlignLeft])
+        .widths(vec![0.5, 0.25])
+        .build();
+    let (caption, specs, head, bodies) = match table {
+        Block::Table(_, caption, specs, head, bodies, _) => (caption, specs, head, bodies),
+        _ => unreachable!(),
+    };
+    assert_eq!(caption, (None, vec![plain!["Planets"]]));
+    assert_eq!(
+        specs,
+        vec![
+            (Alignment::AlignLeft, ColWidth::ColWidth(0.5)),
+            (Alignment::AlignDefault, ColWidth::ColWidth(0.25)),
+        ]
+    );
+    assert_eq!(stringify(&(head.1)[0].1[1].4), "Moons");
+    let rows = &bodies[0].3;
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[1].1.len(), 2);
+    assert!(rows[1].1[1].4.is_empty());
+    assert_eq!(rows[2].1[0].4, vec![plain![emph!["Jupiter"]]]);
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn from_csv() {
+    let csv = "Name,Moons\nEarth,1\n\"Mars, red\",2\n";
+    let table = Block::table_from_csv(csv.as_bytes()).unwrap();
+    let expected = TableBuilder::new()
+        .header(vec!["Name", "Moons"])
+        .row(vec!["Earth", "1"])
+        .row(vec!["Mars, red", "2"])
+        .build();
+    assert_eq!(table, expected);
+}
+
+fn texts(table: &table::Table) -> Vec<Vec<String>> {
+    let mut table = table.clone();
+    table
+        .rows_mut()
+        .map(|row| row.1.iter().map(|cell| stringify(&cell.4)).collect())
+        .collect()
+}
+
+#[test]
+fn manipulation() {
+    let block = TableBuilder::new()
+        .header(vec!["Name", "Moons"])
+        .row(vec!["Earth", "1"])
+        .row(vec!["Mars", "2"])
+        .widths(vec![2.0, 2.0])
+        .build();
+    let mut table = table::Table::from_block(block).unwrap();
+    assert!(table::Table::from_block(Block::Null).is_err());
+
+    table.normalize_widths();
+    assert_eq!(table.col_specs[0].1, ColWidth::ColWidth(0.5));
+
+    table.insert_column(1, "Type", vec!["rocky", "rocky"], Alignment::AlignCenter);
+    table.set_alignment(0, Alignment::AlignLeft);
+    assert_eq!(
+        texts(&table),
+        vec![
+            vec!["Name", "Type", "Moons"],
+            vec!["Earth", "rocky", "1"],
+            vec!["Mars", "rocky", "2"],
+        ]
+    );
+    assert_eq!(table.col_specs[1].0, Alignment::AlignCenter);
+    assert_eq!(table.col_specs[0].0, Alignment::AlignLeft);
+
+    table.remove_column(2);
+    table.transpose();
+    assert_eq!(
+        texts(&table),
+        vec![
+            vec!["Name", "Earth", "Mars"],
+            vec!["Type", "rocky", "rocky"]
+        ]
+    );
+    assert_eq!(table.head.1.len(), 0);
+    assert_eq!(table.bodies[0].1, 1);
+    assert_eq!(table.col_specs.len(), 3);
+    table.transpose();
+    assert_eq!(table.head.1.len(), 1);
+    assert_eq!(
+        texts(&table),
+        vec![
+            vec!["Name", "Type"],
+            vec!["Earth", "rocky"],
+            vec!["Mars", "rocky"]
+        ]
+    );
+    let _block = table.into_block();
+}
+
+#[test]
+fn to_csv() {
+    let block = TableBuilder::new()
+        .header(vec!["Name", "Note"])
+        .row(vec!["Mars", "red, \"dusty\""])
+        .row(vec!["Earth", ""])
+        .build();
+    let table = table::Table::from_block(block).unwrap();
+    let mut csv = Vec::new();
+    table
+        .to_csv(&mut csv, &table::CsvOptions::default())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "Name,Note\nMars,\"red, \"\"dusty\"\"\"\nEarth,\n"
+    );
+    let mut tsv = Vec::new();
+    let options = table::CsvOptions {
+        header: false,
+        ..table::CsvOptions::tsv()
+    };
+    table.to_csv(&mut tsv, &options).unwrap();
+    assert_eq!(
+        String::from_utf8(tsv).unwrap(),
+        "Mars\t\"red, \"\"dusty\"\"\"\nEarth\t\n"
+    );
+}
@@ -0,0 +1,52 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::testing::*;
+use pandoc_ast::*;
+use std::fs;
+
+#[test]
+fn roundtrip_and_idempotence() {
+    let doc = Pandoc::builder().block(para!["Hello  world"]).build();
+    assert_eq!(assert_roundtrip(&doc.to_json()), doc);
+    assert_filter_idempotent(
+        |mut doc| {
+            transform::shift_headers(&mut doc, 1);
+            doc
+        },
+        &Pandoc::new(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "applying the filter a second time changed the document:\n+ 1")]
+fn filters_that_are_not_idempotent() {
+    assert_filter_idempotent(
+        |mut doc| {
+            doc.blocks.push(Block::HorizontalRule);
+            doc
+        },
+        &Pandoc::new(),
+    );
+}
+
+#[test]
+fn golden_files() {
+    let path =
+        std::env::temp_dir().join(format!("pandoc-ast-golden-{}/doc.json", std::process::id()));
+    let doc = Pandoc::builder().block(para!["one"]).build();
+    assert_golden(&path, &doc);
+    assert_eq!(fs::read_to_string(&path).unwrap(), doc.to_json_canonical());
+    assert_golden(&path, &doc);
+
+    let changed = Pandoc::builder().block(para!["two"]).build();
+    let message = std::panic::catch_unwind(|| assert_golden(&path, &changed))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+    assert!(
+        message.contains("~ 0/0 Str \"one\" -> Str \"two\""),
+        "{}",
+        message
+    );
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
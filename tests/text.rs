@@ -0,0 +1,40 @@
+#![cfg(feature = "regex")]
+extern crate pandoc_ast;
+
+use pandoc_ast::text::{self, Regex};
+use pandoc_ast::*;
+
+#[test]
+fn replace_across_spaces() {
+    let mut doc = vec![para![
+        "See RFC 2119 and ",
+        emph!["RFC 8174"],
+        Inline::Space,
+        Inline::Note(vec![])
+    ]];
+    let rfc = Regex::new(r"RFC (\d+)").unwrap();
+    text::replace_with(&mut doc, &rfc, |captures| {
+        let url = format!("https://www.rfc-editor.org/rfc/rfc{}", &captures[1]);
+        vec![Inline::link(inlines_from_str(&captures[0]), url)]
+    });
+    assert_eq!(
+        doc,
+        vec![para![
+            "See ",
+            Inline::link(
+                inlines!["RFC 2119"],
+                "https://www.rfc-editor.org/rfc/rfc2119"
+            ),
+            " and ",
+            emph![Inline::link(
+                inlines!["RFC 8174"],
+                "https://www.rfc-editor.org/rfc/rfc8174"
+            )],
+            Inline::Space,
+            Inline::Note(vec![]),
+        ]]
+    );
+
+    text::replace(&mut doc, &Regex::new(r"^(\w+) $").unwrap(), "$1 also ");
+    assert_eq!(stringify(&doc), "See also RFC 2119 and RFC 8174 ");
+}
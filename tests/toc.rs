@@ -0,0 +1,44 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+fn link(text: &str, url: &str) -> Vec<Block> {
+    vec![Block::Plain(vec![Inline::link(
+        inlines_from_str(text),
+        url,
+    )])]
+}
+
+#[test]
+fn nested_toc() {
+    let mut doc = Pandoc {
+        meta: Map::new(),
+        blocks: vec![
+            Block::header(1, "Intro"),
+            Block::header(2, "1. The Setup!"),
+            Block::header(3, "Too deep"),
+            Block::header(1, "Intro"),
+            Block::Header(
+                1,
+                ("intro-2".to_owned(), vec![], vec![]),
+                inlines_from_str("Explicit"),
+            ),
+        ],
+        pandoc_api_version: vec![1, 22],
+    };
+    let toc = toc::bullet_list(&mut doc, 2);
+    let mut intro = link("Intro", "#intro");
+    intro.push(Block::BulletList(vec![link("1. The Setup!", "#the-setup")]));
+    assert_eq!(
+        toc,
+        Block::BulletList(vec![
+            intro,
+            link("Intro", "#intro-1"),
+            link("Explicit", "#intro-2"),
+        ])
+    );
+    match doc.blocks[2] {
+        Block::Header(_, (ref ident, _, _), _) => assert_eq!(ident, "too-deep"),
+        _ => unreachable!(),
+    }
+}
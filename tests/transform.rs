@@ -0,0 +1,530 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::transform::{self, Action, TraversalOrder};
+use pandoc_ast::*;
+
+#[test]
+fn splice_inlines() {
+    let mut blocks = vec![Block::para(vec![
+        Inline::str("a"),
+        Inline::Note(vec![Block::para(vec![Inline::str("note")])]),
+        Inline::Emph(vec![Inline::str("b")]),
+    ])];
+    transform::inlines(
+        &mut blocks,
+        TraversalOrder::BottomUp,
+        |inline| match *inline {
+            Inline::Note(_) => Action::Delete,
+            Inline::Str(ref s) if s == "b" => {
+                Action::ReplaceMany(vec![Inline::str("b1"), Inline::str("b2")])
+            }
+            _ => Action::Keep,
+        },
+    );
+    assert_eq!(
+        blocks,
+        vec![Block::para(vec![
+            Inline::str("a"),
+            Inline::Emph(vec![Inline::str("b1"), Inline::str("b2")]),
+        ])]
+    );
+}
+
+#[test]
+fn splice_blocks() {
+    let mut blocks = vec![
+        Block::div("unwrap", vec![Block::HorizontalRule, Block::Null]),
+        Block::Null,
+    ];
+    transform::blocks(
+        &mut blocks,
+        TraversalOrder::BottomUp,
+        |block| match *block {
+            Block::Null => Action::Delete,
+            Block::Div(_, ref mut content) => Action::ReplaceMany(content.split_off(0)),
+            _ => Action::Keep,
+        },
+    );
+    assert_eq!(blocks, vec![Block::HorizontalRule]);
+}
+
+#[test]
+fn traversal_order() {
+    let doc = vec![Block::div("outer", vec![Block::div("inner", vec![])])];
+    for &(order, expected) in &[
+        (TraversalOrder::TopDown, ["outer", "inner"]),
+        (TraversalOrder::BottomUp, ["inner", "outer"]),
+    ] {
+        let mut seen = Vec::new();
+        transform::blocks(&mut doc.clone(), order, |block| {
+            if let Block::Div((_, ref classes, _), _) = *block {
+                seen.push(classes[0].clone());
+            }
+            Action::Keep
+        });
+        assert_eq!(seen, expected);
+    }
+}
+
+#[test]
+fn topdown_visits_children_of_replacements() {
+    let mut blocks = vec![Block::div(
+        "unwrap",
+        vec![Block::div("unwrap", vec![Block::Null])],
+    )];
+    transform::blocks(&mut blocks, TraversalOrder::TopDown, |block| match *block {
+        Block::Div(_, ref mut content) => Action::ReplaceMany(content.split_off(0)),
+        _ => Action::Keep,
+    });
+    // the inner div was spliced into the top level list and not revisited
+    assert_eq!(blocks, vec![Block::div("unwrap", vec![Block::Null])]);
+}
+
+#[test]
+fn shift_and_number() {
+    let mut blocks = vec![
+        Block::header(1, "A"),
+        Block::header(3, "A.0.1"),
+        Block::header(2, "A.1"),
+        Block::Header(
+            2,
+            (String::new(), vec!["unnumbered".to_owned()], vec![]),
+            vec![],
+        ),
+        Block::header(6, "B"),
+    ];
+    transform::shift_headers(&mut blocks, 1);
+    let levels: Vec<_> = query::headers(&blocks).iter().map(|h| h.0).collect();
+    assert_eq!(levels, [2, 4, 3, 3, 6]);
+    transform::shift_headers(&mut blocks, -1);
+    transform::number_headings(&mut blocks, Some("number"));
+    assert_eq!(stringify(&blocks[0]), "1 A");
+    assert_eq!(stringify(&blocks[1]), "1.0.1 A.0.1");
+    assert_eq!(stringify(&blocks[2]), "1.1 A.1");
+    assert_eq!(stringify(&blocks[3]), "");
+    assert_eq!(stringify(&blocks[4]), "1.1.0.0.1 B");
+    assert_eq!(
+        blocks[2].attr().unwrap().2,
+        vec![("number".to_owned(), "1.1".to_owned())]
+    );
+
+    let mut doc = Pandoc::new();
+    doc.meta.insert(
+        "abstract".to_owned(),
+        MetaValue::MetaBlocks(vec![Block::header(1, "Abstract")]),
+    );
+    doc.blocks = vec![Block::header(1, "A"), Block::header(Int::MAX, "B")];
+    transform::number_headings(&mut doc, None);
+    assert_eq!(stringify(&doc.blocks[0]), "1 A");
+    assert_eq!(stringify(&doc.blocks[1]), "1.0.0.0.0.1 B");
+    assert_eq!(
+        doc.meta["abstract"],
+        MetaValue::MetaBlocks(vec![Block::header(1, "Abstract")])
+    );
+}
+
+#[test]
+fn normalize() {
+    let mut doc = vec![Block::para(vec![
+        Inline::str("foo"),
+        Inline::Emph(vec![]),
+        Inline::str("bar"),
+        Inline::Space,
+        Inline::SoftBreak,
+        Inline::Space,
+        Inline::Strong(vec![Inline::str(""), Inline::str("x"), Inline::str("y")]),
+        Inline::Space,
+        Inline::LineBreak,
+        Inline::Space,
+        Inline::str(""),
+        Inline::str("end"),
+    ])];
+    transform::normalize(&mut doc);
+    assert_eq!(
+        doc,
+        vec![Block::para(vec![
+            Inline::str("foobar"),
+            Inline::SoftBreak,
+            Inline::Strong(vec![Inline::str("xy")]),
+            Inline::LineBreak,
+            Inline::str("end"),
+        ])]
+    );
+}
+
+#[test]
+fn rewrite_urls() {
+    let mut doc = vec![
+        para![
+            Inline::link(inlines!["local"], "img/a.png"),
+            Inline::image(inlines!["remote"], "https://example.com/b.png"),
+            Inline::RawInline(
+                Format("html".to_owned()),
+                r#"<img data-src="x" src='img/c.png'>"#.to_owned()
+            ),
+        ],
+        Block::RawBlock(Format("html".to_owned()), r#"<a HREF="d.html">"#.to_owned()),
+    ];
+    let mut kinds = Vec::new();
+    transform::rewrite_urls(&mut doc, |url, kind| {
+        kinds.push(kind);
+        if url.contains("://") {
+            url.to_owned()
+        } else {
+            format!("https://cdn.example.com/{}", url)
+        }
+    });
+    let urls: Vec<_> = query::links(&doc)
+        .into_iter()
+        .chain(query::images(&doc))
+        .map(|(_, target)| target.0.clone())
+        .collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://cdn.example.com/img/a.png",
+            "https://example.com/b.png"
+        ]
+    );
+    if cfg!(feature = "html") {
+        assert_eq!(
+            kinds,
+            vec![
+                transform::UrlKind::Link,
+                transform::UrlKind::Image,
+                transform::UrlKind::RawSrc,
+                transform::UrlKind::RawHref,
+            ]
+        );
+        assert_eq!(
+            doc[1],
+            Block::RawBlock(
+                Format("html".to_owned()),
+                r#"<a HREF="https://cdn.example.com/d.html">"#.to_owned()
+            )
+        );
+    } else {
+        assert_eq!(kinds.len(), 2);
+    }
+}
+
+#[test]
+fn embed_images() {
+    let dir = std::env::temp_dir().join(format!("pandoc_ast_embed_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("dot.png"), b"Man").unwrap();
+    std::fs::write(dir.join("big.gif"), b"too large").unwrap();
+    let mut doc = vec![para![
+        Inline::image(vec![], "dot.png?v=2"),
+        Inline::image(vec![], "big.gif"),
+        Inline::image(vec![], "https://example.com/remote.png"),
+    ]];
+    transform::embed_images(&mut doc, &dir, Some(4)).unwrap();
+    let urls: Vec<_> = query::images(&doc)
+        .into_iter()
+        .map(|(_, target)| target.0.clone())
+        .collect();
+    assert_eq!(
+        urls,
+        vec![
+            "data:image/png;base64,TWFu",
+            "big.gif",
+            "https://example.com/remote.png"
+        ]
+    );
+    let mut missing = vec![para![Inline::image(vec![], "missing.png")]];
+    assert!(transform::embed_images(&mut missing, &dir, None).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn citation(id: &str) -> Citation {
+    Citation {
+        citationId: id.to_owned(),
+        citationPrefix: vec![],
+        citationSuffix: vec![],
+        citationMode: CitationMode::NormalCitation,
+        citationNoteNum: 0,
+        citationHash: 0,
+    }
+}
+
+#[test]
+fn citations() {
+    let mut doc = vec![para![
+        Inline::Cite(vec![citation("zotero:1"), citation("zotero:2")], vec![]),
+        Inline::Note(vec![para![Inline::Cite(
+            vec![citation("zotero:1")],
+            vec![]
+        )]]),
+        Inline::Cite(vec![citation("other")], vec![]),
+    ]];
+    transform::map_citation_keys(&mut doc, |id| id.replace("zotero:", "key"));
+    transform::renumber_citations(&mut doc);
+    let found: Vec<_> = query::citations(&doc)
+        .into_iter()
+        .map(|(cite, position, c)| {
+            (
+                cite,
+                position,
+                c.citationId.as_str(),
+                c.citationNoteNum,
+                c.citationHash,
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (0, 0, "key1", 0, 1),
+            (0, 1, "key2", 0, 2),
+            (1, 0, "key1", 1, 3),
+            (2, 0, "other", 1, 4),
+        ]
+    );
+}
+
+#[test]
+fn class_dispatch() {
+    let mut doc = vec![
+        Block::div(
+            "theorem",
+            blocks![
+                para![Inline::span("term", inlines!["Lemma"])],
+                Block::div("wrapper", vec![para!["inner"]])
+            ],
+        ),
+        para!["after"],
+    ];
+    transform::on_div_class(&mut doc, "theorem", |attr, content| {
+        attr.0 = "thm-1".to_owned();
+        content.insert(0, para![strong!["Theorem."]]);
+        Action::Keep
+    });
+    transform::on_span_class(&mut doc, "term", |_, content| {
+        Action::Replace(Inline::Emph(content.split_off(0)))
+    });
+    transform::unwrap_div(&mut doc, "wrapper");
+    transform::wrap_blocks_in_div(
+        &mut doc,
+        1..2,
+        (String::new(), vec!["aside".to_owned()], vec![]),
+    );
+    assert_eq!(
+        doc,
+        vec![
+            Block::Div(
+                ("thm-1".to_owned(), vec!["theorem".to_owned()], vec![]),
+                blocks![
+                    para![strong!["Theorem."]],
+                    para![emph!["Lemma"]],
+                    para!["inner"]
+                ],
+            ),
+            Block::div("aside", vec![para!["after"]]),
+        ]
+    );
+}
+
+#[test]
+fn strip_raw() {
+    let raw_block = |format: &str, content: &str| {
+        Block::RawBlock(Format(format.to_owned()), content.to_owned())
+    };
+    let raw_inline = |format: &str, content: &str| {
+        Inline::RawInline(Format(format.to_owned()), content.to_owned())
+    };
+    let mut doc = vec![
+        raw_block("LaTeX", "\\newpage"),
+        raw_block("html", "<hr>"),
+        para![
+            "a",
+            raw_inline("tex", "\\LaTeX"),
+            raw_inline("html", "<br>")
+        ],
+    ];
+    assert_eq!(query::raw_blocks(&doc, "latex"), vec!["\\newpage"]);
+    assert_eq!(query::raw_inlines(&doc, "latex"), vec!["\\LaTeX"]);
+    transform::strip_raw_except(&mut doc, &["latex"]);
+    assert_eq!(
+        doc,
+        vec![
+            raw_block("LaTeX", "\\newpage"),
+            para!["a", raw_inline("tex", "\\LaTeX")]
+        ]
+    );
+    transform::strip_raw_except(&mut doc, &[]);
+    assert_eq!(doc, vec![para!["a"]]);
+}
+
+#[test]
+fn filter_conditional() {
+    let div = |classes: &[&str], attrs: &[(&str, &str)], content| {
+        let classes = classes.iter().map(|class| class.to_string()).collect();
+        let attrs = attrs
+            .iter()
+            .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+            .collect();
+        Block::Div((String::new(), classes, attrs), content)
+    };
+    let doc = vec![
+        div(&["if-html"], &[], vec![para!["web"]]),
+        div(&["if-print", "aside"], &[], vec![para!["paper"]]),
+        div(&["aside"], &[("when", "draft")], vec![para!["draft"]]),
+        div(&["if-html"], &[("when", "draft")], vec![para!["both"]]),
+        para![
+            "always",
+            Inline::Span(
+                (String::new(), vec!["if-print".to_owned()], vec![]),
+                inlines![" printed"]
+            )
+        ],
+        div(
+            &["note"],
+            &[],
+            vec![div(&["if-print"], &[], vec![para!["nested"]])],
+        ),
+    ];
+
+    let mut html = doc.clone();
+    transform::filter_conditional(&mut html, |condition| condition == "html");
+    assert_eq!(
+        html,
+        vec![para!["web"], para!["always"], div(&["note"], &[], vec![])]
+    );
+
+    let mut print = doc;
+    transform::filter_conditional(&mut print, |condition| condition != "html");
+    assert_eq!(
+        print,
+        vec![
+            div(&["aside"], &[], vec![para!["paper"]]),
+            div(&["aside"], &[], vec![para!["draft"]]),
+            para!["always printed"],
+            div(&["note"], &[], vec![para!["nested"]]),
+        ]
+    );
+}
+
+#[test]
+fn map_math() {
+    let math = |math_type, tex: &str| Inline::Math(math_type, tex.to_owned());
+    let mut doc = vec![para![
+        math(MathType::InlineMath, "x"),
+        math(MathType::InlineMath, "y"),
+        math(MathType::DisplayMath, "e^{i\\pi}"),
+        math(MathType::InlineMath, "z"),
+    ]];
+    transform::map_math(&mut doc, |math_type, tex| match (math_type, tex) {
+        (_, "x") => transform::MathReplacement::Keep,
+        (_, "y") => transform::MathReplacement::Tex("\\gamma".to_owned()),
+        (MathType::DisplayMath, _) => transform::MathReplacement::Image("euler.svg".to_owned()),
+        _ => {
+            transform::MathReplacement::Raw(Format("html".to_owned()), "<math>z</math>".to_owned())
+        }
+    });
+    assert_eq!(
+        doc,
+        vec![para![
+            math(MathType::InlineMath, "x"),
+            math(MathType::InlineMath, "\\gamma"),
+            Inline::Image(
+                (
+                    String::new(),
+                    vec!["math".to_owned(), "display".to_owned()],
+                    vec![]
+                ),
+                vec![Inline::str("e^{i\\pi}")],
+                ("euler.svg".to_owned(), String::new()),
+            ),
+            Inline::RawInline(Format("html".to_owned()), "<math>z</math>".to_owned()),
+        ]]
+    );
+    assert_eq!(query::math(&doc).len(), 2);
+}
+
+#[test]
+fn text_case() {
+    let code = Inline::Code(Attr::default(), "let x".to_owned());
+    let mut doc = vec![
+        Block::header(1, "the lord of the rings"),
+        para!["see ", code.clone(), " and ", emph!["the end"]],
+    ];
+    transform::title_case(&mut doc);
+    assert_eq!(
+        doc,
+        vec![
+            Block::header(1, "The Lord of the Rings"),
+            para!["See ", code.clone(), " and ", emph!["The End"]],
+        ]
+    );
+    transform::map_text(&mut doc, |text| text.replace('e', "3"));
+    transform::uppercase(&mut doc);
+    assert_eq!(doc[1], para!["S33 ", code, " AND ", emph!["TH3 END"]]);
+
+    let mut inlines = inlines![
+        "(NASA) and U.S.A. but not I or Nasa",
+        Inline::SmallCaps(inlines!["ESA"])
+    ];
+    transform::small_caps(&mut inlines);
+    assert_eq!(
+        inlines,
+        inlines![
+            "(",
+            Inline::SmallCaps(inlines!["nasa"]),
+            ") and ",
+            Inline::SmallCaps(inlines!["u.s.a"]),
+            ". but not I or Nasa",
+            Inline::SmallCaps(inlines!["ESA"])
+        ]
+    );
+}
+
+#[test]
+fn canonicalize() {
+    let mut table = pandoc_ast::table::TableBuilder::new()
+        .row(vec!["a", "b", "c", "d"])
+        .widths(vec![0.5, -0.0, f64::NAN, 0.25])
+        .build();
+    if let Block::Table(_, _, _, _, ref mut bodies, _) = table {
+        let cell = &mut bodies[0].3[0].1[0];
+        cell.2 = 0;
+        cell.3 = -1;
+    }
+    let mut doc = Pandoc::new();
+    doc.meta.insert(
+        "pages".to_owned(),
+        MetaValue::MetaList(vec![MetaValue::MetaNumber(12.into())]),
+    );
+    doc.meta.insert(
+        "abstract".to_owned(),
+        MetaValue::MetaBlocks(vec![table.clone()]),
+    );
+    doc.blocks = vec![table];
+    transform::canonicalize(&mut doc);
+
+    assert_eq!(
+        doc.meta["pages"],
+        MetaValue::MetaList(vec![MetaValue::MetaString("12".to_owned())])
+    );
+    assert_eq!(
+        doc.meta["abstract"],
+        MetaValue::MetaBlocks(doc.blocks.clone())
+    );
+    if let Block::Table(_, _, ref specs, _, ref bodies, _) = doc.blocks[0] {
+        let widths: Vec<ColWidth> = specs.iter().map(|spec| spec.1).collect();
+        assert_eq!(
+            widths,
+            [
+                ColWidth::ColWidth(0.5),
+                ColWidth::ColWidthDefault,
+                ColWidth::ColWidthDefault,
+                ColWidth::ColWidth(0.25),
+            ]
+        );
+        let cell = &bodies[0].3[0].1[0];
+        assert_eq!((cell.2, cell.3), (1, 1));
+    } else {
+        panic!("not a table");
+    }
+    assert_eq!(Pandoc::from_json(&doc.to_json()), doc);
+}
@@ -0,0 +1,88 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::validate::{self, LinkIssue};
+use pandoc_ast::*;
+
+fn doc(blocks: Vec<Block>) -> Pandoc {
+    let mut doc = Pandoc::from_json(r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[]}"#);
+    doc.blocks = blocks;
+    doc
+}
+
+fn with_id(id: &str, text: &str) -> Block {
+    Block::Header(1, (id.into(), vec![], vec![]), inlines_from_str(text))
+}
+
+#[test]
+fn link_issues() {
+    let doc = doc(vec![
+        with_id("intro", "Intro"),
+        Block::para(vec![
+            Inline::link(inlines!["see"], "#intro"),
+            Inline::link(inlines!["later"], "#later"),
+            Inline::link(inlines!["nothing"], ""),
+            Inline::link(inlines!["web"], "https://example.com"),
+        ]),
+        with_id("intro", "Again"),
+    ]);
+    let issues = validate::check_links(&doc);
+    assert_eq!(
+        issues,
+        vec![
+            LinkIssue::BrokenLink {
+                path: AstPath::block(1).child(0, 1),
+                target: "later".into(),
+            },
+            LinkIssue::EmptyTarget {
+                path: AstPath::block(1).child(0, 2),
+            },
+            LinkIssue::DuplicateIdentifier {
+                path: AstPath::block(2),
+                id: "intro".into(),
+            },
+        ]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "1/1: link to missing identifier #later"
+    );
+}
+
+#[test]
+fn accessibility_issues() {
+    use pandoc_ast::table::TableBuilder;
+    use validate::AccessibilityIssue;
+
+    let doc = doc(vec![
+        Block::header(2, "Start"),
+        Block::para(vec![
+            Inline::image(vec![], "cat.png"),
+            Inline::image(inlines!["a cat"], "cat.png"),
+            Inline::link(inlines!["Here"], "https://example.com"),
+        ]),
+        Block::header(4, "Deep"),
+        Block::header(2, "Back"),
+        TableBuilder::new().row(vec!["a", "b"]).build(),
+        TableBuilder::new().header(vec!["x"]).row(vec!["1"]).build(),
+    ]);
+    assert_eq!(
+        validate::accessibility(&doc),
+        vec![
+            AccessibilityIssue::ImageWithoutAlt {
+                path: AstPath::block(1).child(0, 0),
+            },
+            AccessibilityIssue::UninformativeLinkText {
+                path: AstPath::block(1).child(0, 2),
+                text: "Here".into(),
+            },
+            AccessibilityIssue::HeadingLevelJump {
+                path: AstPath::block(2),
+                from: 2,
+                to: 4,
+            },
+            AccessibilityIssue::TableWithoutHeader {
+                path: AstPath::block(4),
+            },
+        ]
+    );
+}
@@ -0,0 +1,56 @@
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+fn var(attrs: &[(&str, &str)], text: &str) -> Inline {
+    let attrs = attrs
+        .iter()
+        .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+        .collect();
+    Inline::Span(
+        (String::new(), vec!["var".to_owned()], attrs),
+        inlines![text],
+    )
+}
+
+#[test]
+fn substitution() {
+    let mut doc = Pandoc::new();
+    doc.meta = Meta::from_json_str(
+        r#"{"product": "Frob Nicator", "draft": true, "authors": [{"name": "Ann"}]}"#,
+    )
+    .unwrap();
+    doc.meta.insert(
+        "title".to_owned(),
+        MetaValue::MetaInlines(inlines![emph!["The"], " Manual"]),
+    );
+    doc.meta.insert(
+        "summary".to_owned(),
+        MetaValue::MetaBlocks(vec![para!["Short."]]),
+    );
+    doc.blocks = vec![
+        para!["(%{title}) by %{authors.0.name}, draft: %{draft}"],
+        para![
+            var(&[], "product"),
+            " ",
+            var(&[("name", "summary")], "placeholder"),
+            " ",
+            var(&[], "missing"),
+            " %{authors} %{nope}%{product}"
+        ],
+    ];
+    let unresolved = variables::substitute(&mut doc);
+    assert_eq!(unresolved, vec!["missing", "authors", "nope"]);
+    assert_eq!(
+        doc.blocks,
+        vec![
+            para!["(", emph!["The"], " Manual) by Ann, draft: true"],
+            para![
+                "Frob Nicator Short. ",
+                var(&[], "missing"),
+                " %{authors} %{nope}Frob",
+                " Nicator"
+            ],
+        ]
+    );
+}
@@ -0,0 +1,106 @@
+extern crate pandoc_ast;
+extern crate serde_json;
+
+use pandoc_ast::*;
+use std::convert::TryFrom;
+
+const OLD_TABLE: &str = r#"{"blocks":[{"t":"Table","c":[[{"t":"Str","c":"Caption"}],[{"t":"AlignLeft"},{"t":"AlignDefault"}],[0.5,0.0],[[{"t":"Plain","c":[{"t":"Str","c":"a"}]}],[]],[[[{"t":"Plain","c":[{"t":"Span","c":[["",["underline"],[]],[{"t":"Str","c":"b"}]]}]}],[]]]]}],"meta":{},"pandoc-api-version":[1,20]}"#;
+
+fn same_json(a: &str, b: &str) {
+    let a: serde_json::Value = serde_json::from_str(a).unwrap();
+    let b: serde_json::Value = serde_json::from_str(b).unwrap();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn old_tables_are_upgraded() {
+    let mut seen = None;
+    let out = filter(OLD_TABLE.to_owned(), |doc| {
+        seen = Some(doc.clone());
+        doc
+    });
+    let doc = seen.unwrap();
+    assert_eq!(doc.pandoc_api_version, vec![1, 23]);
+    match doc.blocks[0] {
+        Block::Table(_, ref caption, ref specs, ref head, ref bodies, _) => {
+            assert_eq!(stringify(&caption.1), "Caption");
+            assert_eq!(specs[0], (Alignment::AlignLeft, ColWidth::ColWidth(0.5)));
+            assert_eq!(specs[1].1, ColWidth::ColWidthDefault);
+            assert_eq!(head.1.len(), 1);
+            let cell = &bodies[0].3[0].1[0].4;
+            assert_eq!(
+                *cell,
+                vec![Block::Plain(vec![Inline::Underline(vec![Inline::str(
+                    "b"
+                )])])]
+            );
+        }
+        ref other => panic!("unexpected {:?}", other),
+    }
+    same_json(&out, OLD_TABLE);
+}
+
+#[test]
+fn implicit_figures_are_upgraded() {
+    let json = r#"{"pandoc-api-version":[1,22,2],"meta":{},"blocks":[{"t":"Para","c":[{"t":"Image","c":[["",[],[]],[{"t":"Str","c":"cat"}],["cat.png","fig:"]]}]}]}"#;
+    let mut figures = 0;
+    let out = filter(json.to_owned(), |doc| {
+        figures = doc
+            .blocks
+            .iter()
+            .filter(|b| matches!(b, Block::Figure(..)))
+            .count();
+        doc
+    });
+    assert_eq!(figures, 1);
+    same_json(&out, json);
+}
+
+#[test]
+fn figures_without_image_become_divs() {
+    let mut doc = Pandoc::from_json(r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[]}"#);
+    doc.blocks.push(Block::Figure(
+        ("fig".into(), vec![], vec![]),
+        (None, vec![Block::plain(inlines!["A table"])]),
+        vec![Block::para(inlines!["content"])],
+    ));
+    let old = v1_20::Pandoc::try_from(v1_22::Pandoc::from(doc)).unwrap();
+    assert_eq!(old.pandoc_api_version, vec![1, 20]);
+    assert_eq!(
+        old.blocks,
+        vec![v1_20::Block::Div(
+            ("fig".into(), vec!["figure".into()], vec![]),
+            vec![
+                v1_20::Block::Para(vec![v1_20::Inline::Str("content".into())]),
+                v1_20::Block::Plain(vec![
+                    v1_20::Inline::Str("A".into()),
+                    v1_20::Inline::Space,
+                    v1_20::Inline::Str("table".into()),
+                ]),
+            ],
+        )]
+    );
+}
+
+#[test]
+fn invalid_unparsed_blocks_to_old_versions() {
+    let json = r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[{"t":"Bogus"}]}"#;
+    let doc = Pandoc::from_json_lazy(json).unwrap();
+    match v1_20::Pandoc::try_from(v1_22::Pandoc::from(doc)) {
+        Err(Error::Json(_)) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn old_nullary_constructors_with_content() {
+    let json = r#"{"blocks":[{"t":"Para","c":[{"t":"Str","c":"a"},{"t":"Space","c":[]},{"t":"Str","c":"b"}]},{"t":"HorizontalRule","c":[]}],"meta":{},"pandoc-api-version":[1,20]}"#;
+    let out = filter(json.to_owned(), |doc| {
+        assert_eq!(doc.blocks[1], Block::HorizontalRule);
+        doc
+    });
+    same_json(
+        &out,
+        r#"{"blocks":[{"t":"Para","c":[{"t":"Str","c":"a"},{"t":"Space"},{"t":"Str","c":"b"}]},{"t":"HorizontalRule"}],"meta":{},"pandoc-api-version":[1,20]}"#,
+    );
+}
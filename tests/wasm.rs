@@ -0,0 +1,15 @@
+#![cfg(feature = "wasm")]
+extern crate pandoc_ast;
+
+use pandoc_ast::*;
+
+#[test]
+fn bindings() {
+    let old = r#"{"pandoc-api-version":[1,22],"meta":{},"blocks":[{"t":"Para","c":[{"t":"Image","c":[["",[],[]],[],["a.png","fig:"]]}]}]}"#;
+    let current = Pandoc::from_json(&wasm::upgrade(old).unwrap());
+    assert!(matches!(current.blocks[..], [Block::Figure(..)]));
+
+    let doc = Pandoc::builder().block(para!["*hi*"]).build().to_json();
+    assert_eq!(wasm::to_markdown(&doc).unwrap(), "\\*hi\\*\n");
+    assert!(filter_json("{}", |doc| doc).is_err());
+}